@@ -28,6 +28,7 @@ const ESCROW_PROGRAM_ID = new PublicKey('6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n
 const LOGGER_PROGRAM_ID = new PublicKey('HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv');
 
 const LOGGER_STATE_FILE = 'logger_state.json';
+const LOGGER_STATE_LEN = 73;
 const USER1_FILE = 'wallet1.json';
 const USER2_FILE = 'wallet2.json';
 
@@ -81,7 +82,7 @@ async function getOrCreateLoggerStateAccount(payer: Keypair): Promise<Keypair> {
   }
   console.log('Creating new logger state...');
   const kp = Keypair.generate();
-  const space = 8;
+  const space = LOGGER_STATE_LEN;
   const lamports = await connection.getMinimumBalanceForRentExemption(space);
   const createIx = SystemProgram.createAccount({
     fromPubkey: payer.publicKey,
@@ -90,12 +91,107 @@ async function getOrCreateLoggerStateAccount(payer: Keypair): Promise<Keypair> {
     space,
     programId: LOGGER_PROGRAM_ID,
   });
-  const tx = new Transaction().add(createIx);
+  // Initialize: tag 0 + authority koja sme da zatvara poruke
+  const initData = Buffer.alloc(1 + 32);
+  initData.writeUInt8(0, 0);
+  payer.publicKey.toBuffer().copy(initData, 1);
+  const initIx = new TransactionInstruction({
+    programId: LOGGER_PROGRAM_ID,
+    keys: [{ pubkey: kp.publicKey, isSigner: false, isWritable: true }],
+    data: initData,
+  });
+  const tx = new Transaction().add(createIx, initIx);
   await sendAndConfirmTransaction(connection, tx, [payer, kp]);
   fs.writeFileSync(LOGGER_STATE_FILE, JSON.stringify(Array.from(kp.secretKey)));
   return kp;
 }
 
+// Escrow CPI-uje samo u logger program i state zapisane u config PDA
+async function ensureEscrowConfig(admin: Keypair, statePubkey: PublicKey): Promise<PublicKey> {
+  const [configPda] = await PublicKey.findProgramAddress([Buffer.from('config')], ESCROW_PROGRAM_ID);
+  const config = await connection.getAccountInfo(configPda);
+  // Raspored: discriminator (8) | admin (32) | logger program (32) | logger state (32)
+  if (config && new PublicKey(config.data.subarray(72, 104)).equals(statePubkey)) {
+    return configPda;
+  }
+  const keys = [
+    { pubkey: admin.publicKey, isSigner: true, isWritable: !config },
+    { pubkey: configPda, isSigner: false, isWritable: true },
+    { pubkey: LOGGER_PROGRAM_ID, isSigner: false, isWritable: false },
+    { pubkey: statePubkey, isSigner: false, isWritable: false },
+  ];
+  if (!config) {
+    keys.push({ pubkey: SystemProgram.programId, isSigner: false, isWritable: false });
+  }
+  const configIx = new TransactionInstruction({
+    programId: ESCROW_PROGRAM_ID,
+    keys,
+    data: Buffer.from([config ? 3 : 2]), // 2 = InitializeConfig, 3 = SetLogger
+  });
+  await sendAndConfirmTransaction(connection, new Transaction().add(configIx), [admin]);
+  return configPda;
+}
+
+async function closeMessage(authority: Keypair, statePubkey: PublicKey, messagePda: PublicKey, payer: PublicKey) {
+  const closeIx = new TransactionInstruction({
+    programId: LOGGER_PROGRAM_ID,
+    keys: [
+      { pubkey: authority.publicKey, isSigner: true, isWritable: false },
+      { pubkey: statePubkey, isSigner: false, isWritable: false },
+      { pubkey: messagePda, isSigner: false, isWritable: true },
+      { pubkey: payer, isSigner: false, isWritable: true },
+    ],
+    data: Buffer.from([2]), // 2 = CloseMessage
+  });
+  const tx = new Transaction().add(closeIx);
+  return sendAndConfirmTransaction(connection, tx, [authority]);
+}
+
+type MessageData = {
+  from: PublicKey;
+  to: PublicKey;
+  amount: bigint;
+  timestamp: bigint;
+  sequence: bigint;
+  payer: PublicKey;
+  slot: bigint;
+  epoch: bigint;
+  consistencyLevel: number; // 0 = Confirmed, 1 = Finalized
+};
+
+function decodeMessageData(data: Buffer): MessageData {
+  return {
+    from: new PublicKey(data.subarray(0, 32)),
+    to: new PublicKey(data.subarray(32, 64)),
+    amount: data.readBigUInt64LE(64),
+    timestamp: data.readBigUInt64LE(72),
+    sequence: data.readBigUInt64LE(80),
+    payer: new PublicKey(data.subarray(88, 120)),
+    slot: data.readBigUInt64LE(120),
+    epoch: data.readBigUInt64LE(128),
+    consistencyLevel: data.readUInt8(136),
+  };
+}
+
+// Ceka dok slot poruke ne dostigne consistency level koji je emiter trazio
+async function waitForFinality(sequence: number, timeoutMs = 60_000): Promise<MessageData> {
+  const [pda] = await getMessagePda(LOGGER_PROGRAM_ID, sequence);
+  const deadline = Date.now() + timeoutMs;
+  while (Date.now() < deadline) {
+    const accountInfo = await connection.getAccountInfo(pda, 'confirmed');
+    if (accountInfo) {
+      const message = decodeMessageData(accountInfo.data);
+      const commitment = message.consistencyLevel === 1 ? 'finalized' : 'confirmed';
+      const slot = await connection.getSlot(commitment);
+      if (BigInt(slot) >= message.slot) {
+        return message;
+      }
+    }
+    await new Promise(resolve => setTimeout(resolve, 1_000));
+  }
+  throw new Error(`Message ${sequence} did not reach its consistency level in time`);
+}
+
 async function getMessagePda(loggerProg: PublicKey, sequence: number): Promise<[PublicKey, number]> {
   const seqBuf = Buffer.alloc(8);
   seqBuf.writeBigUInt64LE(BigInt(sequence), 0);
@@ -111,6 +207,7 @@ async function main() {
 
   const loggerStateKP = await getOrCreateLoggerStateAccount(user1);
   console.log('LoggerState pubkey:', loggerStateKP.publicKey.toBase58());
+  const configPda = await ensureEscrowConfig(user1, loggerStateKP.publicKey);
 
   const mint = await createMint(connection, user1, user1.publicKey, null, 9);
   console.log('Mint:', mint.toBase58());
@@ -132,6 +229,11 @@ async function main() {
     [Buffer.from('vault'), mint.toBuffer()],
     ESCROW_PROGRAM_ID
   );
+  // Pozicija user1 za ovaj mint, depozit je kreira
+  const [positionPda] = await PublicKey.findProgramAddress(
+    [Buffer.from('position'), mint.toBuffer(), user1.publicKey.toBuffer()],
+    ESCROW_PROGRAM_ID
+  );
 
   // ------------------ DEPOSIT ------------------
   const depositAmount = 50;
@@ -158,6 +260,8 @@ async function main() {
       { pubkey: user1.publicKey, isSigner: true, isWritable: true },
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
       { pubkey: mint, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: false },
+      { pubkey: positionPda, isSigner: false, isWritable: true },
     ],
     data: depositData,
   });
@@ -167,6 +271,9 @@ async function main() {
   console.log('✅ Deposit successful. Signature:', depositSig);
   await printLogsForTx(depositSig);
 
+  const depositMessage = await waitForFinality(seqBefore + 1);
+  console.log('Deposit message final at slot', depositMessage.slot.toString());
+
   const balanceAfterDeposit = await getAccount(connection, user1TokenAcc.address);
   const vaultAfterDeposit = await getAccount(connection, vaultPda);
   console.log('User1 balance after deposit:', Number(balanceAfterDeposit.amount));
@@ -195,6 +302,8 @@ async function main() {
       { pubkey: withdrawMessagePda, isSigner: false, isWritable: true },       
       { pubkey: user1.publicKey, isSigner: true, isWritable: true },           
       { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+      { pubkey: configPda, isSigner: false, isWritable: false },
+      { pubkey: positionPda, isSigner: false, isWritable: true },
     ],
     data: withdrawData,
   });
@@ -211,6 +320,12 @@ async function main() {
   console.log('User2 balance after withdraw:', Number(balance2AfterWithdraw.amount));
   console.log('Vault balance after withdraw:', Number(vaultAfterWithdraw.amount));
 
+  // ------------------ CLOSE MESSAGES ------------------
+  for (const pda of [messagePda, withdrawMessagePda]) {
+    const closeSig = await closeMessage(user1, loggerStateKP.publicKey, pda, user1.publicKey);
+    console.log('✅ Message closed:', pda.toBase58(), 'Signature:', closeSig);
+  }
+
 }
 
 main().catch(err => console.error('❌ Main failed:', err));
\ No newline at end of file
@@ -0,0 +1,76 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bench::{Mode, Results},
+    error::BenchError,
+};
+
+/// Checked-in compute units per path, one set per `Mode`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Baseline(BTreeMap<String, Results>);
+
+/// One measured path against its baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub case: String,
+    pub baseline: Option<u64>,
+    pub current: u64,
+    pub regressed: bool,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, BenchError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), BenchError> {
+        let mut text = serde_json::to_string_pretty(self)?;
+        text.push('\n');
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Replaces the numbers of `mode` with `results`.
+    pub fn record(&mut self, mode: Mode, results: Results) {
+        self.0.insert(mode.to_string(), results);
+    }
+
+    /// Compares `results` with the numbers of `mode`; a path regresses when
+    /// it costs more than `threshold` percent over its baseline. Paths the
+    /// baseline does not know yet never regress.
+    pub fn compare(
+        &self,
+        mode: Mode,
+        results: &Results,
+        threshold: f64,
+    ) -> Result<Vec<Comparison>, BenchError> {
+        let baseline = self
+            .0
+            .get(&mode.to_string())
+            .ok_or_else(|| BenchError::NoBaseline(mode.to_string()))?;
+        Ok(results
+            .iter()
+            .map(|(case, current)| {
+                let baseline = baseline.get(case).copied();
+                Comparison {
+                    case: case.clone(),
+                    baseline,
+                    current: *current,
+                    regressed: baseline.is_some_and(|baseline| {
+                        *current as f64 > baseline as f64 * (1.0 + threshold / 100.0)
+                    }),
+                }
+            })
+            .collect())
+    }
+}
+
+impl Comparison {
+    pub fn change_percent(&self) -> Option<f64> {
+        self.baseline
+            .filter(|baseline| *baseline > 0)
+            .map(|baseline| (self.current as f64 - baseline as f64) * 100.0 / baseline as f64)
+    }
+}
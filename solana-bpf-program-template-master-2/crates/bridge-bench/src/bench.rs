@@ -0,0 +1,421 @@
+use std::{collections::BTreeMap, env, fmt};
+
+use escrow_contract::{instruction as escrow_instruction, processor::EscrowProcessor};
+use logger_contract::{
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+};
+use solana_program_test::{
+    find_file, processor, BanksClient, ProgramTest, ProgramTestBanksClientExt,
+};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_token::state::{Account as TokenAccount, Mint};
+
+use crate::error::BenchError;
+
+/// How the programs under test are loaded. Native processors are charged
+/// a single unit per call, so only their CPIs into spl-token and the
+/// system program show up; real numbers need the SBF build.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    Native,
+    Sbf,
+}
+
+impl Mode {
+    /// SBF when `SBF_OUT_DIR` or `BPF_OUT_DIR` is set, the same rule
+    /// `ProgramTest` uses to prefer the shared objects.
+    pub fn from_env() -> Self {
+        if env::var_os("SBF_OUT_DIR").is_some() || env::var_os("BPF_OUT_DIR").is_some() {
+            Mode::Sbf
+        } else {
+            Mode::Native
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Native => write!(f, "native"),
+            Mode::Sbf => write!(f, "sbf"),
+        }
+    }
+}
+
+/// Compute units consumed per measured path, keyed by path name.
+pub type Results = BTreeMap<String, u64>;
+
+/// Runs every measured path once, each in a transaction of its own.
+pub async fn run(mode: Mode) -> Result<Results, BenchError> {
+    let mut bench = Bench::start(mode).await?;
+    bench.logger().await?;
+    bench.escrow().await?;
+    Ok(bench.results)
+}
+
+struct Bench {
+    banks: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    escrow_program_id: Pubkey,
+    logger_program_id: Pubkey,
+    logger_state: Pubkey,
+    results: Results,
+}
+
+impl Bench {
+    async fn start(mode: Mode) -> Result<Self, BenchError> {
+        if mode == Mode::Sbf {
+            for program in ["escrow_contract", "logger_contract"] {
+                if find_file(&format!("{}.so", program)).is_none() {
+                    return Err(BenchError::MissingProgram(program));
+                }
+            }
+        }
+
+        let escrow_program_id = escrow_contract::id();
+        let logger_program_id = logger_contract::id();
+        let mut program_test = ProgramTest::new(
+            "escrow_contract",
+            escrow_program_id,
+            processor!(EscrowProcessor::process),
+        );
+        program_test.add_program(
+            "logger_contract",
+            logger_program_id,
+            processor!(LoggerProcessor::process),
+        );
+        program_test.prefer_bpf(mode == Mode::Sbf);
+
+        let (banks, payer, blockhash) = program_test.start().await;
+        Ok(Bench {
+            banks,
+            payer,
+            blockhash,
+            escrow_program_id,
+            logger_program_id,
+            logger_state: Pubkey::default(),
+            results: Results::new(),
+        })
+    }
+
+    async fn logger(&mut self) -> Result<(), BenchError> {
+        let payer = self.payer.pubkey();
+        let state = Keypair::new();
+        self.process(
+            &[system_instruction::create_account(
+                &payer,
+                &state.pubkey(),
+                Rent::default().minimum_balance(LoggerState::LEN),
+                LoggerState::LEN as u64,
+                &self.logger_program_id,
+            )],
+            &[&state],
+        )
+        .await?;
+        self.logger_state = state.pubkey();
+        self.measure(
+            "logger/initialize",
+            logger_instruction::initialize(&self.logger_program_id, &self.logger_state, &payer),
+            &[],
+        )
+        .await?;
+
+        let message = self.message_address(1);
+        self.measure("logger/post_message/pda", self.post(&message), &[])
+            .await?;
+        self.measure(
+            "logger/close_message",
+            logger_instruction::close_message(
+                &self.logger_program_id,
+                &payer,
+                &self.logger_state,
+                &message,
+                &payer,
+            ),
+            &[],
+        )
+        .await?;
+
+        let ring = Keypair::new();
+        let space = RingHeader::space(2);
+        self.process(
+            &[system_instruction::create_account(
+                &payer,
+                &ring.pubkey(),
+                Rent::default().minimum_balance(space),
+                space as u64,
+                &self.logger_program_id,
+            )],
+            &[&ring],
+        )
+        .await?;
+        self.measure(
+            "logger/initialize_ring",
+            logger_instruction::initialize_ring(
+                &self.logger_program_id,
+                &payer,
+                &self.logger_state,
+                &ring.pubkey(),
+            ),
+            &[],
+        )
+        .await?;
+        self.measure(
+            "logger/post_message/ring",
+            logger_instruction::post_ring_message(
+                &self.logger_program_id,
+                &self.logger_state,
+                &ring.pubkey(),
+                &payer,
+                &Pubkey::new_from_array([1; 32]),
+                &Pubkey::new_from_array([2; 32]),
+                100,
+                1_700_000_000,
+                ConsistencyLevel::Finalized,
+                MessageKind::Transfer,
+            ),
+            &[],
+        )
+        .await?;
+        self.measure(
+            "logger/resize_ring",
+            logger_instruction::resize_ring(
+                &self.logger_program_id,
+                &payer,
+                &self.logger_state,
+                &ring.pubkey(),
+                &payer,
+                4,
+            ),
+            &[],
+        )
+        .await?;
+
+        self.measure(
+            "logger/set_authority",
+            logger_instruction::set_authority(
+                &self.logger_program_id,
+                &payer,
+                &self.logger_state,
+                &payer,
+            ),
+            &[],
+        )
+        .await?;
+        self.measure(
+            "logger/get_sequence",
+            logger_instruction::get_sequence(&self.logger_program_id, &self.logger_state),
+            &[],
+        )
+        .await
+    }
+
+    async fn escrow(&mut self) -> Result<(), BenchError> {
+        let payer = self.payer.pubkey();
+        let user = Keypair::new();
+        let mint = Keypair::new();
+        let token_account = Keypair::new();
+        self.process(
+            &[
+                system_instruction::transfer(&payer, &user.pubkey(), 1_000_000_000),
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    Rent::default().minimum_balance(Mint::LEN),
+                    Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    0,
+                )?,
+                system_instruction::create_account(
+                    &payer,
+                    &token_account.pubkey(),
+                    Rent::default().minimum_balance(TokenAccount::LEN),
+                    TokenAccount::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &token_account.pubkey(),
+                    &mint.pubkey(),
+                    &user.pubkey(),
+                )?,
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &token_account.pubkey(),
+                    &payer,
+                    &[],
+                    1_000_000,
+                )?,
+            ],
+            &[&mint, &token_account],
+        )
+        .await?;
+
+        self.measure(
+            "escrow/initialize_config",
+            escrow_instruction::initialize_config(
+                &self.escrow_program_id,
+                &payer,
+                &self.logger_program_id,
+                &self.logger_state,
+            ),
+            &[],
+        )
+        .await?;
+
+        // Prvi depozit kreira escrow i vault, sledeci samo prebacuje tokene
+        for case in ["escrow/deposit/first", "escrow/deposit/steady"] {
+            let message = self.next_message_address().await?;
+            let instruction = escrow_instruction::deposit(
+                &self.escrow_program_id,
+                &self.logger_program_id,
+                &user.pubkey(),
+                &token_account.pubkey(),
+                &mint.pubkey(),
+                &self.logger_state,
+                &message,
+                1_000,
+            );
+            self.measure(case, instruction, &[&user]).await?;
+        }
+
+        let message = self.next_message_address().await?;
+        let instruction = escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &user.pubkey(),
+            &user.pubkey(),
+            &token_account.pubkey(),
+            &mint.pubkey(),
+            &self.logger_state,
+            &message,
+            500,
+        );
+        self.measure("escrow/withdraw", instruction, &[&user])
+            .await?;
+
+        let instruction = escrow_instruction::set_delegate(
+            &self.escrow_program_id,
+            &user.pubkey(),
+            &mint.pubkey(),
+            &payer,
+            Some(500),
+            None,
+        );
+        self.measure("escrow/set_delegate", instruction, &[&user])
+            .await?;
+
+        // Payer povlaci kao delegat, pa se meri i provera limita
+        let message = self.next_message_address().await?;
+        let instruction = escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &payer,
+            &user.pubkey(),
+            &token_account.pubkey(),
+            &mint.pubkey(),
+            &self.logger_state,
+            &message,
+            100,
+        );
+        self.measure("escrow/withdraw/delegate", instruction, &[])
+            .await
+    }
+
+    async fn measure(
+        &mut self,
+        case: &'static str,
+        instruction: Instruction,
+        signers: &[&Keypair],
+    ) -> Result<(), BenchError> {
+        let transaction = self.transaction(&[instruction], signers);
+        let result = self
+            .banks
+            .process_transaction_with_metadata(transaction)
+            .await?;
+        self.refresh_blockhash().await?;
+        if let Err(err) = result.result {
+            return Err(BenchError::Failed { case, err });
+        }
+        let metadata = result.metadata.ok_or(BenchError::NoMetadata(case))?;
+        self.results
+            .insert(case.to_string(), metadata.compute_units_consumed);
+        Ok(())
+    }
+
+    async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BenchError> {
+        let transaction = self.transaction(instructions, signers);
+        let result = self.banks.process_transaction(transaction).await;
+        self.refresh_blockhash().await?;
+        Ok(result?)
+    }
+
+    fn transaction(&self, instructions: &[Instruction], signers: &[&Keypair]) -> Transaction {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        )
+    }
+
+    async fn refresh_blockhash(&mut self) -> Result<(), BenchError> {
+        self.blockhash = self.banks.get_new_latest_blockhash(&self.blockhash).await?;
+        Ok(())
+    }
+
+    fn post(&self, message: &Pubkey) -> Instruction {
+        logger_instruction::post_message(
+            &self.logger_program_id,
+            &self.logger_state,
+            message,
+            &self.payer.pubkey(),
+            &self.payer.pubkey(),
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            100,
+            1_700_000_000,
+            ConsistencyLevel::Finalized,
+            MessageKind::Transfer,
+        )
+    }
+
+    fn message_address(&self, sequence: u64) -> Pubkey {
+        MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0
+    }
+
+    async fn next_message_address(&mut self) -> Result<Pubkey, BenchError> {
+        let account = self.banks.get_account(self.logger_state).await?;
+        let sequence = account
+            .map(|account| LoggerState::load(&account.data).copied())
+            .transpose()?
+            .map_or(0, |state| state.sequence.get());
+        Ok(self.message_address(sequence + 1))
+    }
+}
@@ -0,0 +1,26 @@
+use solana_program_test::BanksClientError;
+use solana_sdk::{program_error::ProgramError, transaction::TransactionError};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum BenchError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid baseline: {0}")]
+    InvalidBaseline(#[from] serde_json::Error),
+    #[error("Banks client error: {0}")]
+    Banks(#[from] BanksClientError),
+    #[error("Program error: {0}")]
+    Program(#[from] ProgramError),
+    #[error("{0}.so not found, build it with cargo build-sbf and point SBF_OUT_DIR at it")]
+    MissingProgram(&'static str),
+    #[error("Transaction of {case} failed: {err}")]
+    Failed {
+        case: &'static str,
+        err: TransactionError,
+    },
+    #[error("No compute units reported for {0}")]
+    NoMetadata(&'static str),
+    #[error("Baseline has no {0} results, record them with --update")]
+    NoBaseline(String),
+}
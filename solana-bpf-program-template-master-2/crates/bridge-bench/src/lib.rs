@@ -0,0 +1,7 @@
+pub mod baseline;
+pub mod bench;
+pub mod error;
+
+pub use baseline::{Baseline, Comparison};
+pub use bench::{run, Mode, Results};
+pub use error::BenchError;
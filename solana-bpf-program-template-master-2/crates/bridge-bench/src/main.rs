@@ -0,0 +1,98 @@
+use std::{env, path::PathBuf, process::exit};
+
+use bridge_bench::{Baseline, BenchError, Mode};
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    name = "bridge-bench",
+    about = "Measure compute units of the escrow and logger paths"
+)]
+struct Args {
+    /// Baseline file with the accepted numbers per mode
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/baseline.json"))]
+    baseline: PathBuf,
+
+    /// Allowed growth over the baseline, in percent
+    #[arg(long, default_value_t = 5.0)]
+    threshold: f64,
+
+    /// Record the current numbers as the new baseline instead of comparing
+    #[arg(long)]
+    update: bool,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+    // Logovi programa samo sa RUST_LOG, inace zatrpaju izvestaj
+    if env::var_os("RUST_LOG").is_none() {
+        env::set_var("RUST_LOG", "error");
+    }
+    match run(args).await {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            exit(1);
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<bool, BenchError> {
+    let mode = Mode::from_env();
+    let results = bridge_bench::run(mode).await?;
+
+    if args.update {
+        let mut baseline = if args.baseline.exists() {
+            Baseline::load(&args.baseline)?
+        } else {
+            Baseline::default()
+        };
+        for (case, units) in &results {
+            println!("{:<28} {:>8}", case, units);
+        }
+        baseline.record(mode, results);
+        baseline.save(&args.baseline)?;
+        println!("recorded {} baseline in {}", mode, args.baseline.display());
+        return Ok(true);
+    }
+
+    let comparisons = Baseline::load(&args.baseline)?.compare(mode, &results, args.threshold)?;
+    println!(
+        "{:<28} {:>8} {:>8} {:>8}  ({} mode)",
+        "path", "baseline", "current", "change", mode
+    );
+    for comparison in &comparisons {
+        let baseline = comparison
+            .baseline
+            .map_or("-".to_string(), |units| units.to_string());
+        let change = comparison
+            .change_percent()
+            .map_or("new".to_string(), |percent| format!("{:+.1}%", percent));
+        println!(
+            "{:<28} {:>8} {:>8} {:>8}{}",
+            comparison.case,
+            baseline,
+            comparison.current,
+            change,
+            if comparison.regressed {
+                "  REGRESSION"
+            } else {
+                ""
+            }
+        );
+    }
+
+    let regressions = comparisons
+        .iter()
+        .filter(|comparison| comparison.regressed)
+        .count();
+    if regressions > 0 {
+        println!(
+            "{} path(s) regressed by more than {}%",
+            regressions, args.threshold
+        );
+    }
+    Ok(regressions == 0)
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use bridge_bench::{Baseline, BenchError, Mode, Results};
+
+fn results(entries: &[(&str, u64)]) -> Results {
+    entries
+        .iter()
+        .map(|(case, units)| (case.to_string(), *units))
+        .collect()
+}
+
+#[test]
+fn compare_flags_growth_past_threshold() {
+    let mut baseline = Baseline::default();
+    baseline.record(
+        Mode::Sbf,
+        results(&[("escrow/deposit/steady", 1000), ("escrow/withdraw", 1000)]),
+    );
+
+    let comparisons = baseline
+        .compare(
+            Mode::Sbf,
+            &results(&[
+                ("escrow/deposit/steady", 1050),
+                ("escrow/withdraw", 1051),
+                ("logger/get_sequence", 10),
+            ]),
+            5.0,
+        )
+        .unwrap();
+    let regressed = comparisons
+        .iter()
+        .map(|comparison| (comparison.case.as_str(), comparison.regressed))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        regressed,
+        [
+            ("escrow/deposit/steady", false),
+            ("escrow/withdraw", true),
+            ("logger/get_sequence", false),
+        ]
+    );
+    assert_eq!(comparisons[2].baseline, None);
+    assert_eq!(comparisons[0].change_percent(), Some(5.0));
+}
+
+#[test]
+fn compare_requires_numbers_for_the_mode() {
+    let mut baseline = Baseline::default();
+    baseline.record(Mode::Native, results(&[("escrow/withdraw", 1)]));
+    let err = baseline
+        .compare(Mode::Sbf, &results(&[("escrow/withdraw", 1)]), 5.0)
+        .unwrap_err();
+    assert!(matches!(err, BenchError::NoBaseline(mode) if mode == "sbf"));
+}
+
+// Nativni brojevi pokrivaju CPI pozive, SBF baseline se proverava rucno
+#[tokio::test]
+async fn native_run_stays_within_baseline() {
+    let baseline =
+        Baseline::load(&Path::new(env!("CARGO_MANIFEST_DIR")).join("baseline.json")).unwrap();
+    let results = bridge_bench::run(Mode::Native).await.unwrap();
+    for comparison in baseline.compare(Mode::Native, &results, 5.0).unwrap() {
+        assert!(
+            comparison.baseline.is_some() && !comparison.regressed,
+            "{} costs {} units, baseline {:?}",
+            comparison.case,
+            comparison.current,
+            comparison.baseline
+        );
+    }
+}
@@ -0,0 +1,935 @@
+mod output;
+
+use std::{error::Error, path::PathBuf, str::FromStr};
+
+use bridge_client::{reconcile, BridgeClient, BridgeClientError};
+use clap::{Parser, Subcommand};
+use escrow_contract::instruction as escrow_instruction;
+use logger_contract::{instruction as logger_instruction, state::LoggerState};
+use output::{
+    print, AuditView, EscrowView, LoggerStatusView, MessageListView, MessageView, OutputFormat,
+    TransactionView,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, write_keypair_file, Keypair, Signer},
+    system_instruction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account,
+};
+
+const DEFAULT_ESCROW_PROGRAM_ID: &str = "6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R";
+const DEFAULT_LOGGER_PROGRAM_ID: &str = "HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv";
+
+#[derive(Parser)]
+#[command(name = "bridge-cli", about = "Operate the escrow and logger programs")]
+struct Cli {
+    /// JSON RPC URL of the cluster
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    /// Keypair that signs and pays for transactions
+    #[arg(long, short = 'k', global = true)]
+    keypair: Option<PathBuf>,
+
+    #[arg(long, global = true, default_value = DEFAULT_ESCROW_PROGRAM_ID)]
+    escrow_program_id: Pubkey,
+
+    #[arg(long, global = true, default_value = DEFAULT_LOGGER_PROGRAM_ID)]
+    logger_program_id: Pubkey,
+
+    /// Logger state address, or the keypair file it was created from
+    #[arg(long, global = true, default_value = "logger_state.json")]
+    logger_state: String,
+
+    #[arg(long, global = true, value_enum, default_value = "display")]
+    output: OutputFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create and initialize a logger state account
+    Init {
+        /// Where to save the new logger state keypair
+        #[arg(long, default_value = "logger_state.json")]
+        out: PathBuf,
+        /// Keypair of the authority allowed to close messages, which signs
+        /// the initialization too (defaults to the signer)
+        #[arg(long)]
+        authority: Option<PathBuf>,
+    },
+    /// Create the escrow config trusting --logger-program-id and --logger-state
+    /// (signed by the escrow upgrade authority)
+    InitConfig,
+    /// Point the escrow config at --logger-program-id and --logger-state (admin only)
+    SetLogger,
+    /// Hand the escrow admin over to another key or a multisig (admin only)
+    SetAdmin { new_admin: Pubkey },
+    /// Require a multisig to approve withdrawals above a threshold (admin only)
+    SetWithdrawalPolicy {
+        #[arg(long)]
+        multisig: Pubkey,
+        /// Largest amount withdrawable without the multisig (u64::MAX turns the rule off)
+        #[arg(long)]
+        threshold: u64,
+    },
+    /// Create an M-of-N multisig owned by the escrow program
+    CreateMultisig {
+        #[arg(long)]
+        threshold: u8,
+        #[arg(long = "signer", required = true)]
+        signers: Vec<Pubkey>,
+    },
+    /// Propose an admin action of a multisig admin; the signer must be a member
+    Propose {
+        #[arg(long)]
+        multisig: Pubkey,
+        #[command(subcommand)]
+        action: AdminAction,
+    },
+    /// Approve a pending proposal as a multisig member
+    Approve {
+        #[arg(long)]
+        multisig: Pubkey,
+        #[arg(long)]
+        proposal: Pubkey,
+    },
+    /// Run an approved proposal; repeat the proposed action
+    Execute {
+        #[arg(long)]
+        multisig: Pubkey,
+        /// Member who proposed it, refunded the proposal rent
+        #[arg(long)]
+        proposer: Pubkey,
+        #[command(subcommand)]
+        action: AdminAction,
+    },
+    /// Deposit tokens into the escrow vault
+    Deposit {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Source token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Withdraw tokens from the escrow vault
+    Withdraw {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Destination token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+        /// Owner of the position when the signer withdraws as its delegate
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Deposit several mints in one transaction
+    BatchDeposit {
+        /// <mint>:<amount>, from the signer's associated account of the mint
+        #[arg(long = "leg", value_parser = parse_leg, required = true)]
+        legs: Vec<(Pubkey, u64)>,
+    },
+    /// Withdraw several mints in one transaction to the signer's associated accounts
+    BatchWithdraw {
+        /// <mint>:<amount>
+        #[arg(long = "leg", value_parser = parse_leg, required = true)]
+        legs: Vec<(Pubkey, u64)>,
+        /// Owner of the positions when the signer withdraws as their delegate
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+    /// Let a delegate withdraw from the signer's position (no --delegate revokes)
+    SetDelegate {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        delegate: Option<Pubkey>,
+        /// Most the delegate may withdraw in total
+        #[arg(long)]
+        cap: Option<u64>,
+        /// Unix timestamp after which the delegate can no longer withdraw
+        #[arg(long)]
+        expires_at: Option<u64>,
+    },
+    /// Grant tokens to a beneficiary, unlocking linearly until --end
+    CreateVesting {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        beneficiary: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Unix timestamp the schedule starts from
+        #[arg(long)]
+        start: u64,
+        /// Unix timestamp before which nothing can be claimed (defaults to --start)
+        #[arg(long)]
+        cliff: Option<u64>,
+        /// Unix timestamp at which the whole grant is unlocked
+        #[arg(long)]
+        end: u64,
+        /// Let the signer revoke the unvested remainder later
+        #[arg(long)]
+        revocable: bool,
+        /// Source token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Claim the unlocked part of a grant made to the signer
+    ClaimVested {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        grantor: Pubkey,
+        /// Destination token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Take back the unvested part of a revocable grant made by the signer
+    RevokeVesting {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        beneficiary: Pubkey,
+        /// Destination token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Lock a payment for a payee until it is released, ruled on or refunded
+    OpenAgreement {
+        #[arg(long)]
+        mint: Pubkey,
+        #[arg(long)]
+        payee: Pubkey,
+        #[arg(long)]
+        amount: u64,
+        /// Number that tells the signer's agreements apart
+        #[arg(long)]
+        id: u64,
+        /// Unix timestamp after which an undisputed agreement can be refunded
+        #[arg(long)]
+        deadline: u64,
+        /// Who rules on disputes (none means the agreement cannot be disputed)
+        #[arg(long)]
+        arbiter: Option<Pubkey>,
+        /// Source token account (defaults to the signer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Pay one of the signer's agreements out to its payee
+    Release {
+        #[arg(long)]
+        id: u64,
+        /// Destination token account (defaults to the payee's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Hand an agreement to its arbiter, as its payer or payee
+    OpenDispute {
+        /// Payer of the agreement (defaults to the signer)
+        #[arg(long)]
+        payer: Option<Pubkey>,
+        #[arg(long)]
+        id: u64,
+    },
+    /// Rule on a disputed agreement as its arbiter
+    Resolve {
+        #[arg(long)]
+        payer: Pubkey,
+        #[arg(long)]
+        id: u64,
+        /// Rule for the payer instead of the payee
+        #[arg(long)]
+        refund: bool,
+        /// Destination token account (defaults to the winner's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Return an agreement to its payer after the deadline or an unanswered dispute
+    Refund {
+        /// Payer of the agreement (defaults to the signer)
+        #[arg(long)]
+        payer: Option<Pubkey>,
+        #[arg(long)]
+        id: u64,
+        /// Destination token account (defaults to the payer's associated account)
+        #[arg(long)]
+        token_account: Option<Pubkey>,
+    },
+    /// Show the escrow state for a mint
+    ShowEscrow { mint: Pubkey },
+    /// List logger messages still stored on chain
+    ListMessages {
+        #[arg(long, default_value_t = 1)]
+        from_seq: u64,
+    },
+    /// Show a single logger message
+    ShowMessage { sequence: u64 },
+    /// Show the logger sequence, authority and accumulator
+    LoggerStatus,
+    /// Check vault balances, escrow totals and logger history against each other
+    Audit,
+    /// Create a mint owned by the signer and fund the signer's token account
+    CreateTestMint {
+        #[arg(long, default_value_t = 9)]
+        decimals: u8,
+        #[arg(long, default_value_t = 1_000)]
+        amount: u64,
+    },
+}
+
+/// Admin instruction run on behalf of a multisig admin.
+#[derive(Subcommand)]
+enum AdminAction {
+    #[command(name = "set-logger")]
+    Logger,
+    #[command(name = "set-admin")]
+    Admin { new_admin: Pubkey },
+    #[command(name = "set-withdrawal-policy")]
+    WithdrawalPolicy {
+        #[arg(long)]
+        withdrawal_multisig: Pubkey,
+        #[arg(long)]
+        threshold: u64,
+    },
+}
+
+impl AdminAction {
+    fn instruction(&self, client: &BridgeClient, admin: &Pubkey) -> Instruction {
+        match self {
+            AdminAction::Logger => client.set_logger_instruction(admin),
+            AdminAction::Admin { new_admin } => {
+                escrow_instruction::set_admin(&client.escrow_program_id, admin, new_admin, &[])
+            }
+            AdminAction::WithdrawalPolicy {
+                withdrawal_multisig,
+                threshold,
+            } => escrow_instruction::set_withdrawal_policy(
+                &client.escrow_program_id,
+                admin,
+                withdrawal_multisig,
+                *threshold,
+                &[],
+            ),
+        }
+    }
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(err) = run(cli) {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    let rpc = RpcClient::new_with_commitment(cli.url.clone(), CommitmentConfig::confirmed());
+    let logger_state = match cli.command {
+        Command::Init { .. } => Pubkey::default(),
+        _ => resolve_pubkey(&cli.logger_state)?,
+    };
+    let client = BridgeClient::new(
+        rpc,
+        cli.escrow_program_id,
+        cli.logger_program_id,
+        logger_state,
+    );
+
+    match cli.command {
+        Command::Init { ref out, ref authority } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let authority = match authority {
+                Some(_) => Some(load_keypair(authority)?),
+                None => None,
+            };
+            let state = Keypair::new();
+            let space = LoggerState::LEN;
+            let lamports = client.rpc().get_minimum_balance_for_rent_exemption(space)?;
+            let instructions = [
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &state.pubkey(),
+                    lamports,
+                    space as u64,
+                    &cli.logger_program_id,
+                ),
+                logger_instruction::initialize(
+                    &cli.logger_program_id,
+                    &state.pubkey(),
+                    &authority.as_ref().unwrap_or(&payer).pubkey(),
+                ),
+            ];
+            let mut signers = vec![&state];
+            signers.extend(authority.as_ref());
+            let signature = client.send(&instructions, &payer, &signers)?;
+            write_keypair_file(&state, out)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Logger state".to_string(), state.pubkey().to_string())],
+                },
+            );
+        }
+        Command::InitConfig | Command::SetLogger => {
+            let admin = load_keypair(&cli.keypair)?;
+            let signature = match cli.command {
+                Command::InitConfig => client.initialize_config(&admin)?,
+                _ => client.set_logger(&admin)?,
+            };
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![
+                        ("Config".to_string(), client.config_address().to_string()),
+                        ("Logger state".to_string(), client.logger_state.to_string()),
+                    ],
+                },
+            );
+        }
+        Command::SetAdmin { new_admin } => {
+            let admin = load_keypair(&cli.keypair)?;
+            let signature = client.set_admin(&admin, &new_admin)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Admin".to_string(), new_admin.to_string())],
+                },
+            );
+        }
+        Command::SetWithdrawalPolicy {
+            multisig,
+            threshold,
+        } => {
+            let admin = load_keypair(&cli.keypair)?;
+            let signature = client.set_withdrawal_policy(&admin, &multisig, threshold)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Withdrawal multisig".to_string(), multisig.to_string())],
+                },
+            );
+        }
+        Command::CreateMultisig { threshold, signers } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let (signature, multisig) = client.create_multisig(&payer, threshold, &signers)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Multisig".to_string(), multisig.to_string())],
+                },
+            );
+        }
+        Command::Propose {
+            multisig,
+            ref action,
+        } => {
+            let proposer = load_keypair(&cli.keypair)?;
+            let instruction = action.instruction(&client, &multisig);
+            let (signature, proposal) = client.propose(&proposer, &multisig, &instruction)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Proposal".to_string(), proposal.to_string())],
+                },
+            );
+        }
+        Command::Approve { multisig, proposal } => {
+            let signer = load_keypair(&cli.keypair)?;
+            let signature = client.approve(&signer, &multisig, &proposal)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Proposal".to_string(), proposal.to_string())],
+                },
+            );
+        }
+        Command::Execute {
+            multisig,
+            proposer,
+            ref action,
+        } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let instruction = action.instruction(&client, &multisig);
+            let signature = client.execute(&payer, &proposer, &multisig, &instruction)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![("Multisig".to_string(), multisig.to_string())],
+                },
+            );
+        }
+        Command::Deposit {
+            mint,
+            amount,
+            token_account,
+        } => {
+            let user = load_keypair(&cli.keypair)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&user.pubkey(), &mint));
+            let (signature, sequence) = client.deposit(&user, &token_account, &mint, amount)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Vault".to_string(), client.vault_address(&mint).to_string())],
+                },
+            );
+        }
+        Command::Withdraw {
+            mint,
+            amount,
+            token_account,
+            owner,
+        } => {
+            let authority = load_keypair(&cli.keypair)?;
+            let owner = owner.unwrap_or_else(|| authority.pubkey());
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&authority.pubkey(), &mint));
+            let (signature, sequence) =
+                client.withdraw(&authority, &owner, &token_account, &mint, amount)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::BatchDeposit { legs } => {
+            let user = load_keypair(&cli.keypair)?;
+            let legs: Vec<(Pubkey, Pubkey, u64)> = legs
+                .into_iter()
+                .map(|(mint, amount)| {
+                    (get_associated_token_address(&user.pubkey(), &mint), mint, amount)
+                })
+                .collect();
+            let (signature, sequence) = client.batch_deposit(&user, &legs)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: legs
+                        .iter()
+                        .map(|(_, mint, _)| ("Vault".to_string(), client.vault_address(mint).to_string()))
+                        .collect(),
+                },
+            );
+        }
+        Command::BatchWithdraw { legs, owner } => {
+            let authority = load_keypair(&cli.keypair)?;
+            let owner = owner.unwrap_or_else(|| authority.pubkey());
+            let legs: Vec<(Pubkey, Pubkey, u64)> = legs
+                .into_iter()
+                .map(|(mint, amount)| {
+                    (get_associated_token_address(&authority.pubkey(), &mint), mint, amount)
+                })
+                .collect();
+            let (signature, sequence) = client.batch_withdraw(&authority, &owner, &legs)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: legs
+                        .iter()
+                        .map(|(token_account, _, _)| ("Recipient".to_string(), token_account.to_string()))
+                        .collect(),
+                },
+            );
+        }
+        Command::SetDelegate {
+            mint,
+            delegate,
+            cap,
+            expires_at,
+        } => {
+            let owner = load_keypair(&cli.keypair)?;
+            let signature = client.set_delegate(
+                &owner,
+                &mint,
+                &delegate.unwrap_or_default(),
+                cap,
+                expires_at,
+            )?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![(
+                        "Position".to_string(),
+                        client.position_address(&mint, &owner.pubkey()).to_string(),
+                    )],
+                },
+            );
+        }
+        Command::CreateVesting {
+            mint,
+            beneficiary,
+            amount,
+            start,
+            cliff,
+            end,
+            revocable,
+            token_account,
+        } => {
+            let grantor = load_keypair(&cli.keypair)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&grantor.pubkey(), &mint));
+            let (signature, sequence) = client.create_vesting(
+                &grantor,
+                &token_account,
+                &beneficiary,
+                &mint,
+                amount,
+                start,
+                cliff.unwrap_or(start),
+                end,
+                revocable,
+            )?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![(
+                        "Vesting".to_string(),
+                        client
+                            .vesting_address(&mint, &grantor.pubkey(), &beneficiary)
+                            .to_string(),
+                    )],
+                },
+            );
+        }
+        Command::ClaimVested {
+            mint,
+            grantor,
+            token_account,
+        } => {
+            let beneficiary = load_keypair(&cli.keypair)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&beneficiary.pubkey(), &mint));
+            let (signature, sequence) =
+                client.claim_vested(&beneficiary, &token_account, &grantor, &mint)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::RevokeVesting {
+            mint,
+            beneficiary,
+            token_account,
+        } => {
+            let grantor = load_keypair(&cli.keypair)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&grantor.pubkey(), &mint));
+            let (signature, sequence) =
+                client.revoke_vesting(&grantor, &token_account, &beneficiary, &mint)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::OpenAgreement {
+            mint,
+            payee,
+            amount,
+            id,
+            deadline,
+            arbiter,
+            token_account,
+        } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&payer.pubkey(), &mint));
+            let (signature, sequence) = client.open_agreement(
+                &payer,
+                &token_account,
+                &payee,
+                &mint,
+                id,
+                amount,
+                deadline,
+                arbiter,
+            )?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![(
+                        "Agreement".to_string(),
+                        client.agreement_address(&payer.pubkey(), id).to_string(),
+                    )],
+                },
+            );
+        }
+        Command::Release { id, token_account } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let agreement = client.get_agreement(&payer.pubkey(), id)?;
+            let token_account = token_account.unwrap_or_else(|| {
+                get_associated_token_address(&agreement.payee, &agreement.token_mint)
+            });
+            let (signature, sequence) =
+                client.release(&payer, &token_account, &agreement.token_mint, id)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::OpenDispute { payer, id } => {
+            let party = load_keypair(&cli.keypair)?;
+            let payer = payer.unwrap_or_else(|| party.pubkey());
+            let (signature, sequence) = client.open_dispute(&party, &payer, id)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![(
+                        "Agreement".to_string(),
+                        client.agreement_address(&payer, id).to_string(),
+                    )],
+                },
+            );
+        }
+        Command::Resolve {
+            payer,
+            id,
+            refund,
+            token_account,
+        } => {
+            let arbiter = load_keypair(&cli.keypair)?;
+            let agreement = client.get_agreement(&payer, id)?;
+            let winner = if refund { agreement.payer } else { agreement.payee };
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&winner, &agreement.token_mint));
+            let (signature, sequence) = client.resolve(
+                &arbiter,
+                &token_account,
+                &payer,
+                &agreement.token_mint,
+                id,
+                !refund,
+            )?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::Refund {
+            payer,
+            id,
+            token_account,
+        } => {
+            let caller = load_keypair(&cli.keypair)?;
+            let payer = payer.unwrap_or_else(|| caller.pubkey());
+            let agreement = client.get_agreement(&payer, id)?;
+            let token_account = token_account
+                .unwrap_or_else(|| get_associated_token_address(&payer, &agreement.token_mint));
+            let (signature, sequence) =
+                client.refund(&caller, &token_account, &payer, &agreement.token_mint, id)?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: Some(sequence),
+                    accounts: vec![("Recipient".to_string(), token_account.to_string())],
+                },
+            );
+        }
+        Command::ShowEscrow { mint } => {
+            let state = client.get_escrow_state(&mint)?;
+            let vault_balance = client
+                .rpc()
+                .get_token_account_balance(&state.escrow_vault_account)
+                .ok()
+                .and_then(|balance| balance.amount.parse().ok());
+            print(
+                cli.output,
+                &EscrowView::new(&client.escrow_address(&mint), &state, vault_balance),
+            );
+        }
+        Command::ListMessages { from_seq } => {
+            let current = client.logger_sequence()?;
+            let mut list = MessageListView {
+                messages: Vec::new(),
+                closed: Vec::new(),
+            };
+            for sequence in from_seq.max(1)..=current {
+                match client.get_message(sequence) {
+                    Ok(message) => list.messages.push(MessageView::new(
+                        &client.message_address(sequence),
+                        &message,
+                    )),
+                    Err(BridgeClientError::AccountNotFound(_)) => list.closed.push(sequence),
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            print(cli.output, &list);
+        }
+        Command::ShowMessage { sequence } => {
+            let message = client.get_message(sequence)?;
+            print(
+                cli.output,
+                &MessageView::new(&client.message_address(sequence), &message),
+            );
+        }
+        Command::LoggerStatus => {
+            let state = client.get_logger_state()?;
+            print(
+                cli.output,
+                &LoggerStatusView::new(&client.logger_state, &state),
+            );
+        }
+        Command::Audit => {
+            let escrows = client.get_escrow_snapshots()?;
+            let messages = client.get_messages_of(&client.get_logger_states()?)?;
+            let batches = client.get_batches(&messages)?;
+            let discrepancies = reconcile(&escrows, &messages, &batches, &client.config_address());
+            let view = AuditView::new(escrows.len(), messages.len() as u64, &discrepancies);
+            print(cli.output, &view);
+            if !view.discrepancies.is_empty() {
+                return Err(format!("{} discrepancy(ies) found", view.discrepancies.len()).into());
+            }
+        }
+        Command::CreateTestMint { decimals, amount } => {
+            let payer = load_keypair(&cli.keypair)?;
+            let mint = Keypair::new();
+            let lamports = client
+                .rpc()
+                .get_minimum_balance_for_rent_exemption(spl_token::state::Mint::LEN)?;
+            let token_account = get_associated_token_address(&payer.pubkey(), &mint.pubkey());
+            let instructions = [
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    lamports,
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &payer.pubkey(),
+                    None,
+                    decimals,
+                )?,
+                create_associated_token_account(
+                    &payer.pubkey(),
+                    &payer.pubkey(),
+                    &mint.pubkey(),
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &token_account,
+                    &payer.pubkey(),
+                    &[],
+                    amount,
+                )?,
+            ];
+            let signature = client.send(&instructions, &payer, &[&mint])?;
+            print(
+                cli.output,
+                &TransactionView {
+                    signature: signature.to_string(),
+                    sequence: None,
+                    accounts: vec![
+                        ("Mint".to_string(), mint.pubkey().to_string()),
+                        ("Token account".to_string(), token_account.to_string()),
+                    ],
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn load_keypair(path: &Option<PathBuf>) -> Result<Keypair, Box<dyn Error>> {
+    let path = match path {
+        Some(path) => path.clone(),
+        None => {
+            let home = std::env::var("HOME").map_err(|_| "HOME is not set, pass --keypair")?;
+            PathBuf::from(home).join(".config/solana/id.json")
+        }
+    };
+    read_keypair_file(&path)
+        .map_err(|err| format!("failed to read keypair {}: {}", path.display(), err).into())
+}
+
+fn parse_leg(value: &str) -> Result<(Pubkey, u64), String> {
+    let (mint, amount) = value
+        .split_once(':')
+        .ok_or_else(|| format!("{} is not <mint>:<amount>", value))?;
+    let mint = Pubkey::from_str(mint).map_err(|err| format!("bad mint {}: {}", mint, err))?;
+    let amount = amount
+        .parse()
+        .map_err(|err| format!("bad amount {}: {}", amount, err))?;
+    Ok((mint, amount))
+}
+
+fn resolve_pubkey(value: &str) -> Result<Pubkey, Box<dyn Error>> {
+    if let Ok(pubkey) = Pubkey::from_str(value) {
+        return Ok(pubkey);
+    }
+    read_keypair_file(value)
+        .map(|keypair| keypair.pubkey())
+        .map_err(|err| format!("{} is neither a pubkey nor a keypair file: {}", value, err).into())
+}
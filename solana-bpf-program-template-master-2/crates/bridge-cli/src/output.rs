@@ -0,0 +1,207 @@
+use std::fmt;
+
+use bridge_client::Discrepancy;
+use clap::ValueEnum;
+use escrow_contract::state::EscrowState;
+use logger_contract::state::{ConsistencyLevel, LoggerState, MessageData};
+use serde::Serialize;
+use solana_sdk::{hash::Hash, pubkey::Pubkey};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Display,
+    Json,
+}
+
+pub fn print<T: Serialize + fmt::Display>(format: OutputFormat, value: &T) {
+    match format {
+        OutputFormat::Display => println!("{}", value),
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::to_string_pretty(value).expect("output is always serializable")
+        ),
+    }
+}
+
+#[derive(Serialize)]
+pub struct EscrowView {
+    pub address: String,
+    pub token_mint: String,
+    pub vault: String,
+    pub total_deposited: u64,
+    pub vault_balance: Option<u64>,
+}
+
+impl EscrowView {
+    pub fn new(address: &Pubkey, state: &EscrowState, vault_balance: Option<u64>) -> Self {
+        Self {
+            address: address.to_string(),
+            token_mint: state.token_mint.to_string(),
+            vault: state.escrow_vault_account.to_string(),
+            total_deposited: state.total_deposited.get(),
+            vault_balance,
+        }
+    }
+}
+
+impl fmt::Display for EscrowView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Escrow:          {}", self.address)?;
+        writeln!(f, "Mint:            {}", self.token_mint)?;
+        writeln!(f, "Vault:           {}", self.vault)?;
+        write!(f, "Total deposited: {}", self.total_deposited)?;
+        if let Some(balance) = self.vault_balance {
+            write!(f, "\nVault balance:   {}", balance)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+pub struct MessageView {
+    pub address: String,
+    pub sequence: u64,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub timestamp: u64,
+    pub payer: String,
+    pub slot: u64,
+    pub epoch: u64,
+    pub consistency_level: &'static str,
+}
+
+impl MessageView {
+    pub fn new(address: &Pubkey, message: &MessageData) -> Self {
+        Self {
+            address: address.to_string(),
+            sequence: message.sequence.get(),
+            from: message.from_pubkey.to_string(),
+            to: message.to_pubkey.to_string(),
+            amount: message.amount.get(),
+            timestamp: message.timestamp.get(),
+            payer: message.payer.to_string(),
+            slot: message.slot.get(),
+            epoch: message.epoch.get(),
+            consistency_level: match message.consistency_level() {
+                ConsistencyLevel::Confirmed => "confirmed",
+                ConsistencyLevel::Finalized => "finalized",
+            },
+        }
+    }
+}
+
+impl fmt::Display for MessageView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Message #{} ({})", self.sequence, self.address)?;
+        writeln!(f, "  From:        {}", self.from)?;
+        writeln!(f, "  To:          {}", self.to)?;
+        writeln!(f, "  Amount:      {}", self.amount)?;
+        writeln!(f, "  Timestamp:   {}", self.timestamp)?;
+        writeln!(f, "  Payer:       {}", self.payer)?;
+        write!(
+            f,
+            "  Slot/epoch:  {}/{} ({})",
+            self.slot, self.epoch, self.consistency_level
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct MessageListView {
+    pub messages: Vec<MessageView>,
+    pub closed: Vec<u64>,
+}
+
+impl fmt::Display for MessageListView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for message in &self.messages {
+            writeln!(f, "{}", message)?;
+        }
+        write!(
+            f,
+            "{} message(s), {} closed",
+            self.messages.len(),
+            self.closed.len()
+        )
+    }
+}
+
+#[derive(Serialize)]
+pub struct LoggerStatusView {
+    pub address: String,
+    pub sequence: u64,
+    pub authority: String,
+    pub accumulator: String,
+}
+
+impl LoggerStatusView {
+    pub fn new(address: &Pubkey, state: &LoggerState) -> Self {
+        Self {
+            address: address.to_string(),
+            sequence: state.sequence.get(),
+            authority: state.authority.to_string(),
+            accumulator: Hash::new_from_array(state.accumulator).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for LoggerStatusView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Logger state: {}", self.address)?;
+        writeln!(f, "Sequence:     {}", self.sequence)?;
+        writeln!(f, "Authority:    {}", self.authority)?;
+        write!(f, "Accumulator:  {}", self.accumulator)
+    }
+}
+
+#[derive(Serialize)]
+pub struct TransactionView {
+    pub signature: String,
+    pub sequence: Option<u64>,
+    pub accounts: Vec<(String, String)>,
+}
+
+impl fmt::Display for TransactionView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (label, address) in &self.accounts {
+            writeln!(f, "{}: {}", label, address)?;
+        }
+        if let Some(sequence) = self.sequence {
+            writeln!(f, "Logger sequence: {}", sequence)?;
+        }
+        write!(f, "Signature: {}", self.signature)
+    }
+}
+
+#[derive(Serialize)]
+pub struct AuditView {
+    pub escrows: usize,
+    pub messages: u64,
+    pub discrepancies: Vec<String>,
+}
+
+impl AuditView {
+    pub fn new(escrows: usize, messages: u64, discrepancies: &[Discrepancy]) -> Self {
+        Self {
+            escrows,
+            messages,
+            discrepancies: discrepancies.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+impl fmt::Display for AuditView {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for discrepancy in &self.discrepancies {
+            writeln!(f, "{}", discrepancy)?;
+        }
+        write!(
+            f,
+            "Audited {} escrow(s) and {} message(s): {} discrepancy(ies)",
+            self.escrows,
+            self.messages,
+            self.discrepancies.len()
+        )
+    }
+}
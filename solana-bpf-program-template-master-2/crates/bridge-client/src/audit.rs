@@ -0,0 +1,249 @@
+use std::{collections::BTreeMap, fmt};
+
+use escrow_contract::state::Batch;
+use logger_contract::state::MessageData;
+use solana_sdk::pubkey::Pubkey;
+
+/// On-chain view of one escrow used by [`reconcile`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowSnapshot {
+    pub address: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub total_deposited: u64,
+    // None kada vault nalog ne postoji ili nije SPL token nalog
+    pub vault_balance: Option<u64>,
+}
+
+/// Where the audit found the message logged at one sequence.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoggedMessage {
+    /// The message account at the sequence's PDA.
+    Account(MessageData),
+    /// A ring buffer slot of the logger state.
+    Ring(MessageData),
+    /// Closed with `CloseMessage`, rebuilt from its `MessagePosted` event.
+    Closed(MessageData),
+    /// Overwritten in the ring by a newer sequence. Escrow messages always
+    /// go to PDAs, so an evicted one never moved vault tokens.
+    Evicted,
+    /// Neither an account, a ring slot nor a posted event exists.
+    Missing,
+}
+
+impl LoggedMessage {
+    pub fn message(&self) -> Option<&MessageData> {
+        match self {
+            LoggedMessage::Account(message)
+            | LoggedMessage::Ring(message)
+            | LoggedMessage::Closed(message) => Some(message),
+            LoggedMessage::Evicted | LoggedMessage::Missing => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// The vault token account is missing or is not a token account.
+    VaultMissing { mint: Pubkey, vault: Pubkey },
+    /// Vault balance differs from `total_deposited`, e.g. a direct transfer.
+    VaultBalance {
+        mint: Pubkey,
+        vault: Pubkey,
+        balance: u64,
+        total_deposited: u64,
+    },
+    /// Logged deposits minus withdrawals differ from `total_deposited`.
+    /// `messages` are the sequences that most likely explain the difference.
+    Ledger {
+        mint: Pubkey,
+        logged: i128,
+        total_deposited: u64,
+        messages: Vec<(Pubkey, u64)>,
+    },
+    /// No trace of a sequence below the logger sequence: no account, no
+    /// ring slot and no posted event.
+    MissingMessage { logger_state: Pubkey, sequence: u64 },
+    /// The message found for a sequence carries another sequence.
+    MisplacedMessage {
+        logger_state: Pubkey,
+        sequence: u64,
+        stored_sequence: u64,
+    },
+    /// A message names an escrow vault but the escrow did not post it, so
+    /// it is left out of the ledger.
+    ForeignEmitter {
+        logger_state: Pubkey,
+        sequence: u64,
+        emitter: Pubkey,
+    },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Discrepancy::VaultMissing { mint, vault } => {
+                write!(f, "mint {}: vault {} is not a token account", mint, vault)
+            }
+            Discrepancy::VaultBalance {
+                mint,
+                vault,
+                balance,
+                total_deposited,
+            } => write!(
+                f,
+                "mint {}: vault {} holds {} but escrow records {}",
+                mint, vault, balance, total_deposited
+            ),
+            Discrepancy::Ledger {
+                mint,
+                logged,
+                total_deposited,
+                messages,
+            } => write!(
+                f,
+                "mint {}: logged deposits minus withdrawals are {} but escrow records {} (messages {:?})",
+                mint, logged, total_deposited, messages
+            ),
+            Discrepancy::MissingMessage {
+                logger_state,
+                sequence,
+            } => {
+                write!(f, "message {} of {} is missing", sequence, logger_state)
+            }
+            Discrepancy::MisplacedMessage {
+                logger_state,
+                sequence,
+                stored_sequence,
+            } => write!(
+                f,
+                "message account for {} of {} claims sequence {}",
+                sequence, logger_state, stored_sequence
+            ),
+            Discrepancy::ForeignEmitter {
+                logger_state,
+                sequence,
+                emitter,
+            } => write!(
+                f,
+                "message {} of {} names an escrow vault but was posted by {}",
+                sequence, logger_state, emitter
+            ),
+        }
+    }
+}
+
+/// Checks every escrow against its vault balance and against the logger
+/// messages that moved tokens in and out of its vault. `messages` holds
+/// every sequence from 1 to the sequence of each logger state the escrow
+/// logged to, closed ones included, keyed by logger state and sequence;
+/// `batches` holds the legs of the batch messages among them. Only
+/// messages posted by `emitter`, the escrow config, enter the ledger.
+pub fn reconcile(
+    escrows: &[EscrowSnapshot],
+    messages: &BTreeMap<(Pubkey, u64), LoggedMessage>,
+    batches: &BTreeMap<(Pubkey, u64), Batch>,
+    emitter: &Pubkey,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+    let vaults: Vec<&Pubkey> = escrows.iter().map(|escrow| &escrow.vault).collect();
+
+    for ((logger_state, sequence), logged) in messages {
+        match logged.message() {
+            None if *logged == LoggedMessage::Missing => {
+                discrepancies.push(Discrepancy::MissingMessage {
+                    logger_state: *logger_state,
+                    sequence: *sequence,
+                })
+            }
+            Some(message) if message.sequence.get() != *sequence => {
+                discrepancies.push(Discrepancy::MisplacedMessage {
+                    logger_state: *logger_state,
+                    sequence: *sequence,
+                    stored_sequence: message.sequence.get(),
+                })
+            }
+            // Logger je deljen, tudje poruke smetaju tek kada pominju vault
+            Some(message)
+                if message.emitter != *emitter
+                    && (vaults.contains(&&message.from_pubkey)
+                        || vaults.contains(&&message.to_pubkey)) =>
+            {
+                discrepancies.push(Discrepancy::ForeignEmitter {
+                    logger_state: *logger_state,
+                    sequence: *sequence,
+                    emitter: message.emitter,
+                })
+            }
+            _ => {}
+        }
+    }
+
+    // Batch poruka se racuna kroz svoje legove, jer tek oni pogadjaju vault-ove
+    let transfers: Vec<((Pubkey, u64), MessageData)> = messages
+        .iter()
+        .filter_map(|(key, logged)| Some((key, logged.message()?)))
+        .filter(|(_, message)| message.emitter == *emitter)
+        .flat_map(|(key, message)| match batches.get(key) {
+            Some(batch) => batch.leg_messages(message).map(|leg| (*key, leg)).collect(),
+            None => vec![(*key, *message)],
+        })
+        .collect();
+
+    for escrow in escrows {
+        match escrow.vault_balance {
+            None => discrepancies.push(Discrepancy::VaultMissing {
+                mint: escrow.mint,
+                vault: escrow.vault,
+            }),
+            Some(balance) if balance != escrow.total_deposited => {
+                discrepancies.push(Discrepancy::VaultBalance {
+                    mint: escrow.mint,
+                    vault: escrow.vault,
+                    balance,
+                    total_deposited: escrow.total_deposited,
+                })
+            }
+            Some(_) => {}
+        }
+
+        // Deposit loguje korisnik -> vault, withdraw vault -> korisnik
+        let touching: Vec<&((Pubkey, u64), MessageData)> = transfers
+            .iter()
+            .filter(|(_, message)| {
+                message.to_pubkey == escrow.vault || message.from_pubkey == escrow.vault
+            })
+            .collect();
+        let logged = touching.iter().fold(0i128, |logged, (_, message)| {
+            if message.to_pubkey == escrow.vault {
+                logged + message.amount.get() as i128
+            } else {
+                logged - message.amount.get() as i128
+            }
+        });
+        if logged == escrow.total_deposited as i128 {
+            continue;
+        }
+
+        // Poruke ciji iznos tacno pokriva razliku su najverovatniji uzrok
+        // (npr. falsifikovana poruka), inace prijavljujemo sve poruke vault-a
+        let difference = (logged - escrow.total_deposited as i128).unsigned_abs();
+        let exact: Vec<(Pubkey, u64)> = touching
+            .iter()
+            .filter(|(_, message)| message.amount.get() as u128 == difference)
+            .map(|(key, _)| *key)
+            .collect();
+        discrepancies.push(Discrepancy::Ledger {
+            mint: escrow.mint,
+            logged,
+            total_deposited: escrow.total_deposited,
+            messages: if exact.is_empty() {
+                touching.iter().map(|(key, _)| *key).collect()
+            } else {
+                exact
+            },
+        });
+    }
+
+    discrepancies
+}
@@ -0,0 +1,1157 @@
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    thread::sleep,
+    time::{Duration, Instant},
+};
+
+use bridge_events::{parse_logs, BridgeEvent, MessagePosted};
+use escrow_contract::{
+    instruction::{self as escrow_instruction, BatchLeg},
+    state::{Agreement, Batch, EscrowConfig, EscrowState, Multisig, Position, Proposal, Vesting},
+};
+use logger_contract::{
+    pod::ZeroCopy,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
+use solana_sdk::{
+    account::Account,
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{
+    audit::{reconcile, Discrepancy, EscrowSnapshot, LoggedMessage},
+    error::BridgeClientError,
+    pda,
+    program_error::TransactionFailure,
+};
+
+const FINALITY_POLL_INTERVAL: Duration = Duration::from_millis(500);
+// Maksimum naloga po getMultipleAccounts pozivu
+const MULTIPLE_ACCOUNTS_CHUNK: usize = 100;
+// Maksimum potpisa po getSignaturesForAddress pozivu
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Typed access to the escrow and logger programs over JSON RPC.
+pub struct BridgeClient {
+    rpc: RpcClient,
+    pub escrow_program_id: Pubkey,
+    pub logger_program_id: Pubkey,
+    pub logger_state: Pubkey,
+}
+
+impl BridgeClient {
+    pub fn new(
+        rpc: RpcClient,
+        escrow_program_id: Pubkey,
+        logger_program_id: Pubkey,
+        logger_state: Pubkey,
+    ) -> Self {
+        Self {
+            rpc,
+            escrow_program_id,
+            logger_program_id,
+            logger_state,
+        }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn config_address(&self) -> Pubkey {
+        pda::config_address(&self.escrow_program_id)
+    }
+
+    pub fn escrow_address(&self, token_mint: &Pubkey) -> Pubkey {
+        pda::escrow_address(&self.escrow_program_id, token_mint)
+    }
+
+    pub fn vault_address(&self, token_mint: &Pubkey) -> Pubkey {
+        pda::vault_address(&self.escrow_program_id, token_mint)
+    }
+
+    pub fn position_address(&self, token_mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+        pda::position_address(&self.escrow_program_id, token_mint, owner)
+    }
+
+    pub fn vesting_address(
+        &self,
+        token_mint: &Pubkey,
+        grantor: &Pubkey,
+        beneficiary: &Pubkey,
+    ) -> Pubkey {
+        pda::vesting_address(&self.escrow_program_id, token_mint, grantor, beneficiary)
+    }
+
+    pub fn agreement_address(&self, payer: &Pubkey, id: u64) -> Pubkey {
+        pda::agreement_address(&self.escrow_program_id, payer, id)
+    }
+
+    pub fn message_address(&self, sequence: u64) -> Pubkey {
+        self.message_address_in(&self.logger_state, sequence)
+    }
+
+    /// Message PDA of `sequence` under another logger state, e.g. one the
+    /// escrow logged to before `SetLogger`.
+    pub fn message_address_in(&self, logger_state: &Pubkey, sequence: u64) -> Pubkey {
+        pda::message_address(&self.logger_program_id, logger_state, sequence)
+    }
+
+    /// Batch record of the message logged at `sequence`.
+    pub fn batch_address(&self, sequence: u64) -> Pubkey {
+        self.batch_address_in(&self.logger_state, sequence)
+    }
+
+    pub fn batch_address_in(&self, logger_state: &Pubkey, sequence: u64) -> Pubkey {
+        pda::batch_address(
+            &self.escrow_program_id,
+            &self.message_address_in(logger_state, sequence),
+        )
+    }
+
+    pub fn get_account(
+        &self,
+        address: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Account, BridgeClientError> {
+        self.rpc
+            .get_account_with_commitment(address, commitment)?
+            .value
+            .ok_or(BridgeClientError::AccountNotFound(*address))
+    }
+
+    // Nalog na adresi poruke koji logger ne poseduje nije poruka: ili ga je
+    // neko napravio sa laznim sadrzajem ili su na PDA samo poslati lamporti
+    fn get_message_account(
+        &self,
+        address: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Account, BridgeClientError> {
+        let account = self.get_account(address, commitment)?;
+        if account.owner != self.logger_program_id {
+            return Err(BridgeClientError::AccountNotFound(*address));
+        }
+        Ok(account)
+    }
+
+    pub fn get_logger_state(&self) -> Result<LoggerState, BridgeClientError> {
+        let account = self.get_account(&self.logger_state, self.rpc.commitment())?;
+        Ok(*LoggerState::load(&account.data)?)
+    }
+
+    pub fn logger_sequence(&self) -> Result<u64, BridgeClientError> {
+        Ok(self.get_logger_state()?.sequence.get())
+    }
+
+    /// Every logger state the escrow logged to, oldest first: the states
+    /// named by its `LoggerSetEvent`s, then the one in its config.
+    pub fn get_logger_states(&self) -> Result<Vec<Pubkey>, BridgeClientError> {
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.rpc.get_signatures_for_address_with_config(
+                &self.escrow_program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until: None,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(self.rpc.commitment()),
+                },
+            )?;
+            let last_page = page.len() < SIGNATURE_PAGE;
+            before = page
+                .last()
+                .and_then(|status| Signature::from_str(&status.signature).ok());
+            signatures.extend(page);
+            if last_page || before.is_none() {
+                break;
+            }
+        }
+
+        let mut states = Vec::new();
+        // RPC vraca najnovije prvo, migracije citamo od najstarije
+        for status in signatures.iter().rev() {
+            if status.err.is_some() {
+                continue;
+            }
+            let signature = match Signature::from_str(&status.signature) {
+                Ok(signature) => signature,
+                Err(_) => continue,
+            };
+            let logs = self.transaction_logs(&signature, self.rpc.commitment())?;
+            for event in parse_logs(&logs, &[self.escrow_program_id])? {
+                if let BridgeEvent::LoggerSet(set) = event.event {
+                    if event.program_id == self.escrow_program_id {
+                        states.push(set.previous_state);
+                        states.push(set.logger_state);
+                    }
+                }
+            }
+        }
+        // State iz config-a vazi i kada ga escrow jos nije menjao
+        match self.get_config() {
+            Ok(config) => states.push(config.logger_state),
+            Err(BridgeClientError::AccountNotFound(_)) => {}
+            Err(err) => return Err(err),
+        }
+        states.push(self.logger_state);
+
+        let mut unique = Vec::with_capacity(states.len());
+        for state in states {
+            if state != Pubkey::default() && !unique.contains(&state) {
+                unique.push(state);
+            }
+        }
+        Ok(unique)
+    }
+
+    pub fn get_config(&self) -> Result<EscrowConfig, BridgeClientError> {
+        let account = self.get_account(&self.config_address(), self.rpc.commitment())?;
+        Ok(*EscrowConfig::load(&account.data)?)
+    }
+
+    pub fn get_escrow_state(&self, token_mint: &Pubkey) -> Result<EscrowState, BridgeClientError> {
+        let account = self.get_account(&self.escrow_address(token_mint), self.rpc.commitment())?;
+        Ok(*EscrowState::load(&account.data)?)
+    }
+
+    pub fn get_position(
+        &self,
+        token_mint: &Pubkey,
+        owner: &Pubkey,
+    ) -> Result<Position, BridgeClientError> {
+        let account = self.get_account(
+            &self.position_address(token_mint, owner),
+            self.rpc.commitment(),
+        )?;
+        Ok(*Position::load(&account.data)?)
+    }
+
+    pub fn get_vesting(
+        &self,
+        token_mint: &Pubkey,
+        grantor: &Pubkey,
+        beneficiary: &Pubkey,
+    ) -> Result<Vesting, BridgeClientError> {
+        let account = self.get_account(
+            &self.vesting_address(token_mint, grantor, beneficiary),
+            self.rpc.commitment(),
+        )?;
+        Ok(*Vesting::load(&account.data)?)
+    }
+
+    pub fn get_agreement(&self, payer: &Pubkey, id: u64) -> Result<Agreement, BridgeClientError> {
+        let account = self.get_account(&self.agreement_address(payer, id), self.rpc.commitment())?;
+        Ok(*Agreement::load(&account.data)?)
+    }
+
+    pub fn get_message(&self, sequence: u64) -> Result<MessageData, BridgeClientError> {
+        let account =
+            self.get_message_account(&self.message_address(sequence), self.rpc.commitment())?;
+        Ok(*MessageData::load_unchecked(&account.data)?)
+    }
+
+    /// Legs of `message` when a batch logged it, `None` for any other message.
+    pub fn get_batch(&self, message: &MessageData) -> Result<Option<Batch>, BridgeClientError> {
+        self.get_batch_in(&self.logger_state, message)
+    }
+
+    /// Like `get_batch` for a message logged under `logger_state`.
+    pub fn get_batch_in(
+        &self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+    ) -> Result<Option<Batch>, BridgeClientError> {
+        if message.kind() != MessageKind::Batch {
+            return Ok(None);
+        }
+        let address = self.batch_address_in(logger_state, message.sequence.get());
+        let account = self.get_account(&address, self.rpc.commitment())?;
+        Ok(Some(*Batch::load(&account.data)?))
+    }
+
+    /// Batch records of the batch messages among `messages`, keyed by
+    /// logger state and sequence.
+    pub fn get_batches(
+        &self,
+        messages: &BTreeMap<(Pubkey, u64), LoggedMessage>,
+    ) -> Result<BTreeMap<(Pubkey, u64), Batch>, BridgeClientError> {
+        let mut batches = BTreeMap::new();
+        for ((logger_state, sequence), logged) in messages {
+            let batch = match logged.message() {
+                Some(message) => self.get_batch_in(logger_state, message)?,
+                None => None,
+            };
+            if let Some(batch) = batch {
+                batches.insert((*logger_state, *sequence), batch);
+            }
+        }
+        Ok(batches)
+    }
+
+    /// Every initialized escrow of the program with its vault balance.
+    pub fn get_escrow_snapshots(&self) -> Result<Vec<EscrowSnapshot>, BridgeClientError> {
+        let accounts = self.rpc.get_program_accounts_with_config(
+            &self.escrow_program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::DataSize(EscrowState::LEN as u64)]),
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+
+        let mut snapshots = Vec::new();
+        for (address, account) in accounts {
+            let state = EscrowState::load_unchecked(&account.data)?;
+            if !state.is_initialized() {
+                continue;
+            }
+            let vault_balance = self
+                .rpc
+                .get_account_with_commitment(&state.escrow_vault_account, self.rpc.commitment())?
+                .value
+                .filter(|vault| vault.owner == spl_token::id())
+                .and_then(|vault| spl_token::state::Account::unpack(&vault.data).ok())
+                .map(|vault| vault.amount);
+            snapshots.push(EscrowSnapshot {
+                address,
+                mint: state.token_mint,
+                vault: state.escrow_vault_account,
+                total_deposited: state.total_deposited.get(),
+                vault_balance,
+            });
+        }
+        Ok(snapshots)
+    }
+
+    /// Every sequence from `from` to `to`: its PDA account, else its ring
+    /// slot, else the event of a closed message.
+    pub fn get_messages(
+        &self,
+        from: u64,
+        to: u64,
+    ) -> Result<BTreeMap<u64, LoggedMessage>, BridgeClientError> {
+        self.get_messages_in(&self.logger_state, from, to)
+    }
+
+    /// Like `get_messages` for the messages of `logger_state`.
+    pub fn get_messages_in(
+        &self,
+        logger_state: &Pubkey,
+        from: u64,
+        to: u64,
+    ) -> Result<BTreeMap<u64, LoggedMessage>, BridgeClientError> {
+        let commitment = self.rpc.commitment();
+        let rings = self.get_rings_in(logger_state, commitment)?;
+        let sequences: Vec<u64> = (from.max(1)..=to).collect();
+        let mut messages = BTreeMap::new();
+        for chunk in sequences.chunks(MULTIPLE_ACCOUNTS_CHUNK) {
+            let addresses: Vec<Pubkey> = chunk
+                .iter()
+                .map(|sequence| self.message_address_in(logger_state, *sequence))
+                .collect();
+            let accounts = self
+                .rpc
+                .get_multiple_accounts_with_commitment(&addresses, commitment)?
+                .value;
+            for (sequence, account) in chunk.iter().zip(accounts) {
+                let account = account.filter(|account| account.owner == self.logger_program_id);
+                let logged = match account {
+                    Some(account) => {
+                        LoggedMessage::Account(*MessageData::load_unchecked(&account.data)?)
+                    }
+                    None => self.find_message(logger_state, *sequence, &rings, commitment)?,
+                };
+                messages.insert(*sequence, logged);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Every message of every state in `logger_states`, from 1 to the
+    /// sequence of that state, keyed by logger state and sequence.
+    pub fn get_messages_of(
+        &self,
+        logger_states: &[Pubkey],
+    ) -> Result<BTreeMap<(Pubkey, u64), LoggedMessage>, BridgeClientError> {
+        let mut messages = BTreeMap::new();
+        for logger_state in logger_states {
+            let account = self.get_account(logger_state, self.rpc.commitment())?;
+            let sequence = LoggerState::load(&account.data)?.sequence.get();
+            for (sequence, logged) in self.get_messages_in(logger_state, 1, sequence)? {
+                messages.insert((*logger_state, sequence), logged);
+            }
+        }
+        Ok(messages)
+    }
+
+    /// Message `sequence` wherever it is logged, looked up like `get_messages`.
+    pub fn get_logged_message(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        self.get_logged_message_with_commitment(sequence, self.rpc.commitment())
+    }
+
+    /// Like `get_logged_message`, reading every account and transaction at
+    /// `commitment`.
+    pub fn get_logged_message_with_commitment(
+        &self,
+        sequence: u64,
+        commitment: CommitmentConfig,
+    ) -> Result<LoggedMessage, BridgeClientError> {
+        match self.get_message_account(&self.message_address(sequence), commitment) {
+            Ok(account) => Ok(LoggedMessage::Account(*MessageData::load_unchecked(
+                &account.data,
+            )?)),
+            Err(BridgeClientError::AccountNotFound(_)) => {
+                let rings = self.get_rings_with_commitment(commitment)?;
+                self.find_message(&self.logger_state, sequence, &rings, commitment)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Data of every ring buffer bound to the logger state.
+    pub fn get_rings(&self) -> Result<Vec<Vec<u8>>, BridgeClientError> {
+        self.get_rings_with_commitment(self.rpc.commitment())
+    }
+
+    pub fn get_rings_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<Vec<u8>>, BridgeClientError> {
+        self.get_rings_in(&self.logger_state, commitment)
+    }
+
+    fn get_rings_in(
+        &self,
+        logger_state: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<Vec<u8>>, BridgeClientError> {
+        let header = RingHeader::new(*logger_state);
+        let accounts = self.rpc.get_program_accounts_with_config(
+            &self.logger_program_id,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_raw_bytes(
+                    0,
+                    bytemuck::bytes_of(&header).to_vec(),
+                ))]),
+                // Ring je veci od 128 bajtova koje base58 podrzava
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    commitment: Some(commitment),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+        Ok(accounts.into_iter().map(|(_, account)| account.data).collect())
+    }
+
+    // Sekvenca bez PDA naloga: ring slot, zatvorena poruka ili prepisan slot
+    fn find_message(
+        &self,
+        logger_state: &Pubkey,
+        sequence: u64,
+        rings: &[Vec<u8>],
+        commitment: CommitmentConfig,
+    ) -> Result<LoggedMessage, BridgeClientError> {
+        for ring in rings {
+            if let Some(message) = RingHeader::read_message(ring, sequence)? {
+                return Ok(LoggedMessage::Ring(message));
+            }
+        }
+        if let Some(message) = self.get_closed_message_in(logger_state, sequence, commitment)? {
+            return Ok(LoggedMessage::Closed(message));
+        }
+        // Slot sa novijom sekvencom istog ostatka znaci da je poruka prepisana
+        let evicted = rings.iter().any(|ring| {
+            let capacity = RingHeader::capacity(ring.len());
+            capacity > 0 && {
+                let offset = RingHeader::slot_offset(sequence, capacity);
+                MessageData::load(&ring[offset..offset + MessageData::LEN])
+                    .is_ok_and(|slot| slot.sequence.get() > sequence)
+            }
+        });
+        Ok(if evicted {
+            LoggedMessage::Evicted
+        } else {
+            LoggedMessage::Missing
+        })
+    }
+
+    /// Message `sequence` rebuilt from the `MessagePosted` event of the
+    /// transaction that created its PDA, once `CloseMessage` removed it.
+    pub fn get_closed_message(&self, sequence: u64) -> Result<Option<MessageData>, BridgeClientError> {
+        self.get_closed_message_with_commitment(sequence, self.rpc.commitment())
+    }
+
+    pub fn get_closed_message_with_commitment(
+        &self,
+        sequence: u64,
+        commitment: CommitmentConfig,
+    ) -> Result<Option<MessageData>, BridgeClientError> {
+        self.get_closed_message_in(&self.logger_state, sequence, commitment)
+    }
+
+    fn get_closed_message_in(
+        &self,
+        logger_state: &Pubkey,
+        sequence: u64,
+        commitment: CommitmentConfig,
+    ) -> Result<Option<MessageData>, BridgeClientError> {
+        let address = self.message_address_in(logger_state, sequence);
+        let signatures = self.rpc.get_signatures_for_address_with_config(
+            &address,
+            GetConfirmedSignaturesForAddress2Config {
+                commitment: Some(commitment),
+                ..GetConfirmedSignaturesForAddress2Config::default()
+            },
+        )?;
+        for status in signatures {
+            if status.err.is_some() {
+                continue;
+            }
+            let signature = match Signature::from_str(&status.signature) {
+                Ok(signature) => signature,
+                Err(_) => continue,
+            };
+            let logs = self.transaction_logs(&signature, commitment)?;
+            for event in parse_logs(&logs, &[self.logger_program_id])? {
+                match event.event {
+                    BridgeEvent::MessagePosted(posted)
+                        if event.program_id == self.logger_program_id
+                            && posted.logger_state == *logger_state
+                            && posted.sequence == sequence =>
+                    {
+                        return Ok(Some(closed_message(&posted)?));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    fn transaction_logs(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+    ) -> Result<Vec<String>, BridgeClientError> {
+        let transaction = self.rpc.get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(commitment),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        Ok(transaction
+            .transaction
+            .meta
+            .and_then(|meta| Option::from(meta.log_messages))
+            .unwrap_or_default())
+    }
+
+    /// Reconciles every escrow against its vault and the history of every
+    /// logger state it logged to.
+    pub fn audit(&self) -> Result<Vec<Discrepancy>, BridgeClientError> {
+        let escrows = self.get_escrow_snapshots()?;
+        let messages = self.get_messages_of(&self.get_logger_states()?)?;
+        let batches = self.get_batches(&messages)?;
+        Ok(reconcile(
+            &escrows,
+            &messages,
+            &batches,
+            &self.config_address(),
+        ))
+    }
+
+    /// Deposits `amount` of `token_mint` and returns the signature together
+    /// with the logger sequence assigned to the deposit message.
+    pub fn deposit(
+        &self,
+        user: &Keypair,
+        user_token_account: &Pubkey,
+        token_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &user.pubkey(),
+            user_token_account,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+            amount,
+        );
+        let signature = self.send(&[instruction], user, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Withdraws `amount` of `token_mint` from the position of `owner` into
+    /// `recipient_token_account` and returns the signature together with the
+    /// logger sequence. `authority` is the owner or its delegate.
+    pub fn withdraw(
+        &self,
+        authority: &Keypair,
+        owner: &Pubkey,
+        recipient_token_account: &Pubkey,
+        token_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &authority.pubkey(),
+            owner,
+            recipient_token_account,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+            amount,
+        );
+        let signature = self.send(&[instruction], authority, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Deposits every `(token account, mint, amount)` leg in one instruction.
+    /// The batch logs a single message; returns the signature and its
+    /// sequence, whose legs [`Self::get_batch`] reads back.
+    pub fn batch_deposit(
+        &self,
+        user: &Keypair,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::batch_deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &user.pubkey(),
+            &self.logger_state,
+            &self.message_address(sequence),
+            &batch_legs(legs),
+        );
+        let signature = self.send(&[instruction], user, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Withdraws every `(token account, mint, amount)` leg from the positions
+    /// of `owner` in one instruction, like [`Self::batch_deposit`].
+    pub fn batch_withdraw(
+        &self,
+        authority: &Keypair,
+        owner: &Pubkey,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::batch_withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &authority.pubkey(),
+            owner,
+            &self.logger_state,
+            &self.message_address(sequence),
+            &batch_legs(legs),
+        );
+        let signature = self.send(&[instruction], authority, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Names `delegate` as allowed to withdraw from the `token_mint` position
+    /// of `owner`, up to `cap` and until `expires_at` (unix seconds).
+    /// `Pubkey::default()` revokes the delegate.
+    pub fn set_delegate(
+        &self,
+        owner: &Keypair,
+        token_mint: &Pubkey,
+        delegate: &Pubkey,
+        cap: Option<u64>,
+        expires_at: Option<u64>,
+    ) -> Result<Signature, BridgeClientError> {
+        let instruction = escrow_instruction::set_delegate(
+            &self.escrow_program_id,
+            &owner.pubkey(),
+            token_mint,
+            delegate,
+            cap,
+            expires_at,
+        );
+        self.send(&[instruction], owner, &[])
+    }
+
+    /// Grants `amount` of `token_mint` to `beneficiary`, unlocking linearly
+    /// from `start` to `end` (unix seconds) with nothing claimable before
+    /// `cliff`. Returns the signature and the logger sequence.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_vesting(
+        &self,
+        grantor: &Keypair,
+        grantor_token_account: &Pubkey,
+        beneficiary: &Pubkey,
+        token_mint: &Pubkey,
+        amount: u64,
+        start: u64,
+        cliff: u64,
+        end: u64,
+        revocable: bool,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::create_vesting(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &grantor.pubkey(),
+            grantor_token_account,
+            beneficiary,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+            amount,
+            start,
+            cliff,
+            end,
+            revocable,
+        );
+        let signature = self.send(&[instruction], grantor, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Claims everything unlocked so far of `grantor`'s grant into
+    /// `recipient_token_account`.
+    pub fn claim_vested(
+        &self,
+        beneficiary: &Keypair,
+        recipient_token_account: &Pubkey,
+        grantor: &Pubkey,
+        token_mint: &Pubkey,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::claim_vested(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &beneficiary.pubkey(),
+            recipient_token_account,
+            grantor,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+        );
+        let signature = self.send(&[instruction], beneficiary, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Revokes the grant to `beneficiary`, returning the unvested remainder
+    /// to `grantor_token_account`.
+    pub fn revoke_vesting(
+        &self,
+        grantor: &Keypair,
+        grantor_token_account: &Pubkey,
+        beneficiary: &Pubkey,
+        token_mint: &Pubkey,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::revoke(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &grantor.pubkey(),
+            grantor_token_account,
+            beneficiary,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+        );
+        let signature = self.send(&[instruction], grantor, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Locks `amount` of `token_mint` for `payee` as agreement `id` until the
+    /// payer releases it, the arbiter rules, or `deadline` (unix seconds)
+    /// passes. Returns the signature and the logger sequence.
+    #[allow(clippy::too_many_arguments)]
+    pub fn open_agreement(
+        &self,
+        payer: &Keypair,
+        payer_token_account: &Pubkey,
+        payee: &Pubkey,
+        token_mint: &Pubkey,
+        id: u64,
+        amount: u64,
+        deadline: u64,
+        arbiter: Option<Pubkey>,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::open_agreement(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &payer.pubkey(),
+            payer_token_account,
+            payee,
+            token_mint,
+            &self.logger_state,
+            &self.message_address(sequence),
+            id,
+            amount,
+            deadline,
+            arbiter,
+        );
+        let signature = self.send(&[instruction], payer, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Releases the payer's agreement `id` to `payee_token_account`.
+    pub fn release(
+        &self,
+        payer: &Keypair,
+        payee_token_account: &Pubkey,
+        token_mint: &Pubkey,
+        id: u64,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::release(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &payer.pubkey(),
+            payee_token_account,
+            token_mint,
+            id,
+            &self.logger_state,
+            &self.message_address(sequence),
+        );
+        let signature = self.send(&[instruction], payer, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Disputes agreement `id` of `payer`; `party` is the payer or the payee.
+    pub fn open_dispute(
+        &self,
+        party: &Keypair,
+        payer: &Pubkey,
+        id: u64,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::open_dispute(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &party.pubkey(),
+            payer,
+            id,
+            &self.logger_state,
+            &self.message_address(sequence),
+        );
+        let signature = self.send(&[instruction], party, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Rules on a disputed agreement, paying `winner_token_account` of the
+    /// payee when `to_payee` and of the payer otherwise.
+    pub fn resolve(
+        &self,
+        arbiter: &Keypair,
+        winner_token_account: &Pubkey,
+        payer: &Pubkey,
+        token_mint: &Pubkey,
+        id: u64,
+        to_payee: bool,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::resolve(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &arbiter.pubkey(),
+            winner_token_account,
+            payer,
+            token_mint,
+            id,
+            &self.logger_state,
+            &self.message_address(sequence),
+            to_payee,
+        );
+        let signature = self.send(&[instruction], arbiter, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Refunds an agreement past its deadline (or its dispute deadline) to
+    /// `payer_token_account`; `caller` pays the fee and the message rent.
+    pub fn refund(
+        &self,
+        caller: &Keypair,
+        payer_token_account: &Pubkey,
+        payer: &Pubkey,
+        token_mint: &Pubkey,
+        id: u64,
+    ) -> Result<(Signature, u64), BridgeClientError> {
+        let sequence = self.logger_sequence()? + 1;
+        let instruction = escrow_instruction::refund(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &caller.pubkey(),
+            payer_token_account,
+            payer,
+            token_mint,
+            id,
+            &self.logger_state,
+            &self.message_address(sequence),
+        );
+        let signature = self.send(&[instruction], caller, &[])?;
+        Ok((signature, sequence))
+    }
+
+    /// Creates the escrow config with `admin` as its admin, trusting this
+    /// client's logger program and state.
+    pub fn initialize_config(&self, admin: &Keypair) -> Result<Signature, BridgeClientError> {
+        let instruction = escrow_instruction::initialize_config(
+            &self.escrow_program_id,
+            &admin.pubkey(),
+            &self.logger_program_id,
+            &self.logger_state,
+        );
+        self.send(&[instruction], admin, &[])
+    }
+
+    /// Points the escrow config at this client's logger program and state.
+    pub fn set_logger(&self, admin: &Keypair) -> Result<Signature, BridgeClientError> {
+        let instruction = self.set_logger_instruction(&admin.pubkey());
+        self.send(&[instruction], admin, &[])
+    }
+
+    /// `SetLogger` for `admin` without signers, the form a multisig admin
+    /// proposes and executes.
+    pub fn set_logger_instruction(&self, admin: &Pubkey) -> Instruction {
+        escrow_instruction::set_logger(
+            &self.escrow_program_id,
+            admin,
+            &self.logger_program_id,
+            &self.logger_state,
+            &[],
+        )
+    }
+
+    pub fn set_admin(
+        &self,
+        admin: &Keypair,
+        new_admin: &Pubkey,
+    ) -> Result<Signature, BridgeClientError> {
+        let instruction =
+            escrow_instruction::set_admin(&self.escrow_program_id, &admin.pubkey(), new_admin, &[]);
+        self.send(&[instruction], admin, &[])
+    }
+
+    /// Requires `multisig` to approve every withdrawal above `threshold`.
+    pub fn set_withdrawal_policy(
+        &self,
+        admin: &Keypair,
+        multisig: &Pubkey,
+        threshold: u64,
+    ) -> Result<Signature, BridgeClientError> {
+        let instruction = escrow_instruction::set_withdrawal_policy(
+            &self.escrow_program_id,
+            &admin.pubkey(),
+            multisig,
+            threshold,
+            &[],
+        );
+        self.send(&[instruction], admin, &[])
+    }
+
+    pub fn get_multisig(&self, address: &Pubkey) -> Result<Multisig, BridgeClientError> {
+        let account = self.get_account(address, self.rpc.commitment())?;
+        Ok(*Multisig::load(&account.data)?)
+    }
+
+    pub fn get_proposal(&self, address: &Pubkey) -> Result<Proposal, BridgeClientError> {
+        let account = self.get_account(address, self.rpc.commitment())?;
+        Ok(*Proposal::load(&account.data)?)
+    }
+
+    /// Creates a `threshold`-of-`signers` multisig paid by `payer` and
+    /// returns its address.
+    pub fn create_multisig(
+        &self,
+        payer: &Keypair,
+        threshold: u8,
+        signers: &[Pubkey],
+    ) -> Result<(Signature, Pubkey), BridgeClientError> {
+        let multisig = Keypair::new();
+        let lamports = self
+            .rpc
+            .get_minimum_balance_for_rent_exemption(Multisig::LEN)?;
+        let signers: Vec<&Pubkey> = signers.iter().collect();
+        let instructions = [
+            system_instruction::create_account(
+                &payer.pubkey(),
+                &multisig.pubkey(),
+                lamports,
+                Multisig::LEN as u64,
+                &self.escrow_program_id,
+            ),
+            escrow_instruction::initialize_multisig(
+                &self.escrow_program_id,
+                &multisig.pubkey(),
+                &signers,
+                threshold,
+            ),
+        ];
+        let signature = self.send(&instructions, payer, &[&multisig])?;
+        Ok((signature, multisig.pubkey()))
+    }
+
+    /// Proposes `instruction` to `multisig` and returns the proposal
+    /// address; the proposer's approval is included.
+    pub fn propose(
+        &self,
+        proposer: &Keypair,
+        multisig: &Pubkey,
+        instruction: &Instruction,
+    ) -> Result<(Signature, Pubkey), BridgeClientError> {
+        let propose = escrow_instruction::propose(
+            &self.escrow_program_id,
+            &proposer.pubkey(),
+            multisig,
+            instruction,
+        );
+        let signature = self.send(&[propose], proposer, &[])?;
+        let proposal =
+            escrow_instruction::proposal_address(&self.escrow_program_id, multisig, instruction);
+        Ok((signature, proposal))
+    }
+
+    pub fn approve(
+        &self,
+        signer: &Keypair,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+    ) -> Result<Signature, BridgeClientError> {
+        let instruction = escrow_instruction::approve(
+            &self.escrow_program_id,
+            &signer.pubkey(),
+            multisig,
+            proposal,
+        );
+        self.send(&[instruction], signer, &[])
+    }
+
+    /// Runs an approved `instruction` for `multisig` and closes its proposal.
+    pub fn execute(
+        &self,
+        payer: &Keypair,
+        proposer: &Pubkey,
+        multisig: &Pubkey,
+        instruction: &Instruction,
+    ) -> Result<Signature, BridgeClientError> {
+        let execute =
+            escrow_instruction::execute(&self.escrow_program_id, proposer, multisig, instruction);
+        self.send(&[execute], payer, &[])
+    }
+
+    /// Whether `message` is logged at the consistency level the emitter
+    /// asked for.
+    pub fn is_final(&self, message: &MessageData) -> Result<bool, BridgeClientError> {
+        let commitment = match message.consistency_level() {
+            ConsistencyLevel::Confirmed => CommitmentConfig::confirmed(),
+            ConsistencyLevel::Finalized => CommitmentConfig::finalized(),
+        };
+        self.is_final_with_commitment(message, commitment)
+    }
+
+    /// Whether `message` is logged at `commitment`. A slot that reached the
+    /// commitment proves nothing on its own, the message may sit on a fork
+    /// that never gets there, so the message itself is read back.
+    pub fn is_final_with_commitment(
+        &self,
+        message: &MessageData,
+        commitment: CommitmentConfig,
+    ) -> Result<bool, BridgeClientError> {
+        let logged = self.get_logged_message_with_commitment(message.sequence.get(), commitment)?;
+        Ok(logged.message() == Some(message))
+    }
+
+    /// Polls until message `sequence` is logged at the consistency level the
+    /// emitter asked for.
+    pub fn wait_for_finality(
+        &self,
+        sequence: u64,
+        timeout: Duration,
+    ) -> Result<MessageData, BridgeClientError> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let logged =
+                self.get_logged_message_with_commitment(sequence, CommitmentConfig::confirmed())?;
+            if let Some(message) = logged.message() {
+                if self.is_final(message)? {
+                    return Ok(*message);
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(BridgeClientError::FinalityTimeout(sequence));
+            }
+            sleep(FINALITY_POLL_INTERVAL);
+        }
+    }
+
+    pub fn send(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        extra_signers: &[&Keypair],
+    ) -> Result<Signature, BridgeClientError> {
+        let mut signers = vec![payer];
+        signers.extend_from_slice(extra_signers);
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&payer.pubkey()),
+            &signers,
+            blockhash,
+        );
+        self.rpc
+            .send_and_confirm_transaction(&transaction)
+            .map_err(|err| match err.get_transaction_error() {
+                Some(tx_err) => BridgeClientError::Transaction(TransactionFailure::decode(
+                    &tx_err,
+                    instructions,
+                )),
+                None => err.into(),
+            })
+    }
+}
+
+fn batch_legs(legs: &[(Pubkey, Pubkey, u64)]) -> Vec<BatchLeg> {
+    legs.iter()
+        .map(|&(token_account, mint, amount)| BatchLeg {
+            token_account,
+            mint,
+            amount,
+        })
+        .collect()
+}
+
+// Event nosi sva polja naloga, pa je zatvorena poruka ista kao procitana
+fn closed_message(posted: &MessagePosted) -> Result<MessageData, BridgeClientError> {
+    Ok(MessageData::new(
+        posted.from,
+        posted.to,
+        posted.amount,
+        posted.timestamp,
+        posted.sequence,
+        posted.payer,
+        posted.slot,
+        posted.epoch,
+        ConsistencyLevel::try_from(posted.consistency_level)?,
+        posted.emitter,
+        MessageKind::try_from(posted.kind)?,
+    ))
+}
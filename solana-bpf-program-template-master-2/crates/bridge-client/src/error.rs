@@ -0,0 +1,28 @@
+use bridge_events::error::EventError;
+use solana_client::client_error::ClientError;
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey};
+use thiserror::Error;
+
+use crate::program_error::TransactionFailure;
+
+#[derive(Error, Debug)]
+pub enum BridgeClientError {
+    #[error("RPC error: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("Failed to decode account: {0}")]
+    Decode(#[from] ProgramError),
+    #[error("Transaction failed: {0}")]
+    Transaction(TransactionFailure),
+    #[error("Account not found: {0}")]
+    AccountNotFound(Pubkey),
+    #[error("Message {0} did not reach its consistency level in time")]
+    FinalityTimeout(u64),
+    #[error("Failed to decode event: {0}")]
+    Event(#[from] EventError),
+}
+
+impl From<ClientError> for BridgeClientError {
+    fn from(e: ClientError) -> Self {
+        BridgeClientError::Rpc(Box::new(e))
+    }
+}
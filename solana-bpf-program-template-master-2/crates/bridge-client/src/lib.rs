@@ -0,0 +1,10 @@
+pub mod audit;
+pub mod client;
+pub mod error;
+pub mod pda;
+pub mod program_error;
+
+pub use audit::{reconcile, Discrepancy, EscrowSnapshot, LoggedMessage};
+pub use client::BridgeClient;
+pub use error::BridgeClientError;
+pub use program_error::TransactionFailure;
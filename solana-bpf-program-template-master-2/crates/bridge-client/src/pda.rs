@@ -0,0 +1,40 @@
+use escrow_contract::state::{Agreement, Batch, EscrowConfig, EscrowState, Position, Vesting};
+use logger_contract::state::MessageData;
+use solana_sdk::pubkey::Pubkey;
+
+pub fn config_address(escrow_program_id: &Pubkey) -> Pubkey {
+    EscrowConfig::find_address(escrow_program_id).0
+}
+
+pub fn escrow_address(escrow_program_id: &Pubkey, token_mint: &Pubkey) -> Pubkey {
+    EscrowState::find_address(token_mint, escrow_program_id).0
+}
+
+pub fn vault_address(escrow_program_id: &Pubkey, token_mint: &Pubkey) -> Pubkey {
+    EscrowState::find_vault_address(token_mint, escrow_program_id).0
+}
+
+pub fn position_address(escrow_program_id: &Pubkey, token_mint: &Pubkey, owner: &Pubkey) -> Pubkey {
+    Position::find_address(token_mint, owner, escrow_program_id).0
+}
+
+pub fn vesting_address(
+    escrow_program_id: &Pubkey,
+    token_mint: &Pubkey,
+    grantor: &Pubkey,
+    beneficiary: &Pubkey,
+) -> Pubkey {
+    Vesting::find_address(token_mint, grantor, beneficiary, escrow_program_id).0
+}
+
+pub fn agreement_address(escrow_program_id: &Pubkey, payer: &Pubkey, id: u64) -> Pubkey {
+    Agreement::find_address(payer, id, escrow_program_id).0
+}
+
+pub fn message_address(logger_program_id: &Pubkey, logger_state: &Pubkey, sequence: u64) -> Pubkey {
+    MessageData::find_address(logger_state, sequence, logger_program_id).0
+}
+
+pub fn batch_address(escrow_program_id: &Pubkey, message: &Pubkey) -> Pubkey {
+    Batch::find_address(message, escrow_program_id).0
+}
@@ -0,0 +1,72 @@
+use escrow_contract::error::{EscrowError, ESCROW_ERROR_CODES};
+use logger_contract::error::{LoggerError, LOGGER_ERROR_CODES};
+use num_traits::FromPrimitive;
+use solana_sdk::{
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    transaction::TransactionError,
+};
+use spl_token::error::TokenError;
+use thiserror::Error;
+
+/// A failed bridge transaction, decoded to the error of the program that
+/// raised it.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TransactionFailure {
+    #[error("escrow program failed: {0}")]
+    Escrow(EscrowError),
+    #[error("logger program failed: {0}")]
+    Logger(LoggerError),
+    #[error("token program failed: {0}")]
+    Token(TokenError),
+    /// Any other instruction error, attributed to the program of the
+    /// failing top-level instruction.
+    #[error("program {program} failed: {error}")]
+    Program {
+        program: Pubkey,
+        error: InstructionError,
+    },
+    /// The transaction was rejected before any instruction ran.
+    #[error("transaction rejected: {0}")]
+    Transaction(TransactionError),
+}
+
+impl TransactionFailure {
+    /// Decodes `err` returned for a transaction made of `instructions`.
+    /// Escrow and logger codes have ranges of their own, so they are named
+    /// correctly even when raised inside a CPI.
+    pub fn decode(err: &TransactionError, instructions: &[Instruction]) -> Self {
+        let (index, error) = match err {
+            TransactionError::InstructionError(index, error) => (*index, error),
+            other => return TransactionFailure::Transaction(other.clone()),
+        };
+        let program = match instructions.get(index as usize) {
+            Some(instruction) => instruction.program_id,
+            None => return TransactionFailure::Transaction(err.clone()),
+        };
+        if let InstructionError::Custom(code) = *error {
+            if let Some(failure) = decode_custom(code, &program) {
+                return failure;
+            }
+        }
+        TransactionFailure::Program {
+            program,
+            error: error.clone(),
+        }
+    }
+}
+
+// Kodovi van nasih opsega su dvosmisleni (spl-token i system program
+// dele male brojeve), pa token gresku citamo samo kada je token program
+// sam pozvan
+fn decode_custom(code: u32, program: &Pubkey) -> Option<TransactionFailure> {
+    if ESCROW_ERROR_CODES.contains(&code) {
+        EscrowError::from_code(code).map(TransactionFailure::Escrow)
+    } else if LOGGER_ERROR_CODES.contains(&code) {
+        LoggerError::from_code(code).map(TransactionFailure::Logger)
+    } else if *program == spl_token::id() {
+        TokenError::from_u32(code).map(TransactionFailure::Token)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::BTreeMap;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bridge_client::{reconcile, BridgeClient, Discrepancy, EscrowSnapshot, LoggedMessage};
+use bridge_events::{Event, LoggerSetEvent};
+use escrow_contract::state::EscrowConfig;
+use logger_contract::state::{ConsistencyLevel, MessageData, MessageKind};
+use serde_json::json;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    rpc_client::{Mocks, RpcClient},
+    rpc_request::RpcRequest,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+
+const LOGGER_STATE: Pubkey = Pubkey::new_from_array([3; 32]);
+const EMITTER: Pubkey = Pubkey::new_from_array([4; 32]);
+
+fn message(sequence: u64, from: Pubkey, to: Pubkey, amount: u64) -> MessageData {
+    message_from(EMITTER, sequence, from, to, amount)
+}
+
+fn message_from(
+    emitter: Pubkey,
+    sequence: u64,
+    from: Pubkey,
+    to: Pubkey,
+    amount: u64,
+) -> MessageData {
+    MessageData::new(
+        from,
+        to,
+        amount,
+        0,
+        sequence,
+        Pubkey::default(),
+        0,
+        0,
+        ConsistencyLevel::Finalized,
+        emitter,
+        MessageKind::Transfer,
+    )
+}
+
+fn escrow(vault: Pubkey, total_deposited: u64) -> EscrowSnapshot {
+    EscrowSnapshot {
+        address: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        vault,
+        total_deposited,
+        vault_balance: Some(total_deposited),
+    }
+}
+
+#[test]
+fn closed_and_ring_messages_count_towards_the_ledger() {
+    let user = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let messages = BTreeMap::from([
+        (
+            (LOGGER_STATE, 1),
+            LoggedMessage::Account(message(1, user, vault, 500)),
+        ),
+        // Zatvorena poruka se i dalje racuna kroz svoj dogadjaj
+        (
+            (LOGGER_STATE, 2),
+            LoggedMessage::Closed(message(2, vault, user, 200)),
+        ),
+        (
+            (LOGGER_STATE, 3),
+            LoggedMessage::Ring(message(3, user, vault, 50)),
+        ),
+        ((LOGGER_STATE, 4), LoggedMessage::Evicted),
+    ]);
+
+    assert_eq!(
+        reconcile(&[escrow(vault, 350)], &messages, &BTreeMap::new(), &EMITTER),
+        Vec::new()
+    );
+}
+
+#[test]
+fn only_missing_and_misplaced_sequences_are_reported() {
+    let user = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let messages = BTreeMap::from([
+        (
+            (LOGGER_STATE, 1),
+            LoggedMessage::Account(message(1, user, vault, 100)),
+        ),
+        ((LOGGER_STATE, 2), LoggedMessage::Missing),
+        ((LOGGER_STATE, 3), LoggedMessage::Evicted),
+        (
+            (LOGGER_STATE, 4),
+            LoggedMessage::Account(message(7, user, Pubkey::new_unique(), 1)),
+        ),
+    ]);
+
+    assert_eq!(
+        reconcile(&[escrow(vault, 100)], &messages, &BTreeMap::new(), &EMITTER),
+        vec![
+            Discrepancy::MissingMessage {
+                logger_state: LOGGER_STATE,
+                sequence: 2,
+            },
+            Discrepancy::MisplacedMessage {
+                logger_state: LOGGER_STATE,
+                sequence: 4,
+                stored_sequence: 7,
+            },
+        ]
+    );
+}
+
+#[test]
+fn a_closed_withdrawal_left_out_of_the_ledger_would_be_a_discrepancy() {
+    let user = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let escrows = [escrow(vault, 300)];
+    let mut messages = BTreeMap::from([
+        (
+            (LOGGER_STATE, 1),
+            LoggedMessage::Account(message(1, user, vault, 500)),
+        ),
+        (
+            (LOGGER_STATE, 2),
+            LoggedMessage::Closed(message(2, vault, user, 200)),
+        ),
+    ]);
+    assert!(reconcile(&escrows, &messages, &BTreeMap::new(), &EMITTER).is_empty());
+
+    messages.insert((LOGGER_STATE, 2), LoggedMessage::Missing);
+    assert_eq!(
+        reconcile(&escrows, &messages, &BTreeMap::new(), &EMITTER),
+        vec![
+            Discrepancy::MissingMessage {
+                logger_state: LOGGER_STATE,
+                sequence: 2,
+            },
+            Discrepancy::Ledger {
+                mint: escrows[0].mint,
+                logged: 500,
+                total_deposited: 300,
+                messages: vec![(LOGGER_STATE, 1)],
+            },
+        ]
+    );
+}
+
+#[test]
+fn messages_of_every_logger_state_count_towards_the_ledger() {
+    let user = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let migrated = Pubkey::new_unique();
+    // Posle SetLogger sekvence krecu od 1 i u novom state-u
+    let messages = BTreeMap::from([
+        (
+            (LOGGER_STATE, 1),
+            LoggedMessage::Account(message(1, user, vault, 500)),
+        ),
+        (
+            (migrated, 1),
+            LoggedMessage::Account(message(1, vault, user, 200)),
+        ),
+        ((migrated, 2), LoggedMessage::Missing),
+    ]);
+
+    assert_eq!(
+        reconcile(&[escrow(vault, 300)], &messages, &BTreeMap::new(), &EMITTER),
+        vec![Discrepancy::MissingMessage {
+            logger_state: migrated,
+            sequence: 2,
+        }]
+    );
+}
+
+#[test]
+fn foreign_messages_naming_a_vault_are_flagged_and_left_out_of_the_ledger() {
+    let user = Pubkey::new_unique();
+    let vault = Pubkey::new_unique();
+    let forger = Pubkey::new_unique();
+    let messages = BTreeMap::from([
+        (
+            (LOGGER_STATE, 1),
+            LoggedMessage::Account(message(1, user, vault, 500)),
+        ),
+        (
+            (LOGGER_STATE, 2),
+            LoggedMessage::Account(message_from(forger, 2, vault, user, 500)),
+        ),
+        // Tudja poruka koja ne pominje vault je obican korisnik logger-a
+        (
+            (LOGGER_STATE, 3),
+            LoggedMessage::Ring(message_from(forger, 3, user, forger, 7)),
+        ),
+    ]);
+
+    assert_eq!(
+        reconcile(&[escrow(vault, 500)], &messages, &BTreeMap::new(), &EMITTER),
+        vec![Discrepancy::ForeignEmitter {
+            logger_state: LOGGER_STATE,
+            sequence: 2,
+            emitter: forger,
+        }]
+    );
+}
+
+fn data_line<E: Event>(event: &E) -> String {
+    format!(
+        "Program data: {} {}",
+        STANDARD.encode(E::DISCRIMINATOR),
+        STANDARD.encode(borsh::to_vec(event).unwrap())
+    )
+}
+
+#[test]
+fn logger_states_follow_the_logger_set_chain_to_the_config() {
+    let escrow = Pubkey::new_unique();
+    let logger = Pubkey::new_unique();
+    let (first, second, current) = (
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+    );
+    let set = |previous_state, logger_state| LoggerSetEvent {
+        previous_program: logger,
+        previous_state,
+        logger_program: logger,
+        logger_state,
+        timestamp: 0,
+    };
+    let signature = Signature::from([1; 64]).to_string();
+
+    let mut mocks = Mocks::new();
+    mocks.insert(
+        RpcRequest::GetSignaturesForAddress,
+        json!([{
+            "signature": signature,
+            "slot": 10,
+            "err": null,
+            "memo": null,
+            "blockTime": null,
+            "confirmationStatus": "finalized",
+        }]),
+    );
+    // Dve migracije u istoj transakciji, pa config pokazuje na treci state
+    mocks.insert(
+        RpcRequest::GetTransaction,
+        json!({
+            "slot": 10,
+            "blockTime": null,
+            "version": "legacy",
+            "transaction": {
+                "signatures": [signature],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 0,
+                    },
+                    "accountKeys": [],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": [],
+                },
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "logMessages": [
+                    format!("Program {} invoke [1]", escrow),
+                    data_line(&set(first, second)),
+                    data_line(&set(second, current)),
+                    format!("Program {} success", escrow),
+                ],
+            },
+        }),
+    );
+    let config = Account {
+        lamports: 1_000_000,
+        data: bytemuck::bytes_of(&EscrowConfig::new(Pubkey::new_unique(), logger, current))
+            .to_vec(),
+        owner: escrow,
+        executable: false,
+        rent_epoch: 0,
+    };
+    mocks.insert(
+        RpcRequest::GetAccountInfo,
+        json!({
+            "context": { "slot": 10 },
+            "value": UiAccount::encode(
+                &Pubkey::default(),
+                &config,
+                UiAccountEncoding::Base64,
+                None,
+                None,
+            ),
+        }),
+    );
+    let client = BridgeClient::new(
+        RpcClient::new_mock_with_mocks("succeeds", mocks),
+        escrow,
+        logger,
+        current,
+    );
+
+    assert_eq!(
+        client.get_logger_states().unwrap(),
+        vec![first, second, current]
+    );
+}
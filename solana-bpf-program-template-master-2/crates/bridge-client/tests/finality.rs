@@ -0,0 +1,124 @@
+use bridge_client::{BridgeClient, BridgeClientError, LoggedMessage};
+use logger_contract::state::{ConsistencyLevel, MessageData, MessageKind};
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    rpc_client::{Mocks, RpcClient},
+    rpc_request::RpcRequest,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+fn message(amount: u64, slot: u64) -> MessageData {
+    MessageData::new(
+        Pubkey::new_from_array([1; 32]),
+        Pubkey::new_from_array([2; 32]),
+        amount,
+        1_700_000_000,
+        1,
+        Pubkey::new_from_array([3; 32]),
+        slot,
+        0,
+        ConsistencyLevel::Finalized,
+        Pubkey::new_from_array([4; 32]),
+        MessageKind::Transfer,
+    )
+}
+
+// getAccountInfo odgovor sa porukom, ili null kad nalog ne postoji
+fn account_info(logger_program_id: &Pubkey, message: Option<&MessageData>) -> Value {
+    let value = message.map(|message| {
+        let account = Account {
+            lamports: 1_000_000,
+            data: bytemuck::bytes_of(message).to_vec(),
+            owner: *logger_program_id,
+            executable: false,
+            rent_epoch: 0,
+        };
+        UiAccount::encode(
+            &Pubkey::default(),
+            &account,
+            UiAccountEncoding::Base64,
+            None,
+            None,
+        )
+    });
+    json!({ "context": { "slot": 1 }, "value": value })
+}
+
+fn client(message: Option<&MessageData>) -> BridgeClient {
+    client_with_owner(message, None)
+}
+
+// Nalog poruke pripada `owner`-u, a bez njega logger programu
+fn client_with_owner(message: Option<&MessageData>, owner: Option<Pubkey>) -> BridgeClient {
+    let logger_program_id = Pubkey::new_unique();
+    let mut mocks = Mocks::new();
+    mocks.insert(
+        RpcRequest::GetAccountInfo,
+        account_info(&owner.unwrap_or(logger_program_id), message),
+    );
+    mocks.insert(RpcRequest::GetProgramAccounts, json!([]));
+    mocks.insert(RpcRequest::GetSignaturesForAddress, json!([]));
+    BridgeClient::new(
+        RpcClient::new_mock_with_mocks("succeeds", mocks),
+        Pubkey::new_unique(),
+        logger_program_id,
+        Pubkey::new_unique(),
+    )
+}
+
+#[test]
+fn message_read_back_at_the_commitment_is_final() {
+    let posted = message(100, 10);
+    let client = client(Some(&posted));
+    assert!(client
+        .is_final_with_commitment(&posted, CommitmentConfig::finalized())
+        .unwrap());
+}
+
+#[test]
+fn message_replaced_on_another_fork_is_not_final() {
+    // Isti sequence i slot, ali finalizovana grana ima drugu poruku
+    let posted = message(100, 10);
+    let client = client(Some(&message(200, 10)));
+    assert!(!client
+        .is_final_with_commitment(&posted, CommitmentConfig::finalized())
+        .unwrap());
+}
+
+#[test]
+fn message_of_another_epoch_is_not_final() {
+    // Zatvorena poruka iz eventa nosi epohu, pa se i ona poredi
+    let posted = message(100, 10);
+    let client = client(Some(&MessageData {
+        epoch: 1.into(),
+        ..posted
+    }));
+    assert!(!client
+        .is_final_with_commitment(&posted, CommitmentConfig::finalized())
+        .unwrap());
+}
+
+#[test]
+fn message_missing_at_the_commitment_is_not_final() {
+    let posted = message(100, 10);
+    let client = client(None);
+    assert!(!client.is_final(&posted).unwrap());
+}
+
+#[test]
+fn message_account_of_another_owner_is_missing() {
+    // Isti bajtovi na adresi poruke, ali nalog ne poseduje logger
+    // Mock odgovori se trose, pa svaka provera dobija svoj klijent
+    let posted = message(100, 10);
+    let client = || client_with_owner(Some(&posted), Some(Pubkey::new_unique()));
+    assert!(matches!(
+        client().get_message(1),
+        Err(BridgeClientError::AccountNotFound(_))
+    ));
+    assert!(matches!(
+        client().get_logged_message(1).unwrap(),
+        LoggedMessage::Missing
+    ));
+    assert!(!client().is_final(&posted).unwrap());
+}
@@ -0,0 +1,118 @@
+use bridge_client::TransactionFailure;
+use escrow_contract::error::{EscrowError, ESCROW_ERROR_CODES};
+use logger_contract::error::{LoggerError, LOGGER_ERROR_CODES};
+use num_traits::FromPrimitive;
+use solana_sdk::{
+    decode_error::DecodeError,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+    system_program,
+    transaction::TransactionError,
+};
+use spl_token::error::TokenError;
+
+fn instruction(program_id: Pubkey) -> Instruction {
+    Instruction::new_with_bytes(program_id, &[], Vec::new())
+}
+
+fn custom(index: u8, code: u32) -> TransactionError {
+    TransactionError::InstructionError(index, InstructionError::Custom(code))
+}
+
+#[test]
+fn error_codes_stay_inside_their_ranges() {
+    assert!(ESCROW_ERROR_CODES.end() < LOGGER_ERROR_CODES.start());
+    for code in ESCROW_ERROR_CODES {
+        if let Some(err) = EscrowError::from_code(code) {
+            assert_eq!(err as u32, code);
+        }
+    }
+    for code in LOGGER_ERROR_CODES {
+        if let Some(err) = LoggerError::from_code(code) {
+            assert_eq!(err as u32, code);
+        }
+    }
+    // Brojevi su deo ABI-ja, prvi u svakom opsegu se ne pomera
+    assert_eq!(EscrowError::InvalidInstruction as u32, 1000);
+    assert_eq!(LoggerError::InvalidInstruction as u32, 2000);
+    assert!((1000..3000).all(|code| TokenError::from_u32(code).is_none()));
+    assert_eq!(
+        <EscrowError as DecodeError<EscrowError>>::type_of(),
+        "EscrowError"
+    );
+    assert_eq!(
+        <LoggerError as DecodeError<LoggerError>>::type_of(),
+        "LoggerError"
+    );
+}
+
+#[test]
+fn logger_error_raised_through_escrow_cpi_names_the_logger() {
+    let escrow = Pubkey::new_unique();
+    let failure = TransactionFailure::decode(
+        &custom(0, LoggerError::AccountNotWritable as u32),
+        &[instruction(escrow)],
+    );
+    assert_eq!(
+        failure,
+        TransactionFailure::Logger(LoggerError::AccountNotWritable)
+    );
+    assert_eq!(
+        failure.to_string(),
+        "logger program failed: Account Not Writable"
+    );
+}
+
+#[test]
+fn escrow_error_is_decoded_at_any_instruction_index() {
+    let failure = TransactionFailure::decode(
+        &custom(1, EscrowError::InsufficientAmount as u32),
+        &[
+            instruction(Pubkey::new_unique()),
+            instruction(Pubkey::new_unique()),
+        ],
+    );
+    assert_eq!(
+        failure,
+        TransactionFailure::Escrow(EscrowError::InsufficientAmount)
+    );
+}
+
+#[test]
+fn token_codes_are_decoded_only_for_the_token_program() {
+    let code = TokenError::InsufficientFunds as u32;
+    assert_eq!(
+        TransactionFailure::decode(&custom(0, code), &[instruction(spl_token::id())]),
+        TransactionFailure::Token(TokenError::InsufficientFunds)
+    );
+    // Isti broj iz CPI-ja moze biti i system program greska
+    let escrow = Pubkey::new_unique();
+    assert_eq!(
+        TransactionFailure::decode(&custom(0, code), &[instruction(escrow)]),
+        TransactionFailure::Program {
+            program: escrow,
+            error: InstructionError::Custom(code),
+        }
+    );
+}
+
+#[test]
+fn builtin_and_transaction_errors_keep_their_program() {
+    let err = TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature);
+    assert_eq!(
+        TransactionFailure::decode(&err, &[instruction(system_program::id())]),
+        TransactionFailure::Program {
+            program: system_program::id(),
+            error: InstructionError::MissingRequiredSignature,
+        }
+    );
+    assert_eq!(
+        TransactionFailure::decode(&TransactionError::BlockhashNotFound, &[]),
+        TransactionFailure::Transaction(TransactionError::BlockhashNotFound)
+    );
+    // Indeks van transakcije ne sme da panikuje
+    assert_eq!(
+        TransactionFailure::decode(&custom(3, 7), &[]),
+        TransactionFailure::Transaction(custom(3, 7))
+    );
+}
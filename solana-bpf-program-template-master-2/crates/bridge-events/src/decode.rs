@@ -0,0 +1,91 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use solana_program::pubkey::Pubkey;
+use std::str::FromStr;
+
+use crate::{
+    error::EventError,
+    event::{
+        BridgeEvent, DelegateEvent, DepositEvent, Event, LoggerSetEvent, MessagePosted,
+        WithdrawEvent,
+    },
+};
+
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProgramEvent {
+    // Program koji je emitovao event (vrh CPI steka u trenutku loga)
+    pub program_id: Pubkey,
+    pub event: BridgeEvent,
+}
+
+/// Decodes one `sol_log_data` payload of a bridge program: the
+/// discriminator field followed by the Borsh body. Returns `Ok(None)` for
+/// other field counts and unknown discriminators; callers decide which
+/// program's data is worth decoding.
+pub fn decode_event(fields: &[Vec<u8>]) -> Result<Option<BridgeEvent>, EventError> {
+    let (discriminator, body) = match fields {
+        [discriminator, body] => (discriminator.as_slice(), body.as_slice()),
+        _ => return Ok(None),
+    };
+    if discriminator.len() != 8 {
+        return Err(EventError::MissingDiscriminator);
+    }
+
+    let event = if discriminator == DepositEvent::DISCRIMINATOR {
+        BridgeEvent::Deposit(borsh::from_slice(body)?)
+    } else if discriminator == WithdrawEvent::DISCRIMINATOR {
+        BridgeEvent::Withdraw(borsh::from_slice(body)?)
+    } else if discriminator == DelegateEvent::DISCRIMINATOR {
+        BridgeEvent::Delegate(borsh::from_slice(body)?)
+    } else if discriminator == MessagePosted::DISCRIMINATOR {
+        BridgeEvent::MessagePosted(borsh::from_slice(body)?)
+    } else if discriminator == LoggerSetEvent::DISCRIMINATOR {
+        BridgeEvent::LoggerSet(borsh::from_slice(body)?)
+    } else {
+        return Ok(None);
+    };
+    Ok(Some(event))
+}
+
+/// Walks the `log_messages` of a transaction, tracking the invoke stack so
+/// every `Program data:` line is attributed to the program that emitted it.
+/// Only data of `programs` is decoded; anything another program logs is
+/// skipped, however malformed.
+pub fn parse_logs<S: AsRef<str>>(
+    logs: &[S],
+    programs: &[Pubkey],
+) -> Result<Vec<ProgramEvent>, EventError> {
+    let mut stack: Vec<Pubkey> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(data) = line.strip_prefix(PROGRAM_DATA_PREFIX) {
+            let program_id = match stack.last() {
+                Some(program_id) if programs.contains(program_id) => *program_id,
+                // Tudji program moze da loguje sta hoce
+                _ => continue,
+            };
+            let fields = data
+                .split_whitespace()
+                .map(|field| STANDARD.decode(field))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(event) = decode_event(&fields)? {
+                events.push(ProgramEvent { program_id, event });
+            }
+        } else if let Some(rest) = line.strip_prefix("Program ") {
+            let mut words = rest.split_whitespace();
+            let program_id = words.next().and_then(|id| Pubkey::from_str(id).ok());
+            match (program_id, words.next()) {
+                (Some(program_id), Some("invoke")) => stack.push(program_id),
+                (Some(_), Some("success")) | (Some(_), Some("failed:")) => {
+                    stack.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
@@ -0,0 +1,11 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EventError {
+    #[error("Invalid base64 in program data: {0}")]
+    InvalidBase64(#[from] base64::DecodeError),
+    #[error("Program data is missing the event discriminator")]
+    MissingDiscriminator,
+    #[error("Invalid event body: {0}")]
+    InvalidBody(#[from] std::io::Error),
+}
@@ -0,0 +1,102 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{log::sol_log_data, pubkey::Pubkey};
+
+// Diskriminator je sha256("event:<Ime>")[..8]
+pub trait Event: BorshSerialize + BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+
+    fn emit(&self) {
+        let body = borsh::to_vec(self).expect("event serialization cannot fail");
+        sol_log_data(&[&Self::DISCRIMINATOR, &body]);
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DepositEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+impl Event for DepositEvent {
+    const DISCRIMINATOR: [u8; 8] = [120, 248, 61, 83, 31, 142, 107, 144];
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawEvent {
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub total_deposited: u64,
+    pub timestamp: i64,
+}
+
+impl Event for WithdrawEvent {
+    const DISCRIMINATOR: [u8; 8] = [22, 9, 133, 26, 160, 44, 71, 192];
+}
+
+// Delegat nije transfer, pa ide kao event umesto logger poruke
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DelegateEvent {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub position: Pubkey,
+    // Pubkey::default() kada je delegat ukinut
+    pub delegate: Pubkey,
+    pub cap: Option<u64>,
+    pub expires_at: Option<u64>,
+    pub timestamp: i64,
+}
+
+impl Event for DelegateEvent {
+    const DISCRIMINATOR: [u8; 8] = [190, 32, 21, 167, 70, 227, 97, 240];
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MessagePosted {
+    pub sequence: u64,
+    // Sekvenca je jedinstvena samo unutar svog state-a
+    pub logger_state: Pubkey,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+    pub timestamp: u64,
+    pub payer: Pubkey,
+    pub slot: u64,
+    pub epoch: u64,
+    pub consistency_level: u8,
+    pub emitter: Pubkey,
+    pub kind: u8,
+}
+
+impl Event for MessagePosted {
+    const DISCRIMINATOR: [u8; 8] = [11, 28, 144, 13, 232, 160, 251, 5];
+}
+
+// Escrow prelazi na drugi logger state; stare poruke ostaju pod prethodnim
+#[derive(BorshSerialize, BorshDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LoggerSetEvent {
+    pub previous_program: Pubkey,
+    pub previous_state: Pubkey,
+    pub logger_program: Pubkey,
+    pub logger_state: Pubkey,
+    pub timestamp: i64,
+}
+
+impl Event for LoggerSetEvent {
+    const DISCRIMINATOR: [u8; 8] = [118, 253, 120, 248, 41, 95, 178, 56];
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BridgeEvent {
+    Deposit(DepositEvent),
+    Withdraw(WithdrawEvent),
+    Delegate(DelegateEvent),
+    MessagePosted(MessagePosted),
+    LoggerSet(LoggerSetEvent),
+}
@@ -0,0 +1,8 @@
+pub mod decode;
+pub mod error;
+pub mod event;
+
+pub use decode::{decode_event, parse_logs, ProgramEvent};
+pub use event::{
+    BridgeEvent, DelegateEvent, DepositEvent, Event, LoggerSetEvent, MessagePosted, WithdrawEvent,
+};
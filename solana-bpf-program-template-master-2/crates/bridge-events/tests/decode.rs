@@ -0,0 +1,243 @@
+use std::str::FromStr;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bridge_events::{
+    decode_event, error::EventError, parse_logs, BridgeEvent, DelegateEvent, DepositEvent, Event,
+    LoggerSetEvent, MessagePosted, ProgramEvent,
+};
+use solana_program::pubkey::Pubkey;
+
+const ESCROW: &str = "6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R";
+const LOGGER: &str = "HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv";
+const TOKEN: &str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+
+// Linije kako ih runtime ispisuje za sol_log_data: diskriminator pa Borsh telo
+const DEPOSIT_DATA: &str = "Program data: ePg9Ux+Oa5A= AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQECAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMD6AMAAAAAAADoAwAAAAAAAADxU2UAAAAA";
+const MESSAGE_POSTED_DATA: &str = "Program data: CxyQDeig+wU= BwAAAAAAAAAHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwMDAwPoAwAAAAAAAADxU2UAAAAAAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEqAAAAAAAAAAMAAAAAAAAAAQYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGBgYGAA==";
+const DELEGATE_DATA: &str = "Program data: viAVp0bjYfA= AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQECAgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUB9AEAAAAAAAAAAPFTZQAAAAA=";
+// Nepoznat diskriminator 00..07 sa telom od tri bajta
+const UNKNOWN_DATA: &str = "Program data: AAECAwQFBgc= AQID";
+
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+fn program(id: &str) -> Pubkey {
+    Pubkey::from_str(id).unwrap()
+}
+
+fn bridge() -> [Pubkey; 2] {
+    [program(ESCROW), program(LOGGER)]
+}
+
+fn deposit() -> DepositEvent {
+    DepositEvent {
+        user: key(1),
+        mint: key(2),
+        vault: key(3),
+        amount: 1_000,
+        total_deposited: 1_000,
+        timestamp: 1_700_000_000,
+    }
+}
+
+fn message_posted() -> MessagePosted {
+    MessagePosted {
+        sequence: 7,
+        logger_state: key(7),
+        from: key(1),
+        to: key(3),
+        amount: 1_000,
+        timestamp: 1_700_000_000,
+        payer: key(1),
+        slot: 42,
+        epoch: 3,
+        consistency_level: 1,
+        emitter: key(6),
+        kind: 0,
+    }
+}
+
+fn delegate() -> DelegateEvent {
+    DelegateEvent {
+        owner: key(1),
+        mint: key(2),
+        position: key(4),
+        delegate: key(5),
+        cap: Some(500),
+        expires_at: None,
+        timestamp: 1_700_000_000,
+    }
+}
+
+fn fields<E: Event>(event: &E) -> Vec<Vec<u8>> {
+    vec![E::DISCRIMINATOR.to_vec(), borsh::to_vec(event).unwrap()]
+}
+
+/// Logs of a deposit: escrow at depth 1 emits `DepositEvent`, then CPIs
+/// into spl-token and into the logger, which emits `MessagePosted` at depth 2.
+fn deposit_logs() -> Vec<String> {
+    [
+        format!("Program {} invoke [1]", ESCROW),
+        "Program log: Instruction: Deposit".to_string(),
+        format!("Program {} invoke [2]", TOKEN),
+        "Program log: Instruction: Transfer".to_string(),
+        format!("Program {} consumed 4645 of 190738 compute units", TOKEN),
+        format!("Program {} success", TOKEN),
+        DEPOSIT_DATA.to_string(),
+        format!("Program {} invoke [2]", LOGGER),
+        "Program log: Message 7 posted".to_string(),
+        MESSAGE_POSTED_DATA.to_string(),
+        format!("Program {} consumed 12120 of 180311 compute units", LOGGER),
+        format!("Program {} success", LOGGER),
+        DELEGATE_DATA.to_string(),
+        format!("Program {} consumed 31902 of 200000 compute units", ESCROW),
+        format!("Program {} success", ESCROW),
+    ]
+    .to_vec()
+}
+
+#[test]
+fn decode_event_reads_every_known_event() {
+    assert_eq!(
+        decode_event(&fields(&deposit())).unwrap(),
+        Some(BridgeEvent::Deposit(deposit()))
+    );
+    assert_eq!(
+        decode_event(&fields(&message_posted())).unwrap(),
+        Some(BridgeEvent::MessagePosted(message_posted()))
+    );
+    assert_eq!(
+        decode_event(&fields(&delegate())).unwrap(),
+        Some(BridgeEvent::Delegate(delegate()))
+    );
+    let logger_set = LoggerSetEvent {
+        previous_program: key(8),
+        previous_state: key(7),
+        logger_program: key(8),
+        logger_state: key(9),
+        timestamp: 1_700_000_000,
+    };
+    assert_eq!(
+        decode_event(&fields(&logger_set)).unwrap(),
+        Some(BridgeEvent::LoggerSet(logger_set))
+    );
+}
+
+#[test]
+fn decode_event_ignores_unknown_discriminators_and_other_shapes() {
+    let unknown = vec![(0..8).collect::<Vec<u8>>(), vec![1, 2, 3]];
+    assert_eq!(decode_event(&unknown).unwrap(), None);
+    assert_eq!(decode_event(&[]).unwrap(), None);
+    assert_eq!(decode_event(&[DepositEvent::DISCRIMINATOR.to_vec()]).unwrap(), None);
+
+    let mut three = fields(&deposit());
+    three.push(vec![0]);
+    assert_eq!(decode_event(&three).unwrap(), None);
+}
+
+#[test]
+fn decode_event_rejects_short_discriminator_and_bad_body() {
+    assert!(matches!(
+        decode_event(&[vec![1, 2, 3], vec![]]),
+        Err(EventError::MissingDiscriminator)
+    ));
+
+    let mut truncated = fields(&deposit());
+    truncated[1].pop();
+    assert!(matches!(
+        decode_event(&truncated),
+        Err(EventError::InvalidBody(_))
+    ));
+}
+
+#[test]
+fn parse_logs_attributes_events_through_nested_cpis() {
+    assert_eq!(
+        parse_logs(&deposit_logs(), &bridge()).unwrap(),
+        vec![
+            ProgramEvent {
+                program_id: program(ESCROW),
+                event: BridgeEvent::Deposit(deposit()),
+            },
+            ProgramEvent {
+                program_id: program(LOGGER),
+                event: BridgeEvent::MessagePosted(message_posted()),
+            },
+            // Posle povratka iz loggera event opet pripada escrow-u
+            ProgramEvent {
+                program_id: program(ESCROW),
+                event: BridgeEvent::Delegate(delegate()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn parse_logs_pops_failed_invocations() {
+    let logs = [
+        format!("Program {} invoke [1]", ESCROW),
+        format!("Program {} invoke [2]", LOGGER),
+        format!(
+            "Program {} failed: custom program error: 0x7d1",
+            LOGGER
+        ),
+        DEPOSIT_DATA.to_string(),
+        format!("Program {} success", ESCROW),
+    ];
+    let events = parse_logs(&logs, &bridge()).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].program_id, program(ESCROW));
+}
+
+#[test]
+fn parse_logs_skips_unknown_data_and_data_outside_any_program() {
+    let logs = [
+        DEPOSIT_DATA.to_string(),
+        format!("Program {} invoke [1]", TOKEN),
+        UNKNOWN_DATA.to_string(),
+        format!("Program {} success", TOKEN),
+        "Program return: TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA AQ==".to_string(),
+    ];
+    assert_eq!(parse_logs(&logs, &bridge()).unwrap(), vec![]);
+}
+
+#[test]
+fn parse_logs_rejects_invalid_base64() {
+    let logs = [
+        format!("Program {} invoke [1]", ESCROW),
+        "Program data: not*base64".to_string(),
+    ];
+    assert!(matches!(
+        parse_logs(&logs, &bridge()),
+        Err(EventError::InvalidBase64(_))
+    ));
+}
+
+#[test]
+fn parse_logs_ignores_malformed_data_of_other_programs() {
+    let mut truncated = fields(&deposit());
+    truncated[1].pop();
+    let logs = [
+        format!("Program {} invoke [1]", ESCROW),
+        format!("Program {} invoke [2]", TOKEN),
+        "Program data: not*base64".to_string(),
+        // Diskriminator od tri bajta i pogresno Borsh telo pod DepositEvent diskriminatorom
+        "Program data: AQID AQID".to_string(),
+        format!(
+            "Program data: {} {}",
+            STANDARD.encode(&truncated[0]),
+            STANDARD.encode(&truncated[1])
+        ),
+        format!("Program {} success", TOKEN),
+        DEPOSIT_DATA.to_string(),
+        format!("Program {} success", ESCROW),
+    ];
+    assert_eq!(
+        parse_logs(&logs, &bridge()).unwrap(),
+        vec![ProgramEvent {
+            program_id: program(ESCROW),
+            event: BridgeEvent::Deposit(deposit()),
+        }]
+    );
+}
@@ -0,0 +1,120 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::OsStr,
+    fs,
+    io::{BufRead, BufReader},
+    net::TcpStream,
+    path::Path,
+};
+
+use log::warn;
+use solana_sdk::{hash::Hash, pubkey::Pubkey, signature::Signature};
+
+use crate::{attestation::Attestation, error::GuardianError};
+
+/// Digest of one message together with enough guardian signatures.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quorum {
+    pub sequence: u64,
+    pub digest: Hash,
+    pub signatures: Vec<(Pubkey, Signature)>,
+}
+
+/// Collects attestations from a fixed guardian set and reports the
+/// messages that reached `quorum` signatures over the same digest.
+pub struct Aggregator {
+    guardians: BTreeSet<Pubkey>,
+    quorum: usize,
+    // sequence -> digest -> guardian -> potpis; razliciti digest-ovi za istu
+    // poruku se broje odvojeno
+    attestations: BTreeMap<u64, BTreeMap<Hash, BTreeMap<Pubkey, Signature>>>,
+}
+
+impl Aggregator {
+    pub fn new<I: IntoIterator<Item = Pubkey>>(guardians: I, quorum: usize) -> Self {
+        Self {
+            guardians: guardians.into_iter().collect(),
+            quorum,
+            attestations: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, attestation: &Attestation) -> Result<(), GuardianError> {
+        if !self.guardians.contains(&attestation.guardian) {
+            return Err(GuardianError::UnknownGuardian(attestation.guardian));
+        }
+        if !attestation.verify() {
+            return Err(GuardianError::InvalidSignature {
+                guardian: attestation.guardian,
+                sequence: attestation.sequence,
+            });
+        }
+        self.attestations
+            .entry(attestation.sequence)
+            .or_default()
+            .entry(attestation.digest)
+            .or_default()
+            .insert(attestation.guardian, attestation.signature);
+        Ok(())
+    }
+
+    pub fn quorum(&self, sequence: u64) -> Option<Quorum> {
+        let (digest, signatures) = self
+            .attestations
+            .get(&sequence)?
+            .iter()
+            .find(|(_, signatures)| signatures.len() >= self.quorum)?;
+        Some(Quorum {
+            sequence,
+            digest: *digest,
+            signatures: signatures
+                .iter()
+                .map(|(guardian, signature)| (*guardian, *signature))
+                .collect(),
+        })
+    }
+
+    pub fn quorums(&self) -> impl Iterator<Item = Quorum> + '_ {
+        self.attestations
+            .keys()
+            .filter_map(|sequence| self.quorum(*sequence))
+    }
+
+    /// Adds every `.att` file in `dir`. Attestations that fail verification
+    /// are reported and skipped so one bad file cannot block the rest.
+    pub fn load_directory<P: AsRef<Path>>(&mut self, dir: P) -> Result<usize, GuardianError> {
+        let mut added = 0;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension() != Some(OsStr::new("att")) {
+                continue;
+            }
+            let line = fs::read_to_string(&path)?;
+            match line
+                .trim()
+                .parse()
+                .and_then(|attestation| self.add(&attestation))
+            {
+                Ok(()) => added += 1,
+                Err(err) => warn!("aggregator: skipping {}: {}", path.display(), err),
+            }
+        }
+        Ok(added)
+    }
+
+    /// Reads attestations from one publisher connection until it closes.
+    pub fn receive(&mut self, stream: TcpStream) -> Result<usize, GuardianError> {
+        let mut added = 0;
+        for line in BufReader::new(stream).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.parse().and_then(|attestation| self.add(&attestation)) {
+                Ok(()) => added += 1,
+                Err(err) => warn!("aggregator: rejected attestation: {}", err),
+            }
+        }
+        Ok(added)
+    }
+}
@@ -0,0 +1,89 @@
+use std::{fmt, str::FromStr};
+
+use escrow_contract::state::BatchTransfer;
+use logger_contract::state::MessageData;
+use solana_sdk::{
+    hash::{hashv, Hash},
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use crate::error::GuardianError;
+
+const DIGEST_DOMAIN: &[u8] = b"bridge-attestation-v1";
+
+/// Canonical digest guardians sign: the raw `MessageData` layout bound to the
+/// logger program that emitted it, so the same bytes from another
+/// deployment never collect a quorum. A batch message also commits to its
+/// legs; plain messages pass no legs.
+pub fn message_digest(
+    logger_program_id: &Pubkey,
+    message: &MessageData,
+    legs: &[BatchTransfer],
+) -> Hash {
+    let mut parts = vec![
+        DIGEST_DOMAIN,
+        logger_program_id.as_ref(),
+        bytemuck::bytes_of(message),
+    ];
+    parts.extend(legs.iter().map(bytemuck::bytes_of));
+    hashv(&parts)
+}
+
+/// One guardian's signature over a message digest.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attestation {
+    pub sequence: u64,
+    pub digest: Hash,
+    pub guardian: Pubkey,
+    pub signature: Signature,
+}
+
+impl Attestation {
+    pub fn sign(keypair: &Keypair, sequence: u64, digest: Hash) -> Self {
+        Self {
+            sequence,
+            digest,
+            guardian: keypair.pubkey(),
+            signature: keypair.sign_message(digest.as_ref()),
+        }
+    }
+
+    pub fn verify(&self) -> bool {
+        self.signature
+            .verify(self.guardian.as_ref(), self.digest.as_ref())
+    }
+}
+
+// Jedna linija: "<sequence> <digest> <guardian> <signature>", isti format
+// za fajlove u deljenom direktorijumu i za TCP
+impl fmt::Display for Attestation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.sequence, self.digest, self.guardian, self.signature
+        )
+    }
+}
+
+impl FromStr for Attestation {
+    type Err = GuardianError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let invalid = || GuardianError::InvalidAttestation(line.to_string());
+        let mut fields = line.split_whitespace();
+        let mut next = || fields.next().ok_or_else(invalid);
+
+        let attestation = Attestation {
+            sequence: next()?.parse().map_err(|_| invalid())?,
+            digest: next()?.parse().map_err(|_| invalid())?,
+            guardian: next()?.parse().map_err(|_| invalid())?,
+            signature: next()?.parse().map_err(|_| invalid())?,
+        };
+        if fields.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(attestation)
+    }
+}
@@ -0,0 +1,14 @@
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum GuardianError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Malformed attestation: {0}")]
+    InvalidAttestation(String),
+    #[error("{0} is not in the guardian set")]
+    UnknownGuardian(Pubkey),
+    #[error("Bad signature from {guardian} on message {sequence}")]
+    InvalidSignature { guardian: Pubkey, sequence: u64 },
+}
@@ -0,0 +1,115 @@
+use bridge_client::{BridgeClient, BridgeClientError, LoggedMessage};
+use bridge_relayer::{Destination, DestinationError, MessageSource};
+use escrow_contract::state::{Batch, BatchTransfer, EscrowConfig};
+use logger_contract::state::{MessageData, MessageKind};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+use crate::{
+    attestation::{message_digest, Attestation},
+    publisher::Publisher,
+};
+
+/// Signs every message the escrow emitted and publishes the signature.
+/// Plugs into `bridge_relayer::Relayer` as a destination, which keeps the
+/// cursor, ordering and retry behaviour identical to the relayer's.
+pub struct Guardian<P: Publisher> {
+    keypair: Keypair,
+    logger_program_id: Pubkey,
+    emitter: Pubkey,
+    publisher: P,
+    name: String,
+}
+
+impl<P: Publisher> Guardian<P> {
+    pub fn new(
+        keypair: Keypair,
+        escrow_program_id: Pubkey,
+        logger_program_id: Pubkey,
+        publisher: P,
+    ) -> Self {
+        let name = format!("guardian {}", keypair.pubkey());
+        Self {
+            keypair,
+            logger_program_id,
+            emitter: EscrowConfig::find_address(&escrow_program_id).0,
+            publisher,
+            name,
+        }
+    }
+
+    pub fn attest(&self, message: &MessageData, legs: &[BatchTransfer]) -> Attestation {
+        let digest = message_digest(&self.logger_program_id, message, legs);
+        Attestation::sign(&self.keypair, message.sequence.get(), digest)
+    }
+
+    fn publish(&mut self, message: &MessageData, legs: &[BatchTransfer]) -> Result<(), DestinationError> {
+        // Logger prihvata poruku od bilo kog potpisnika, pa se potpisuje samo ono sto je poslao escrow
+        if message.emitter != self.emitter {
+            return Err(DestinationError::Rejected(format!(
+                "message #{} emitted by {}, not by the escrow",
+                message.sequence, message.emitter
+            )));
+        }
+        // Bez legova digest ne bi vezao iznose, amount batch poruke je samo broj legova
+        if message.kind() == MessageKind::Batch && legs.is_empty() {
+            return Err(DestinationError::Rejected(format!(
+                "batch message #{} has no legs",
+                message.sequence
+            )));
+        }
+        let attestation = self.attest(message, legs);
+        self.publisher
+            .publish(&attestation)
+            .map_err(|err| DestinationError::Retryable(err.to_string()))
+    }
+}
+
+impl<P: Publisher> Destination for Guardian<P> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn deliver(&mut self, _logger_state: &Pubkey, message: &MessageData) -> Result<(), DestinationError> {
+        self.publish(message, &[])
+    }
+
+    fn deliver_batch(
+        &mut self,
+        _logger_state: &Pubkey,
+        message: &MessageData,
+        batch: &Batch,
+    ) -> Result<(), DestinationError> {
+        self.publish(message, batch.legs())
+    }
+}
+
+/// Message source that only releases messages once they are readable at
+/// finalized commitment, whatever consistency level the emitter asked for.
+pub struct FinalizedSource(pub BridgeClient);
+
+impl MessageSource for FinalizedSource {
+    fn logger_state(&self) -> Pubkey {
+        self.0.logger_state
+    }
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError> {
+        self.0.logger_sequence()
+    }
+
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        self.0.fetch(sequence)
+    }
+
+    fn fetch_batch(&self, message: &MessageData) -> Result<Option<Batch>, BridgeClientError> {
+        self.0.get_batch(message)
+    }
+
+    fn is_final(&self, message: &MessageData) -> Result<bool, BridgeClientError> {
+        self.0
+            .is_final_with_commitment(message, CommitmentConfig::finalized())
+    }
+}
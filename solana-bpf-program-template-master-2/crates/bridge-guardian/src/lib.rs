@@ -0,0 +1,11 @@
+pub mod aggregator;
+pub mod attestation;
+pub mod error;
+pub mod guardian;
+pub mod publisher;
+
+pub use aggregator::{Aggregator, Quorum};
+pub use attestation::{message_digest, Attestation};
+pub use error::GuardianError;
+pub use guardian::{FinalizedSource, Guardian};
+pub use publisher::{DirectoryPublisher, Publisher, TcpPublisher};
@@ -0,0 +1,211 @@
+use std::{
+    collections::BTreeSet,
+    error::Error,
+    net::{SocketAddr, TcpListener},
+    path::PathBuf,
+    process::exit,
+    str::FromStr,
+    thread::sleep,
+    time::Duration,
+};
+
+use bridge_client::BridgeClient;
+use bridge_guardian::{Aggregator, DirectoryPublisher, FinalizedSource, Guardian, TcpPublisher};
+use bridge_relayer::{Cursor, Relayer, RelayerConfig};
+use clap::{Parser, Subcommand};
+use log::warn;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signer},
+};
+
+const DEFAULT_ESCROW_PROGRAM_ID: &str = "6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R";
+const DEFAULT_LOGGER_PROGRAM_ID: &str = "HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv";
+
+#[derive(Parser)]
+#[command(name = "bridge-guardian", about = "Attest finalized logger messages")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Sign every finalized logger message and publish the signature
+    Run {
+        #[arg(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+        url: String,
+
+        #[arg(long, default_value = DEFAULT_ESCROW_PROGRAM_ID)]
+        escrow_program_id: Pubkey,
+
+        #[arg(long, default_value = DEFAULT_LOGGER_PROGRAM_ID)]
+        logger_program_id: Pubkey,
+
+        /// Logger state address, or the keypair file it was created from
+        #[arg(long, default_value = "logger_state.json")]
+        logger_state: String,
+
+        /// Guardian signing key
+        #[arg(long, short = 'k')]
+        keypair: PathBuf,
+
+        /// File holding the last attested sequence
+        #[arg(long, default_value = "guardian.cursor")]
+        cursor: PathBuf,
+
+        /// Shared directory to write attestations into
+        #[arg(long, conflicts_with = "connect", required_unless_present = "connect")]
+        dir: Option<PathBuf>,
+
+        /// Aggregator TCP endpoint to send attestations to
+        #[arg(long)]
+        connect: Option<SocketAddr>,
+
+        #[arg(long, default_value_t = 2_000)]
+        poll_interval_ms: u64,
+    },
+    /// Collect attestations and print the messages that reached quorum
+    Aggregate {
+        /// Guardian public key, repeat once per guardian
+        #[arg(long = "guardian", required = true)]
+        guardians: Vec<Pubkey>,
+
+        #[arg(long)]
+        quorum: usize,
+
+        /// Shared directory guardians write into
+        #[arg(long, conflicts_with = "listen", required_unless_present = "listen")]
+        dir: Option<PathBuf>,
+
+        /// Address to accept guardian connections on
+        #[arg(long)]
+        listen: Option<SocketAddr>,
+
+        #[arg(long, default_value_t = 2_000)]
+        poll_interval_ms: u64,
+    },
+}
+
+fn main() {
+    solana_logger::setup_with_default("info");
+    let args = Args::parse();
+    if let Err(err) = run(args) {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    match args.command {
+        Command::Run {
+            url,
+            escrow_program_id,
+            logger_program_id,
+            logger_state,
+            keypair,
+            cursor,
+            dir,
+            connect,
+            poll_interval_ms,
+        } => {
+            let keypair = read_keypair_file(&keypair)
+                .map_err(|err| format!("failed to read keypair {}: {}", keypair.display(), err))?;
+            let logger_state = match Pubkey::from_str(&logger_state) {
+                Ok(logger_state) => logger_state,
+                Err(_) => read_keypair_file(&logger_state)
+                    .map_err(|err| format!("invalid --logger-state {}: {}", logger_state, err))?
+                    .pubkey(),
+            };
+            let rpc = RpcClient::new_with_commitment(url, CommitmentConfig::finalized());
+            let source = FinalizedSource(BridgeClient::new(
+                rpc,
+                escrow_program_id,
+                logger_program_id,
+                logger_state,
+            ));
+            let cursor = Cursor::load(&cursor, &logger_state)?;
+            let config = RelayerConfig {
+                poll_interval: Duration::from_millis(poll_interval_ms),
+                ..RelayerConfig::default()
+            };
+            println!("guardian: {}", keypair.pubkey());
+
+            match (dir, connect) {
+                (Some(dir), _) => {
+                    let publisher = DirectoryPublisher::new(dir)?;
+                    let guardian =
+                        Guardian::new(keypair, escrow_program_id, logger_program_id, publisher);
+                    Relayer::new(source, guardian, cursor, config).run()?;
+                }
+                (None, Some(addr)) => {
+                    let publisher = TcpPublisher::new(addr);
+                    let guardian =
+                        Guardian::new(keypair, escrow_program_id, logger_program_id, publisher);
+                    Relayer::new(source, guardian, cursor, config).run()?;
+                }
+                (None, None) => unreachable!("clap requires --dir or --connect"),
+            }
+            Ok(())
+        }
+        Command::Aggregate {
+            guardians,
+            quorum,
+            dir,
+            listen,
+            poll_interval_ms,
+        } => aggregate(
+            Aggregator::new(guardians, quorum),
+            dir,
+            listen,
+            Duration::from_millis(poll_interval_ms),
+        ),
+    }
+}
+
+fn aggregate(
+    mut aggregator: Aggregator,
+    dir: Option<PathBuf>,
+    listen: Option<SocketAddr>,
+    poll_interval: Duration,
+) -> Result<(), Box<dyn Error>> {
+    let mut reported = BTreeSet::new();
+    let mut report = |aggregator: &Aggregator| {
+        for quorum in aggregator.quorums() {
+            if reported.insert(quorum.sequence) {
+                println!(
+                    "message #{} reached quorum: digest {} signed by {} guardian(s)",
+                    quorum.sequence,
+                    quorum.digest,
+                    quorum.signatures.len()
+                );
+            }
+        }
+    };
+
+    if let Some(dir) = dir {
+        loop {
+            aggregator.load_directory(&dir)?;
+            report(&aggregator);
+            sleep(poll_interval);
+        }
+    }
+
+    let listen = listen.expect("clap requires --dir or --listen");
+    let listener = TcpListener::bind(listen)?;
+    println!("aggregator: listening on {}", listener.local_addr()?);
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                if let Err(err) = aggregator.receive(stream) {
+                    warn!("aggregator: connection error: {}", err);
+                }
+                report(&aggregator);
+            }
+            Err(err) => warn!("aggregator: accept failed: {}", err),
+        }
+    }
+    Ok(())
+}
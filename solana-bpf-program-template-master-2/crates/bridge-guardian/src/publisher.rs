@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    io::Write,
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::attestation::Attestation;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub trait Publisher {
+    fn publish(&mut self, attestation: &Attestation) -> std::io::Result<()>;
+}
+
+/// Writes every attestation to `<dir>/<sequence>-<guardian>.att`, so any
+/// number of guardians can share one directory.
+pub struct DirectoryPublisher {
+    dir: PathBuf,
+}
+
+impl DirectoryPublisher {
+    pub fn new<P: AsRef<Path>>(dir: P) -> std::io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn path_for(dir: &Path, attestation: &Attestation) -> PathBuf {
+        dir.join(format!(
+            "{}-{}.att",
+            attestation.sequence, attestation.guardian
+        ))
+    }
+}
+
+impl Publisher for DirectoryPublisher {
+    fn publish(&mut self, attestation: &Attestation) -> std::io::Result<()> {
+        // Kao i cursor: privremeni fajl pa rename, agregator nikad ne vidi pola linije
+        let path = Self::path_for(&self.dir, attestation);
+        let tmp = path.with_extension("tmp");
+        fs::write(&tmp, format!("{}\n", attestation))?;
+        fs::rename(&tmp, &path)
+    }
+}
+
+/// Sends every attestation as one line over a fresh TCP connection.
+pub struct TcpPublisher {
+    addr: SocketAddr,
+}
+
+impl TcpPublisher {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self { addr }
+    }
+}
+
+impl Publisher for TcpPublisher {
+    fn publish(&mut self, attestation: &Attestation) -> std::io::Result<()> {
+        let mut stream = TcpStream::connect_timeout(&self.addr, CONNECT_TIMEOUT)?;
+        writeln!(stream, "{}", attestation)?;
+        stream.flush()
+    }
+}
@@ -0,0 +1,265 @@
+use std::{net::TcpListener, path::PathBuf, thread};
+
+use bridge_client::{BridgeClientError, LoggedMessage};
+use bridge_guardian::{
+    message_digest, Aggregator, Attestation, DirectoryPublisher, Guardian, GuardianError,
+    Publisher, TcpPublisher,
+};
+use bridge_relayer::{
+    Cursor, Destination, DestinationError, MessageSource, Relayer, RelayerConfig, RelayerError,
+};
+use escrow_contract::state::{Batch, BatchTransfer, EscrowConfig};
+use logger_contract::state::{ConsistencyLevel, MessageData, MessageKind};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+const ESCROW_PROGRAM_ID: Pubkey = Pubkey::new_from_array([5; 32]);
+const LOGGER_STATE: Pubkey = Pubkey::new_from_array([7; 32]);
+
+struct Messages(Vec<MessageData>);
+
+impl MessageSource for Messages {
+    fn logger_state(&self) -> Pubkey {
+        LOGGER_STATE
+    }
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError> {
+        Ok(self.0.len() as u64)
+    }
+
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        Ok(self
+            .0
+            .get(sequence as usize - 1)
+            .map_or(LoggedMessage::Missing, |message| LoggedMessage::Account(*message)))
+    }
+
+    fn is_final(&self, _message: &MessageData) -> Result<bool, BridgeClientError> {
+        Ok(true)
+    }
+}
+
+// Deterministicke poruke, svaki guardian mora da vidi iste bajtove
+fn messages(count: u64) -> Messages {
+    messages_from(count, EscrowConfig::find_address(&ESCROW_PROGRAM_ID).0)
+}
+
+fn messages_from(count: u64, emitter: Pubkey) -> Messages {
+    Messages(
+        (1..=count)
+            .map(|sequence| {
+                MessageData::new(
+                    Pubkey::new_from_array([1; 32]),
+                    Pubkey::new_from_array([2; 32]),
+                    100 * sequence,
+                    1_700_000_000 + sequence,
+                    sequence,
+                    Pubkey::new_from_array([3; 32]),
+                    10 + sequence,
+                    0,
+                    ConsistencyLevel::Finalized,
+                    emitter,
+                    MessageKind::Transfer,
+                )
+            })
+            .collect(),
+    )
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path =
+        std::env::temp_dir().join(format!("bridge-guardian-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+// Svaki guardian je zaseban relayer sa sopstvenim kljucem i cursor-om
+fn run_guardian<P: Publisher>(
+    name: &str,
+    keypair: Keypair,
+    logger_program_id: Pubkey,
+    source: Messages,
+    publisher: P,
+) -> u64 {
+    try_run_guardian(name, keypair, logger_program_id, source, publisher).unwrap()
+}
+
+fn try_run_guardian<P: Publisher>(
+    name: &str,
+    keypair: Keypair,
+    logger_program_id: Pubkey,
+    source: Messages,
+    publisher: P,
+) -> Result<u64, RelayerError> {
+    let cursor = Cursor::load(temp_path(&format!("{}.cursor", name)), &LOGGER_STATE).unwrap();
+    let guardian = Guardian::new(keypair, ESCROW_PROGRAM_ID, logger_program_id, publisher);
+    Relayer::new(source, guardian, cursor, RelayerConfig::default()).run_once()
+}
+
+#[derive(Default)]
+struct Collected(Vec<Attestation>);
+
+impl Publisher for &mut Collected {
+    fn publish(&mut self, attestation: &Attestation) -> std::io::Result<()> {
+        self.0.push(attestation.clone());
+        Ok(())
+    }
+}
+
+#[test]
+fn guardians_reach_quorum_through_shared_directory() {
+    let logger_program_id = Pubkey::new_unique();
+    let dir = temp_path("dir");
+    let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let mut aggregator = Aggregator::new(keypairs.iter().map(|keypair| keypair.pubkey()), 2);
+
+    // Samo prvi guardian je video poruke, jos nema kvoruma
+    let first = keypairs[0].insecure_clone();
+    let publisher = DirectoryPublisher::new(&dir).unwrap();
+    assert_eq!(
+        run_guardian("dir-0", first, logger_program_id, messages(2), publisher),
+        2
+    );
+    aggregator.load_directory(&dir).unwrap();
+    assert_eq!(aggregator.quorums().count(), 0);
+
+    let second = keypairs[1].insecure_clone();
+    let publisher = DirectoryPublisher::new(&dir).unwrap();
+    run_guardian("dir-1", second, logger_program_id, messages(2), publisher);
+    aggregator.load_directory(&dir).unwrap();
+
+    let quorums: Vec<_> = aggregator.quorums().collect();
+    assert_eq!(quorums.len(), 2);
+    for quorum in &quorums {
+        let message = &messages(2).0[quorum.sequence as usize - 1];
+        assert_eq!(quorum.digest, message_digest(&logger_program_id, message, &[]));
+        assert_eq!(quorum.signatures.len(), 2);
+    }
+}
+
+#[test]
+fn guardians_reach_quorum_over_tcp() {
+    let logger_program_id = Pubkey::new_unique();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let keypairs: Vec<Keypair> = (0..3).map(|_| Keypair::new()).collect();
+    let mut aggregator = Aggregator::new(keypairs.iter().map(|keypair| keypair.pubkey()), 3);
+
+    // Svaki guardian salje po jednu konekciju za svaku od 2 poruke
+    let server = thread::spawn(move || {
+        for stream in listener.incoming().take(6) {
+            aggregator.receive(stream.unwrap()).unwrap();
+        }
+        aggregator
+    });
+    let guardians: Vec<_> = keypairs
+        .into_iter()
+        .enumerate()
+        .map(|(index, keypair)| {
+            thread::spawn(move || {
+                run_guardian(
+                    &format!("tcp-{}", index),
+                    keypair,
+                    logger_program_id,
+                    messages(2),
+                    TcpPublisher::new(addr),
+                )
+            })
+        })
+        .collect();
+    for guardian in guardians {
+        assert_eq!(guardian.join().unwrap(), 2);
+    }
+
+    let aggregator = server.join().unwrap();
+    assert_eq!(aggregator.quorums().count(), 2);
+}
+
+#[test]
+fn aggregator_rejects_unknown_guardians_and_forged_signatures() {
+    let guardian = Keypair::new();
+    let outsider = Keypair::new();
+    let mut aggregator = Aggregator::new([guardian.pubkey()], 1);
+    let digest = message_digest(&Pubkey::new_unique(), &messages(1).0[0], &[]);
+
+    let foreign = Attestation::sign(&outsider, 1, digest);
+    assert!(matches!(
+        aggregator.add(&foreign),
+        Err(GuardianError::UnknownGuardian(_))
+    ));
+
+    let mut forged = Attestation::sign(&outsider, 1, digest);
+    forged.guardian = guardian.pubkey();
+    assert!(matches!(
+        aggregator.add(&forged),
+        Err(GuardianError::InvalidSignature { sequence: 1, .. })
+    ));
+
+    let attestation = Attestation::sign(&guardian, 1, digest);
+    let parsed: Attestation = attestation.to_string().parse().unwrap();
+    assert_eq!(parsed, attestation);
+    aggregator.add(&parsed).unwrap();
+    assert_eq!(aggregator.quorum(1).unwrap().digest, digest);
+}
+
+#[test]
+fn guardian_refuses_messages_the_escrow_did_not_emit() {
+    let dir = temp_path("foreign");
+    let forger = Pubkey::new_unique();
+    let publisher = DirectoryPublisher::new(&dir).unwrap();
+
+    let err = try_run_guardian(
+        "foreign",
+        Keypair::new(),
+        Pubkey::new_unique(),
+        messages_from(1, forger),
+        publisher,
+    )
+    .unwrap_err();
+    assert!(matches!(err, RelayerError::Rejected { sequence: 1, .. }));
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
+
+#[test]
+fn batch_attestation_commits_to_the_legs() {
+    let logger_program_id = Pubkey::new_unique();
+    let keypair = Keypair::new();
+    let transfer = messages(1).0[0];
+    let message = MessageData {
+        amount: 2.into(),
+        kind: MessageKind::Batch as u8,
+        ..transfer
+    };
+    let leg = |amount: u64| BatchTransfer {
+        from_pubkey: Pubkey::new_from_array([1; 32]),
+        to_pubkey: Pubkey::new_from_array([6; 32]),
+        amount: amount.into(),
+    };
+    let batch = Batch::new(Pubkey::new_unique(), &[leg(40), leg(60)]);
+    // Isti zbir na drugacije legove mora dati drugi digest
+    let altered = Batch::new(Pubkey::new_unique(), &[leg(10), leg(90)]);
+
+    let mut collected = Collected::default();
+    let mut guardian = Guardian::new(keypair, ESCROW_PROGRAM_ID, logger_program_id, &mut collected);
+    guardian.deliver_batch(&LOGGER_STATE, &message, &batch).unwrap();
+    guardian.deliver_batch(&LOGGER_STATE, &message, &altered).unwrap();
+    // Batch poruka bez legova se ne potpisuje
+    assert!(matches!(
+        guardian.deliver(&LOGGER_STATE, &message),
+        Err(DestinationError::Rejected(_))
+    ));
+    guardian.deliver(&LOGGER_STATE, &transfer).unwrap();
+    drop(guardian);
+
+    let digests: Vec<_> = collected.0.iter().map(|attestation| attestation.digest).collect();
+    assert_eq!(
+        digests[0],
+        message_digest(&logger_program_id, &message, batch.legs())
+    );
+    assert_ne!(digests[0], digests[1]);
+    assert_ne!(digests[0], digests[2]);
+    assert_eq!(digests[2], message_digest(&logger_program_id, &transfer, &[]));
+}
@@ -0,0 +1,422 @@
+use std::{path::Path, str::FromStr};
+
+use bridge_events::{DepositEvent, MessagePosted, WithdrawEvent};
+use escrow_contract::state::EscrowState;
+use logger_contract::state::{ConsistencyLevel, MessageData};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+use crate::error::IndexerError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS deposits (
+    signature       TEXT    NOT NULL,
+    event_index     INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    user            TEXT    NOT NULL,
+    mint            TEXT    NOT NULL,
+    vault           TEXT    NOT NULL,
+    amount          INTEGER NOT NULL,
+    total_deposited INTEGER NOT NULL,
+    timestamp       INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS deposits_user ON deposits (user, timestamp);
+
+CREATE TABLE IF NOT EXISTS withdrawals (
+    signature       TEXT    NOT NULL,
+    event_index     INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    user            TEXT    NOT NULL,
+    mint            TEXT    NOT NULL,
+    vault           TEXT    NOT NULL,
+    destination     TEXT    NOT NULL,
+    amount          INTEGER NOT NULL,
+    total_deposited INTEGER NOT NULL,
+    timestamp       INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS withdrawals_user ON withdrawals (user, timestamp);
+
+CREATE TABLE IF NOT EXISTS messages (
+    logger_state      TEXT    NOT NULL,
+    sequence          INTEGER NOT NULL,
+    from_pubkey       TEXT    NOT NULL,
+    to_pubkey         TEXT    NOT NULL,
+    amount            INTEGER NOT NULL,
+    timestamp         INTEGER NOT NULL,
+    payer             TEXT    NOT NULL,
+    slot              INTEGER NOT NULL,
+    epoch             INTEGER NOT NULL,
+    consistency_level INTEGER NOT NULL,
+    address           TEXT,
+    signature         TEXT,
+    PRIMARY KEY (logger_state, sequence)
+);
+
+-- State iz escrow config-a i svi sa kojih je escrow migrirao
+CREATE TABLE IF NOT EXISTS logger_states (
+    address TEXT PRIMARY KEY
+);
+
+CREATE TABLE IF NOT EXISTS escrow_snapshots (
+    mint            TEXT    NOT NULL,
+    escrow          TEXT    NOT NULL,
+    vault           TEXT    NOT NULL,
+    total_deposited INTEGER NOT NULL,
+    slot            INTEGER NOT NULL,
+    PRIMARY KEY (mint, slot)
+);
+
+CREATE TABLE IF NOT EXISTS cursors (
+    program   TEXT    PRIMARY KEY,
+    signature TEXT    NOT NULL,
+    slot      INTEGER NOT NULL
+);
+";
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// One deposit or withdrawal of a wallet, newest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub kind: &'static str,
+    pub signature: String,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Deposited and withdrawn totals of one mint on one UTC day.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VolumeRow {
+    pub day: String,
+    pub mint: Pubkey,
+    pub deposited: u64,
+    pub withdrawn: u64,
+}
+
+/// SQLite store of everything the indexer has seen on chain.
+pub struct Db {
+    conn: Connection,
+}
+
+impl Db {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(conn: Connection) -> Result<Self, IndexerError> {
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Last signature indexed for `program`, used as `until` for the next sync.
+    pub fn cursor(&self, program: &Pubkey) -> Result<Option<Signature>, IndexerError> {
+        let signature: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT signature FROM cursors WHERE program = ?1",
+                params![program.to_string()],
+                |row| row.get(0),
+            )
+            .optional()?;
+        signature
+            .map(|signature| {
+                Signature::from_str(&signature).map_err(|_| IndexerError::Corrupt(signature))
+            })
+            .transpose()
+    }
+
+    pub fn transaction(&mut self) -> Result<Transaction<'_>, IndexerError> {
+        Ok(self.conn.transaction()?)
+    }
+
+    /// Logger states whose messages belong to the escrow.
+    pub fn logger_states(&self) -> Result<Vec<Pubkey>, IndexerError> {
+        let mut statement = self.conn.prepare("SELECT address FROM logger_states")?;
+        let rows = statement.query_map([], |row| row.get::<_, String>(0))?;
+        let mut states = Vec::new();
+        for row in rows {
+            states.push(parse_pubkey(row?)?);
+        }
+        Ok(states)
+    }
+
+    /// Indexed message sequences of one logger state, ascending.
+    pub fn message_sequences(&self, logger_state: &Pubkey) -> Result<Vec<u64>, IndexerError> {
+        let mut statement = self.conn.prepare(
+            "SELECT sequence FROM messages WHERE logger_state = ?1 ORDER BY sequence",
+        )?;
+        let rows = statement.query_map(params![logger_state.to_string()], |row| row.get(0))?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    pub fn history(
+        &self,
+        wallet: &Pubkey,
+        days: u32,
+        now: i64,
+    ) -> Result<Vec<HistoryEntry>, IndexerError> {
+        let since = now - i64::from(days) * SECONDS_PER_DAY;
+        let mut statement = self.conn.prepare(
+            "SELECT 'deposit', signature, mint, amount, timestamp
+               FROM deposits WHERE user = ?1 AND timestamp >= ?2
+             UNION ALL
+             SELECT 'withdraw', signature, mint, amount, timestamp
+               FROM withdrawals WHERE user = ?1 AND timestamp >= ?2
+             ORDER BY timestamp DESC",
+        )?;
+        let rows = statement.query_map(params![wallet.to_string(), since], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, u64>(3)?,
+                row.get::<_, i64>(4)?,
+            ))
+        })?;
+
+        let mut history = Vec::new();
+        for row in rows {
+            let (kind, signature, mint, amount, timestamp) = row?;
+            history.push(HistoryEntry {
+                kind: if kind == "deposit" {
+                    "deposit"
+                } else {
+                    "withdraw"
+                },
+                signature,
+                mint: parse_pubkey(mint)?,
+                amount,
+                timestamp,
+            });
+        }
+        Ok(history)
+    }
+
+    pub fn volume(
+        &self,
+        mint: Option<&Pubkey>,
+        days: u32,
+        now: i64,
+    ) -> Result<Vec<VolumeRow>, IndexerError> {
+        let since = now - i64::from(days) * SECONDS_PER_DAY;
+        let mut statement = self.conn.prepare(
+            "SELECT date(timestamp, 'unixepoch') AS day, mint,
+                    SUM(deposited), SUM(withdrawn)
+               FROM (SELECT timestamp, mint, amount AS deposited, 0 AS withdrawn FROM deposits
+                     UNION ALL
+                     SELECT timestamp, mint, 0, amount FROM withdrawals)
+              WHERE timestamp >= ?1 AND (?2 IS NULL OR mint = ?2)
+              GROUP BY day, mint
+              ORDER BY day, mint",
+        )?;
+        let rows =
+            statement.query_map(params![since, mint.map(|mint| mint.to_string())], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, u64>(2)?,
+                    row.get::<_, u64>(3)?,
+                ))
+            })?;
+
+        let mut volume = Vec::new();
+        for row in rows {
+            let (day, mint, deposited, withdrawn) = row?;
+            volume.push(VolumeRow {
+                day,
+                mint: parse_pubkey(mint)?,
+                deposited,
+                withdrawn,
+            });
+        }
+        Ok(volume)
+    }
+}
+
+// Upisi idu kroz transakciju da bi cursor i redovi jedne Solana
+// transakcije bili sacuvani zajedno
+pub fn set_cursor(
+    conn: &Connection,
+    program: &Pubkey,
+    signature: &str,
+    slot: u64,
+) -> Result<(), IndexerError> {
+    conn.execute(
+        "INSERT INTO cursors (program, signature, slot) VALUES (?1, ?2, ?3)
+         ON CONFLICT (program) DO UPDATE SET signature = excluded.signature, slot = excluded.slot",
+        params![program.to_string(), signature, slot],
+    )?;
+    Ok(())
+}
+
+/// Returns false when the event was already indexed.
+pub fn insert_deposit(
+    conn: &Connection,
+    signature: &str,
+    event_index: usize,
+    slot: u64,
+    event: &DepositEvent,
+) -> Result<bool, IndexerError> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO deposits
+            (signature, event_index, slot, user, mint, vault, amount, total_deposited, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            signature,
+            event_index,
+            slot,
+            event.user.to_string(),
+            event.mint.to_string(),
+            event.vault.to_string(),
+            event.amount,
+            event.total_deposited,
+            event.timestamp,
+        ],
+    )?;
+    Ok(inserted == 1)
+}
+
+/// Returns false when the event was already indexed.
+pub fn insert_withdrawal(
+    conn: &Connection,
+    signature: &str,
+    event_index: usize,
+    slot: u64,
+    event: &WithdrawEvent,
+) -> Result<bool, IndexerError> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO withdrawals
+            (signature, event_index, slot, user, mint, vault, destination, amount,
+             total_deposited, timestamp)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            signature,
+            event_index,
+            slot,
+            event.user.to_string(),
+            event.mint.to_string(),
+            event.vault.to_string(),
+            event.destination.to_string(),
+            event.amount,
+            event.total_deposited,
+            event.timestamp,
+        ],
+    )?;
+    Ok(inserted == 1)
+}
+
+pub fn insert_logger_state(conn: &Connection, logger_state: &Pubkey) -> Result<(), IndexerError> {
+    conn.execute(
+        "INSERT OR IGNORE INTO logger_states (address) VALUES (?1)",
+        params![logger_state.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Returns false when the message was already indexed, from its event or
+/// its account.
+pub fn insert_posted_message(
+    conn: &Connection,
+    signature: &str,
+    event: &MessagePosted,
+) -> Result<bool, IndexerError> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO messages
+            (logger_state, sequence, from_pubkey, to_pubkey, amount, timestamp, payer, slot,
+             epoch, consistency_level, signature)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            event.logger_state.to_string(),
+            event.sequence,
+            event.from.to_string(),
+            event.to.to_string(),
+            event.amount,
+            event.timestamp,
+            event.payer.to_string(),
+            event.slot,
+            event.epoch,
+            event.consistency_level,
+            signature,
+        ],
+    )?;
+    if inserted == 0 {
+        // Red je vec upisan sa naloga, dopunjujemo samo potpis
+        conn.execute(
+            "UPDATE messages SET signature = ?3
+              WHERE logger_state = ?1 AND sequence = ?2 AND signature IS NULL",
+            params![event.logger_state.to_string(), event.sequence, signature],
+        )?;
+    }
+    Ok(inserted == 1)
+}
+
+// Poruka procitana sa naloga ima i adresu, koju event nema.
+// Vraca false kada je poruka vec bila indeksirana.
+pub fn upsert_message_account(
+    conn: &Connection,
+    logger_state: &Pubkey,
+    address: &Pubkey,
+    message: &MessageData,
+) -> Result<bool, IndexerError> {
+    let consistency_level: u8 = match message.consistency_level() {
+        ConsistencyLevel::Confirmed => 0,
+        ConsistencyLevel::Finalized => 1,
+    };
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO messages
+            (logger_state, sequence, from_pubkey, to_pubkey, amount, timestamp, payer, slot,
+             epoch, consistency_level, address)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        params![
+            logger_state.to_string(),
+            message.sequence.get(),
+            message.from_pubkey.to_string(),
+            message.to_pubkey.to_string(),
+            message.amount.get(),
+            message.timestamp.get(),
+            message.payer.to_string(),
+            message.slot.get(),
+            message.epoch.get(),
+            consistency_level,
+            address.to_string(),
+        ],
+    )?;
+    if inserted == 0 {
+        conn.execute(
+            "UPDATE messages SET address = ?3 WHERE logger_state = ?1 AND sequence = ?2",
+            params![logger_state.to_string(), message.sequence.get(), address.to_string()],
+        )?;
+    }
+    Ok(inserted == 1)
+}
+
+pub fn insert_escrow_snapshot(
+    conn: &Connection,
+    escrow: &Pubkey,
+    state: &EscrowState,
+    slot: u64,
+) -> Result<(), IndexerError> {
+    conn.execute(
+        "INSERT OR REPLACE INTO escrow_snapshots (mint, escrow, vault, total_deposited, slot)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            state.token_mint.to_string(),
+            escrow.to_string(),
+            state.escrow_vault_account.to_string(),
+            state.total_deposited.get(),
+            slot,
+        ],
+    )?;
+    Ok(())
+}
+
+fn parse_pubkey(value: String) -> Result<Pubkey, IndexerError> {
+    Pubkey::from_str(&value).map_err(|_| IndexerError::Corrupt(value))
+}
@@ -0,0 +1,24 @@
+use bridge_events::error::EventError;
+use solana_client::client_error::ClientError;
+use solana_sdk::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("RPC error: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("Database error: {0}")]
+    Db(#[from] rusqlite::Error),
+    #[error("Invalid program logs: {0}")]
+    Event(#[from] EventError),
+    #[error("Failed to decode account data: {0}")]
+    Decode(#[from] ProgramError),
+    #[error("Invalid value stored in the index: {0}")]
+    Corrupt(String),
+}
+
+impl From<ClientError> for IndexerError {
+    fn from(err: ClientError) -> Self {
+        IndexerError::Rpc(Box::new(err))
+    }
+}
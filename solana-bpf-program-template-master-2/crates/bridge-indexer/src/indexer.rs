@@ -0,0 +1,283 @@
+use std::str::FromStr;
+
+use bridge_events::{parse_logs, BridgeEvent, ProgramEvent};
+use escrow_contract::state::{EscrowConfig, EscrowState};
+use logger_contract::{pod::ZeroCopy, state::MessageData};
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::{RpcProgramAccountsConfig, RpcTransactionConfig},
+    rpc_filter::RpcFilterType,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::{db, db::Db, error::IndexerError};
+
+// Maksimum koji getSignaturesForAddress vraca po pozivu
+const SIGNATURE_PAGE: usize = 1_000;
+
+/// Counts of what one sync pass added to the index.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SyncReport {
+    pub transactions: u64,
+    pub deposits: u64,
+    pub withdrawals: u64,
+    pub messages: u64,
+    pub snapshots: u64,
+}
+
+/// Backfills escrow and logger history from RPC into a [`Db`]. Messages
+/// are indexed only from logger states the escrow config pointed at and
+/// only when the escrow emitted them.
+pub struct Indexer {
+    rpc: RpcClient,
+    db: Db,
+    pub escrow_program_id: Pubkey,
+    pub logger_program_id: Pubkey,
+    emitter: Pubkey,
+}
+
+impl Indexer {
+    pub fn new(
+        rpc: RpcClient,
+        db: Db,
+        escrow_program_id: Pubkey,
+        logger_program_id: Pubkey,
+    ) -> Self {
+        Self {
+            rpc,
+            db,
+            escrow_program_id,
+            logger_program_id,
+            emitter: EscrowConfig::find_address(&escrow_program_id).0,
+        }
+    }
+
+    pub fn db(&self) -> &Db {
+        &self.db
+    }
+
+    /// Indexes every transaction since the stored cursors, then refreshes
+    /// message accounts and takes a snapshot of every escrow.
+    pub fn sync(&mut self) -> Result<SyncReport, IndexerError> {
+        let mut report = SyncReport::default();
+        // Escrow instrukcije rade CPI u logger, pa se iste transakcije
+        // pojavljuju za oba programa; primarni kljucevi sprecavaju duplikate,
+        // a report broji samo nove redove
+        self.sync_config()?;
+        for program in [self.escrow_program_id, self.logger_program_id] {
+            self.sync_program(&program, &mut report)?;
+        }
+        self.sync_messages(&mut report)?;
+        self.snapshot_escrows(&mut report)?;
+        Ok(report)
+    }
+
+    fn sync_program(
+        &mut self,
+        program: &Pubkey,
+        report: &mut SyncReport,
+    ) -> Result<(), IndexerError> {
+        let signatures = self.new_signatures(program)?;
+
+        // RPC vraca najnovije prvo, indeksiramo od najstarijeg da bi cursor
+        // uvek pokazivao na poslednju obradjenu transakciju
+        let mut transactions = Vec::with_capacity(signatures.len());
+        for status in signatures.iter().rev() {
+            let logs = if status.err.is_none() {
+                self.transaction_logs(&status.signature)?
+            } else {
+                Vec::new()
+            };
+            let programs = [self.escrow_program_id, self.logger_program_id];
+            transactions.push((status, parse_logs(&logs, &programs)?));
+        }
+
+        // Migracije prvo, jer poruke starog state-a stizu pre SetLogger transakcije
+        let tx = self.db.transaction()?;
+        for event in transactions.iter().flat_map(|(_, events)| events) {
+            if let ProgramEvent {
+                program_id,
+                event: BridgeEvent::LoggerSet(set),
+            } = event
+            {
+                if *program_id == self.escrow_program_id {
+                    db::insert_logger_state(&tx, &set.previous_state)?;
+                    db::insert_logger_state(&tx, &set.logger_state)?;
+                }
+            }
+        }
+        tx.commit()?;
+        let states = self.db.logger_states()?;
+
+        for (status, events) in transactions {
+            let tx = self.db.transaction()?;
+            for (index, event) in events.into_iter().enumerate() {
+                match event.event {
+                    BridgeEvent::Deposit(deposit) if event.program_id == self.escrow_program_id => {
+                        report.deposits += u64::from(db::insert_deposit(
+                            &tx,
+                            &status.signature,
+                            index,
+                            status.slot,
+                            &deposit,
+                        )?);
+                    }
+                    BridgeEvent::Withdraw(withdraw)
+                        if event.program_id == self.escrow_program_id =>
+                    {
+                        report.withdrawals += u64::from(db::insert_withdrawal(
+                            &tx,
+                            &status.signature,
+                            index,
+                            status.slot,
+                            &withdraw,
+                        )?);
+                    }
+                    BridgeEvent::MessagePosted(posted)
+                        if event.program_id == self.logger_program_id
+                            && posted.emitter == self.emitter
+                            && states.contains(&posted.logger_state) =>
+                    {
+                        report.messages +=
+                            u64::from(db::insert_posted_message(&tx, &status.signature, &posted)?);
+                    }
+                    // Isti discriminator iz nekog drugog programa ne brojimo
+                    _ => {}
+                }
+            }
+            db::set_cursor(&tx, program, &status.signature, status.slot)?;
+            tx.commit()?;
+            report.transactions += 1;
+        }
+        Ok(())
+    }
+
+    fn new_signatures(
+        &self,
+        program: &Pubkey,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, IndexerError> {
+        let until = self.db.cursor(program)?;
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let page = self.rpc.get_signatures_for_address_with_config(
+                program,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(SIGNATURE_PAGE),
+                    commitment: Some(self.rpc.commitment()),
+                },
+            )?;
+            let last_page = page.len() < SIGNATURE_PAGE;
+            before = match page.last() {
+                Some(status) => Some(
+                    Signature::from_str(&status.signature)
+                        .map_err(|_| IndexerError::Corrupt(status.signature.clone()))?,
+                ),
+                None => None,
+            };
+            signatures.extend(page);
+            if last_page {
+                return Ok(signatures);
+            }
+        }
+    }
+
+    fn transaction_logs(&self, signature: &str) -> Result<Vec<String>, IndexerError> {
+        let signature = Signature::from_str(signature)
+            .map_err(|_| IndexerError::Corrupt(signature.to_string()))?;
+        let transaction = self.rpc.get_transaction_with_config(
+            &signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(self.rpc.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        Ok(transaction
+            .transaction
+            .meta
+            .and_then(|meta| Option::from(meta.log_messages))
+            .unwrap_or_default())
+    }
+
+    // State iz config-a se belezi pri svakom sync-u, i kada ga escrow jos nije menjao
+    fn sync_config(&mut self) -> Result<(), IndexerError> {
+        let config = self
+            .program_accounts(&self.escrow_program_id, EscrowConfig::LEN)?
+            .into_iter()
+            .find(|(address, _)| *address == self.emitter);
+        if let Some((_, data)) = config {
+            let config = EscrowConfig::load(&data)?;
+            let tx = self.db.transaction()?;
+            db::insert_logger_state(&tx, &config.logger_state)?;
+            tx.commit()?;
+        }
+        Ok(())
+    }
+
+    fn sync_messages(&mut self, report: &mut SyncReport) -> Result<(), IndexerError> {
+        // PDA poruke zavisi od state-a, pa prihvatamo samo poruke state-ova
+        // koje je escrow koristio
+        let states = self.db.logger_states()?;
+        let accounts = self.program_accounts(&self.logger_program_id, MessageData::LEN)?;
+        let tx = self.db.transaction()?;
+        for (address, data) in accounts {
+            // Samo PDA poruke, ostali nalozi iste velicine nisu poruke
+            let message = match MessageData::load(&data) {
+                Ok(message) => message,
+                Err(_) => continue,
+            };
+            if message.emitter != self.emitter {
+                continue;
+            }
+            let sequence = message.sequence.get();
+            let state = states.iter().find(|state| {
+                MessageData::find_address(state, sequence, &self.logger_program_id).0 == address
+            });
+            if let Some(state) = state {
+                report.messages +=
+                    u64::from(db::upsert_message_account(&tx, state, &address, message)?);
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn snapshot_escrows(&mut self, report: &mut SyncReport) -> Result<(), IndexerError> {
+        let slot = self.rpc.get_slot()?;
+        let accounts = self.program_accounts(&self.escrow_program_id, EscrowState::LEN)?;
+        let tx = self.db.transaction()?;
+        for (address, data) in accounts {
+            let state = EscrowState::load_unchecked(&data)?;
+            if !state.is_initialized() {
+                continue;
+            }
+            db::insert_escrow_snapshot(&tx, &address, state, slot)?;
+            report.snapshots += 1;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn program_accounts(
+        &self,
+        program: &Pubkey,
+        data_size: usize,
+    ) -> Result<Vec<(Pubkey, Vec<u8>)>, IndexerError> {
+        let accounts = self.rpc.get_program_accounts_with_config(
+            program,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::DataSize(data_size as u64)]),
+                ..RpcProgramAccountsConfig::default()
+            },
+        )?;
+        Ok(accounts
+            .into_iter()
+            .map(|(address, account)| (address, account.data))
+            .collect())
+    }
+}
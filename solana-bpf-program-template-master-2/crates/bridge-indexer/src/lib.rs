@@ -0,0 +1,7 @@
+pub mod db;
+pub mod error;
+pub mod indexer;
+
+pub use db::{Db, HistoryEntry, VolumeRow};
+pub use error::IndexerError;
+pub use indexer::{Indexer, SyncReport};
@@ -0,0 +1,135 @@
+use std::{
+    path::PathBuf,
+    process::exit,
+    thread::sleep,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bridge_indexer::{Db, Indexer, IndexerError, SyncReport};
+use clap::{Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+const DEFAULT_ESCROW_PROGRAM_ID: &str = "6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R";
+const DEFAULT_LOGGER_PROGRAM_ID: &str = "HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv";
+
+#[derive(Parser)]
+#[command(
+    name = "bridge-indexer",
+    about = "Index escrow and logger history into SQLite"
+)]
+struct Args {
+    #[arg(
+        long,
+        short = 'u',
+        global = true,
+        default_value = "http://127.0.0.1:8899"
+    )]
+    url: String,
+
+    #[arg(long, global = true, default_value = DEFAULT_ESCROW_PROGRAM_ID)]
+    escrow_program_id: Pubkey,
+
+    #[arg(long, global = true, default_value = DEFAULT_LOGGER_PROGRAM_ID)]
+    logger_program_id: Pubkey,
+
+    /// SQLite database file
+    #[arg(long, global = true, default_value = "bridge-index.sqlite")]
+    db: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Backfill everything since the last sync and exit
+    Sync,
+    /// Keep syncing until interrupted
+    Tail {
+        #[arg(long, default_value_t = 5_000)]
+        poll_interval_ms: u64,
+    },
+    /// Deposits and withdrawals of a wallet
+    History {
+        #[arg(long)]
+        wallet: Pubkey,
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// Deposited and withdrawn amounts per mint per day
+    Volume {
+        #[arg(long)]
+        mint: Option<Pubkey>,
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+    },
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(err) = run(args) {
+        eprintln!("error: {}", err);
+        exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), IndexerError> {
+    let db = Db::open(&args.db)?;
+    match args.command {
+        Command::Sync => {
+            let report = indexer(&args, db).sync()?;
+            print_report(&report);
+        }
+        Command::Tail { poll_interval_ms } => {
+            let mut indexer = indexer(&args, db);
+            loop {
+                match indexer.sync() {
+                    Ok(report) if report.transactions > 0 => print_report(&report),
+                    Ok(_) => {}
+                    // RPC greske su cesto prolazne, pokusavamo ponovo u sledecem krugu
+                    Err(IndexerError::Rpc(err)) => eprintln!("indexer: RPC error: {}", err),
+                    Err(err) => return Err(err),
+                }
+                sleep(Duration::from_millis(poll_interval_ms));
+            }
+        }
+        Command::History { wallet, days } => {
+            let history = db.history(&wallet, days, now())?;
+            for entry in &history {
+                println!(
+                    "{:<9} {:>20} {} {} {}",
+                    entry.kind, entry.amount, entry.mint, entry.timestamp, entry.signature
+                );
+            }
+            println!("{} transfer(s) in the last {} day(s)", history.len(), days);
+        }
+        Command::Volume { mint, days } => {
+            for row in db.volume(mint.as_ref(), days, now())? {
+                println!(
+                    "{} {} deposited {} withdrawn {}",
+                    row.day, row.mint, row.deposited, row.withdrawn
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn indexer(args: &Args, db: Db) -> Indexer {
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    Indexer::new(rpc, db, args.escrow_program_id, args.logger_program_id)
+}
+
+fn print_report(report: &SyncReport) {
+    println!(
+        "indexer: {} transaction(s), {} deposit(s), {} withdrawal(s), {} message(s), {} snapshot(s)",
+        report.transactions, report.deposits, report.withdrawals, report.messages, report.snapshots
+    );
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() as i64)
+}
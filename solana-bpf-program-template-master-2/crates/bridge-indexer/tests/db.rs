@@ -0,0 +1,157 @@
+use bridge_events::{DepositEvent, WithdrawEvent};
+use bridge_indexer::{db, Db, HistoryEntry, VolumeRow};
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+// 2023-11-14 22:13:20 UTC
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 86_400;
+
+fn deposit(user: Pubkey, mint: Pubkey, amount: u64, timestamp: i64) -> DepositEvent {
+    DepositEvent {
+        user,
+        mint,
+        vault: Pubkey::new_from_array([9; 32]),
+        amount,
+        total_deposited: amount,
+        timestamp,
+    }
+}
+
+fn withdrawal(user: Pubkey, mint: Pubkey, amount: u64, timestamp: i64) -> WithdrawEvent {
+    WithdrawEvent {
+        user,
+        mint,
+        vault: Pubkey::new_from_array([9; 32]),
+        destination: user,
+        amount,
+        total_deposited: 0,
+        timestamp,
+    }
+}
+
+fn signature(byte: u8) -> String {
+    Signature::from([byte; 64]).to_string()
+}
+
+struct Fixture {
+    db: Db,
+    alice: Pubkey,
+    bob: Pubkey,
+    usdc: Pubkey,
+    wsol: Pubkey,
+}
+
+// Alice: dva depozita i jedan withdraw, Bob: jedan stari depozit
+fn fixture() -> Fixture {
+    let mut db = Db::open_in_memory().unwrap();
+    let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let (usdc, wsol) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+    let tx = db.transaction().unwrap();
+    db::insert_deposit(&tx, &signature(1), 0, 10, &deposit(alice, usdc, 100, NOW - 2 * DAY)).unwrap();
+    db::insert_deposit(&tx, &signature(2), 0, 20, &deposit(alice, wsol, 7, NOW - DAY)).unwrap();
+    db::insert_withdrawal(&tx, &signature(3), 0, 30, &withdrawal(alice, usdc, 40, NOW - DAY)).unwrap();
+    db::insert_deposit(&tx, &signature(4), 0, 5, &deposit(bob, usdc, 500, NOW - 40 * DAY)).unwrap();
+    // Ista transakcija i indeks se upisuju samo jednom
+    db::insert_deposit(&tx, &signature(1), 0, 10, &deposit(alice, usdc, 100, NOW - 2 * DAY)).unwrap();
+    tx.commit().unwrap();
+
+    Fixture {
+        db,
+        alice,
+        bob,
+        usdc,
+        wsol,
+    }
+}
+
+#[test]
+fn history_lists_a_wallet_newest_first_within_the_window() {
+    let Fixture {
+        db,
+        alice,
+        bob,
+        usdc,
+        wsol,
+    } = fixture();
+
+    let history = db.history(&alice, 30, NOW).unwrap();
+    assert_eq!(
+        history.iter().map(|entry| entry.kind).collect::<Vec<_>>(),
+        vec!["deposit", "withdraw", "deposit"]
+    );
+    assert_eq!(
+        history[1],
+        HistoryEntry {
+            kind: "withdraw",
+            signature: signature(3),
+            mint: usdc,
+            amount: 40,
+            timestamp: NOW - DAY,
+        }
+    );
+    assert_eq!((history[0].mint, history[2].mint), (wsol, usdc));
+
+    assert_eq!(db.history(&alice, 1, NOW).unwrap().len(), 2);
+    assert!(db.history(&bob, 30, NOW).unwrap().is_empty());
+    assert_eq!(db.history(&bob, 40, NOW).unwrap().len(), 1);
+    assert!(db.history(&Pubkey::new_unique(), 365, NOW).unwrap().is_empty());
+}
+
+#[test]
+fn volume_sums_per_day_and_mint() {
+    let Fixture {
+        db, usdc, wsol, ..
+    } = fixture();
+
+    let mut expected = vec![
+        VolumeRow {
+            day: "2023-11-12".to_string(),
+            mint: usdc,
+            deposited: 100,
+            withdrawn: 0,
+        },
+        VolumeRow {
+            day: "2023-11-13".to_string(),
+            mint: usdc,
+            deposited: 0,
+            withdrawn: 40,
+        },
+        VolumeRow {
+            day: "2023-11-13".to_string(),
+            mint: wsol,
+            deposited: 7,
+            withdrawn: 0,
+        },
+    ];
+    // Redovi istog dana idu po mint-u kao tekstu
+    expected[1..].sort_by_key(|row| row.mint.to_string());
+    assert_eq!(db.volume(None, 30, NOW).unwrap(), expected);
+
+    assert_eq!(
+        db.volume(Some(&wsol), 30, NOW).unwrap(),
+        vec![VolumeRow {
+            day: "2023-11-13".to_string(),
+            mint: wsol,
+            deposited: 7,
+            withdrawn: 0,
+        }]
+    );
+    assert_eq!(db.volume(Some(&usdc), 60, NOW).unwrap().len(), 3);
+    assert!(db.volume(None, 0, NOW).unwrap().is_empty());
+}
+
+#[test]
+fn cursor_is_stored_per_program() {
+    let mut db = Db::open_in_memory().unwrap();
+    let (escrow, logger) = (Pubkey::new_unique(), Pubkey::new_unique());
+    assert_eq!(db.cursor(&escrow).unwrap(), None);
+
+    let tx = db.transaction().unwrap();
+    db::set_cursor(&tx, &escrow, &signature(1), 10).unwrap();
+    db::set_cursor(&tx, &escrow, &signature(2), 20).unwrap();
+    tx.commit().unwrap();
+
+    assert_eq!(db.cursor(&escrow).unwrap(), Some(Signature::from([2; 64])));
+    assert_eq!(db.cursor(&logger).unwrap(), None);
+}
@@ -0,0 +1,432 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use bridge_events::{DepositEvent, Event, LoggerSetEvent, MessagePosted, WithdrawEvent};
+use bridge_indexer::{Db, Indexer, SyncReport};
+use bytemuck::bytes_of;
+use escrow_contract::state::{EscrowConfig, EscrowState};
+use logger_contract::state::{ConsistencyLevel, LoggerState, MessageData, MessageKind};
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use solana_sdk::{account::Account, pubkey::Pubkey, signature::Signature};
+
+const NOW: i64 = 1_700_000_000;
+
+struct Transaction {
+    signature: String,
+    slot: u64,
+    logs: Vec<String>,
+}
+
+#[derive(Default)]
+struct Ledger {
+    // Potpisi po adresi, najnoviji na kraju
+    signatures: HashMap<String, Vec<String>>,
+    transactions: HashMap<String, Transaction>,
+    accounts: Vec<(Pubkey, Account)>,
+}
+
+/// Answers the RPC methods the indexer calls from an in-memory ledger the
+/// test appends to between syncs.
+#[derive(Clone, Default)]
+struct FakeRpc(Arc<Mutex<Ledger>>);
+
+impl FakeRpc {
+    fn add_transaction(&self, addresses: &[Pubkey], slot: u64, logs: Vec<String>) -> String {
+        let mut ledger = self.0.lock().unwrap();
+        let signature = Signature::from([ledger.transactions.len() as u8 + 1; 64]).to_string();
+        for address in addresses {
+            ledger
+                .signatures
+                .entry(address.to_string())
+                .or_default()
+                .push(signature.clone());
+        }
+        ledger.transactions.insert(
+            signature.clone(),
+            Transaction {
+                signature: signature.clone(),
+                slot,
+                logs,
+            },
+        );
+        signature
+    }
+
+    fn add_account<T: bytemuck::Pod>(&self, address: Pubkey, owner: Pubkey, data: &T) {
+        self.0.lock().unwrap().accounts.push((
+            address,
+            Account {
+                lamports: 1_000_000,
+                data: bytes_of(data).to_vec(),
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            },
+        ));
+    }
+
+    fn signatures(&self, params: &Value) -> Value {
+        let ledger = self.0.lock().unwrap();
+        let until = params[1]["until"].as_str();
+        let mut statuses = Vec::new();
+        let signatures = ledger
+            .signatures
+            .get(params[0].as_str().unwrap())
+            .cloned()
+            .unwrap_or_default();
+        for signature in signatures.iter().rev() {
+            if Some(signature.as_str()) == until {
+                break;
+            }
+            statuses.push(json!({
+                "signature": signature,
+                "slot": ledger.transactions[signature].slot,
+                "err": null,
+                "memo": null,
+                "blockTime": null,
+                "confirmationStatus": "finalized",
+            }));
+        }
+        json!(statuses)
+    }
+
+    fn transaction(&self, params: &Value) -> Value {
+        let ledger = self.0.lock().unwrap();
+        let transaction = &ledger.transactions[params[0].as_str().unwrap()];
+        json!({
+            "slot": transaction.slot,
+            "blockTime": null,
+            "version": "legacy",
+            "transaction": {
+                "signatures": [transaction.signature],
+                "message": {
+                    "header": {
+                        "numRequiredSignatures": 1,
+                        "numReadonlySignedAccounts": 0,
+                        "numReadonlyUnsignedAccounts": 0,
+                    },
+                    "accountKeys": [],
+                    "recentBlockhash": "11111111111111111111111111111111",
+                    "instructions": [],
+                },
+            },
+            "meta": {
+                "err": null,
+                "status": { "Ok": null },
+                "fee": 5000,
+                "preBalances": [],
+                "postBalances": [],
+                "logMessages": transaction.logs,
+            },
+        })
+    }
+
+    fn program_accounts(&self, params: &Value) -> Value {
+        let ledger = self.0.lock().unwrap();
+        let program = params[0].as_str().unwrap();
+        let data_size = params[1]["filters"][0]["dataSize"].as_u64();
+        let accounts: Vec<Value> = ledger
+            .accounts
+            .iter()
+            .filter(|(_, account)| account.owner.to_string() == program)
+            .filter(|(_, account)| data_size.is_none_or(|size| account.data.len() as u64 == size))
+            .map(|(address, account)| {
+                json!({
+                    "pubkey": address.to_string(),
+                    "account": UiAccount::encode(
+                        address,
+                        account,
+                        UiAccountEncoding::Base64,
+                        None,
+                        None,
+                    ),
+                })
+            })
+            .collect();
+        json!(accounts)
+    }
+}
+
+#[async_trait]
+impl RpcSender for FakeRpc {
+    async fn send(&self, request: RpcRequest, params: Value) -> ClientResult<Value> {
+        Ok(match request {
+            RpcRequest::GetSignaturesForAddress => self.signatures(&params),
+            RpcRequest::GetTransaction => self.transaction(&params),
+            RpcRequest::GetProgramAccounts => self.program_accounts(&params),
+            RpcRequest::GetSlot => json!(100),
+            RpcRequest::GetVersion => json!({ "solana-core": "1.18.26", "feature-set": 0 }),
+            other => panic!("unexpected RPC request {}", other),
+        })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats::default()
+    }
+
+    fn url(&self) -> String {
+        "fake".to_string()
+    }
+}
+
+fn data_line<E: Event>(event: &E) -> String {
+    format!(
+        "Program data: {} {}",
+        STANDARD.encode(E::DISCRIMINATOR),
+        STANDARD.encode(borsh::to_vec(event).unwrap())
+    )
+}
+
+struct Bridge {
+    rpc: FakeRpc,
+    escrow: Pubkey,
+    logger: Pubkey,
+    logger_state: Pubkey,
+    user: Pubkey,
+    mint: Pubkey,
+    vault: Pubkey,
+}
+
+impl Bridge {
+    fn new() -> Self {
+        let rpc = FakeRpc::default();
+        let (escrow, logger) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let logger_state = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let vault = EscrowState::find_vault_address(&mint, &escrow).0;
+        rpc.add_account(logger_state, logger, &LoggerState::new(Pubkey::new_unique()));
+        rpc.add_account(
+            EscrowConfig::find_address(&escrow).0,
+            escrow,
+            &EscrowConfig::new(Pubkey::new_unique(), logger, logger_state),
+        );
+        rpc.add_account(
+            EscrowState::find_address(&mint, &escrow).0,
+            escrow,
+            &EscrowState::new(mint, vault, 0),
+        );
+        Self {
+            rpc,
+            escrow,
+            logger,
+            logger_state,
+            user: Pubkey::new_unique(),
+            mint,
+            vault,
+        }
+    }
+
+    fn indexer(&self, db: Db) -> Indexer {
+        Indexer::new(
+            RpcClient::new_sender(self.rpc.clone(), RpcClientConfig::default()),
+            db,
+            self.escrow,
+            self.logger,
+        )
+    }
+
+    // Escrow na dubini 1 emituje svoj event, logger kroz CPI na dubini 2
+    fn transfer(&self, sequence: u64, amount: u64, deposit: bool) -> String {
+        self.transfer_to(&self.logger_state, sequence, amount, deposit)
+    }
+
+    fn transfer_to(&self, logger_state: &Pubkey, sequence: u64, amount: u64, deposit: bool) -> String {
+        let (from, to) = if deposit {
+            (self.user, self.vault)
+        } else {
+            (self.vault, self.user)
+        };
+        let message = MessageData::new(
+            from,
+            to,
+            amount,
+            NOW as u64,
+            sequence,
+            self.user,
+            10 * sequence,
+            0,
+            ConsistencyLevel::Finalized,
+            EscrowConfig::find_address(&self.escrow).0,
+            MessageKind::Transfer,
+        );
+        let posted = MessagePosted {
+            sequence,
+            logger_state: *logger_state,
+            from,
+            to,
+            amount,
+            timestamp: NOW as u64,
+            payer: self.user,
+            slot: 10 * sequence,
+            epoch: 0,
+            consistency_level: 1,
+            emitter: message.emitter,
+            kind: message.kind,
+        };
+        let escrow_event = if deposit {
+            data_line(&DepositEvent {
+                user: self.user,
+                mint: self.mint,
+                vault: self.vault,
+                amount,
+                total_deposited: amount,
+                timestamp: NOW,
+            })
+        } else {
+            data_line(&WithdrawEvent {
+                user: self.user,
+                mint: self.mint,
+                vault: self.vault,
+                destination: self.user,
+                amount,
+                total_deposited: 0,
+                timestamp: NOW,
+            })
+        };
+        let logs = vec![
+            format!("Program {} invoke [1]", self.escrow),
+            escrow_event,
+            format!("Program {} invoke [2]", self.logger),
+            data_line(&posted),
+            format!("Program {} success", self.logger),
+            format!("Program {} success", self.escrow),
+        ];
+
+        let address = MessageData::find_address(logger_state, sequence, &self.logger).0;
+        self.rpc.add_account(address, self.logger, &message);
+        self.rpc
+            .add_transaction(&[self.escrow, self.logger], 10 * sequence, logs)
+    }
+}
+
+#[test]
+fn sync_indexes_events_messages_and_escrows() {
+    let bridge = Bridge::new();
+    bridge.transfer(1, 300, true);
+    let last = bridge.transfer(2, 100, false);
+    // Nalog iste velicine kao poruka, ali na adresi koja nije njen PDA
+    bridge.rpc.add_account(
+        Pubkey::new_unique(),
+        bridge.logger,
+        &MessageData::new(
+            bridge.user,
+            bridge.vault,
+            1,
+            0,
+            9,
+            bridge.user,
+            0,
+            0,
+            ConsistencyLevel::Confirmed,
+            EscrowConfig::find_address(&bridge.escrow).0,
+            MessageKind::Transfer,
+        ),
+    );
+
+    let mut indexer = bridge.indexer(Db::open_in_memory().unwrap());
+    let report = indexer.sync().unwrap();
+    assert_eq!(
+        report,
+        SyncReport {
+            // Iste dve transakcije za escrow i za logger
+            transactions: 4,
+            deposits: 1,
+            withdrawals: 1,
+            // Event i PDA nalog iste poruke su jedan red
+            messages: 2,
+            snapshots: 1,
+        }
+    );
+
+    let db = indexer.db();
+    let last = Some(last.parse::<Signature>().unwrap());
+    assert_eq!(db.cursor(&bridge.escrow).unwrap(), last);
+    assert_eq!(db.cursor(&bridge.logger).unwrap(), last);
+
+    let history = db.history(&bridge.user, 1, NOW).unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(
+        history.iter().map(|entry| entry.amount).sum::<u64>(),
+        400
+    );
+    let volume = db.volume(Some(&bridge.mint), 1, NOW).unwrap();
+    assert_eq!(volume.len(), 1);
+    assert_eq!((volume[0].deposited, volume[0].withdrawn), (300, 100));
+}
+
+#[test]
+fn sync_resumes_after_the_stored_cursor() {
+    let bridge = Bridge::new();
+    bridge.transfer(1, 300, true);
+    let mut indexer = bridge.indexer(Db::open_in_memory().unwrap());
+    assert_eq!(indexer.sync().unwrap().transactions, 2);
+
+    // Drugi prolaz bez novih transakcija ne dodaje evente
+    let report = indexer.sync().unwrap();
+    assert_eq!(report.transactions, 0);
+    assert_eq!(report.deposits, 0);
+
+    bridge.transfer(2, 50, true);
+    let report = indexer.sync().unwrap();
+    assert_eq!((report.transactions, report.deposits), (2, 1));
+    assert_eq!(indexer.db().history(&bridge.user, 1, NOW).unwrap().len(), 2);
+    assert_eq!(
+        indexer.db().volume(None, 1, NOW).unwrap()[0].deposited,
+        350
+    );
+}
+
+#[test]
+fn messages_are_keyed_by_state_and_taken_only_from_escrow_states() {
+    let bridge = Bridge::new();
+    let (previous, foreign) = (Pubkey::new_unique(), Pubkey::new_unique());
+    for state in [previous, foreign] {
+        bridge
+            .rpc
+            .add_account(state, bridge.logger, &LoggerState::new(Pubkey::new_unique()));
+    }
+
+    // Escrow je poceo na `previous` i presao na state iz config-a;
+    // `foreign` je state istog logger programa koji escrow nikad nije koristio
+    bridge.transfer_to(&previous, 1, 300, true);
+    bridge.rpc.add_transaction(
+        &[bridge.escrow],
+        15,
+        vec![
+            format!("Program {} invoke [1]", bridge.escrow),
+            data_line(&LoggerSetEvent {
+                previous_program: bridge.logger,
+                previous_state: previous,
+                logger_program: bridge.logger,
+                logger_state: bridge.logger_state,
+                timestamp: NOW,
+            }),
+            format!("Program {} success", bridge.escrow),
+        ],
+    );
+    bridge.transfer(1, 100, false);
+    bridge.transfer_to(&foreign, 1, 50, true);
+
+    let mut indexer = bridge.indexer(Db::open_in_memory().unwrap());
+    let report = indexer.sync().unwrap();
+    assert_eq!(report.messages, 2);
+
+    let db = indexer.db();
+    assert_eq!(db.message_sequences(&previous).unwrap(), vec![1]);
+    assert_eq!(db.message_sequences(&bridge.logger_state).unwrap(), vec![1]);
+    assert!(db.message_sequences(&foreign).unwrap().is_empty());
+
+    // Drugi prolaz samo osvezava postojece redove
+    assert_eq!(indexer.sync().unwrap().messages, 0);
+}
@@ -0,0 +1,114 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::RelayerError;
+
+/// A sequence the cursor moved past without delivering it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeadLetter {
+    pub sequence: u64,
+    pub reason: String,
+}
+
+/// Last delivered logger sequence, persisted after every delivery so a
+/// restarted relayer continues from the next message. Skipped sequences are
+/// appended to a dead-letter file next to it, with the `.dead` extension.
+/// Sequences restart in every logger state, so the file also records the
+/// state it counts and refuses to load for another one.
+pub struct Cursor {
+    path: PathBuf,
+    logger_state: Pubkey,
+    last_delivered: u64,
+    dead_letters: Vec<DeadLetter>,
+}
+
+impl Cursor {
+    pub fn load<P: AsRef<Path>>(path: P, logger_state: &Pubkey) -> Result<Self, RelayerError> {
+        let path = path.as_ref().to_path_buf();
+        let last_delivered = match fs::read_to_string(&path) {
+            Ok(contents) => {
+                // Format: state, razmak, poslednja isporucena sekvenca
+                let invalid = || RelayerError::InvalidCursor(path.display().to_string());
+                let (state, sequence) = contents.trim().split_once(' ').ok_or_else(invalid)?;
+                let state = Pubkey::from_str(state).map_err(|_| invalid())?;
+                let sequence = sequence.parse().map_err(|_| invalid())?;
+                if state != *logger_state {
+                    return Err(RelayerError::CursorStateMismatch {
+                        cursor: state,
+                        logger_state: *logger_state,
+                    });
+                }
+                sequence
+            }
+            Err(err) if err.kind() == ErrorKind::NotFound => 0,
+            Err(err) => return Err(err.into()),
+        };
+        let dead_letters = match fs::read_to_string(Self::dead_letter_path(&path)) {
+            Ok(contents) => contents
+                .lines()
+                .map(|line| {
+                    let (sequence, reason) = line.split_once('\t').unwrap_or((line, ""));
+                    Ok(DeadLetter {
+                        sequence: sequence
+                            .parse()
+                            .map_err(|_| RelayerError::InvalidCursor(line.to_string()))?,
+                        reason: reason.to_string(),
+                    })
+                })
+                .collect::<Result<_, RelayerError>>()?,
+            Err(err) if err.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self {
+            path,
+            logger_state: *logger_state,
+            last_delivered,
+            dead_letters,
+        })
+    }
+
+    pub fn logger_state(&self) -> &Pubkey {
+        &self.logger_state
+    }
+
+    pub fn last_delivered(&self) -> u64 {
+        self.last_delivered
+    }
+
+    pub fn dead_letters(&self) -> &[DeadLetter] {
+        &self.dead_letters
+    }
+
+    /// Records `sequence` in the dead-letter file, then advances past it.
+    pub fn skip(&mut self, sequence: u64, reason: &str) -> Result<(), RelayerError> {
+        // Jedna linija po poruci: sekvenca, tab, razlog
+        let reason = reason.replace(['\n', '\r', '\t'], " ");
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(Self::dead_letter_path(&self.path))?;
+        writeln!(file, "{}\t{}", sequence, reason)?;
+        file.sync_all()?;
+        self.dead_letters.push(DeadLetter { sequence, reason });
+        self.advance(sequence)
+    }
+
+    pub fn advance(&mut self, sequence: u64) -> Result<(), RelayerError> {
+        // Upis preko privremenog fajla da prekid ne ostavi polovican cursor
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, format!("{} {}", self.logger_state, sequence))?;
+        fs::rename(&tmp, &self.path)?;
+        self.last_delivered = sequence;
+        Ok(())
+    }
+
+    fn dead_letter_path(path: &Path) -> PathBuf {
+        path.with_extension("dead")
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::{BTreeMap, HashMap};
+
+use escrow_contract::state::Batch;
+use logger_contract::state::MessageData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::DestinationError;
+
+pub trait Destination {
+    fn name(&self) -> &str;
+
+    /// Delivers one message of `logger_state`. Implementations should treat
+    /// a repeated sequence of the same state as already delivered.
+    fn deliver(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+    ) -> Result<(), DestinationError>;
+
+    /// Delivers a batch message together with its legs. Destinations that
+    /// only need the combined message keep this default.
+    fn deliver_batch(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+        _batch: &Batch,
+    ) -> Result<(), DestinationError> {
+        self.deliver(logger_state, message)
+    }
+}
+
+pub struct LogDestination;
+
+impl Destination for LogDestination {
+    fn name(&self) -> &str {
+        "log"
+    }
+
+    fn deliver(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+    ) -> Result<(), DestinationError> {
+        println!(
+            "message #{} of {} slot {}: {} -> {} amount {} (payer {})",
+            message.sequence,
+            logger_state,
+            message.slot,
+            message.from_pubkey,
+            message.to_pubkey,
+            message.amount,
+            message.payer
+        );
+        Ok(())
+    }
+}
+
+/// In-memory ledger that applies every message as a transfer `from -> to`.
+/// Messages are keyed by `(logger_state, sequence)`.
+#[derive(Default)]
+pub struct MockChainDestination {
+    pub delivered: BTreeMap<(Pubkey, u64), MessageData>,
+    pub net_flow: HashMap<Pubkey, i128>,
+}
+
+impl MockChainDestination {
+    pub fn last_sequence(&self, logger_state: &Pubkey) -> u64 {
+        self.delivered
+            .range((*logger_state, 0)..=(*logger_state, u64::MAX))
+            .next_back()
+            .map_or(0, |((_, sequence), _)| *sequence)
+    }
+}
+
+impl Destination for MockChainDestination {
+    fn name(&self) -> &str {
+        "mock-chain"
+    }
+
+    fn deliver(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+    ) -> Result<(), DestinationError> {
+        let key = (*logger_state, message.sequence.get());
+        if self.delivered.contains_key(&key) {
+            return Ok(());
+        }
+        // Redosled se proverava samo unutar istog state-a
+        let last = self.last_sequence(logger_state);
+        if key.1 < last {
+            return Err(DestinationError::Rejected(format!(
+                "sequence {} of {} arrived after {}",
+                key.1, logger_state, last
+            )));
+        }
+
+        *self.net_flow.entry(message.from_pubkey).or_default() -= message.amount.get() as i128;
+        *self.net_flow.entry(message.to_pubkey).or_default() += message.amount.get() as i128;
+        self.delivered.insert(key, *message);
+        Ok(())
+    }
+}
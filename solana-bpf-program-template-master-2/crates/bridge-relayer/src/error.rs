@@ -0,0 +1,38 @@
+use bridge_client::BridgeClientError;
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RelayerError {
+    #[error("Message source error: {0}")]
+    Source(#[from] BridgeClientError),
+    #[error("Cursor I/O error: {0}")]
+    CursorIo(#[from] std::io::Error),
+    #[error("Invalid cursor file: {0}")]
+    InvalidCursor(String),
+    #[error("Cursor counts logger state {cursor}, not {logger_state}")]
+    CursorStateMismatch {
+        cursor: Pubkey,
+        logger_state: Pubkey,
+    },
+    #[error("Destination {destination} rejected message {sequence}: {reason}")]
+    Rejected {
+        destination: String,
+        sequence: u64,
+        reason: String,
+    },
+    #[error("Message {0} was overwritten in the ring before it was delivered")]
+    Evicted(u64),
+    #[error("Cannot skip message {sequence}, the next undelivered message is {next}")]
+    SkipOutOfOrder { sequence: u64, next: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum DestinationError {
+    // Privremena greska, relayer ponavlja isporuku sa backoff-om
+    #[error("{0}")]
+    Retryable(String),
+    // Trajna greska, run_once staje, a run() je upisuje u dead-letter fajl i preskace
+    #[error("{0}")]
+    Rejected(String),
+}
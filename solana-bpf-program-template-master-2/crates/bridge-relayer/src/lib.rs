@@ -0,0 +1,11 @@
+pub mod cursor;
+pub mod destination;
+pub mod error;
+pub mod relayer;
+pub mod source;
+
+pub use cursor::{Cursor, DeadLetter};
+pub use destination::{Destination, LogDestination, MockChainDestination};
+pub use error::{DestinationError, RelayerError};
+pub use relayer::{Relayer, RelayerConfig};
+pub use source::MessageSource;
@@ -0,0 +1,133 @@
+use std::{path::PathBuf, process::exit, str::FromStr, time::Duration};
+
+use bridge_client::BridgeClient;
+use bridge_relayer::{
+    Cursor, Destination, LogDestination, MockChainDestination, Relayer, RelayerConfig,
+    RelayerError,
+};
+use clap::{Parser, ValueEnum};
+use log::{error, info};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    pubkey::Pubkey,
+    signature::{read_keypair_file, Signer},
+};
+
+const DEFAULT_ESCROW_PROGRAM_ID: &str = "6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R";
+const DEFAULT_LOGGER_PROGRAM_ID: &str = "HFroz2wV8jgypuLEggSmZWTsxnnLNewjkfNX42UnFjyv";
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DestinationKind {
+    Log,
+    MockChain,
+}
+
+#[derive(Parser)]
+#[command(name = "bridge-relayer", about = "Relay logger messages to a destination")]
+struct Args {
+    #[arg(long, short = 'u', default_value = "http://127.0.0.1:8899")]
+    url: String,
+
+    #[arg(long, default_value = DEFAULT_ESCROW_PROGRAM_ID)]
+    escrow_program_id: Pubkey,
+
+    #[arg(long, default_value = DEFAULT_LOGGER_PROGRAM_ID)]
+    logger_program_id: Pubkey,
+
+    /// Logger state address, or the keypair file it was created from
+    #[arg(long, default_value = "logger_state.json")]
+    logger_state: String,
+
+    /// File holding the logger state and its last delivered sequence
+    #[arg(long, default_value = "relayer.cursor")]
+    cursor: PathBuf,
+
+    #[arg(long, value_enum, default_value = "log")]
+    destination: DestinationKind,
+
+    #[arg(long, default_value_t = 2_000)]
+    poll_interval_ms: u64,
+
+    /// Deliver everything that is final and exit
+    #[arg(long)]
+    once: bool,
+
+    /// Record SEQUENCE in the dead-letter file, move the cursor past it and exit
+    #[arg(long, value_name = "SEQUENCE", conflicts_with = "once")]
+    skip: Option<u64>,
+}
+
+fn main() {
+    solana_logger::setup_with_default("info");
+    let args = Args::parse();
+
+    let logger_state = Pubkey::from_str(&args.logger_state).or_else(|_| {
+        read_keypair_file(&args.logger_state).map(|keypair| keypair.pubkey())
+    });
+    let logger_state = match logger_state {
+        Ok(logger_state) => logger_state,
+        Err(err) => {
+            error!("invalid --logger-state {}: {}", args.logger_state, err);
+            exit(1);
+        }
+    };
+
+    let rpc = RpcClient::new_with_commitment(args.url.clone(), CommitmentConfig::confirmed());
+    let client = BridgeClient::new(
+        rpc,
+        args.escrow_program_id,
+        args.logger_program_id,
+        logger_state,
+    );
+    let config = RelayerConfig {
+        poll_interval: Duration::from_millis(args.poll_interval_ms),
+        ..RelayerConfig::default()
+    };
+
+    let result = match args.destination {
+        DestinationKind::Log => relay(client, LogDestination, &args, config),
+        DestinationKind::MockChain => {
+            relay(client, MockChainDestination::default(), &args, config)
+        }
+    };
+    if let Err(err) = result {
+        error!("{}", err);
+        match err {
+            RelayerError::Evicted(sequence) => {
+                error!("rerun with --skip {} to give up on it", sequence)
+            }
+            RelayerError::CursorStateMismatch { .. } => {
+                error!("pass another --cursor to relay the new logger state from sequence 1")
+            }
+            _ => {}
+        }
+        exit(1);
+    }
+}
+
+fn relay<D: Destination>(
+    client: BridgeClient,
+    destination: D,
+    args: &Args,
+    config: RelayerConfig,
+) -> Result<(), RelayerError> {
+    let cursor = Cursor::load(&args.cursor, &client.logger_state)?;
+    info!(
+        "delivering to {} from sequence {}",
+        destination.name(),
+        cursor.last_delivered() + 1
+    );
+    let mut relayer = Relayer::new(client, destination, cursor, config);
+    if let Some(sequence) = args.skip {
+        relayer.skip(sequence, "skipped by operator")?;
+        info!("skipped message {}", sequence);
+        Ok(())
+    } else if args.once {
+        let delivered = relayer.run_once()?;
+        info!("advanced {} message(s)", delivered);
+        Ok(())
+    } else {
+        relayer.run()
+    }
+}
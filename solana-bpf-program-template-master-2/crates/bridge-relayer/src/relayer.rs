@@ -0,0 +1,205 @@
+use std::{thread::sleep, time::Duration};
+
+use bridge_client::LoggedMessage;
+use escrow_contract::state::Batch;
+use log::{error, info, warn};
+use logger_contract::state::MessageData;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    cursor::Cursor,
+    destination::Destination,
+    error::{DestinationError, RelayerError},
+    source::MessageSource,
+};
+
+#[derive(Clone, Debug)]
+pub struct RelayerConfig {
+    pub poll_interval: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl Default for RelayerConfig {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(2),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl Backoff {
+    fn new(config: &RelayerConfig) -> Self {
+        Self {
+            initial: config.initial_backoff,
+            max: config.max_backoff,
+            current: config.initial_backoff,
+        }
+    }
+
+    fn next_delay(&mut self) -> Duration {
+        let delay = self.current;
+        self.current = (self.current * 2).min(self.max);
+        delay
+    }
+
+    fn reset(&mut self) {
+        self.current = self.initial;
+    }
+}
+
+/// Delivers logger messages to a destination strictly in sequence order.
+pub struct Relayer<S: MessageSource, D: Destination> {
+    source: S,
+    destination: D,
+    cursor: Cursor,
+    config: RelayerConfig,
+}
+
+impl<S: MessageSource, D: Destination> Relayer<S, D> {
+    pub fn new(source: S, destination: D, cursor: Cursor, config: RelayerConfig) -> Self {
+        Self {
+            source,
+            destination,
+            cursor,
+            config,
+        }
+    }
+
+    pub fn destination(&self) -> &D {
+        &self.destination
+    }
+
+    pub fn destination_mut(&mut self) -> &mut D {
+        &mut self.destination
+    }
+
+    pub fn cursor(&self) -> &Cursor {
+        &self.cursor
+    }
+
+    /// Delivers every message that is already final and returns how many
+    /// sequences the cursor advanced by. Stops at the first message that is
+    /// not visible yet and leaves it for the next pass; a message evicted
+    /// from the ring is an error until an operator skips it.
+    pub fn run_once(&mut self) -> Result<u64, RelayerError> {
+        let logger_state = self.source.logger_state();
+        if logger_state != *self.cursor.logger_state() {
+            return Err(RelayerError::CursorStateMismatch {
+                cursor: *self.cursor.logger_state(),
+                logger_state,
+            });
+        }
+        let start = self.cursor.last_delivered();
+        let latest = self.source.latest_sequence()?;
+
+        for sequence in start + 1..=latest {
+            let message = match self.source.fetch(sequence)? {
+                LoggedMessage::Account(message)
+                | LoggedMessage::Ring(message)
+                | LoggedMessage::Closed(message) => message,
+                LoggedMessage::Evicted => return Err(RelayerError::Evicted(sequence)),
+                LoggedMessage::Missing => {
+                    info!("message {} is not visible yet, waiting", sequence);
+                    break;
+                }
+            };
+            if !self.source.is_final(&message)? {
+                break;
+            }
+            let batch = self.source.fetch_batch(&message)?;
+            self.deliver_with_retry(&logger_state, sequence, &message, batch.as_ref())?;
+            self.cursor.advance(sequence)?;
+        }
+
+        Ok(self.cursor.last_delivered() - start)
+    }
+
+    /// Moves the cursor past the next undelivered `sequence` without
+    /// delivering it and records it as a dead letter.
+    pub fn skip(&mut self, sequence: u64, reason: &str) -> Result<(), RelayerError> {
+        let next = self.cursor.last_delivered() + 1;
+        if sequence != next {
+            return Err(RelayerError::SkipOutOfOrder { sequence, next });
+        }
+        self.cursor.skip(sequence, reason)
+    }
+
+    /// Relays until a cursor error or an evicted message. Messages the
+    /// destination rejects are recorded as dead letters and skipped so one
+    /// bad message does not stall the rest.
+    pub fn run(&mut self) -> Result<(), RelayerError> {
+        let mut backoff = Backoff::new(&self.config);
+        loop {
+            match self.run_once() {
+                Ok(_) => {
+                    backoff.reset();
+                    sleep(self.config.poll_interval);
+                }
+                Err(RelayerError::Source(err)) => {
+                    let delay = backoff.next_delay();
+                    warn!("source error: {}, retrying in {:?}", err, delay);
+                    sleep(delay);
+                }
+                Err(RelayerError::Rejected {
+                    destination,
+                    sequence,
+                    reason,
+                }) => {
+                    // Poruka ostaje u logu i u dead-letter fajlu, cursor ide dalje
+                    error!(
+                        "{} rejected message {}: {}, skipping",
+                        destination, sequence, reason
+                    );
+                    self.skip(sequence, &format!("{} rejected: {}", destination, reason))?;
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    fn deliver_with_retry(
+        &mut self,
+        logger_state: &Pubkey,
+        sequence: u64,
+        message: &MessageData,
+        batch: Option<&Batch>,
+    ) -> Result<(), RelayerError> {
+        let mut backoff = Backoff::new(&self.config);
+        loop {
+            let delivered = match batch {
+                Some(batch) => self.destination.deliver_batch(logger_state, message, batch),
+                None => self.destination.deliver(logger_state, message),
+            };
+            match delivered {
+                Ok(()) => return Ok(()),
+                Err(DestinationError::Retryable(reason)) => {
+                    let delay = backoff.next_delay();
+                    warn!(
+                        "{} failed message {}: {}, retrying in {:?}",
+                        self.destination.name(),
+                        sequence,
+                        reason,
+                        delay
+                    );
+                    sleep(delay);
+                }
+                Err(DestinationError::Rejected(reason)) => {
+                    return Err(RelayerError::Rejected {
+                        destination: self.destination.name().to_string(),
+                        sequence,
+                        reason,
+                    })
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use bridge_client::{BridgeClient, BridgeClientError, LoggedMessage};
+use escrow_contract::state::Batch;
+use logger_contract::state::MessageData;
+use solana_sdk::pubkey::Pubkey;
+
+pub trait MessageSource {
+    /// Logger state whose sequences the source reads. A sequence is unique
+    /// only within its state.
+    fn logger_state(&self) -> Pubkey;
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError>;
+
+    /// Where message `sequence` is logged. `Missing` means it is not
+    /// visible yet, `Evicted` that it can never be read again.
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError>;
+
+    /// Returns the legs of `message` when a batch logged it. Sources that
+    /// keep the default hand destinations only the combined message.
+    fn fetch_batch(&self, _message: &MessageData) -> Result<Option<Batch>, BridgeClientError> {
+        Ok(None)
+    }
+
+    fn is_final(&self, message: &MessageData) -> Result<bool, BridgeClientError>;
+}
+
+impl MessageSource for BridgeClient {
+    fn logger_state(&self) -> Pubkey {
+        self.logger_state
+    }
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError> {
+        self.logger_sequence()
+    }
+
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        self.get_logged_message(sequence)
+    }
+
+    fn fetch_batch(&self, message: &MessageData) -> Result<Option<Batch>, BridgeClientError> {
+        self.get_batch(message)
+    }
+
+    fn is_final(&self, message: &MessageData) -> Result<bool, BridgeClientError> {
+        BridgeClient::is_final(self, message)
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, VecDeque},
+    fs,
+    path::PathBuf,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use bridge_client::{BridgeClientError, LoggedMessage};
+use bridge_relayer::{
+    Cursor, DeadLetter, Destination, DestinationError, MessageSource, MockChainDestination,
+    Relayer, RelayerConfig, RelayerError,
+};
+use logger_contract::state::{ConsistencyLevel, MessageData, MessageKind};
+use solana_sdk::pubkey::Pubkey;
+
+const LOGGER_STATE: Pubkey = Pubkey::new_from_array([9; 32]);
+
+/// Source whose log the test edits between passes.
+#[derive(Clone, Default)]
+struct Messages(Rc<RefCell<BTreeMap<u64, LoggedMessage>>>);
+
+impl Messages {
+    fn set(&self, sequence: u64, logged: LoggedMessage) {
+        self.0.borrow_mut().insert(sequence, logged);
+    }
+}
+
+impl MessageSource for Messages {
+    fn logger_state(&self) -> Pubkey {
+        LOGGER_STATE
+    }
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError> {
+        Ok(self.0.borrow().keys().next_back().copied().unwrap_or(0))
+    }
+
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        Ok(self
+            .0
+            .borrow()
+            .get(&sequence)
+            .copied()
+            .unwrap_or(LoggedMessage::Missing))
+    }
+
+    fn is_final(&self, _message: &MessageData) -> Result<bool, BridgeClientError> {
+        Ok(true)
+    }
+}
+
+/// Destination that answers with queued failures before accepting.
+#[derive(Default)]
+struct Scripted {
+    failures: VecDeque<DestinationError>,
+    attempts: u32,
+    delivered: Vec<u64>,
+}
+
+impl Destination for Scripted {
+    fn name(&self) -> &str {
+        "scripted"
+    }
+
+    fn deliver(&mut self, _logger_state: &Pubkey, message: &MessageData) -> Result<(), DestinationError> {
+        self.attempts += 1;
+        if let Some(err) = self.failures.pop_front() {
+            return Err(err);
+        }
+        self.delivered.push(message.sequence.get());
+        Ok(())
+    }
+}
+
+fn message(sequence: u64) -> MessageData {
+    MessageData::new(
+        Pubkey::new_from_array([1; 32]),
+        Pubkey::new_from_array([2; 32]),
+        100,
+        1_700_000_000,
+        sequence,
+        Pubkey::new_from_array([3; 32]),
+        10 + sequence,
+        0,
+        ConsistencyLevel::Confirmed,
+        Pubkey::new_from_array([4; 32]),
+        MessageKind::Transfer,
+    )
+}
+
+fn account(sequence: u64) -> LoggedMessage {
+    LoggedMessage::Account(message(sequence))
+}
+
+fn cursor_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "bridge-relayer-{}-{}.cursor",
+        name,
+        std::process::id()
+    ));
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_file(path.with_extension("dead"));
+    path
+}
+
+fn config(initial_backoff_ms: u64, max_backoff_ms: u64) -> RelayerConfig {
+    RelayerConfig {
+        poll_interval: Duration::from_millis(1),
+        initial_backoff: Duration::from_millis(initial_backoff_ms),
+        max_backoff: Duration::from_millis(max_backoff_ms),
+    }
+}
+
+fn relayer(path: &PathBuf, source: Messages, destination: Scripted) -> Relayer<Messages, Scripted> {
+    Relayer::new(
+        source,
+        destination,
+        Cursor::load(path, &LOGGER_STATE).unwrap(),
+        config(1, 1),
+    )
+}
+
+#[test]
+fn cursor_resumes_after_the_last_delivered_sequence() {
+    let path = cursor_path("resume");
+    let source = Messages::default();
+    for sequence in 1..=4 {
+        source.set(sequence, account(sequence));
+    }
+
+    let mut first = relayer(&path, source.clone(), Scripted::default());
+    first.skip(1, "operator").unwrap();
+    first.skip(2, "operator").unwrap();
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        format!("{} 2", LOGGER_STATE)
+    );
+
+    // Novi relayer nad istim fajlom nastavlja od sekvence 3
+    let mut second = relayer(&path, source, Scripted::default());
+    assert_eq!(second.cursor().last_delivered(), 2);
+    assert_eq!(second.run_once().unwrap(), 2);
+    assert_eq!(second.destination().delivered, vec![3, 4]);
+    assert_eq!(Cursor::load(&path, &LOGGER_STATE).unwrap().last_delivered(), 4);
+}
+
+#[test]
+fn invalid_cursor_file_is_an_error() {
+    let path = cursor_path("invalid");
+    fs::write(&path, "not a sequence").unwrap();
+    assert!(matches!(
+        Cursor::load(&path, &LOGGER_STATE),
+        Err(RelayerError::InvalidCursor(_))
+    ));
+}
+
+#[test]
+fn retryable_failures_back_off_until_delivered() {
+    let path = cursor_path("backoff");
+    let source = Messages::default();
+    source.set(1, account(1));
+    let destination = Scripted {
+        failures: (0..3)
+            .map(|_| DestinationError::Retryable("busy".to_string()))
+            .collect(),
+        ..Scripted::default()
+    };
+    let mut relayer = Relayer::new(
+        source,
+        destination,
+        Cursor::load(&path, &LOGGER_STATE).unwrap(),
+        config(20, 30),
+    );
+
+    // Cekanja su 20, 30 i 30 ms jer max_backoff ogranicava udvostrucavanje
+    let started = Instant::now();
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    let elapsed = started.elapsed();
+    assert!(elapsed >= Duration::from_millis(80), "{:?}", elapsed);
+    assert_eq!(relayer.destination().attempts, 4);
+    assert_eq!(relayer.destination().delivered, vec![1]);
+}
+
+#[test]
+fn missing_message_holds_the_cursor() {
+    let path = cursor_path("missing");
+    let source = Messages::default();
+    source.set(1, account(1));
+    source.set(2, LoggedMessage::Missing);
+    source.set(3, account(3));
+    let mut relayer = relayer(&path, source.clone(), Scripted::default());
+
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    assert_eq!(relayer.run_once().unwrap(), 0);
+    assert_eq!(relayer.cursor().last_delivered(), 1);
+
+    // Zatvorena poruka procitana iz eventa se isporucuje kao i svaka druga
+    source.set(2, LoggedMessage::Closed(message(2)));
+    assert_eq!(relayer.run_once().unwrap(), 2);
+    assert_eq!(relayer.destination().delivered, vec![1, 2, 3]);
+}
+
+#[test]
+fn evicted_message_stops_the_relayer_until_skipped() {
+    let path = cursor_path("evicted");
+    let source = Messages::default();
+    source.set(1, LoggedMessage::Ring(message(1)));
+    source.set(2, LoggedMessage::Evicted);
+    source.set(3, LoggedMessage::Ring(message(3)));
+    let mut relayer = relayer(&path, source, Scripted::default());
+
+    assert!(matches!(relayer.run_once(), Err(RelayerError::Evicted(2))));
+    assert_eq!(relayer.cursor().last_delivered(), 1);
+    assert!(matches!(relayer.run(), Err(RelayerError::Evicted(2))));
+
+    // Preskace se samo prva neisporucena sekvenca
+    assert!(matches!(
+        relayer.skip(3, "operator"),
+        Err(RelayerError::SkipOutOfOrder { sequence: 3, next: 2 })
+    ));
+    relayer.skip(2, "evicted").unwrap();
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    assert_eq!(relayer.destination().delivered, vec![1, 3]);
+    assert_eq!(
+        Cursor::load(&path, &LOGGER_STATE).unwrap().dead_letters(),
+        [DeadLetter {
+            sequence: 2,
+            reason: "evicted".to_string(),
+        }]
+    );
+}
+
+#[test]
+fn rejected_message_stops_the_pass_until_skipped() {
+    let path = cursor_path("rejected");
+    let source = Messages::default();
+    for sequence in 1..=3 {
+        source.set(sequence, account(sequence));
+    }
+    let destination = Scripted {
+        failures: VecDeque::from([DestinationError::Rejected("bad".to_string())]),
+        ..Scripted::default()
+    };
+    let mut relayer = relayer(&path, source, destination);
+
+    match relayer.run_once() {
+        Err(RelayerError::Rejected { sequence, .. }) => assert_eq!(sequence, 1),
+        other => panic!("expected rejected message, got {:?}", other.map(|_| ())),
+    }
+    assert_eq!(relayer.cursor().last_delivered(), 0);
+
+    relayer.skip(1, "bad").unwrap();
+    assert_eq!(relayer.run_once().unwrap(), 2);
+    assert_eq!(relayer.destination().delivered, vec![2, 3]);
+    assert_eq!(relayer.cursor().dead_letters()[0].sequence, 1);
+}
+
+#[test]
+fn cursor_of_another_logger_state_is_rejected() {
+    let path = cursor_path("state");
+    let old_state = Pubkey::new_from_array([8; 32]);
+    let mut cursor = Cursor::load(&path, &old_state).unwrap();
+    cursor.advance(5).unwrap();
+
+    // Posle SetLogger sekvence krecu od 1, stari cursor bi preskocio prvih 5
+    assert!(matches!(
+        Cursor::load(&path, &LOGGER_STATE),
+        Err(RelayerError::CursorStateMismatch { cursor, logger_state })
+            if cursor == old_state && logger_state == LOGGER_STATE
+    ));
+    let source = Messages::default();
+    source.set(1, account(1));
+    let mut relayer = Relayer::new(source, Scripted::default(), cursor, config(1, 1));
+    assert!(matches!(
+        relayer.run_once(),
+        Err(RelayerError::CursorStateMismatch { .. })
+    ));
+    assert!(relayer.destination().delivered.is_empty());
+}
+
+#[test]
+fn mock_chain_keys_messages_by_logger_state() {
+    let new_state = Pubkey::new_from_array([8; 32]);
+    let mut chain = MockChainDestination::default();
+    chain.deliver(&LOGGER_STATE, &message(1)).unwrap();
+    chain.deliver(&LOGGER_STATE, &message(2)).unwrap();
+
+    // Sekvenca 1 novog state-a je nova poruka, ne ponovljena
+    chain.deliver(&new_state, &message(1)).unwrap();
+    chain.deliver(&new_state, &message(1)).unwrap();
+    assert_eq!(chain.delivered.len(), 3);
+    assert_eq!(chain.last_sequence(&LOGGER_STATE), 2);
+    assert_eq!(chain.last_sequence(&new_state), 1);
+    assert_eq!(chain.net_flow[&Pubkey::new_from_array([2; 32])], 300);
+
+    // Redosled vazi unutar state-a
+    chain.deliver(&new_state, &message(3)).unwrap();
+    assert!(matches!(
+        chain.deliver(&new_state, &message(2)),
+        Err(DestinationError::Rejected(_))
+    ));
+}
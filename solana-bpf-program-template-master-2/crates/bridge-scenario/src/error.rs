@@ -0,0 +1,27 @@
+use solana_program_test::BanksClientError;
+use solana_sdk::program_error::ProgramError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ScenarioError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid YAML scenario: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("Invalid JSON scenario: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Unknown user {0}")]
+    UnknownUser(String),
+    #[error("Unknown mint {0}")]
+    UnknownMint(String),
+    #[error("Banks client error: {0}")]
+    Banks(#[from] BanksClientError),
+    #[error("Program error: {0}")]
+    Program(#[from] ProgramError),
+    #[error("Step {step} ({description}): {reason}")]
+    StepFailed {
+        step: usize,
+        description: String,
+        reason: String,
+    },
+}
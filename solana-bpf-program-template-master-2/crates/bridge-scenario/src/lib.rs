@@ -0,0 +1,7 @@
+pub mod error;
+pub mod runner;
+pub mod scenario;
+
+pub use error::ScenarioError;
+pub use runner::{run, Runner};
+pub use scenario::{EscrowExpect, Expect, MessageExpect, Scenario, Step};
@@ -0,0 +1,48 @@
+use std::{env, path::PathBuf, process::exit};
+
+use bridge_scenario::Scenario;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(
+    name = "bridge-scenario",
+    about = "Run YAML or JSON escrow scenarios against the programs"
+)]
+struct Args {
+    /// Scenario files, `.json` or YAML
+    #[arg(required = true)]
+    scenarios: Vec<PathBuf>,
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() {
+    let args = Args::parse();
+    // Logovi programa samo sa RUST_LOG, inace zatrpaju izvestaj
+    if env::var_os("RUST_LOG").is_none() {
+        env::set_var("RUST_LOG", "error");
+    }
+    let mut failed = 0;
+    for path in &args.scenarios {
+        let result = match Scenario::load(path) {
+            Ok(scenario) => bridge_scenario::run(&scenario)
+                .await
+                .map(|()| scenario.name),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(name) => println!("ok    {} ({})", name, path.display()),
+            Err(err) => {
+                println!("FAIL  {}: {}", path.display(), err);
+                failed += 1;
+            }
+        }
+    }
+    println!(
+        "{} passed, {} failed",
+        args.scenarios.len() - failed,
+        failed
+    );
+    if failed > 0 {
+        exit(1);
+    }
+}
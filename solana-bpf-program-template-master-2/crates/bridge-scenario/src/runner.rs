@@ -0,0 +1,517 @@
+use std::collections::BTreeMap;
+
+use escrow_contract::{
+    error::EscrowError, instruction as escrow_instruction, processor::EscrowProcessor,
+    state::EscrowState,
+};
+use logger_contract::{
+    error::LoggerError,
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{LoggerState, MessageData},
+};
+use num_traits::FromPrimitive;
+use solana_program_test::{
+    processor, BanksClient, BanksClientError, ProgramTest, ProgramTestBanksClientExt,
+};
+use solana_sdk::{
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+use spl_token::{error::TokenError, state::Account as TokenAccount};
+
+use crate::{
+    error::ScenarioError,
+    scenario::{Expect, Scenario, Step},
+};
+
+/// Runs every step of `scenario` on a fresh bank and stops at the first
+/// step whose outcome differs from its expectations.
+pub async fn run(scenario: &Scenario) -> Result<(), ScenarioError> {
+    let mut runner = Runner::start(scenario).await?;
+    for (index, step) in scenario.steps.iter().enumerate() {
+        runner.step(index + 1, step).await?;
+    }
+    Ok(())
+}
+
+/// Both programs loaded into one `ProgramTest` bank together with the
+/// mints, users and token accounts a scenario declares.
+pub struct Runner {
+    banks: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    escrow_program_id: Pubkey,
+    logger_program_id: Pubkey,
+    logger_state: Pubkey,
+    mints: BTreeMap<String, Pubkey>,
+    users: BTreeMap<String, Keypair>,
+    token_accounts: BTreeMap<(String, String), Pubkey>,
+}
+
+impl Runner {
+    pub async fn start(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        let escrow_program_id = escrow_contract::id();
+        let logger_program_id = logger_contract::id();
+        let mut program_test = ProgramTest::new(
+            "escrow_contract",
+            escrow_program_id,
+            processor!(EscrowProcessor::process),
+        );
+        program_test.add_program(
+            "logger_contract",
+            logger_program_id,
+            processor!(LoggerProcessor::process),
+        );
+        program_test.prefer_bpf(false);
+
+        let (banks, payer, blockhash) = program_test.start().await;
+        let mut runner = Runner {
+            banks,
+            payer,
+            blockhash,
+            escrow_program_id,
+            logger_program_id,
+            logger_state: Pubkey::default(),
+            mints: BTreeMap::new(),
+            users: BTreeMap::new(),
+            token_accounts: BTreeMap::new(),
+        };
+
+        let logger_state = Keypair::new();
+        let payer = runner.payer.pubkey();
+        runner
+            .process(
+                &[
+                    system_instruction::create_account(
+                        &payer,
+                        &logger_state.pubkey(),
+                        Rent::default().minimum_balance(LoggerState::LEN),
+                        LoggerState::LEN as u64,
+                        &logger_program_id,
+                    ),
+                    logger_instruction::initialize(
+                        &logger_program_id,
+                        &logger_state.pubkey(),
+                        &payer,
+                    ),
+                    escrow_instruction::initialize_config(
+                        &escrow_program_id,
+                        &payer,
+                        &logger_program_id,
+                        &logger_state.pubkey(),
+                    ),
+                ],
+                &[&logger_state],
+            )
+            .await?;
+        runner.logger_state = logger_state.pubkey();
+
+        for name in &scenario.mints {
+            let mint = runner.create_mint().await?;
+            runner.mints.insert(name.clone(), mint);
+        }
+        for (name, balances) in &scenario.users {
+            if let Some(mint) = balances
+                .keys()
+                .find(|mint| !runner.mints.contains_key(*mint))
+            {
+                return Err(ScenarioError::UnknownMint(mint.clone()));
+            }
+            let user = runner.create_user().await?;
+            for (mint_name, mint) in runner.mints.clone() {
+                let amount = balances.get(&mint_name).copied().unwrap_or(0);
+                let account = runner
+                    .create_token_account(&mint, &user.pubkey(), amount)
+                    .await?;
+                runner
+                    .token_accounts
+                    .insert((name.clone(), mint_name), account);
+            }
+            runner.users.insert(name.clone(), user);
+        }
+        Ok(runner)
+    }
+
+    /// Sends the transaction of `step` and checks its expectations.
+    pub async fn step(&mut self, index: usize, step: &Step) -> Result<(), ScenarioError> {
+        let sequence = self.logger_sequence().await?;
+        let message = MessageData::find_address(&self.logger_state, sequence + 1, &self.logger_program_id).0;
+
+        let (user, instruction) = match step {
+            Step::Deposit {
+                user,
+                mint,
+                amount,
+                from,
+                ..
+            } => (
+                user,
+                escrow_instruction::deposit(
+                    &self.escrow_program_id,
+                    &self.logger_program_id,
+                    &self.user(user)?.pubkey(),
+                    &self.token_account(from.as_ref().unwrap_or(user), mint)?,
+                    &self.mint(mint)?,
+                    &self.logger_state,
+                    &message,
+                    *amount,
+                ),
+            ),
+            Step::Withdraw {
+                user,
+                mint,
+                amount,
+                to,
+                ..
+            } => (
+                user,
+                escrow_instruction::withdraw(
+                    &self.escrow_program_id,
+                    &self.logger_program_id,
+                    &self.user(user)?.pubkey(),
+                    &self.user(user)?.pubkey(),
+                    &self.token_account(to.as_ref().unwrap_or(user), mint)?,
+                    &self.mint(mint)?,
+                    &self.logger_state,
+                    &message,
+                    *amount,
+                ),
+            ),
+        };
+        let signer = self.user(user)?.insecure_clone();
+        let result = match self.process(&[instruction], &[&signer]).await {
+            Ok(()) => Ok(()),
+            Err(BanksClientError::TransactionError(err))
+            | Err(BanksClientError::SimulationError { err, .. }) => Err(err),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut mismatches = Vec::new();
+        let expect = step.expect();
+        match (&expect.error, &result) {
+            (None, Err(err)) => mismatches.push(format!("failed with {}", describe(err))),
+            (Some(expected), Ok(())) => mismatches.push(format!(
+                "expected error {}, transaction succeeded",
+                expected
+            )),
+            (Some(expected), Err(err)) if !error_matches(expected, err) => mismatches.push(
+                format!("expected error {}, failed with {}", expected, describe(err)),
+            ),
+            _ => {}
+        }
+        self.check(expect, sequence, result.is_ok(), &mut mismatches)
+            .await?;
+
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(ScenarioError::StepFailed {
+                step: index,
+                description: step.to_string(),
+                reason: mismatches.join("; "),
+            })
+        }
+    }
+
+    async fn check(
+        &mut self,
+        expect: &Expect,
+        sequence: u64,
+        succeeded: bool,
+        mismatches: &mut Vec<String>,
+    ) -> Result<(), ScenarioError> {
+        for (user, balances) in &expect.balances {
+            for (mint, expected) in balances {
+                let account = self.token_account(user, mint)?;
+                let actual = self.token_balance(&account).await?;
+                if actual != *expected {
+                    mismatches.push(format!(
+                        "{} holds {} {}, expected {}",
+                        user, actual, mint, expected
+                    ));
+                }
+            }
+        }
+
+        for (mint, expected) in &expect.vaults {
+            let vault =
+                EscrowState::find_vault_address(&self.mint(mint)?, &self.escrow_program_id).0;
+            let actual = self.token_balance(&vault).await?;
+            if actual != *expected {
+                mismatches.push(format!(
+                    "vault of {} holds {}, expected {}",
+                    mint, actual, expected
+                ));
+            }
+        }
+
+        for (mint, expected) in &expect.escrows {
+            let address = EscrowState::find_address(&self.mint(mint)?, &self.escrow_program_id).0;
+            let actual = self
+                .banks
+                .get_account(address)
+                .await?
+                .and_then(|account| EscrowState::load(&account.data).ok().copied());
+            match (expected, actual) {
+                (None, Some(_)) => mismatches.push(format!("escrow of {} exists", mint)),
+                (Some(_), None) => mismatches.push(format!("escrow of {} does not exist", mint)),
+                (Some(expected), Some(actual))
+                    if actual.total_deposited.get() != expected.total_deposited =>
+                {
+                    mismatches.push(format!(
+                        "escrow of {} has {} deposited, expected {}",
+                        mint, actual.total_deposited, expected.total_deposited
+                    ))
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(expected) = &expect.message {
+            if !succeeded {
+                mismatches.push("no message was posted".to_string());
+                return Ok(());
+            }
+            let posted = self.logger_sequence().await?;
+            let sequence = expected.sequence.unwrap_or(sequence + 1);
+            if posted != sequence {
+                mismatches.push(format!(
+                    "logger sequence is {}, expected {}",
+                    posted, sequence
+                ));
+                return Ok(());
+            }
+            let address = MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0;
+            let message = match self.banks.get_account(address).await? {
+                Some(account) => *MessageData::load_unchecked(&account.data)?,
+                None => {
+                    mismatches.push(format!("message {} does not exist", sequence));
+                    return Ok(());
+                }
+            };
+            if let Some(from) = &expected.from {
+                if message.from_pubkey != self.resolve(from)? {
+                    mismatches.push(format!(
+                        "message {} is from {}, expected {}",
+                        sequence, message.from_pubkey, from
+                    ));
+                }
+            }
+            if let Some(to) = &expected.to {
+                if message.to_pubkey != self.resolve(to)? {
+                    mismatches.push(format!(
+                        "message {} is to {}, expected {}",
+                        sequence, message.to_pubkey, to
+                    ));
+                }
+            }
+            if let Some(amount) = expected.amount {
+                if message.amount.get() != amount {
+                    mismatches.push(format!(
+                        "message {} carries {}, expected {}",
+                        sequence, message.amount, amount
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        );
+        let result = self.banks.process_transaction(transaction).await;
+        // Novi blockhash da ponovljeni korak ne bi dao isti potpis
+        self.blockhash = self.banks.get_new_latest_blockhash(&self.blockhash).await?;
+        result
+    }
+
+    async fn create_mint(&mut self) -> Result<Pubkey, ScenarioError> {
+        let mint = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    Rent::default().minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    0,
+                )?,
+            ],
+            &[&mint],
+        )
+        .await?;
+        Ok(mint.pubkey())
+    }
+
+    async fn create_token_account(
+        &mut self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Result<Pubkey, ScenarioError> {
+        let account = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &account.pubkey(),
+                    Rent::default().minimum_balance(TokenAccount::LEN),
+                    TokenAccount::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &account.pubkey(),
+                    mint,
+                    owner,
+                )?,
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    mint,
+                    &account.pubkey(),
+                    &payer,
+                    &[],
+                    amount,
+                )?,
+            ],
+            &[&account],
+        )
+        .await?;
+        Ok(account.pubkey())
+    }
+
+    async fn create_user(&mut self) -> Result<Keypair, ScenarioError> {
+        let user = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[system_instruction::transfer(
+                &payer,
+                &user.pubkey(),
+                1_000_000_000,
+            )],
+            &[],
+        )
+        .await?;
+        Ok(user)
+    }
+
+    async fn logger_sequence(&mut self) -> Result<u64, ScenarioError> {
+        let account = self.banks.get_account(self.logger_state).await?;
+        Ok(account
+            .and_then(|account| LoggerState::load(&account.data).ok().copied())
+            .map_or(0, |state| state.sequence.get()))
+    }
+
+    // Nalog koji ne postoji ili nije token nalog ima stanje 0
+    async fn token_balance(&mut self, account: &Pubkey) -> Result<u64, ScenarioError> {
+        let account = self.banks.get_account(*account).await?;
+        Ok(account
+            .and_then(|account| TokenAccount::unpack(&account.data).ok())
+            .map_or(0, |account| account.amount))
+    }
+
+    fn user(&self, name: &str) -> Result<&Keypair, ScenarioError> {
+        self.users
+            .get(name)
+            .ok_or_else(|| ScenarioError::UnknownUser(name.to_string()))
+    }
+
+    fn mint(&self, name: &str) -> Result<Pubkey, ScenarioError> {
+        self.mints
+            .get(name)
+            .copied()
+            .ok_or_else(|| ScenarioError::UnknownMint(name.to_string()))
+    }
+
+    fn token_account(&self, user: &str, mint: &str) -> Result<Pubkey, ScenarioError> {
+        self.user(user)?;
+        self.mint(mint)?;
+        Ok(self.token_accounts[&(user.to_string(), mint.to_string())])
+    }
+
+    // Ime korisnika ili vault:<mint>
+    fn resolve(&self, name: &str) -> Result<Pubkey, ScenarioError> {
+        match name.strip_prefix("vault:") {
+            Some(mint) => {
+                Ok(EscrowState::find_vault_address(&self.mint(mint)?, &self.escrow_program_id).0)
+            }
+            None => Ok(self.user(name)?.pubkey()),
+        }
+    }
+}
+
+// Ocekivana greska je ime varijante InstructionError ili TransactionError,
+// ili ime greske jednog od programa, sa ili bez prefiksa tipa
+fn error_matches(expected: &str, actual: &TransactionError) -> bool {
+    let err = match actual {
+        TransactionError::InstructionError(_, err) => err,
+        other => return expected == format!("{:?}", other),
+    };
+    if expected == format!("{:?}", err) {
+        return true;
+    }
+    match err {
+        InstructionError::Custom(code) => custom_error_names(*code)
+            .iter()
+            .any(|name| name == expected || name.split("::").nth(1) == Some(expected)),
+        _ => false,
+    }
+}
+
+// Escrow i logger imaju svoje opsege, ali token kodovi se i dalje mogu
+// poklopiti sa system programom, pa vracamo sva moguca imena
+fn custom_error_names(code: u32) -> Vec<String> {
+    let mut names = Vec::new();
+    if let Some(err) = EscrowError::from_code(code) {
+        names.push(format!("EscrowError::{:?}", err));
+    }
+    if let Some(err) = LoggerError::from_code(code) {
+        names.push(format!("LoggerError::{:?}", err));
+    }
+    if let Some(err) = TokenError::from_u32(code) {
+        names.push(format!("TokenError::{:?}", err));
+    }
+    names
+}
+
+fn describe(err: &TransactionError) -> String {
+    match err {
+        TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+            format!(
+                "Custom({}) ({})",
+                code,
+                custom_error_names(*code).join(" / ")
+            )
+        }
+        TransactionError::InstructionError(_, err) => format!("{:?}", err),
+        other => format!("{:?}", other),
+    }
+}
@@ -0,0 +1,117 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use serde::Deserialize;
+
+use crate::error::ScenarioError;
+
+/// A scripted sequence of escrow calls and the state expected after each.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: String,
+    /// Mint names; every user gets a token account for each of them
+    pub mints: Vec<String>,
+    /// Starting token balance of every user per mint, missing ones start at 0
+    pub users: BTreeMap<String, BTreeMap<String, u64>>,
+    pub steps: Vec<Step>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Step {
+    Deposit {
+        user: String,
+        mint: String,
+        amount: u64,
+        /// Owner of the source token account, `user` by default
+        #[serde(default)]
+        from: Option<String>,
+        #[serde(default)]
+        expect: Expect,
+    },
+    Withdraw {
+        user: String,
+        mint: String,
+        amount: u64,
+        /// Owner of the destination token account, `user` by default
+        #[serde(default)]
+        to: Option<String>,
+        #[serde(default)]
+        expect: Expect,
+    },
+}
+
+/// Checks run after a step; only the listed accounts are compared.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Expect {
+    /// Error the transaction must fail with, e.g. `InsufficientAmount`,
+    /// `EscrowError::MintMismatch`, `TokenError::InsufficientFunds` or
+    /// `MissingRequiredSignature`; the step must succeed when absent
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Token balance per user per mint
+    #[serde(default)]
+    pub balances: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Vault balance per mint
+    #[serde(default)]
+    pub vaults: BTreeMap<String, u64>,
+    /// Escrow state per mint, `null` when the escrow must not exist yet
+    #[serde(default)]
+    pub escrows: BTreeMap<String, Option<EscrowExpect>>,
+    /// Logger message posted by this step
+    #[serde(default)]
+    pub message: Option<MessageExpect>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EscrowExpect {
+    pub total_deposited: u64,
+}
+
+/// `from` and `to` name a user or `vault:<mint>`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MessageExpect {
+    #[serde(default)]
+    pub sequence: Option<u64>,
+    #[serde(default)]
+    pub from: Option<String>,
+    #[serde(default)]
+    pub to: Option<String>,
+    #[serde(default)]
+    pub amount: Option<u64>,
+}
+
+impl Scenario {
+    /// Reads a scenario from a `.json` file, anything else is parsed as YAML.
+    pub fn load(path: &Path) -> Result<Self, ScenarioError> {
+        let text = fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("json") => Ok(serde_json::from_str(&text)?),
+            _ => Ok(serde_yaml::from_str(&text)?),
+        }
+    }
+}
+
+impl Step {
+    pub fn expect(&self) -> &Expect {
+        match self {
+            Step::Deposit { expect, .. } | Step::Withdraw { expect, .. } => expect,
+        }
+    }
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Step::Deposit {
+                user, mint, amount, ..
+            } => write!(f, "{} deposits {} {}", user, amount, mint),
+            Step::Withdraw {
+                user, mint, amount, ..
+            } => write!(f, "{} withdraws {} {}", user, amount, mint),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+use std::{fs, path::Path};
+
+use bridge_scenario::{Scenario, ScenarioError};
+
+#[tokio::test]
+async fn bundled_scenarios_pass() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut paths = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+
+    for path in paths {
+        let scenario = Scenario::load(&path).unwrap();
+        if let Err(err) = bridge_scenario::run(&scenario).await {
+            panic!("{}: {}", path.display(), err);
+        }
+    }
+}
+
+#[tokio::test]
+async fn wrong_expectation_fails_the_step() {
+    let scenario: Scenario = serde_yaml::from_str(
+        r#"
+name: wrong expectation
+mints: [usdc]
+users:
+  alice: { usdc: 10 }
+steps:
+  - action: deposit
+    user: alice
+    mint: usdc
+    amount: 5
+    expect:
+      error: InsufficientAmount
+      balances:
+        alice: { usdc: 10 }
+"#,
+    )
+    .unwrap();
+
+    match bridge_scenario::run(&scenario).await {
+        Err(ScenarioError::StepFailed { step, reason, .. }) => {
+            assert_eq!(step, 1);
+            assert!(reason.contains("expected error InsufficientAmount, transaction succeeded"));
+            assert!(reason.contains("alice holds 5 usdc, expected 10"));
+        }
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    let err = serde_yaml::from_str::<Scenario>(
+        r#"
+name: typo
+mints: [usdc]
+users: {}
+steps:
+  - action: deposit
+    user: alice
+    mint: usdc
+    amount: 5
+    expect:
+      balance: {}
+"#,
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("unknown field `balance`"));
+}
@@ -0,0 +1,278 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use bridge_relayer::{Destination, DestinationError};
+use escrow_contract::state::{Batch, EscrowConfig, EscrowState};
+use logger_contract::state::{MessageData, MessageKind};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::error::SimError;
+
+/// Burn on the simulated chain that still has to be unlocked on Solana.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReturnMessage {
+    pub nonce: u64,
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+}
+
+/// Destination ledger of the bridge. Deposits into a vault mint wrapped
+/// tokens to the depositor, burns create return messages, and withdrawals
+/// out of a vault settle those return messages. A withdrawal that settles
+/// no burn would leave wrapped tokens without locked backing and is rejected.
+/// Only transfer messages emitted by the escrow config are credited.
+/// Messages are applied once per `(logger_state, sequence)`, since every
+/// logger state the escrow migrates to starts again at sequence 1.
+///
+/// The rejection is where the sim departs from a real vault: the escrow
+/// lets a depositor withdraw on Solana without burning anything here, so a
+/// real deployment can see such unlocks. The sim treats them as
+/// [`SimError::UnbackedUnlock`] to keep `supply + pending == locked` checkable.
+#[derive(Clone)]
+pub struct SimChain {
+    escrow_program_id: Pubkey,
+    emitter: Pubkey,
+    vaults: HashMap<Pubkey, Pubkey>,
+    balances: HashMap<(Pubkey, Pubkey), u64>,
+    supply: HashMap<Pubkey, u64>,
+    locked: HashMap<Pubkey, u64>,
+    pending_returns: BTreeMap<u64, ReturnMessage>,
+    processed: BTreeSet<(Pubkey, u64)>,
+    next_nonce: u64,
+}
+
+impl SimChain {
+    pub fn new(escrow_program_id: Pubkey) -> Self {
+        Self {
+            escrow_program_id,
+            emitter: EscrowConfig::find_address(&escrow_program_id).0,
+            vaults: HashMap::new(),
+            balances: HashMap::new(),
+            supply: HashMap::new(),
+            locked: HashMap::new(),
+            pending_returns: BTreeMap::new(),
+            processed: BTreeSet::new(),
+            next_nonce: 1,
+        }
+    }
+
+    pub fn register_mint(&mut self, mint: Pubkey) {
+        let (vault, _) = EscrowState::find_vault_address(&mint, &self.escrow_program_id);
+        self.vaults.insert(vault, mint);
+    }
+
+    pub fn balance_of(&self, mint: &Pubkey, owner: &Pubkey) -> u64 {
+        self.balances.get(&(*mint, *owner)).copied().unwrap_or(0)
+    }
+
+    pub fn supply_of(&self, mint: &Pubkey) -> u64 {
+        self.supply.get(mint).copied().unwrap_or(0)
+    }
+
+    pub fn locked_of(&self, mint: &Pubkey) -> u64 {
+        self.locked.get(mint).copied().unwrap_or(0)
+    }
+
+    pub fn pending_returns(&self) -> impl Iterator<Item = &ReturnMessage> {
+        self.pending_returns.values()
+    }
+
+    pub fn is_processed(&self, logger_state: &Pubkey, sequence: u64) -> bool {
+        self.processed.contains(&(*logger_state, sequence))
+    }
+
+    /// Applies one message relayed from `logger_state`.
+    pub fn apply(&mut self, logger_state: &Pubkey, message: &MessageData) -> Result<(), SimError> {
+        // Iznos batch poruke je broj legova, pa bez legova nema sta da se primeni
+        if message.kind() == MessageKind::Batch {
+            return Err(SimError::MissingLegs(message.sequence.get()));
+        }
+        self.apply_transfers(logger_state, message.sequence.get(), std::slice::from_ref(message))
+    }
+
+    /// Applies a batch message leg by leg; either every leg applies or none.
+    pub fn apply_batch(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+        batch: &Batch,
+    ) -> Result<(), SimError> {
+        let legs: Vec<MessageData> = batch.leg_messages(message).collect();
+        self.apply_transfers(logger_state, message.sequence.get(), &legs)
+    }
+
+    fn apply_transfers(
+        &mut self,
+        logger_state: &Pubkey,
+        sequence: u64,
+        transfers: &[MessageData],
+    ) -> Result<(), SimError> {
+        let key = (*logger_state, sequence);
+        if self.processed.contains(&key) {
+            return Err(SimError::Replay {
+                logger_state: *logger_state,
+                sequence,
+            });
+        }
+
+        // Legovi se primenjuju na kopiju, pa greska u jednom ne ostavlja pola batch-a
+        let mut next = self.clone();
+        for transfer in transfers {
+            next.apply_transfer(transfer)?;
+        }
+        next.processed.insert(key);
+        *self = next;
+        Ok(())
+    }
+
+    fn apply_transfer(&mut self, message: &MessageData) -> Result<(), SimError> {
+        // Poruku moze postaviti bilo ko, ali samo escrow potpisuje kao config PDA
+        if message.emitter != self.emitter {
+            return Err(SimError::UnknownEmitter(message.emitter));
+        }
+        // Vesting i agreement tokovi ne prelaze most
+        if message.kind() != MessageKind::Transfer {
+            return Ok(());
+        }
+
+        let amount = message.amount.get();
+        if let Some(mint) = self.vaults.get(&message.to_pubkey).copied() {
+            // Lock na Solani -> mint wrapped tokena depozitoru
+            add(self.locked.entry(mint).or_default(), amount)?;
+            add(self.supply.entry(mint).or_default(), amount)?;
+            add(
+                self.balances
+                    .entry((mint, message.from_pubkey))
+                    .or_default(),
+                amount,
+            )?;
+        } else if let Some(mint) = self.vaults.get(&message.from_pubkey).copied() {
+            // Unlock na Solani mora da odgovara ranijem burn-u
+            let nonce = self
+                .pending_returns
+                .values()
+                .find(|pending| {
+                    pending.mint == mint
+                        && pending.recipient == message.to_pubkey
+                        && pending.amount == amount
+                })
+                .map(|pending| pending.nonce)
+                .ok_or(SimError::UnbackedUnlock {
+                    mint,
+                    recipient: message.to_pubkey,
+                    amount,
+                })?;
+            self.pending_returns.remove(&nonce);
+            sub(self.locked.entry(mint).or_default(), amount)?;
+        }
+        Ok(())
+    }
+
+    /// Burns wrapped tokens and returns the message that entitles `owner`
+    /// to withdraw the same amount from the Solana vault.
+    pub fn burn(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<ReturnMessage, SimError> {
+        if !self.vaults.values().any(|registered| registered == mint) {
+            return Err(SimError::UnknownMint(*mint));
+        }
+
+        let balance = self.balances.entry((*mint, *owner)).or_default();
+        if *balance < amount {
+            return Err(SimError::InsufficientBalance {
+                mint: *mint,
+                owner: *owner,
+                amount,
+            });
+        }
+        *balance -= amount;
+        sub(self.supply.entry(*mint).or_default(), amount)?;
+
+        let message = ReturnMessage {
+            nonce: self.next_nonce,
+            mint: *mint,
+            recipient: *owner,
+            amount,
+        };
+        self.next_nonce += 1;
+        self.pending_returns.insert(message.nonce, message.clone());
+        Ok(message)
+    }
+
+    /// Everything locked on Solana is either circulating as wrapped supply
+    /// or waiting to be unlocked by a pending return message.
+    pub fn check_invariants(&self) -> Result<(), SimError> {
+        for mint in self.vaults.values() {
+            let locked = self.locked_of(mint);
+            let supply = self.supply_of(mint);
+            let pending = self
+                .pending_returns
+                .values()
+                .filter(|pending| pending.mint == *mint)
+                .map(|pending| pending.amount)
+                .sum::<u64>();
+            if supply.checked_add(pending) != Some(locked) {
+                return Err(SimError::InvariantViolated {
+                    mint: *mint,
+                    locked,
+                    supply,
+                    pending,
+                });
+            }
+
+            let held = self
+                .balances
+                .iter()
+                .filter(|((balance_mint, _), _)| balance_mint == mint)
+                .map(|(_, amount)| *amount)
+                .sum::<u64>();
+            if held != supply {
+                return Err(SimError::InvariantViolated {
+                    mint: *mint,
+                    locked,
+                    supply,
+                    pending,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// Ponovljena poruka se ne preskace tiho: relayer je isporucuje dvaput samo
+// kad nesto nije u redu, a test to treba da vidi
+impl Destination for SimChain {
+    fn name(&self) -> &str {
+        "sim-chain"
+    }
+
+    fn deliver(&mut self, logger_state: &Pubkey, message: &MessageData) -> Result<(), DestinationError> {
+        delivered(self.apply(logger_state, message))
+    }
+
+    fn deliver_batch(
+        &mut self,
+        logger_state: &Pubkey,
+        message: &MessageData,
+        batch: &Batch,
+    ) -> Result<(), DestinationError> {
+        delivered(self.apply_batch(logger_state, message, batch))
+    }
+}
+
+fn delivered(result: Result<(), SimError>) -> Result<(), DestinationError> {
+    result.map_err(|err| DestinationError::Rejected(err.to_string()))
+}
+
+fn add(value: &mut u64, amount: u64) -> Result<(), SimError> {
+    *value = value.checked_add(amount).ok_or(SimError::Overflow)?;
+    Ok(())
+}
+
+fn sub(value: &mut u64, amount: u64) -> Result<(), SimError> {
+    *value = value.checked_sub(amount).ok_or(SimError::Overflow)?;
+    Ok(())
+}
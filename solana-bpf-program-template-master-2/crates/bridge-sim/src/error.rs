@@ -0,0 +1,36 @@
+use solana_sdk::pubkey::Pubkey;
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SimError {
+    #[error("Message {sequence} of {logger_state} was already applied")]
+    Replay { logger_state: Pubkey, sequence: u64 },
+    #[error("Message emitted by {0}, not by the escrow")]
+    UnknownEmitter(Pubkey),
+    #[error("Batch message {0} was delivered without its legs")]
+    MissingLegs(u64),
+    #[error("Mint {0} is not registered")]
+    UnknownMint(Pubkey),
+    #[error("{owner} holds less than {amount} wrapped {mint}")]
+    InsufficientBalance {
+        mint: Pubkey,
+        owner: Pubkey,
+        amount: u64,
+    },
+    // Escrow na Solani dozvoljava withdraw bez burn-a; sim ga odbija namerno
+    #[error("Unlock of {amount} {mint} to {recipient} has no matching burn")]
+    UnbackedUnlock {
+        mint: Pubkey,
+        recipient: Pubkey,
+        amount: u64,
+    },
+    #[error("Amount overflow")]
+    Overflow,
+    #[error("Supply invariant violated for {mint}: locked {locked}, wrapped {supply}, pending returns {pending}")]
+    InvariantViolated {
+        mint: Pubkey,
+        locked: u64,
+        supply: u64,
+        pending: u64,
+    },
+}
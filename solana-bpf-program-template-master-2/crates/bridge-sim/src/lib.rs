@@ -0,0 +1,5 @@
+pub mod chain;
+pub mod error;
+
+pub use chain::{ReturnMessage, SimChain};
+pub use error::SimError;
@@ -0,0 +1,209 @@
+use bridge_relayer::Destination;
+use bridge_sim::{SimChain, SimError};
+use escrow_contract::state::{Batch, BatchTransfer, EscrowConfig, EscrowState};
+use logger_contract::state::{ConsistencyLevel, MessageData, MessageKind};
+use solana_sdk::pubkey::Pubkey;
+
+struct Ledger {
+    sim: SimChain,
+    mint: Pubkey,
+    vault: Pubkey,
+    emitter: Pubkey,
+    logger_state: Pubkey,
+}
+
+impl Ledger {
+    fn new() -> Self {
+        let program_id = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let mut sim = SimChain::new(program_id);
+        sim.register_mint(mint);
+        Self {
+            sim,
+            mint,
+            vault: EscrowState::find_vault_address(&mint, &program_id).0,
+            emitter: EscrowConfig::find_address(&program_id).0,
+            logger_state: Pubkey::new_unique(),
+        }
+    }
+
+    fn transfer(&self, sequence: u64, from: Pubkey, to: Pubkey, amount: u64) -> MessageData {
+        self.message(sequence, from, to, amount, self.emitter, MessageKind::Transfer)
+    }
+
+    fn message(
+        &self,
+        sequence: u64,
+        from: Pubkey,
+        to: Pubkey,
+        amount: u64,
+        emitter: Pubkey,
+        kind: MessageKind,
+    ) -> MessageData {
+        MessageData::new(
+            from,
+            to,
+            amount,
+            1_700_000_000,
+            sequence,
+            Pubkey::default(),
+            10 + sequence,
+            0,
+            ConsistencyLevel::Finalized,
+            emitter,
+            kind,
+        )
+    }
+}
+
+#[test]
+fn unlock_to_another_recipient_without_burn_is_rejected() {
+    let mut ledger = Ledger::new();
+    let (user, accomplice) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mint = ledger.mint;
+
+    // Korisnik zadrzava wrapped tokene i povlaci isti iznos na drugu adresu
+    let lock = ledger.transfer(1, user, ledger.vault, 300);
+    let unlock = ledger.transfer(2, ledger.vault, accomplice, 300);
+    ledger.sim.deliver(&ledger.logger_state, &lock).unwrap();
+    assert_eq!(
+        ledger.sim.apply(&ledger.logger_state, &unlock),
+        Err(SimError::UnbackedUnlock {
+            mint,
+            recipient: accomplice,
+            amount: 300,
+        })
+    );
+    assert!(ledger.sim.deliver(&ledger.logger_state, &unlock).is_err());
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    assert!(!sim.is_processed(&ledger.logger_state, 2));
+    assert_eq!(sim.balance_of(&mint, &user), 300);
+    assert_eq!(sim.supply_of(&mint), 300);
+    assert_eq!(sim.locked_of(&mint), 300);
+}
+
+#[test]
+fn unlock_settles_only_the_burn_it_matches() {
+    let mut ledger = Ledger::new();
+    let user = Pubkey::new_unique();
+    let mint = ledger.mint;
+
+    ledger
+        .sim
+        .deliver(&ledger.logger_state, &ledger.transfer(1, user, ledger.vault, 100))
+        .unwrap();
+    ledger.sim.burn(&user, &mint, 30).unwrap();
+
+    // 30 namiruje burn, 50 nema burn i ne sme da prodje
+    ledger
+        .sim
+        .deliver(&ledger.logger_state, &ledger.transfer(2, ledger.vault, user, 30))
+        .unwrap();
+    assert!(matches!(
+        ledger.sim.apply(&ledger.logger_state, &ledger.transfer(3, ledger.vault, user, 50)),
+        Err(SimError::UnbackedUnlock { amount: 50, .. })
+    ));
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    assert_eq!(sim.pending_returns().count(), 0);
+    assert_eq!(sim.balance_of(&mint, &user), 70);
+    assert_eq!(sim.supply_of(&mint), 70);
+    assert_eq!(sim.locked_of(&mint), 70);
+}
+
+#[test]
+fn messages_from_another_emitter_are_rejected() {
+    let mut ledger = Ledger::new();
+    let (user, forger) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mint = ledger.mint;
+
+    // Bilo ko moze postaviti poruku ka vault-u, ali je ne potpisuje config PDA
+    let forged = ledger.message(1, user, ledger.vault, 1_000, forger, MessageKind::Transfer);
+    assert_eq!(ledger.sim.apply(&ledger.logger_state, &forged), Err(SimError::UnknownEmitter(forger)));
+    assert!(ledger.sim.deliver(&ledger.logger_state, &forged).is_err());
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    assert!(!sim.is_processed(&ledger.logger_state, 1));
+    assert_eq!(sim.balance_of(&mint, &user), 0);
+    assert_eq!(sim.supply_of(&mint), 0);
+}
+
+#[test]
+fn escrow_messages_move_no_wrapped_tokens() {
+    let mut ledger = Ledger::new();
+    let (grantor, beneficiary) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mint = ledger.mint;
+
+    // Vesting grant i claim prolaze kroz vault, ali ne kroz most
+    let grant = ledger.message(1, grantor, ledger.vault, 500, ledger.emitter, MessageKind::Escrow);
+    let claim = ledger.message(2, ledger.vault, beneficiary, 500, ledger.emitter, MessageKind::Escrow);
+    ledger.sim.deliver(&ledger.logger_state, &grant).unwrap();
+    ledger.sim.deliver(&ledger.logger_state, &claim).unwrap();
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    let state = &ledger.logger_state;
+    assert!(sim.is_processed(state, 1) && sim.is_processed(state, 2));
+    assert_eq!(sim.balance_of(&mint, &grantor), 0);
+    assert_eq!(sim.supply_of(&mint), 0);
+    assert_eq!(sim.locked_of(&mint), 0);
+}
+
+#[test]
+fn batch_messages_apply_only_through_their_legs() {
+    let mut ledger = Ledger::new();
+    let user = Pubkey::new_unique();
+    let batch_account = Pubkey::new_unique();
+    let mint = ledger.mint;
+
+    // Amount batch poruke je broj legova, ne iznos koji se zakljucava
+    let message = ledger.message(1, user, batch_account, 2, ledger.emitter, MessageKind::Batch);
+    assert_eq!(ledger.sim.apply(&ledger.logger_state, &message), Err(SimError::MissingLegs(1)));
+    assert!(!ledger.sim.is_processed(&ledger.logger_state, 1));
+
+    let leg = |amount: u64| BatchTransfer {
+        from_pubkey: user,
+        to_pubkey: ledger.vault,
+        amount: amount.into(),
+    };
+    let batch = Batch::new(Pubkey::new_unique(), &[leg(300), leg(500)]);
+    ledger.sim.deliver_batch(&ledger.logger_state, &message, &batch).unwrap();
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    assert_eq!(sim.balance_of(&mint, &user), 800);
+    assert_eq!(sim.locked_of(&mint), 800);
+}
+
+#[test]
+fn replays_are_keyed_by_logger_state_and_surfaced() {
+    let mut ledger = Ledger::new();
+    let user = Pubkey::new_unique();
+    let (old_state, new_state) = (ledger.logger_state, Pubkey::new_unique());
+    let mint = ledger.mint;
+
+    let lock = ledger.transfer(1, user, ledger.vault, 100);
+    ledger.sim.apply(&old_state, &lock).unwrap();
+    assert_eq!(
+        ledger.sim.apply(&old_state, &lock),
+        Err(SimError::Replay {
+            logger_state: old_state,
+            sequence: 1,
+        })
+    );
+    assert!(ledger.sim.deliver(&old_state, &lock).is_err());
+
+    // Posle migracije sekvence krecu od 1, to nije ponovljena poruka
+    let migrated = ledger.transfer(1, user, ledger.vault, 40);
+    ledger.sim.deliver(&new_state, &migrated).unwrap();
+
+    let sim = &ledger.sim;
+    sim.check_invariants().unwrap();
+    assert!(sim.is_processed(&old_state, 1) && sim.is_processed(&new_state, 1));
+    assert_eq!(sim.balance_of(&mint, &user), 140);
+    assert_eq!(sim.locked_of(&mint), 140);
+}
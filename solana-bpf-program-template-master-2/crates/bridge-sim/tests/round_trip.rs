@@ -0,0 +1,493 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use bridge_client::{BridgeClientError, LoggedMessage};
+use bridge_relayer::{Cursor, MessageSource, Relayer, RelayerConfig, RelayerError};
+use bridge_sim::SimChain;
+use escrow_contract::{
+    instruction::{self as escrow_instruction, BatchLeg},
+    processor::EscrowProcessor,
+    state::{Batch, EscrowState},
+};
+use logger_contract::{
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{LoggerState, MessageData},
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    hash::Hash,
+    instruction::Instruction,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::Transaction,
+};
+
+// Poruke jednog logger state-a procitane iz BanksClient-a, relayer ih vidi
+// kao da dolaze sa RPC-a. Batch zapisi se cuvaju po sekvenci poruke koju prate.
+#[derive(Clone, Default)]
+struct FetchedMessages(
+    Rc<RefCell<Vec<MessageData>>>,
+    Rc<RefCell<BTreeMap<u64, Batch>>>,
+    Pubkey,
+);
+
+impl FetchedMessages {
+    fn new(logger_state: Pubkey) -> Self {
+        Self(Rc::default(), Rc::default(), logger_state)
+    }
+}
+
+impl MessageSource for FetchedMessages {
+    fn logger_state(&self) -> Pubkey {
+        self.2
+    }
+
+    fn latest_sequence(&self) -> Result<u64, BridgeClientError> {
+        Ok(self
+            .0
+            .borrow()
+            .last()
+            .map_or(0, |message| message.sequence.get()))
+    }
+
+    fn fetch(&self, sequence: u64) -> Result<LoggedMessage, BridgeClientError> {
+        Ok(self
+            .0
+            .borrow()
+            .iter()
+            .find(|message| message.sequence.get() == sequence)
+            .map_or(LoggedMessage::Missing, |message| LoggedMessage::Account(*message)))
+    }
+
+    fn is_final(&self, _message: &MessageData) -> Result<bool, BridgeClientError> {
+        Ok(true)
+    }
+
+    fn fetch_batch(&self, message: &MessageData) -> Result<Option<Batch>, BridgeClientError> {
+        Ok(self.1.borrow().get(&message.sequence.get()).copied())
+    }
+}
+
+struct Bridge {
+    banks: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    escrow_program_id: Pubkey,
+    logger_program_id: Pubkey,
+    logger_state: Pubkey,
+    mint: Pubkey,
+    user_token_account: Pubkey,
+}
+
+impl Bridge {
+    async fn start() -> Self {
+        let escrow_program_id = escrow_contract::id();
+        let logger_program_id = logger_contract::id();
+        let mut program_test = ProgramTest::new(
+            "escrow_contract",
+            escrow_program_id,
+            processor!(EscrowProcessor::process),
+        );
+        program_test.add_program(
+            "logger_contract",
+            logger_program_id,
+            processor!(LoggerProcessor::process),
+        );
+        program_test.prefer_bpf(false);
+
+        let (banks, payer, blockhash) = program_test.start().await;
+        let mut bridge = Bridge {
+            banks,
+            payer,
+            blockhash,
+            escrow_program_id,
+            logger_program_id,
+            logger_state: Pubkey::default(),
+            mint: Pubkey::default(),
+            user_token_account: Pubkey::default(),
+        };
+
+        let rent = Rent::default();
+        let logger_state = Keypair::new();
+        let mint = Keypair::new();
+        let user_token_account = Keypair::new();
+        let payer_key = bridge.payer.pubkey();
+        bridge
+            .send(
+                &[
+                    system_instruction::create_account(
+                        &payer_key,
+                        &logger_state.pubkey(),
+                        rent.minimum_balance(LoggerState::LEN),
+                        LoggerState::LEN as u64,
+                        &logger_program_id,
+                    ),
+                    logger_instruction::initialize(
+                        &logger_program_id,
+                        &logger_state.pubkey(),
+                        &payer_key,
+                    ),
+                    escrow_instruction::initialize_config(
+                        &escrow_program_id,
+                        &payer_key,
+                        &logger_program_id,
+                        &logger_state.pubkey(),
+                    ),
+                    system_instruction::create_account(
+                        &payer_key,
+                        &mint.pubkey(),
+                        rent.minimum_balance(spl_token::state::Mint::LEN),
+                        spl_token::state::Mint::LEN as u64,
+                        &spl_token::id(),
+                    ),
+                    spl_token::instruction::initialize_mint(
+                        &spl_token::id(),
+                        &mint.pubkey(),
+                        &payer_key,
+                        None,
+                        0,
+                    )
+                    .unwrap(),
+                    system_instruction::create_account(
+                        &payer_key,
+                        &user_token_account.pubkey(),
+                        rent.minimum_balance(spl_token::state::Account::LEN),
+                        spl_token::state::Account::LEN as u64,
+                        &spl_token::id(),
+                    ),
+                    spl_token::instruction::initialize_account(
+                        &spl_token::id(),
+                        &user_token_account.pubkey(),
+                        &mint.pubkey(),
+                        &payer_key,
+                    )
+                    .unwrap(),
+                    spl_token::instruction::mint_to(
+                        &spl_token::id(),
+                        &mint.pubkey(),
+                        &user_token_account.pubkey(),
+                        &payer_key,
+                        &[],
+                        1_000,
+                    )
+                    .unwrap(),
+                ],
+                &[&logger_state, &mint, &user_token_account],
+            )
+            .await;
+
+        bridge.logger_state = logger_state.pubkey();
+        bridge.mint = mint.pubkey();
+        bridge.user_token_account = user_token_account.pubkey();
+        bridge
+    }
+
+    async fn send(&mut self, instructions: &[Instruction], signers: &[&Keypair]) {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        );
+        self.banks.process_transaction(transaction).await.unwrap();
+        self.blockhash = self
+            .banks
+            .get_new_latest_blockhash(&self.blockhash)
+            .await
+            .unwrap();
+    }
+
+    // Escrow prelazi na novi logger state, cije sekvence krecu od 1
+    async fn set_logger(&mut self) {
+        let logger_state = Keypair::new();
+        let payer_key = self.payer.pubkey();
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer_key,
+                    &logger_state.pubkey(),
+                    Rent::default().minimum_balance(LoggerState::LEN),
+                    LoggerState::LEN as u64,
+                    &self.logger_program_id,
+                ),
+                logger_instruction::initialize(
+                    &self.logger_program_id,
+                    &logger_state.pubkey(),
+                    &payer_key,
+                ),
+                escrow_instruction::set_logger(
+                    &self.escrow_program_id,
+                    &payer_key,
+                    &self.logger_program_id,
+                    &logger_state.pubkey(),
+                    &[],
+                ),
+            ],
+            &[&logger_state],
+        )
+        .await;
+        self.logger_state = logger_state.pubkey();
+    }
+
+    async fn next_message_address(&mut self) -> (Pubkey, u64) {
+        let state = self
+            .banks
+            .get_account(self.logger_state)
+            .await
+            .unwrap()
+            .unwrap();
+        let sequence = LoggerState::load(&state.data).unwrap().sequence.get() + 1;
+        (
+            MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0,
+            sequence,
+        )
+    }
+
+    async fn message(&mut self, sequence: u64) -> MessageData {
+        let address = MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0;
+        let account = self.banks.get_account(address).await.unwrap().unwrap();
+        *MessageData::load_unchecked(&account.data).unwrap()
+    }
+
+    async fn deposit(&mut self, amount: u64) -> MessageData {
+        let (message, sequence) = self.next_message_address().await;
+        let instruction = escrow_instruction::deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &self.payer.pubkey(),
+            &self.user_token_account,
+            &self.mint,
+            &self.logger_state,
+            &message,
+            amount,
+        );
+        self.send(&[instruction], &[]).await;
+        self.message(sequence).await
+    }
+
+    async fn withdraw(&mut self, amount: u64) -> MessageData {
+        let (message, sequence) = self.next_message_address().await;
+        let instruction = escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &self.payer.pubkey(),
+            &self.payer.pubkey(),
+            &self.user_token_account,
+            &self.mint,
+            &self.logger_state,
+            &message,
+            amount,
+        );
+        self.send(&[instruction], &[]).await;
+        self.message(sequence).await
+    }
+
+    async fn batch(&mut self, sequence: u64) -> (MessageData, Batch) {
+        let message = self.message(sequence).await;
+        let address = MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0;
+        let batch = Batch::find_address(&address, &self.escrow_program_id).0;
+        let account = self.banks.get_account(batch).await.unwrap().unwrap();
+        (message, *Batch::load(&account.data).unwrap())
+    }
+
+    fn batch_legs(&self, amounts: &[u64]) -> Vec<BatchLeg> {
+        amounts
+            .iter()
+            .map(|&amount| BatchLeg {
+                token_account: self.user_token_account,
+                mint: self.mint,
+                amount,
+            })
+            .collect()
+    }
+
+    async fn batch_deposit(&mut self, amounts: &[u64]) -> (MessageData, Batch) {
+        let (message, sequence) = self.next_message_address().await;
+        let instruction = escrow_instruction::batch_deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &self.payer.pubkey(),
+            &self.logger_state,
+            &message,
+            &self.batch_legs(amounts),
+        );
+        self.send(&[instruction], &[]).await;
+        self.batch(sequence).await
+    }
+
+    async fn batch_withdraw(&mut self, amounts: &[u64]) -> (MessageData, Batch) {
+        let (message, sequence) = self.next_message_address().await;
+        let instruction = escrow_instruction::batch_withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &self.payer.pubkey(),
+            &self.payer.pubkey(),
+            &self.logger_state,
+            &message,
+            &self.batch_legs(amounts),
+        );
+        self.send(&[instruction], &[]).await;
+        self.batch(sequence).await
+    }
+
+    async fn token_balance(&mut self, address: Pubkey) -> u64 {
+        let account = self.banks.get_account(address).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+}
+
+fn relayer(
+    name: &str,
+    source: FetchedMessages,
+    sim: SimChain,
+) -> Relayer<FetchedMessages, SimChain> {
+    let path =
+        std::env::temp_dir().join(format!("bridge-sim-{}-{}.cursor", name, std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let cursor = Cursor::load(&path, &source.logger_state()).unwrap();
+    Relayer::new(source, sim, cursor, RelayerConfig::default())
+}
+
+#[tokio::test]
+async fn lock_relay_mint_burn_unlock_round_trip() {
+    let mut bridge = Bridge::start().await;
+    let source = FetchedMessages::new(bridge.logger_state);
+    let mut sim = SimChain::new(bridge.escrow_program_id);
+    sim.register_mint(bridge.mint);
+    let mut relayer = relayer("round-trip", source.clone(), sim);
+    let user = bridge.payer.pubkey();
+    let mint = bridge.mint;
+
+    // Lock
+    let deposit = bridge.deposit(400).await;
+    source.0.borrow_mut().push(deposit);
+
+    // Relay + mint
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    assert_eq!(relayer.destination().balance_of(&mint, &user), 400);
+    relayer.destination().check_invariants().unwrap();
+
+    // Burn
+    let burn = relayer.destination_mut().burn(&user, &mint, 150).unwrap();
+    assert_eq!(burn.amount, 150);
+    relayer.destination().check_invariants().unwrap();
+
+    // Unlock
+    let withdraw = bridge.withdraw(burn.amount).await;
+    source.0.borrow_mut().push(withdraw);
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    assert_eq!(relayer.cursor().last_delivered(), 2);
+
+    let sim = relayer.destination();
+    sim.check_invariants().unwrap();
+    assert_eq!(sim.balance_of(&mint, &user), 250);
+    assert_eq!(sim.supply_of(&mint), 250);
+    assert_eq!(sim.locked_of(&mint), 250);
+    assert_eq!(sim.pending_returns().count(), 0);
+
+    let vault = EscrowState::find_vault_address(&mint, &bridge.escrow_program_id).0;
+    assert_eq!(bridge.token_balance(vault).await, 250);
+    assert_eq!(bridge.token_balance(bridge.user_token_account).await, 750);
+}
+
+#[tokio::test]
+async fn batch_messages_are_applied_per_leg() {
+    let mut bridge = Bridge::start().await;
+    let source = FetchedMessages::new(bridge.logger_state);
+    let mut sim = SimChain::new(bridge.escrow_program_id);
+    sim.register_mint(bridge.mint);
+    let mut relayer = relayer("batch", source.clone(), sim);
+    let user = bridge.payer.pubkey();
+    let mint = bridge.mint;
+
+    let (deposit, batch) = bridge.batch_deposit(&[300, 100]).await;
+    // Poruka nosi broj legova, iznosi su samo u batch nalogu
+    assert_eq!(deposit.amount.get(), 2);
+    source.0.borrow_mut().push(deposit);
+    source.1.borrow_mut().insert(1, batch);
+    assert_eq!(relayer.run_once().unwrap(), 1);
+    assert_eq!(relayer.destination().balance_of(&mint, &user), 400);
+    assert_eq!(relayer.destination().locked_of(&mint), 400);
+
+    // Svaki leg povlacenja trazi svoj burn
+    relayer.destination_mut().burn(&user, &mint, 100).unwrap();
+    relayer.destination_mut().burn(&user, &mint, 50).unwrap();
+    let (withdraw, batch) = bridge.batch_withdraw(&[100, 50]).await;
+    source.0.borrow_mut().push(withdraw);
+    source.1.borrow_mut().insert(2, batch);
+    assert_eq!(relayer.run_once().unwrap(), 1);
+
+    let sim = relayer.destination();
+    sim.check_invariants().unwrap();
+    assert_eq!(sim.balance_of(&mint, &user), 250);
+    assert_eq!(sim.locked_of(&mint), 250);
+    assert_eq!(sim.pending_returns().count(), 0);
+}
+
+#[tokio::test]
+async fn unlock_without_burn_is_rejected() {
+    let mut bridge = Bridge::start().await;
+    let source = FetchedMessages::new(bridge.logger_state);
+    let mut sim = SimChain::new(bridge.escrow_program_id);
+    sim.register_mint(bridge.mint);
+    let mut relayer = relayer("unbacked", source.clone(), sim);
+    let user = bridge.payer.pubkey();
+    let mint = bridge.mint;
+
+    let deposit = bridge.deposit(100).await;
+    source.0.borrow_mut().push(deposit);
+    relayer.run_once().unwrap();
+
+    // Korisnik povlaci na Solani a wrapped tokene zadrzava
+    let withdraw = bridge.withdraw(60).await;
+    source.0.borrow_mut().push(withdraw);
+    match relayer.run_once() {
+        Err(RelayerError::Rejected { sequence, .. }) => assert_eq!(sequence, 2),
+        other => panic!("expected rejected unlock, got {:?}", other.map(|_| ())),
+    }
+    assert_eq!(relayer.cursor().last_delivered(), 1);
+
+    let sim = relayer.destination();
+    sim.check_invariants().unwrap();
+    assert_eq!(sim.balance_of(&mint, &user), 100);
+    assert_eq!(sim.locked_of(&mint), 100);
+}
+
+#[tokio::test]
+async fn messages_of_a_migrated_logger_state_are_applied() {
+    let mut bridge = Bridge::start().await;
+    let old_source = FetchedMessages::new(bridge.logger_state);
+    let mut sim = SimChain::new(bridge.escrow_program_id);
+    sim.register_mint(bridge.mint);
+    let mut old = relayer("migrate-old", old_source.clone(), sim);
+    let user = bridge.payer.pubkey();
+    let mint = bridge.mint;
+
+    let deposit = bridge.deposit(100).await;
+    old_source.0.borrow_mut().push(deposit);
+    assert_eq!(old.run_once().unwrap(), 1);
+
+    // Nova poruka ima istu sekvencu kao prva poruka starog state-a
+    let old_state = bridge.logger_state;
+    bridge.set_logger().await;
+    let new_source = FetchedMessages::new(bridge.logger_state);
+    let mut new = relayer("migrate-new", new_source.clone(), old.destination().clone());
+    let deposit = bridge.deposit(40).await;
+    assert_eq!(deposit.sequence.get(), 1);
+    new_source.0.borrow_mut().push(deposit);
+    assert_eq!(new.run_once().unwrap(), 1);
+
+    let sim = new.destination();
+    sim.check_invariants().unwrap();
+    assert!(sim.is_processed(&old_state, 1) && sim.is_processed(&bridge.logger_state, 1));
+    assert_eq!(sim.balance_of(&mint, &user), 140);
+    assert_eq!(sim.locked_of(&mint), 140);
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+use escrow_contract::instruction::EscrowInstruction;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = EscrowInstruction::unpack(data) {
+        let packed = instruction.pack();
+        assert!(data.starts_with(&packed));
+        assert_eq!(EscrowInstruction::unpack(&packed).unwrap(), instruction);
+    }
+});
@@ -0,0 +1,6 @@
+#![no_main]
+
+use bridge_fuzz::escrow::{run, EscrowCase};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|case: EscrowCase| run(case));
@@ -0,0 +1,17 @@
+#![no_main]
+
+use escrow_contract::state::EscrowState;
+use libfuzzer_sys::fuzz_target;
+use logger_contract::pod::ZeroCopy;
+
+fuzz_target!(|data: &[u8]| {
+    // Cast proverava duzinu, pa ulaz bilo koje duzine ne sme da panikuje
+    if let Ok(value) = EscrowState::load(data) {
+        assert_eq!(bytemuck::bytes_of(value), data);
+        assert_eq!(&data[..8], EscrowState::DISCRIMINATOR);
+    }
+    assert_eq!(
+        EscrowState::load_unchecked(data).is_ok(),
+        data.len() == EscrowState::LEN
+    );
+});
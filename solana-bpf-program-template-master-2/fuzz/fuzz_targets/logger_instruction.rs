@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logger_contract::instruction::LoggerInstruction;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(instruction) = LoggerInstruction::unpack(data) {
+        let packed = instruction.pack();
+        assert!(data.starts_with(&packed));
+        assert_eq!(LoggerInstruction::unpack(&packed).unwrap(), instruction);
+    }
+});
@@ -0,0 +1,6 @@
+#![no_main]
+
+use bridge_fuzz::logger::{run, LoggerCase};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|case: LoggerCase| run(case));
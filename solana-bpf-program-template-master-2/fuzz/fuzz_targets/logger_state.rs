@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logger_contract::{pod::ZeroCopy, state::LoggerState};
+
+fuzz_target!(|data: &[u8]| {
+    // Cast proverava duzinu, pa ulaz bilo koje duzine ne sme da panikuje
+    if let Ok(value) = LoggerState::load(data) {
+        assert_eq!(bytemuck::bytes_of(value), data);
+        assert_eq!(&data[..8], LoggerState::DISCRIMINATOR);
+    }
+    assert_eq!(
+        LoggerState::load_unchecked(data).is_ok(),
+        data.len() == LoggerState::LEN
+    );
+});
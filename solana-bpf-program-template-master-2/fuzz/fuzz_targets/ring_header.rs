@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use logger_contract::{pod::ZeroCopy, state::RingHeader};
+
+fuzz_target!(|input: (u64, &[u8])| {
+    let (sequence, data) = input;
+    if let Ok(header) = RingHeader::load(data) {
+        assert_eq!(bytemuck::bytes_of(header), data);
+    }
+    if let Ok(Some(message)) = RingHeader::read_message(data, sequence) {
+        assert_eq!(message.sequence.get(), sequence);
+    }
+});
@@ -0,0 +1,602 @@
+use std::collections::HashMap;
+
+use arbitrary::Arbitrary;
+use escrow_contract::{
+    instruction::{self as escrow_instruction, EscrowInstruction},
+    processor::EscrowProcessor,
+    state::{Batch, EscrowConfig, EscrowState, Position},
+};
+use logger_contract::{
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{LoggerState, MessageData},
+};
+use solana_program::{
+    instruction::Instruction, program_option::COption, program_pack::Pack, pubkey::Pubkey,
+    system_program, sysvar,
+};
+use spl_token::state::{Account as TokenAccount, AccountState, Mint};
+
+use crate::{
+    is_signer,
+    mock::{MockAccount, MockBank, CLOCK},
+    mutate, Mutation, PoolKey, ESCROW_PROGRAM_ID, LOGGER_PROGRAM_ID,
+};
+
+const MINT: Pubkey = Pubkey::new_from_array([0x40; 32]);
+const OTHER_MINT: Pubkey = Pubkey::new_from_array([0x41; 32]);
+const USER: Pubkey = Pubkey::new_from_array([0x42; 32]);
+const ATTACKER: Pubkey = Pubkey::new_from_array([0x43; 32]);
+const USER_TOKENS: Pubkey = Pubkey::new_from_array([0x44; 32]);
+const ATTACKER_TOKENS: Pubkey = Pubkey::new_from_array([0x45; 32]);
+const OTHER_MINT_TOKENS: Pubkey = Pubkey::new_from_array([0x46; 32]);
+const LOGGER_STATE: Pubkey = Pubkey::new_from_array([0x47; 32]);
+const LOGGER_AUTHORITY: Pubkey = Pubkey::new_from_array([0x48; 32]);
+
+#[derive(Arbitrary, Debug)]
+pub enum EscrowCall {
+    Deposit(u64),
+    Withdraw(u64),
+    // ATTACKER postaje delegat pozicije korisnika
+    SetDelegate {
+        cap: Option<u64>,
+        expires_at: Option<u64>,
+    },
+    // Proizvoljni bajtovi sa nalozima za Deposit
+    Raw(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct EscrowCase {
+    pub user_balance: u64,
+    pub attacker_balance: u64,
+    // None kada escrow, odnosno vault, jos ne postoji
+    pub total_deposited: Option<u64>,
+    pub vault_balance: Option<u64>,
+    // None kada pozicija korisnika jos ne postoji
+    pub position: Option<u64>,
+    // Limit i istek za ATTACKER kao delegata pozicije
+    pub delegate: Option<(Option<u64>, Option<u64>)>,
+    pub sequence: u64,
+    pub call: EscrowCall,
+    pub mutations: Vec<Mutation>,
+}
+
+/// Builds the accounts for `case`, runs the corrupted instruction and
+/// panics on any broken invariant.
+pub fn run(case: EscrowCase) {
+    let mut bank = MockBank::new(&[
+        (ESCROW_PROGRAM_ID, EscrowProcessor::process),
+        (LOGGER_PROGRAM_ID, LoggerProcessor::process),
+    ]);
+    let escrow = EscrowState::find_address(&MINT, &ESCROW_PROGRAM_ID).0;
+    let vault = EscrowState::find_vault_address(&MINT, &ESCROW_PROGRAM_ID).0;
+    let config = EscrowConfig::find_address(&ESCROW_PROGRAM_ID).0;
+    let position = Position::find_address(&MINT, &USER, &ESCROW_PROGRAM_ID).0;
+
+    for key in [USER, ATTACKER, LOGGER_AUTHORITY] {
+        bank.insert(MockAccount::new(
+            key,
+            system_program::id(),
+            10_000_000_000,
+            Vec::new(),
+        ));
+    }
+    let supply = case
+        .user_balance
+        .saturating_add(case.attacker_balance)
+        .saturating_add(case.vault_balance.unwrap_or(0));
+    bank.insert(mint(MINT, supply));
+    bank.insert(mint(OTHER_MINT, case.user_balance));
+    bank.insert(token_account(USER_TOKENS, MINT, USER, case.user_balance));
+    bank.insert(token_account(
+        ATTACKER_TOKENS,
+        MINT,
+        ATTACKER,
+        case.attacker_balance,
+    ));
+    bank.insert(token_account(
+        OTHER_MINT_TOKENS,
+        OTHER_MINT,
+        USER,
+        case.user_balance,
+    ));
+
+    if let Some(total_deposited) = case.total_deposited {
+        let state = EscrowState::new(MINT, vault, total_deposited);
+        bank.insert(MockAccount::with_data(
+            escrow,
+            ESCROW_PROGRAM_ID,
+            bytemuck::bytes_of(&state).to_vec(),
+        ));
+    }
+    if let Some(balance) = case.vault_balance {
+        bank.insert(token_account(vault, MINT, vault, balance));
+    }
+    if let Some(amount) = case.position {
+        let mut state = Position {
+            amount: amount.into(),
+            ..Position::new(USER, MINT)
+        };
+        if let Some((cap, expires_at)) = case.delegate {
+            state.set_delegate(ATTACKER, cap, expires_at);
+        }
+        bank.insert(MockAccount::with_data(
+            position,
+            ESCROW_PROGRAM_ID,
+            bytemuck::bytes_of(&state).to_vec(),
+        ));
+    }
+
+    let state = LoggerState {
+        sequence: case.sequence.into(),
+        ..LoggerState::new(LOGGER_AUTHORITY)
+    };
+    bank.insert(MockAccount::with_data(
+        LOGGER_STATE,
+        LOGGER_PROGRAM_ID,
+        bytemuck::bytes_of(&state).to_vec(),
+    ));
+    let config_state = EscrowConfig::new(LOGGER_AUTHORITY, LOGGER_PROGRAM_ID, LOGGER_STATE);
+    bank.insert(MockAccount::with_data(
+        config,
+        ESCROW_PROGRAM_ID,
+        bytemuck::bytes_of(&config_state).to_vec(),
+    ));
+    let message = case
+        .sequence
+        .checked_add(1)
+        .map(|sequence| MessageData::find_address(&LOGGER_STATE, sequence, &LOGGER_PROGRAM_ID).0)
+        .unwrap_or_default();
+    let batch = Batch::find_address(&message, &ESCROW_PROGRAM_ID).0;
+
+    let mut instruction = match case.call {
+        EscrowCall::Withdraw(amount) => escrow_instruction::withdraw(
+            &ESCROW_PROGRAM_ID,
+            &LOGGER_PROGRAM_ID,
+            &USER,
+            &USER,
+            &USER_TOKENS,
+            &MINT,
+            &LOGGER_STATE,
+            &message,
+            amount,
+        ),
+        EscrowCall::SetDelegate { cap, expires_at } => escrow_instruction::set_delegate(
+            &ESCROW_PROGRAM_ID,
+            &USER,
+            &MINT,
+            &ATTACKER,
+            cap,
+            expires_at,
+        ),
+        EscrowCall::Deposit(amount) => escrow_instruction::deposit(
+            &ESCROW_PROGRAM_ID,
+            &LOGGER_PROGRAM_ID,
+            &USER,
+            &USER_TOKENS,
+            &MINT,
+            &LOGGER_STATE,
+            &message,
+            amount,
+        ),
+        EscrowCall::Raw(_) => escrow_instruction::deposit(
+            &ESCROW_PROGRAM_ID,
+            &LOGGER_PROGRAM_ID,
+            &USER,
+            &USER_TOKENS,
+            &MINT,
+            &LOGGER_STATE,
+            &message,
+            0,
+        ),
+    };
+    if let EscrowCall::Raw(data) = case.call {
+        instruction.data = data;
+    }
+
+    let pool = [
+        PoolKey {
+            key: USER,
+            signable: true,
+        },
+        PoolKey {
+            key: ATTACKER,
+            signable: true,
+        },
+        PoolKey {
+            key: USER_TOKENS,
+            signable: false,
+        },
+        PoolKey {
+            key: ATTACKER_TOKENS,
+            signable: false,
+        },
+        PoolKey {
+            key: OTHER_MINT_TOKENS,
+            signable: false,
+        },
+        PoolKey {
+            key: escrow,
+            signable: false,
+        },
+        PoolKey {
+            key: vault,
+            signable: false,
+        },
+        PoolKey {
+            key: MINT,
+            signable: false,
+        },
+        PoolKey {
+            key: OTHER_MINT,
+            signable: false,
+        },
+        PoolKey {
+            key: LOGGER_STATE,
+            signable: false,
+        },
+        PoolKey {
+            key: config,
+            signable: false,
+        },
+        PoolKey {
+            key: position,
+            signable: false,
+        },
+        PoolKey {
+            key: message,
+            signable: false,
+        },
+        PoolKey {
+            key: batch,
+            signable: false,
+        },
+        PoolKey {
+            key: system_program::id(),
+            signable: false,
+        },
+        PoolKey {
+            key: spl_token::id(),
+            signable: false,
+        },
+        PoolKey {
+            key: sysvar::rent::id(),
+            signable: false,
+        },
+        PoolKey {
+            key: LOGGER_PROGRAM_ID,
+            signable: false,
+        },
+        PoolKey {
+            key: ESCROW_PROGRAM_ID,
+            signable: false,
+        },
+    ];
+    mutate(&mut instruction, &case.mutations, &pool);
+
+    let before = bank.clone();
+    if bank.process(&instruction).is_err() {
+        return;
+    }
+    check(&before, &bank, &instruction);
+}
+
+fn mint(key: Pubkey, supply: u64) -> MockAccount {
+    let mint = Mint {
+        mint_authority: COption::Some(ATTACKER),
+        supply,
+        decimals: 0,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    MockAccount::with_data(key, spl_token::id(), packed(mint))
+}
+
+fn token_account(key: Pubkey, mint: Pubkey, owner: Pubkey, amount: u64) -> MockAccount {
+    let account = TokenAccount {
+        mint,
+        owner,
+        amount,
+        state: AccountState::Initialized,
+        ..TokenAccount::default()
+    };
+    MockAccount::with_data(key, spl_token::id(), packed(account))
+}
+
+fn packed<T: Pack>(value: T) -> Vec<u8> {
+    let mut data = vec![0u8; T::LEN];
+    T::pack(value, &mut data).unwrap();
+    data
+}
+
+fn escrow_state(bank: &MockBank, key: &Pubkey) -> Option<EscrowState> {
+    bank.get(key)
+        .filter(|account| account.owner == ESCROW_PROGRAM_ID)
+        .map(|account| *EscrowState::load(&account.data).unwrap())
+}
+
+fn position_state(bank: &MockBank, key: &Pubkey) -> Option<Position> {
+    bank.get(key)
+        .filter(|account| account.owner == ESCROW_PROGRAM_ID)
+        .map(|account| *Position::load(&account.data).unwrap())
+}
+
+fn token_balance(bank: &MockBank, key: &Pubkey) -> u64 {
+    bank.get(key)
+        .filter(|account| account.owner == spl_token::id())
+        .and_then(|account| TokenAccount::unpack(&account.data).ok())
+        .map_or(0, |account| account.amount)
+}
+
+fn logger_sequence(bank: &MockBank, key: &Pubkey) -> u64 {
+    LoggerState::load(&bank.get(key).unwrap().data)
+        .unwrap()
+        .sequence
+        .get()
+}
+
+// Ukupna kolicina tokena po mint-u, CPI u spl-token samo premesta tokene
+fn token_totals(bank: &MockBank) -> HashMap<Pubkey, u128> {
+    let mut totals = HashMap::new();
+    for account in &bank.accounts {
+        if account.owner != spl_token::id() {
+            continue;
+        }
+        if let Ok(token) = TokenAccount::unpack(&account.data) {
+            *totals.entry(token.mint).or_insert(0) += token.amount as u128;
+        }
+    }
+    totals
+}
+
+// Povlacenje sme samo vlasnik ili delegat u okviru limita i roka
+fn check_withdraw(
+    before: &MockBank,
+    instruction: &Instruction,
+    escrow: &Pubkey,
+    position: &Pubkey,
+    amount: u64,
+) {
+    let authority = instruction.accounts[0].pubkey;
+    assert!(
+        is_signer(instruction, &authority),
+        "withdraw without authority signature"
+    );
+    let state = escrow_state(before, escrow).unwrap();
+    assert!(
+        state.total_deposited.get() >= amount,
+        "withdrew more than deposited"
+    );
+    let position = position_state(before, position).unwrap();
+    assert_eq!(position.token_mint, state.token_mint);
+    assert!(
+        position.amount.get() >= amount,
+        "withdrew more than the position holds"
+    );
+    if authority != position.owner {
+        assert_eq!(
+            position.delegate(),
+            Some(authority),
+            "withdraw by neither owner nor delegate"
+        );
+        assert!(
+            position.delegate_cap().is_none_or(|cap| cap >= amount),
+            "delegate withdrew over its cap"
+        );
+        assert!(
+            position
+                .delegate_expires_at()
+                .is_none_or(|expires_at| (CLOCK.unix_timestamp as u64) < expires_at),
+            "expired delegate withdrew"
+        );
+    }
+}
+
+// Batch zapis mora da pokrije poruku i da nosi ukupan iznos legova
+fn check_batch(after: &MockBank, message: &Pubkey, batch: &Pubkey, total: u64) {
+    assert_eq!(
+        *batch,
+        Batch::find_address(message, &ESCROW_PROGRAM_ID).0,
+        "batch record is not derived from its message"
+    );
+    let record = after
+        .get(batch)
+        .filter(|account| account.owner == ESCROW_PROGRAM_ID)
+        .map(|account| *Batch::load(&account.data).unwrap())
+        .expect("batch left no batch record");
+    assert_eq!(record.message, *message);
+    assert_eq!(
+        record.legs().iter().map(|leg| leg.amount.get()).sum::<u64>(),
+        total,
+        "batch record does not match its legs"
+    );
+}
+
+// Uspesna instrukcija mora biti validna i ostaviti stanje kakvo opisuje
+fn check(before: &MockBank, after: &MockBank, instruction: &Instruction) {
+    let metas = &instruction.accounts;
+    assert_eq!(
+        token_totals(before),
+        token_totals(after),
+        "tokens were minted or burned"
+    );
+
+    let (escrow, logger_state, position, token_account, delta) =
+        match EscrowInstruction::unpack(&instruction.data).unwrap() {
+            EscrowInstruction::Deposit { amount } => {
+                assert!(
+                    is_signer(instruction, &metas[0].pubkey),
+                    "deposit without user signature"
+                );
+                let mint = metas[12].pubkey;
+                assert_eq!(
+                    metas[2].pubkey,
+                    EscrowState::find_address(&mint, &ESCROW_PROGRAM_ID).0
+                );
+                assert_eq!(
+                    metas[14].pubkey,
+                    Position::find_address(&mint, &metas[0].pubkey, &ESCROW_PROGRAM_ID).0,
+                    "deposit credited to a position of someone else"
+                );
+                (
+                    metas[2].pubkey,
+                    metas[8].pubkey,
+                    metas[14].pubkey,
+                    metas[1].pubkey,
+                    amount as i128,
+                )
+            }
+            EscrowInstruction::Withdraw { amount } => {
+                check_withdraw(before, instruction, &metas[2].pubkey, &metas[12].pubkey, amount);
+                (
+                    metas[2].pubkey,
+                    metas[6].pubkey,
+                    metas[12].pubkey,
+                    metas[1].pubkey,
+                    -(amount as i128),
+                )
+            }
+            // Nalozi za Deposit imaju mesta za tacno jedan leg
+            EscrowInstruction::BatchDeposit { legs } => {
+                assert_eq!(legs.len(), 1, "batch ran a leg without its accounts");
+                let (mint, amount) = legs[0];
+                assert!(
+                    is_signer(instruction, &metas[0].pubkey),
+                    "batch deposit without user signature"
+                );
+                check_batch(after, &metas[6].pubkey, &metas[7].pubkey, amount);
+                assert_eq!(metas[10].pubkey, mint, "leg deposited a mint it did not name");
+                assert_eq!(
+                    metas[11].pubkey,
+                    EscrowState::find_address(&mint, &ESCROW_PROGRAM_ID).0
+                );
+                assert_eq!(
+                    metas[13].pubkey,
+                    Position::find_address(&mint, &metas[0].pubkey, &ESCROW_PROGRAM_ID).0,
+                    "batch deposit credited to a position of someone else"
+                );
+                (
+                    metas[11].pubkey,
+                    metas[5].pubkey,
+                    metas[13].pubkey,
+                    metas[9].pubkey,
+                    amount as i128,
+                )
+            }
+            EscrowInstruction::BatchWithdraw { legs } => {
+                assert_eq!(legs.len(), 1, "batch ran a leg without its accounts");
+                let (mint, amount) = legs[0];
+                check_withdraw(before, instruction, &metas[9].pubkey, &metas[11].pubkey, amount);
+                check_batch(after, &metas[4].pubkey, &metas[5].pubkey, amount);
+                assert_eq!(
+                    escrow_state(before, &metas[9].pubkey).unwrap().token_mint,
+                    mint,
+                    "leg withdrew a mint it did not name"
+                );
+                (
+                    metas[9].pubkey,
+                    metas[3].pubkey,
+                    metas[11].pubkey,
+                    metas[8].pubkey,
+                    -(amount as i128),
+                )
+            }
+            EscrowInstruction::SetDelegate {
+                delegate,
+                cap,
+                expires_at,
+            } => {
+                let owner = metas[0].pubkey;
+                assert!(
+                    is_signer(instruction, &owner),
+                    "delegate set without owner signature"
+                );
+                let mut expected = position_state(before, &metas[1].pubkey).unwrap();
+                assert_eq!(
+                    expected.owner, owner,
+                    "delegate set by someone other than the owner"
+                );
+                expected.set_delegate(delegate, cap, expires_at);
+                assert_eq!(position_state(after, &metas[1].pubkey), Some(expected));
+                // Delegat nije transfer i ne sme da zavrsi u toku poruka
+                assert_eq!(
+                    logger_sequence(after, &LOGGER_STATE),
+                    logger_sequence(before, &LOGGER_STATE),
+                    "delegate change posted a logger message"
+                );
+                return;
+            }
+            // Config vec postoji, a admin nije u pool-u
+            EscrowInstruction::InitializeConfig
+            | EscrowInstruction::SetLogger
+            | EscrowInstruction::SetAdmin { .. }
+            | EscrowInstruction::SetWithdrawalPolicy { .. } => {
+                panic!("config changed without the admin signature")
+            }
+            // U pool-u nema multisig naloga
+            EscrowInstruction::InitializeMultisig { .. }
+            | EscrowInstruction::Propose { .. }
+            | EscrowInstruction::Approve
+            | EscrowInstruction::Execute => {
+                panic!("multisig instruction succeeded without a multisig")
+            }
+            // U pool-u nema vesting PDA naloga
+            EscrowInstruction::CreateVesting { .. }
+            | EscrowInstruction::ClaimVested
+            | EscrowInstruction::Revoke => {
+                panic!("vesting instruction succeeded without a vesting account")
+            }
+            // U pool-u nema agreement PDA naloga
+            EscrowInstruction::OpenAgreement { .. }
+            | EscrowInstruction::Release
+            | EscrowInstruction::OpenDispute
+            | EscrowInstruction::Resolve { .. }
+            | EscrowInstruction::Refund => {
+                panic!("agreement instruction succeeded without an agreement account")
+            }
+        };
+    assert_eq!(
+        logger_state, LOGGER_STATE,
+        "logged to an untrusted logger state"
+    );
+
+    let pre_position = position_state(before, &position).map_or(0, |state| state.amount.get());
+    let post_position = position_state(after, &position).unwrap();
+    assert_eq!(
+        post_position.amount.get() as i128,
+        pre_position as i128 + delta
+    );
+
+    let pre = escrow_state(before, &escrow);
+    let post = escrow_state(after, &escrow).unwrap();
+    let pre_total = pre.map_or(0, |state| state.total_deposited.get());
+    assert_eq!(
+        post.total_deposited.get() as i128,
+        pre_total as i128 + delta
+    );
+    assert_eq!(
+        escrow,
+        EscrowState::find_address(&post.token_mint, &ESCROW_PROGRAM_ID).0
+    );
+
+    // Vault koji escrow vodi mora da se pomeri tacno za iznos, osim kada
+    // je korisnikov token nalog bas taj vault
+    let vault = post.escrow_vault_account;
+    let pre_vault = token_balance(before, &vault);
+    let post_vault = token_balance(after, &vault);
+    if token_account != vault {
+        assert_eq!(post_vault as i128, pre_vault as i128 + delta);
+    }
+    if pre_vault >= pre_total {
+        assert!(
+            post_vault >= post.total_deposited.get(),
+            "escrow became insolvent"
+        );
+    }
+
+    assert_eq!(
+        logger_sequence(after, &logger_state),
+        logger_sequence(before, &logger_state) + 1
+    );
+}
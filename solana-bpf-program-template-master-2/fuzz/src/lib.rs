@@ -0,0 +1,66 @@
+//! Fuzz harness for the escrow and logger programs. The decoder targets
+//! feed raw bytes to the hand-written unpackers; the processor targets
+//! build a mostly valid set of accounts, let the fuzzer corrupt the
+//! instruction and check the invariants of every successful run.
+
+pub mod escrow;
+pub mod logger;
+pub mod mock;
+
+use arbitrary::Arbitrary;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+
+pub const ESCROW_PROGRAM_ID: Pubkey = escrow_contract::ID;
+pub const LOGGER_PROGRAM_ID: Pubkey = logger_contract::ID;
+
+/// One corruption of the instruction a case starts from.
+#[derive(Arbitrary, Debug)]
+pub enum Mutation {
+    DropSigner(u8),
+    ToggleWritable(u8),
+    // Zamena naloga nekim iz pool-a, potpis ostaje samo za keypair naloge
+    Replace(u8, u8),
+    Truncate(u8),
+}
+
+/// A key the fuzzer may put into an instruction and whether anyone can
+/// sign for it. PDAs cannot sign at the top level.
+#[derive(Clone, Copy, Debug)]
+pub struct PoolKey {
+    pub key: Pubkey,
+    pub signable: bool,
+}
+
+/// Whether the runtime treats `key` as a signer of `instruction`; every
+/// occurrence of an account gets the privileges of all of them.
+pub fn is_signer(instruction: &Instruction, key: &Pubkey) -> bool {
+    instruction
+        .accounts
+        .iter()
+        .any(|meta| meta.pubkey == *key && meta.is_signer)
+}
+
+pub fn mutate(instruction: &mut Instruction, mutations: &[Mutation], pool: &[PoolKey]) {
+    for mutation in mutations {
+        let len = instruction.accounts.len();
+        if len == 0 {
+            return;
+        }
+        match *mutation {
+            Mutation::DropSigner(index) => {
+                instruction.accounts[index as usize % len].is_signer = false;
+            }
+            Mutation::ToggleWritable(index) => {
+                let meta = &mut instruction.accounts[index as usize % len];
+                meta.is_writable = !meta.is_writable;
+            }
+            Mutation::Replace(index, with) => {
+                let replacement = pool[with as usize % pool.len()];
+                let meta = &mut instruction.accounts[index as usize % len];
+                meta.pubkey = replacement.key;
+                meta.is_signer &= replacement.signable;
+            }
+            Mutation::Truncate(keep) => instruction.accounts.truncate(keep as usize),
+        }
+    }
+}
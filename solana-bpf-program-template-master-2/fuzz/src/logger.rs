@@ -0,0 +1,443 @@
+use arbitrary::Arbitrary;
+use bytemuck::bytes_of;
+use logger_contract::{
+    instruction::{self as logger_instruction, LoggerInstruction},
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+};
+use solana_program::{
+    hash::hashv, instruction::Instruction, pubkey::Pubkey, rent::Rent, system_program,
+};
+
+use crate::{
+    is_signer,
+    mock::{MockAccount, MockBank, CLOCK},
+    mutate, Mutation, PoolKey, LOGGER_PROGRAM_ID,
+};
+
+const AUTHORITY: Pubkey = Pubkey::new_from_array([0x20; 32]);
+const PAYER: Pubkey = Pubkey::new_from_array([0x21; 32]);
+const ATTACKER: Pubkey = Pubkey::new_from_array([0x22; 32]);
+const STATE: Pubkey = Pubkey::new_from_array([0x23; 32]);
+const UNINITIALIZED_STATE: Pubkey = Pubkey::new_from_array([0x24; 32]);
+const RING: Pubkey = Pubkey::new_from_array([0x25; 32]);
+const OTHER_STATE: Pubkey = Pubkey::new_from_array([0x26; 32]);
+const EMPTY: Pubkey = Pubkey::new_from_array([0x27; 32]);
+
+#[derive(Arbitrary, Debug)]
+pub enum LoggerCall {
+    Initialize,
+    PostToPda {
+        amount: u64,
+        timestamp: u64,
+        finalized: bool,
+    },
+    PostToRing {
+        amount: u64,
+        timestamp: u64,
+        finalized: bool,
+    },
+    Close,
+    SetAuthority,
+    GetSequence,
+    InitializeRing,
+    ResizeRing {
+        capacity: u16,
+    },
+    // Proizvoljni bajtovi sa nalozima za PostMessage
+    Raw(Vec<u8>),
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct LoggerCase {
+    pub sequence: u64,
+    pub initialized: bool,
+    pub ring_capacity: u8,
+    pub ring_initialized: bool,
+    pub ring_bound: bool,
+    pub call: LoggerCall,
+    pub mutations: Vec<Mutation>,
+}
+
+/// Builds the accounts for `case`, runs the corrupted instruction and
+/// panics on any broken invariant.
+pub fn run(case: LoggerCase) {
+    let mut bank = MockBank::new(&[(LOGGER_PROGRAM_ID, LoggerProcessor::process)]);
+    bank.insert(MockAccount::new(
+        AUTHORITY,
+        system_program::id(),
+        1_000_000_000,
+        Vec::new(),
+    ));
+    bank.insert(MockAccount::new(
+        PAYER,
+        system_program::id(),
+        10_000_000_000,
+        Vec::new(),
+    ));
+    bank.insert(MockAccount::new(
+        ATTACKER,
+        system_program::id(),
+        1_000_000_000,
+        Vec::new(),
+    ));
+
+    let state = if case.initialized {
+        LoggerState {
+            sequence: case.sequence.into(),
+            ..LoggerState::new(AUTHORITY)
+        }
+    } else {
+        LoggerState::default()
+    };
+    bank.insert(MockAccount::with_data(
+        STATE,
+        LOGGER_PROGRAM_ID,
+        bytes_of(&state).to_vec(),
+    ));
+    bank.insert(MockAccount::with_data(
+        UNINITIALIZED_STATE,
+        LOGGER_PROGRAM_ID,
+        vec![0u8; LoggerState::LEN],
+    ));
+
+    let capacity = (case.ring_capacity % 4) as u64;
+    let mut ring = vec![0u8; RingHeader::space(capacity)];
+    if capacity > 0 {
+        let header = RingHeader::new(if case.ring_bound { STATE } else { OTHER_STATE });
+        ring[..RingHeader::LEN].copy_from_slice(bytes_of(&header));
+        if !case.ring_initialized {
+            ring[..8].fill(0);
+        }
+    }
+    bank.insert(MockAccount::with_data(RING, LOGGER_PROGRAM_ID, ring));
+    // Nalog programa bez podataka, kraci od svakog zaglavlja
+    bank.insert(MockAccount::with_data(EMPTY, LOGGER_PROGRAM_ID, Vec::new()));
+
+    // Poslednja objavljena poruka postoji, sledeca PDA je prazna
+    let posted = MessageData::find_address(&STATE, state.sequence.get(), &LOGGER_PROGRAM_ID).0;
+    let message = MessageData {
+        discriminator: MessageData::DISCRIMINATOR,
+        sequence: state.sequence,
+        payer: PAYER,
+        ..MessageData::default()
+    };
+    bank.insert(MockAccount::with_data(
+        posted,
+        LOGGER_PROGRAM_ID,
+        bytes_of(&message).to_vec(),
+    ));
+    let next = state
+        .sequence
+        .get()
+        .checked_add(1)
+        .map(|sequence| MessageData::find_address(&STATE, sequence, &LOGGER_PROGRAM_ID).0)
+        .unwrap_or_default();
+
+    let mut instruction = match case.call {
+        LoggerCall::Initialize => {
+            logger_instruction::initialize(&LOGGER_PROGRAM_ID, &UNINITIALIZED_STATE, &AUTHORITY)
+        }
+        LoggerCall::PostToPda {
+            amount,
+            timestamp,
+            finalized,
+        } => post(&next, amount, timestamp, finalized),
+        LoggerCall::PostToRing {
+            amount,
+            timestamp,
+            finalized,
+        } => logger_instruction::post_ring_message(
+            &LOGGER_PROGRAM_ID,
+            &STATE,
+            &RING,
+            &PAYER,
+            &Pubkey::new_from_array([0x30; 32]),
+            &Pubkey::new_from_array([0x31; 32]),
+            amount,
+            timestamp,
+            consistency_level(finalized),
+            MessageKind::Transfer,
+        ),
+        LoggerCall::Close => logger_instruction::close_message(
+            &LOGGER_PROGRAM_ID,
+            &AUTHORITY,
+            &STATE,
+            &posted,
+            &PAYER,
+        ),
+        LoggerCall::SetAuthority => {
+            logger_instruction::set_authority(&LOGGER_PROGRAM_ID, &AUTHORITY, &STATE, &ATTACKER)
+        }
+        LoggerCall::GetSequence => logger_instruction::get_sequence(&LOGGER_PROGRAM_ID, &STATE),
+        LoggerCall::InitializeRing => {
+            logger_instruction::initialize_ring(&LOGGER_PROGRAM_ID, &AUTHORITY, &STATE, &RING)
+        }
+        LoggerCall::ResizeRing { capacity } => logger_instruction::resize_ring(
+            &LOGGER_PROGRAM_ID,
+            &AUTHORITY,
+            &STATE,
+            &RING,
+            &PAYER,
+            capacity as u64,
+        ),
+        LoggerCall::Raw(data) => Instruction {
+            data,
+            ..post(&next, 0, 0, false)
+        },
+    };
+
+    let pool = [
+        PoolKey {
+            key: STATE,
+            signable: false,
+        },
+        PoolKey {
+            key: UNINITIALIZED_STATE,
+            signable: false,
+        },
+        PoolKey {
+            key: RING,
+            signable: false,
+        },
+        PoolKey {
+            key: EMPTY,
+            signable: false,
+        },
+        PoolKey {
+            key: posted,
+            signable: false,
+        },
+        PoolKey {
+            key: next,
+            signable: false,
+        },
+        PoolKey {
+            key: AUTHORITY,
+            signable: true,
+        },
+        PoolKey {
+            key: PAYER,
+            signable: true,
+        },
+        PoolKey {
+            key: ATTACKER,
+            signable: true,
+        },
+        PoolKey {
+            key: system_program::id(),
+            signable: false,
+        },
+        PoolKey {
+            key: LOGGER_PROGRAM_ID,
+            signable: false,
+        },
+    ];
+    mutate(&mut instruction, &case.mutations, &pool);
+
+    let before = bank.clone();
+    if bank.process(&instruction).is_err() {
+        return;
+    }
+    check(&before, &bank, &instruction);
+}
+
+fn post(message: &Pubkey, amount: u64, timestamp: u64, finalized: bool) -> Instruction {
+    logger_instruction::post_message(
+        &LOGGER_PROGRAM_ID,
+        &STATE,
+        message,
+        &PAYER,
+        &PAYER,
+        &Pubkey::new_from_array([0x30; 32]),
+        &Pubkey::new_from_array([0x31; 32]),
+        amount,
+        timestamp,
+        consistency_level(finalized),
+        MessageKind::Transfer,
+    )
+}
+
+fn consistency_level(finalized: bool) -> ConsistencyLevel {
+    if finalized {
+        ConsistencyLevel::Finalized
+    } else {
+        ConsistencyLevel::Confirmed
+    }
+}
+
+fn data<'a>(bank: &'a MockBank, key: &Pubkey) -> &'a [u8] {
+    bank.get(key)
+        .map(|account| account.data.as_slice())
+        .unwrap_or(&[])
+}
+
+fn logger_state(bank: &MockBank, key: &Pubkey) -> LoggerState {
+    *LoggerState::load_unchecked(data(bank, key)).unwrap()
+}
+
+// Objava podize sekvencu za tacno jedan i ne dira authority
+fn check_sequence(before: &MockBank, after: &MockBank, state: &Pubkey) -> u64 {
+    let pre = logger_state(before, state);
+    let post = logger_state(after, state);
+    assert_eq!(post.sequence.get(), pre.sequence.get() + 1);
+    assert_eq!(post.authority, pre.authority);
+    post.sequence.get()
+}
+
+fn check_accumulator(before: &MockBank, after: &MockBank, state: &Pubkey, message: &MessageData) {
+    let accumulator = hashv(&[&logger_state(before, state).accumulator, message.hash().as_ref()]);
+    assert_eq!(logger_state(after, state).accumulator, accumulator.to_bytes());
+}
+
+// Uspesna instrukcija mora biti validna i ostaviti stanje kakvo opisuje
+fn check(before: &MockBank, after: &MockBank, instruction: &Instruction) {
+    let metas = &instruction.accounts;
+    let decoded = LoggerInstruction::unpack(&instruction.data).unwrap();
+
+    // Svaka instrukcija osim objave poruke trazi authority potpis; Initialize
+    // ga nosi kao drugi nalog, jer authority jos nije upisan u state
+    let authorized = |state: &Pubkey| {
+        let state = logger_state(before, state);
+        assert!(state.is_initialized());
+        assert!(
+            is_signer(instruction, &metas[0].pubkey),
+            "authority did not sign"
+        );
+        assert_eq!(
+            metas[0].pubkey, state.authority,
+            "signer is not the authority"
+        );
+    };
+
+    match decoded {
+        LoggerInstruction::Initialize { authority } => {
+            assert!(!logger_state(before, &metas[0].pubkey).is_initialized());
+            assert_eq!(metas[1].pubkey, authority, "signer is not the authority");
+            assert!(is_signer(instruction, &authority), "authority did not sign");
+            let state = logger_state(after, &metas[0].pubkey);
+            assert!(state.is_initialized());
+            assert_eq!(state.sequence.get(), 0);
+            assert_eq!(state.authority, authority);
+        }
+        LoggerInstruction::PostMessage {
+            from_pubkey,
+            to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        } => {
+            assert!(is_signer(instruction, &metas[4].pubkey), "emitter did not sign");
+            let sequence = check_sequence(before, after, &metas[0].pubkey);
+            let target = &metas[1].pubkey;
+            assert_eq!(
+                *target,
+                MessageData::find_address(&metas[0].pubkey, sequence, &LOGGER_PROGRAM_ID).0,
+                "message posted outside its PDA"
+            );
+            assert!(
+                before
+                    .get(target)
+                    .is_none_or(|account| account.owner != LOGGER_PROGRAM_ID),
+                "post overwrote an existing message"
+            );
+            let message = *MessageData::load(data(after, target)).unwrap();
+            let expected = MessageData::new(
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                sequence,
+                metas[2].pubkey,
+                CLOCK.slot,
+                CLOCK.epoch,
+                consistency_level,
+                metas[4].pubkey,
+                kind,
+            );
+            assert_eq!(message, expected);
+            check_accumulator(before, after, &metas[0].pubkey, &message);
+        }
+        LoggerInstruction::PostRingMessage {
+            from_pubkey,
+            to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        } => {
+            assert!(is_signer(instruction, &metas[2].pubkey), "emitter did not sign");
+            let sequence = check_sequence(before, after, &metas[0].pubkey);
+            let ring = data(after, &metas[1].pubkey);
+            let header = RingHeader::load(&ring[..RingHeader::LEN]).unwrap();
+            assert_eq!(
+                header.logger_state, metas[0].pubkey,
+                "posted into a ring of another state"
+            );
+            let message = RingHeader::read_message(ring, sequence)
+                .unwrap()
+                .expect("ring slot does not hold the posted message");
+            let expected = MessageData::new(
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                sequence,
+                Pubkey::default(),
+                CLOCK.slot,
+                CLOCK.epoch,
+                consistency_level,
+                metas[2].pubkey,
+                kind,
+            );
+            assert_eq!(message, expected);
+            check_accumulator(before, after, &metas[0].pubkey, &message);
+        }
+        LoggerInstruction::CloseMessage => {
+            authorized(&metas[1].pubkey);
+            let message = &metas[2].pubkey;
+            let stored = MessageData::load(data(before, message)).unwrap();
+            assert_eq!(
+                *message,
+                MessageData::find_address(&metas[1].pubkey, stored.sequence.get(), &LOGGER_PROGRAM_ID).0
+            );
+            assert_eq!(stored.payer, metas[3].pubkey);
+            let closed = after.get(message).unwrap();
+            assert_eq!(closed.lamports, 0);
+            assert_eq!(closed.owner, system_program::id());
+            assert!(closed.data.is_empty());
+        }
+        LoggerInstruction::SetAuthority { new_authority } => {
+            authorized(&metas[1].pubkey);
+            let pre = logger_state(before, &metas[1].pubkey);
+            let post = logger_state(after, &metas[1].pubkey);
+            assert_eq!(
+                post,
+                LoggerState {
+                    authority: new_authority,
+                    ..pre
+                }
+            );
+        }
+        LoggerInstruction::GetSequence => {
+            assert_eq!(before.accounts, after.accounts);
+        }
+        LoggerInstruction::InitializeRing => {
+            authorized(&metas[1].pubkey);
+            let ring = data(after, &metas[2].pubkey);
+            let header = RingHeader::load(&ring[..RingHeader::LEN]).unwrap();
+            assert_eq!(header.logger_state, metas[1].pubkey);
+            assert!(RingHeader::capacity(ring.len()) > 0);
+        }
+        LoggerInstruction::ResizeRing { capacity } => {
+            authorized(&metas[1].pubkey);
+            let pre = data(before, &metas[2].pubkey);
+            let ring = after.get(&metas[2].pubkey).unwrap();
+            assert_eq!(ring.data.len(), RingHeader::space(capacity));
+            assert!(ring.data.starts_with(pre), "resize lost ring contents");
+            assert!(ring.lamports >= Rent::default().minimum_balance(ring.data.len()));
+        }
+    }
+}
@@ -0,0 +1,417 @@
+use std::{cell::RefCell, collections::HashMap, sync::Once};
+
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader,
+    clock::Clock,
+    entrypoint::{
+        deserialize, ProcessInstruction, ProgramResult, BPF_ALIGN_OF_U128,
+        MAX_PERMITTED_DATA_INCREASE, SUCCESS,
+    },
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction::{SystemError, SystemInstruction},
+    system_program,
+};
+
+/// Clock every mock sysvar read returns.
+pub const CLOCK: Clock = Clock {
+    slot: 42,
+    epoch_start_timestamp: 1_700_000_000,
+    epoch: 1,
+    leader_schedule_epoch: 1,
+    unix_timestamp: 1_700_000_000,
+};
+
+const NON_DUP_MARKER: u8 = u8::MAX;
+
+thread_local! {
+    static PROGRAMS: RefCell<HashMap<Pubkey, ProcessInstruction>> = RefCell::new(HashMap::new());
+    // Program koji trenutno izvrsava, za proveru PDA potpisa u CPI
+    static CALLERS: RefCell<Vec<Pubkey>> = const { RefCell::new(Vec::new()) };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+impl MockAccount {
+    pub fn new(key: Pubkey, owner: Pubkey, lamports: u64, data: Vec<u8>) -> Self {
+        MockAccount {
+            key,
+            owner,
+            lamports,
+            data,
+            executable: false,
+        }
+    }
+
+    /// A rent-exempt account holding `data`.
+    pub fn with_data(key: Pubkey, owner: Pubkey, data: Vec<u8>) -> Self {
+        let lamports = Rent::default().minimum_balance(data.len());
+        MockAccount::new(key, owner, lamports, data)
+    }
+
+    pub fn program(key: Pubkey) -> Self {
+        MockAccount {
+            executable: true,
+            ..MockAccount::new(key, bpf_loader::id(), 1, Vec::new())
+        }
+    }
+
+    fn missing(key: Pubkey) -> Self {
+        MockAccount::new(key, system_program::id(), 0, Vec::new())
+    }
+}
+
+/// Accounts plus the programs that may run against them. Instructions go
+/// through the loader's input serialization, so `realloc` and `assign`
+/// behave as on chain, and CPI is dispatched to the registered processors.
+#[derive(Clone, Debug, Default)]
+pub struct MockBank {
+    pub accounts: Vec<MockAccount>,
+}
+
+impl MockBank {
+    pub fn new(programs: &[(Pubkey, ProcessInstruction)]) -> Self {
+        static STUBS: Once = Once::new();
+        STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(MockStubs));
+        });
+
+        PROGRAMS.with(|registry| {
+            let mut registry = registry.borrow_mut();
+            registry.clear();
+            registry.insert(spl_token::id(), spl_token::processor::Processor::process);
+            registry.extend(programs.iter().copied());
+        });
+
+        let mut bank = MockBank::default();
+        bank.insert(MockAccount::program(system_program::id()));
+        bank.insert(MockAccount::program(spl_token::id()));
+        for (program_id, _) in programs {
+            bank.insert(MockAccount::program(*program_id));
+        }
+        bank.insert(MockAccount::with_data(
+            solana_program::sysvar::rent::id(),
+            solana_program::sysvar::id(),
+            bincode::serialize(&Rent::default()).unwrap(),
+        ));
+        bank
+    }
+
+    pub fn insert(&mut self, account: MockAccount) {
+        match self.accounts.iter_mut().find(|a| a.key == account.key) {
+            Some(existing) => *existing = account,
+            None => self.accounts.push(account),
+        }
+    }
+
+    pub fn get(&self, key: &Pubkey) -> Option<&MockAccount> {
+        self.accounts.iter().find(|account| account.key == *key)
+    }
+
+    /// Runs `instruction` and keeps its writes only when both the program
+    /// and the runtime checks after it succeed.
+    pub fn process(&mut self, instruction: &Instruction) -> Result<(), MockError> {
+        let process = PROGRAMS
+            .with(|registry| registry.borrow().get(&instruction.program_id).copied())
+            .ok_or(ProgramError::IncorrectProgramId)?;
+
+        // Duplikati dele privilegije kao u runtime-u
+        let metas = &instruction.accounts;
+        let first: Vec<usize> = metas
+            .iter()
+            .map(|meta| metas.iter().position(|m| m.pubkey == meta.pubkey).unwrap())
+            .collect();
+        let mut input = Vec::new();
+        input.extend_from_slice(&(metas.len() as u64).to_le_bytes());
+        for (index, meta) in metas.iter().enumerate() {
+            if first[index] != index {
+                input.push(first[index] as u8);
+                input.extend_from_slice(&[0u8; 7]);
+                continue;
+            }
+            let account = self.account(&meta.pubkey);
+            let dups = metas.iter().filter(|m| m.pubkey == meta.pubkey);
+            let is_signer = dups.clone().any(|m| m.is_signer);
+            let is_writable = dups.clone().any(|m| m.is_writable);
+
+            input.extend_from_slice(&[
+                NON_DUP_MARKER,
+                is_signer as u8,
+                is_writable as u8,
+                account.executable as u8,
+            ]);
+            input.extend_from_slice(&[0u8; 4]);
+            input.extend_from_slice(account.key.as_ref());
+            input.extend_from_slice(account.owner.as_ref());
+            input.extend_from_slice(&account.lamports.to_le_bytes());
+            input.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            input.extend_from_slice(&account.data);
+            input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            input.resize(input.len().next_multiple_of(BPF_ALIGN_OF_U128), 0);
+            input.extend_from_slice(&0u64.to_le_bytes());
+        }
+        input.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        input.extend_from_slice(&instruction.data);
+        input.extend_from_slice(instruction.program_id.as_ref());
+
+        // u64 baferi su poravnati kao ulaz koji loader daje programu
+        let mut buffer = vec![0u64; input.len().div_ceil(8)];
+        let bytes = buffer.as_mut_ptr() as *mut u8;
+        unsafe { std::ptr::copy_nonoverlapping(input.as_ptr(), bytes, input.len()) };
+        let (_, infos, _) = unsafe { deserialize(bytes) };
+
+        call(process, &instruction.program_id, &infos, &instruction.data)?;
+        let accounts: Vec<(MockAccount, MockAccount)> = infos
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| first[*index] == *index)
+            .map(|(_, info)| {
+                let post = MockAccount {
+                    key: *info.key,
+                    owner: *info.owner,
+                    lamports: info.lamports(),
+                    data: info.data.borrow().to_vec(),
+                    executable: info.executable,
+                };
+                (self.account(info.key), post)
+            })
+            .collect();
+        drop(infos);
+
+        check_runtime_rules(&accounts, metas)?;
+        for (pre, post) in accounts {
+            if pre != post {
+                self.insert(post);
+            }
+        }
+        Ok(())
+    }
+
+    fn account(&self, key: &Pubkey) -> MockAccount {
+        self.get(key)
+            .cloned()
+            .unwrap_or_else(|| MockAccount::missing(*key))
+    }
+}
+
+/// Why [`MockBank::process`] rejected an instruction.
+#[derive(Debug)]
+pub enum MockError {
+    Program(ProgramError),
+    /// The program returned success but broke a rule the runtime enforces
+    /// after every instruction, so the transaction would fail on chain.
+    Runtime(String),
+}
+
+impl From<ProgramError> for MockError {
+    fn from(err: ProgramError) -> Self {
+        MockError::Program(err)
+    }
+}
+
+// Isto sto runtime proverava posle instrukcije: balans lamport-a, read-only
+// naloge i izmene naloga koje ne poseduje nijedan program iz instrukcije
+fn check_runtime_rules(
+    accounts: &[(MockAccount, MockAccount)],
+    metas: &[AccountMeta],
+) -> Result<(), MockError> {
+    let total = |post: bool| {
+        accounts
+            .iter()
+            .map(|(pre, after)| if post { after } else { pre }.lamports as u128)
+            .sum::<u128>()
+    };
+    if total(false) != total(true) {
+        return Err(MockError::Runtime("unbalanced lamports".into()));
+    }
+
+    let programs: Vec<Pubkey> =
+        PROGRAMS.with(|registry| registry.borrow().keys().copied().collect());
+    for (pre, post) in accounts {
+        if pre == post {
+            continue;
+        }
+        let metas = metas.iter().filter(|m| m.pubkey == post.key);
+        if pre.executable || !metas.clone().any(|m| m.is_writable) {
+            return Err(MockError::Runtime(format!(
+                "read-only {} modified",
+                post.key
+            )));
+        }
+
+        let fresh = pre.owner == system_program::id() && pre.data.is_empty();
+        if (pre.data != post.data || pre.owner != post.owner)
+            && !programs.contains(&pre.owner)
+            && !fresh
+        {
+            return Err(MockError::Runtime(format!(
+                "external {} modified",
+                post.key
+            )));
+        }
+        if post.lamports < pre.lamports
+            && !programs.contains(&pre.owner)
+            && !(fresh && metas.clone().any(|m| m.is_signer))
+        {
+            return Err(MockError::Runtime(format!("external {} debited", post.key)));
+        }
+    }
+    Ok(())
+}
+
+fn call(
+    process: ProcessInstruction,
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    CALLERS.with(|callers| callers.borrow_mut().push(*program_id));
+    let result = process(program_id, accounts, data);
+    CALLERS.with(|callers| callers.borrow_mut().pop());
+    result
+}
+
+struct MockStubs;
+
+impl SyscallStubs for MockStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, _fields: &[&[u8]]) {}
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CALLERS.with(|callers| *callers.borrow().last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        // Runtime trazi i nalog programa koji se poziva
+        if !account_infos
+            .iter()
+            .any(|info| *info.key == instruction.program_id)
+        {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        }
+
+        let mut accounts = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            if meta.is_signer && !info.is_signer && !signers.contains(&meta.pubkey) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            // Kao i na vrhu, duplikati u CPI dele privilegije
+            let dups = instruction
+                .accounts
+                .iter()
+                .filter(|m| m.pubkey == meta.pubkey);
+            accounts.push(AccountInfo {
+                is_signer: dups.clone().any(|m| m.is_signer),
+                is_writable: dups.clone().any(|m| m.is_writable),
+                ..info.clone()
+            });
+        }
+
+        if instruction.program_id == system_program::id() {
+            return process_system(&accounts, &instruction.data);
+        }
+        let process = PROGRAMS
+            .with(|registry| registry.borrow().get(&instruction.program_id).copied())
+            .ok_or(ProgramError::IncorrectProgramId)?;
+        call(
+            process,
+            &instruction.program_id,
+            &accounts,
+            &instruction.data,
+        )
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Clock) = CLOCK };
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        SUCCESS
+    }
+}
+
+// Samo ono sto programi u ovom repou pozivaju: CreateAccount i Transfer
+fn process_system(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let from = accounts.first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    let to = accounts.get(1).ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *from.owner != system_program::id() || !from.data_is_empty() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            if !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() != 0 || !to.data_is_empty() || *to.owner != system_program::id() {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            move_lamports(from, to, lamports)?;
+            to.realloc(space as usize, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Transfer { lamports } => move_lamports(from, to, lamports),
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn move_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_writable || !to.is_writable {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let remaining = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ))?;
+    let credited = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **from.lamports.borrow_mut() = remaining;
+    **to.lamports.borrow_mut() = credited;
+    Ok(())
+}
@@ -2,10 +2,12 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint,
     entrypoint::ProgramResult,
+    program_error::{PrintProgramError, ProgramError},
     pubkey::Pubkey,
 };
+use logger_contract::error::{LoggerError, LOGGER_ERROR_CODES};
 
-use crate::processor::EscrowProcessor;
+use crate::{error::EscrowError, processor::EscrowProcessor};
 
 // Standardni Solana entrypoint
 entrypoint!(process_instruction);
@@ -15,5 +17,15 @@ fn process_instruction(
     accounts: &[AccountInfo],
     instruction_data: &[u8],
 ) -> ProgramResult {
-    EscrowProcessor::process(program_id, accounts, instruction_data)
+    if let Err(error) = EscrowProcessor::process(program_id, accounts, instruction_data) {
+        // Greska iz logger CPI-ja prolazi kroz escrow nepromenjena
+        match error {
+            ProgramError::Custom(code) if LOGGER_ERROR_CODES.contains(&code) => {
+                error.print::<LoggerError>()
+            }
+            _ => error.print::<EscrowError>(),
+        }
+        return Err(error);
+    }
+    Ok(())
 }
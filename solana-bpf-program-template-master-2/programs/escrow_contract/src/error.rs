@@ -1,20 +1,115 @@
+use std::ops::RangeInclusive;
+
+use logger_contract::validation::AccountCheck;
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
 use thiserror::Error;
-use solana_program::program_error::ProgramError;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+
+/// Custom error codes owned by the escrow program. Codes never move; new
+/// variants take the next free number in the range.
+pub const ESCROW_ERROR_CODES: RangeInclusive<u32> = 1000..=1999;
 
-#[derive(Error, Debug, Copy, Clone)]
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
 pub enum EscrowError {
     #[error("Invalid Instruction")]
-    InvalidInstruction,
+    InvalidInstruction = 1000,
     #[error("Not Rent Exempt")]
-    NotRentExempt,
+    NotRentExempt = 1001,
     #[error("Escrow Account Already Initialized")]
-    AlreadyInitialized,
+    AlreadyInitialized = 1002,
     #[error("Mint Mismatch")]
-    MintMismatch,
+    MintMismatch = 1003,
     #[error("Amount Overflow")]
-    AmountOverflow,
+    AmountOverflow = 1004,
     #[error("Insufficient Amount")]
-    InsufficientAmount,
+    InsufficientAmount = 1005,
+    #[error("Incorrect Escrow Address")]
+    IncorrectEscrowAddress = 1006,
+    #[error("Incorrect Vault Address")]
+    IncorrectVaultAddress = 1007,
+    #[error("Unexpected Account Address")]
+    IncorrectAccountAddress = 1008,
+    #[error("Incorrect Account Owner")]
+    IncorrectAccountOwner = 1009,
+    #[error("Missing Required Signature")]
+    MissingSignature = 1010,
+    #[error("Account Not Writable")]
+    AccountNotWritable = 1011,
+    #[error("Account Not Executable")]
+    AccountNotExecutable = 1012,
+    #[error("Incorrect Config Address")]
+    IncorrectConfigAddress = 1013,
+    #[error("Signer Is Not The Config Admin")]
+    NotAdmin = 1014,
+    #[error("Incorrect Position Address")]
+    IncorrectPositionAddress = 1015,
+    #[error("Signer Is Neither The Position Owner Nor Its Delegate")]
+    NotPositionAuthority = 1016,
+    #[error("Delegate Expired")]
+    DelegateExpired = 1017,
+    #[error("Delegate Cap Exceeded")]
+    DelegateCapExceeded = 1018,
+    #[error("Invalid Multisig Threshold Or Signers")]
+    InvalidMultisig = 1019,
+    #[error("Not Enough Multisig Signatures")]
+    NotEnoughSigners = 1020,
+    #[error("Signer Is Not A Multisig Signer")]
+    NotMultisigSigner = 1021,
+    #[error("Instruction Does Not Match The Proposal")]
+    ProposalMismatch = 1022,
+    #[error("Large Withdrawal Needs Multisig Approval")]
+    LargeWithdrawalNotApproved = 1023,
+    #[error("Invalid Vesting Schedule")]
+    InvalidVestingSchedule = 1024,
+    #[error("Signer Is Not The Vesting Beneficiary")]
+    NotVestingBeneficiary = 1025,
+    #[error("Signer Is Not The Vesting Grantor")]
+    NotVestingGrantor = 1026,
+    #[error("Nothing Vested To Claim")]
+    NothingVested = 1027,
+    #[error("Vesting Is Not Revocable")]
+    NotRevocable = 1028,
+    #[error("Invalid Agreement Terms")]
+    InvalidAgreement = 1029,
+    #[error("Signer Is Not Allowed To Settle Or Dispute The Agreement")]
+    NotAgreementParty = 1030,
+    #[error("Agreement Has No Arbiter Or Signer Is Not The Arbiter")]
+    NotArbiter = 1031,
+    #[error("Agreement Is Disputed")]
+    AgreementDisputed = 1032,
+    #[error("Agreement Is Not Disputed")]
+    AgreementNotDisputed = 1033,
+    #[error("Agreement Deadline Not Reached")]
+    DeadlineNotReached = 1034,
+    #[error("Agreement Deadline Passed")]
+    DeadlinePassed = 1035,
+    #[error("Recipient Token Account Does Not Belong To The Agreement Party")]
+    RecipientMismatch = 1036,
+    #[error("Admin Is Not The Program Upgrade Authority")]
+    NotUpgradeAuthority = 1037,
+}
+
+impl EscrowError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::from_u32(code)
+    }
+}
+
+impl From<AccountCheck> for EscrowError {
+    fn from(check: AccountCheck) -> Self {
+        match check {
+            AccountCheck::Address => EscrowError::IncorrectAccountAddress,
+            AccountCheck::Owner => EscrowError::IncorrectAccountOwner,
+            AccountCheck::Signer => EscrowError::MissingSignature,
+            AccountCheck::Writable => EscrowError::AccountNotWritable,
+            AccountCheck::Executable => EscrowError::AccountNotExecutable,
+        }
+    }
 }
 
 impl From<EscrowError> for ProgramError {
@@ -22,3 +117,18 @@ impl From<EscrowError> for ProgramError {
         ProgramError::Custom(e as u32)
     }
 }
+
+impl<T> DecodeError<T> for EscrowError {
+    fn type_of() -> &'static str {
+        "EscrowError"
+    }
+}
+
+impl PrintProgramError for EscrowError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("EscrowError {}: {}", *self as u32, self);
+    }
+}
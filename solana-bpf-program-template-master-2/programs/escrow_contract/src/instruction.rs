@@ -1,7 +1,19 @@
-use solana_program::program_error::ProgramError;
-use crate::error::EscrowError::InvalidInstruction;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use crate::{
+    error::EscrowError::InvalidInstruction,
+    state::{Agreement, Batch, EscrowConfig, EscrowState, Position, Proposal, Vesting},
+};
 use std::convert::TryInto;
 
+pub use crate::state::MAX_BATCH_LEGS;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum EscrowInstruction {
     Deposit {
         amount: u64,
@@ -9,6 +21,103 @@ pub enum EscrowInstruction {
     Withdraw {
         amount: u64,
     },
+    // Accounts: [admin (s, w), config PDA (w), logger program, logger state, system program,
+    // escrow ProgramData] - admin mora biti upgrade authority programa
+    InitializeConfig,
+    // Accounts: [admin (s), config PDA (w), logger program, logger state,
+    // multisig signers (s)...] - signeri samo kada je admin multisig
+    SetLogger,
+    // Accounts: [owner (s), position PDA (w)]
+    // Pubkey::default() kao delegate ukida delegata
+    SetDelegate {
+        delegate: Pubkey,
+        cap: Option<u64>,
+        expires_at: Option<u64>,
+    },
+    // Accounts: [multisig (w), signers...]
+    InitializeMultisig {
+        threshold: u8,
+    },
+    // Accounts: [admin (s), config PDA (w), multisig signers (s)...]
+    SetAdmin {
+        new_admin: Pubkey,
+    },
+    // Accounts: [admin (s), config PDA (w), withdrawal multisig, admin multisig signers (s)...]
+    // threshold u64::MAX iskljucuje pravilo
+    SetWithdrawalPolicy {
+        threshold: u64,
+    },
+    // Accounts: [proposer (s, w), multisig, proposal PDA (w), system program]
+    Propose {
+        instruction_hash: [u8; 32],
+    },
+    // Accounts: [signer (s), multisig, proposal PDA (w)]
+    Approve,
+    // Accounts: [proposal PDA (w), proposer (w), multisig, accounts of the instruction...]
+    // Posle taga dolaze podaci predlozene instrukcije
+    Execute,
+    // Accounts: [grantor (s, w), grantor token (w), beneficiary, escrow PDA (w),
+    // vault PDA (w), vesting PDA (w), mint, token program, system program,
+    // rent sysvar, logger program, logger state (w), message PDA (w), config PDA]
+    CreateVesting {
+        amount: u64,
+        start: u64,
+        cliff: u64,
+        end: u64,
+        revocable: bool,
+    },
+    // Accounts: [beneficiary (s, w), recipient token (w), escrow PDA (w), vault PDA (w),
+    // vesting PDA (w), grantor (w), token program, logger program, logger state (w),
+    // message PDA (w), system program, config PDA], pa withdrawal multisig i signeri
+    // kada iznos prelazi threshold
+    ClaimVested,
+    // Accounts: [grantor (s, w), grantor token (w), escrow PDA (w), vault PDA (w),
+    // vesting PDA (w), token program, logger program, logger state (w),
+    // message PDA (w), system program, config PDA], pa withdrawal multisig i signeri
+    // kada iznos prelazi threshold
+    Revoke,
+    // Accounts: [payer (s, w), payer token (w), payee, escrow PDA (w), vault PDA (w),
+    // agreement PDA (w), mint, token program, system program, rent sysvar,
+    // logger program, logger state (w), message PDA (w), config PDA]
+    // Pubkey::default() kao arbiter znaci sporazum bez arbitra
+    OpenAgreement {
+        id: u64,
+        amount: u64,
+        deadline: u64,
+        arbiter: Pubkey,
+    },
+    // Accounts: [payer (s, w), payee token (w), escrow PDA (w), vault PDA (w),
+    // agreement PDA (w), payer (w), token program, logger program, logger state (w),
+    // message PDA (w), system program, config PDA], pa withdrawal multisig i signeri
+    // kada iznos prelazi threshold (isto za Resolve i Refund)
+    Release,
+    // Accounts: [payer or payee (s, w), agreement PDA (w), logger program,
+    // logger state (w), message PDA (w), system program, config PDA]
+    OpenDispute,
+    // Accounts: [arbiter (s, w), winner token (w), escrow PDA (w), vault PDA (w),
+    // agreement PDA (w), payer (w), token program, logger program, logger state (w),
+    // message PDA (w), system program, config PDA]
+    Resolve {
+        to_payee: bool,
+    },
+    // Accounts: [caller (s, w), payer token (w), escrow PDA (w), vault PDA (w),
+    // agreement PDA (w), payer (w), token program, logger program, logger state (w),
+    // message PDA (w), system program, config PDA]
+    // Posle roka (ili isteka roka za spor) sporazum moze da vrati bilo ko
+    Refund,
+    // Accounts: [user (s, w), token program, system program, rent sysvar, logger program,
+    // logger state (w), message PDA (w), batch PDA (w), config PDA], pa za svaki leg:
+    // [user token (w), mint, escrow PDA (w), vault PDA (w), position PDA (w)]
+    BatchDeposit {
+        legs: Vec<(Pubkey, u64)>,
+    },
+    // Accounts: [authority (s, w), token program, logger program, logger state (w),
+    // message PDA (w), batch PDA (w), system program, config PDA], pa za svaki leg:
+    // [recipient token (w), escrow PDA (w), vault PDA (w), position PDA (w)],
+    // pa withdrawal multisig i signeri kada zbir nekog minta prelazi threshold
+    BatchWithdraw {
+        legs: Vec<(Pubkey, u64)>,
+    },
 }
 
 impl EscrowInstruction {
@@ -23,10 +132,174 @@ impl EscrowInstruction {
                 let amount = Self::unpack_amount(rest)?;
                 EscrowInstruction::Withdraw { amount }
             },
+            2 => EscrowInstruction::InitializeConfig,
+            3 => EscrowInstruction::SetLogger,
+            4 => {
+                if rest.len() < 50 {
+                    return Err(InvalidInstruction.into());
+                }
+                EscrowInstruction::SetDelegate {
+                    delegate: Pubkey::new_from_array(rest[..32].try_into().unwrap()),
+                    cap: Self::unpack_option(&rest[32..41])?,
+                    expires_at: Self::unpack_option(&rest[41..50])?,
+                }
+            },
+            5 => {
+                let threshold = *rest.first().ok_or(InvalidInstruction)?;
+                EscrowInstruction::InitializeMultisig { threshold }
+            },
+            6 => EscrowInstruction::SetAdmin {
+                new_admin: Self::unpack_pubkey(rest)?,
+            },
+            7 => EscrowInstruction::SetWithdrawalPolicy {
+                threshold: Self::unpack_amount(rest)?,
+            },
+            8 => EscrowInstruction::Propose {
+                instruction_hash: Self::unpack_pubkey(rest)?.to_bytes(),
+            },
+            9 => EscrowInstruction::Approve,
+            10 => EscrowInstruction::Execute,
+            11 => {
+                if rest.len() < 33 {
+                    return Err(InvalidInstruction.into());
+                }
+                EscrowInstruction::CreateVesting {
+                    amount: Self::unpack_amount(&rest[..8])?,
+                    start: Self::unpack_amount(&rest[8..16])?,
+                    cliff: Self::unpack_amount(&rest[16..24])?,
+                    end: Self::unpack_amount(&rest[24..32])?,
+                    revocable: match rest[32] {
+                        0 => false,
+                        1 => true,
+                        _ => return Err(InvalidInstruction.into()),
+                    },
+                }
+            },
+            12 => EscrowInstruction::ClaimVested,
+            13 => EscrowInstruction::Revoke,
+            14 => {
+                if rest.len() < 56 {
+                    return Err(InvalidInstruction.into());
+                }
+                EscrowInstruction::OpenAgreement {
+                    id: Self::unpack_amount(&rest[..8])?,
+                    amount: Self::unpack_amount(&rest[8..16])?,
+                    deadline: Self::unpack_amount(&rest[16..24])?,
+                    arbiter: Self::unpack_pubkey(&rest[24..])?,
+                }
+            },
+            15 => EscrowInstruction::Release,
+            16 => EscrowInstruction::OpenDispute,
+            17 => EscrowInstruction::Resolve {
+                to_payee: match rest.first() {
+                    Some(0) => false,
+                    Some(1) => true,
+                    _ => return Err(InvalidInstruction.into()),
+                },
+            },
+            18 => EscrowInstruction::Refund,
+            19 => EscrowInstruction::BatchDeposit {
+                legs: Self::unpack_legs(rest)?,
+            },
+            20 => EscrowInstruction::BatchWithdraw {
+                legs: Self::unpack_legs(rest)?,
+            },
             _ => return Err(InvalidInstruction.into()),
         })
     }
 
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(9);
+        match self {
+            EscrowInstruction::Deposit { amount } => {
+                buf.push(0);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            EscrowInstruction::Withdraw { amount } => {
+                buf.push(1);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            EscrowInstruction::InitializeConfig => buf.push(2),
+            EscrowInstruction::SetLogger => buf.push(3),
+            EscrowInstruction::SetDelegate {
+                delegate,
+                cap,
+                expires_at,
+            } => {
+                buf.push(4);
+                buf.extend_from_slice(delegate.as_ref());
+                Self::pack_option(&mut buf, *cap);
+                Self::pack_option(&mut buf, *expires_at);
+            }
+            EscrowInstruction::InitializeMultisig { threshold } => {
+                buf.push(5);
+                buf.push(*threshold);
+            }
+            EscrowInstruction::SetAdmin { new_admin } => {
+                buf.push(6);
+                buf.extend_from_slice(new_admin.as_ref());
+            }
+            EscrowInstruction::SetWithdrawalPolicy { threshold } => {
+                buf.push(7);
+                buf.extend_from_slice(&threshold.to_le_bytes());
+            }
+            EscrowInstruction::Propose { instruction_hash } => {
+                buf.push(8);
+                buf.extend_from_slice(instruction_hash);
+            }
+            EscrowInstruction::Approve => buf.push(9),
+            EscrowInstruction::Execute => buf.push(10),
+            EscrowInstruction::CreateVesting {
+                amount,
+                start,
+                cliff,
+                end,
+                revocable,
+            } => {
+                buf.push(11);
+                for value in [amount, start, cliff, end] {
+                    buf.extend_from_slice(&value.to_le_bytes());
+                }
+                buf.push(*revocable as u8);
+            }
+            EscrowInstruction::ClaimVested => buf.push(12),
+            EscrowInstruction::Revoke => buf.push(13),
+            EscrowInstruction::OpenAgreement {
+                id,
+                amount,
+                deadline,
+                arbiter,
+            } => {
+                buf.push(14);
+                for value in [id, amount, deadline] {
+                    buf.extend_from_slice(&value.to_le_bytes());
+                }
+                buf.extend_from_slice(arbiter.as_ref());
+            }
+            EscrowInstruction::Release => buf.push(15),
+            EscrowInstruction::OpenDispute => buf.push(16),
+            EscrowInstruction::Resolve { to_payee } => {
+                buf.push(17);
+                buf.push(*to_payee as u8);
+            }
+            EscrowInstruction::Refund => buf.push(18),
+            EscrowInstruction::BatchDeposit { legs } => {
+                buf.push(19);
+                Self::pack_legs(&mut buf, legs);
+            }
+            EscrowInstruction::BatchWithdraw { legs } => {
+                buf.push(20);
+                Self::pack_legs(&mut buf, legs);
+            }
+        }
+        buf
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<Pubkey, ProgramError> {
+        let bytes = input.get(..32).ok_or(InvalidInstruction)?;
+        Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+    }
+
     fn unpack_amount(input: &[u8]) -> Result<u64, ProgramError> {
         if input.len() < 8 {
             return Err(InvalidInstruction.into());
@@ -34,4 +307,724 @@ impl EscrowInstruction {
         let amount = u64::from_le_bytes(input[..8].try_into().unwrap());
         Ok(amount)
     }
+
+    // Bajt 0/1 za None/Some, pa u64 koji je nula kada je None
+    fn unpack_option(input: &[u8]) -> Result<Option<u64>, ProgramError> {
+        let value = Self::unpack_amount(&input[1..])?;
+        match input[0] {
+            0 if value == 0 => Ok(None),
+            1 => Ok(Some(value)),
+            _ => Err(InvalidInstruction.into()),
+        }
+    }
+
+    fn pack_option(buf: &mut Vec<u8>, value: Option<u64>) {
+        buf.push(value.is_some() as u8);
+        buf.extend_from_slice(&value.unwrap_or(0).to_le_bytes());
+    }
+
+    // Bajt sa brojem legova (1..=MAX_BATCH_LEGS), pa mint i iznos za svaki
+    fn unpack_legs(input: &[u8]) -> Result<Vec<(Pubkey, u64)>, ProgramError> {
+        let (count, rest) = input.split_first().ok_or(InvalidInstruction)?;
+        let count = *count as usize;
+        if count == 0 || count > MAX_BATCH_LEGS || rest.len() < count * 40 {
+            return Err(InvalidInstruction.into());
+        }
+        rest.chunks_exact(40)
+            .take(count)
+            .map(|leg| Ok((Self::unpack_pubkey(leg)?, Self::unpack_amount(&leg[32..])?)))
+            .collect()
+    }
+
+    fn pack_legs(buf: &mut Vec<u8>, legs: &[(Pubkey, u64)]) {
+        buf.push(legs.len() as u8);
+        for (mint, amount) in legs {
+            buf.extend_from_slice(mint.as_ref());
+            buf.extend_from_slice(&amount.to_le_bytes());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    user: &Pubkey,
+    user_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*user, true),
+            AccountMeta::new(*user_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+            AccountMeta::new(Position::find_address(token_mint, user, program_id).0, false),
+        ],
+        data: EscrowInstruction::Deposit { amount }.pack(),
+    }
+}
+
+/// Withdraws from `owner`'s position into `recipient_token_account`;
+/// `authority` is the owner or its delegate.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    recipient_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new_readonly(vault, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new(*authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+            AccountMeta::new(Position::find_address(token_mint, owner, program_id).0, false),
+        ],
+        data: EscrowInstruction::Withdraw { amount }.pack(),
+    }
+}
+
+pub fn set_delegate(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    token_mint: &Pubkey,
+    delegate: &Pubkey,
+    cap: Option<u64>,
+    expires_at: Option<u64>,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(Position::find_address(token_mint, owner, program_id).0, false),
+        ],
+        data: EscrowInstruction::SetDelegate {
+            delegate: *delegate,
+            cap,
+            expires_at,
+        }
+        .pack(),
+    }
+}
+
+pub fn initialize_config(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    logger_program_id: &Pubkey,
+    logger_state: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(EscrowConfig::find_address(program_id).0, false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new_readonly(*logger_state, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::program_data_address(program_id), false),
+        ],
+        data: EscrowInstruction::InitializeConfig.pack(),
+    }
+}
+
+/// `signers` are the multisig members signing for `admin` when it is a
+/// multisig; leave it empty for a single key admin.
+pub fn set_logger(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    logger_program_id: &Pubkey,
+    logger_state: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, signers.is_empty()),
+        AccountMeta::new(EscrowConfig::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*logger_program_id, false),
+        AccountMeta::new_readonly(*logger_state, false),
+    ];
+    accounts.extend(signer_metas(signers));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::SetLogger.pack(),
+    }
+}
+
+pub fn set_admin(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    new_admin: &Pubkey,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, signers.is_empty()),
+        AccountMeta::new(EscrowConfig::find_address(program_id).0, false),
+    ];
+    accounts.extend(signer_metas(signers));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::SetAdmin {
+            new_admin: *new_admin,
+        }
+        .pack(),
+    }
+}
+
+/// Withdrawals above `threshold` need the approval of `multisig`;
+/// `u64::MAX` turns the rule off.
+pub fn set_withdrawal_policy(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    multisig: &Pubkey,
+    threshold: u64,
+    signers: &[&Pubkey],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(*admin, signers.is_empty()),
+        AccountMeta::new(EscrowConfig::find_address(program_id).0, false),
+        AccountMeta::new_readonly(*multisig, false),
+    ];
+    accounts.extend(signer_metas(signers));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::SetWithdrawalPolicy { threshold }.pack(),
+    }
+}
+
+/// Initializes `multisig`, an account of `Multisig::LEN` bytes already
+/// owned by the escrow program.
+pub fn initialize_multisig(
+    program_id: &Pubkey,
+    multisig: &Pubkey,
+    signers: &[&Pubkey],
+    threshold: u8,
+) -> Instruction {
+    let mut accounts = vec![AccountMeta::new(*multisig, false)];
+    accounts.extend(signers.iter().map(|signer| AccountMeta::new_readonly(**signer, false)));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::InitializeMultisig { threshold }.pack(),
+    }
+}
+
+/// Appends `multisig` and its `signers` to a withdraw, batch withdraw,
+/// vesting claim or revoke, or agreement settlement above the large
+/// withdrawal threshold.
+pub fn with_multisig(mut instruction: Instruction, multisig: &Pubkey, signers: &[&Pubkey]) -> Instruction {
+    instruction.accounts.push(AccountMeta::new_readonly(*multisig, false));
+    instruction.accounts.extend(signer_metas(signers));
+    instruction
+}
+
+pub fn proposal_address(program_id: &Pubkey, multisig: &Pubkey, instruction: &Instruction) -> Pubkey {
+    let instruction_hash = Proposal::instruction_hash(
+        &instruction.data,
+        instruction.accounts.iter().map(|meta| &meta.pubkey),
+    );
+    Proposal::find_address(multisig, &instruction_hash, program_id).0
+}
+
+/// Proposes `instruction` for approval by `multisig`; the proposer's
+/// approval is counted right away.
+pub fn propose(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    multisig: &Pubkey,
+    instruction: &Instruction,
+) -> Instruction {
+    let instruction_hash = Proposal::instruction_hash(
+        &instruction.data,
+        instruction.accounts.iter().map(|meta| &meta.pubkey),
+    );
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*proposer, true),
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(Proposal::find_address(multisig, &instruction_hash, program_id).0, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: EscrowInstruction::Propose { instruction_hash }.pack(),
+    }
+}
+
+pub fn approve(program_id: &Pubkey, signer: &Pubkey, multisig: &Pubkey, proposal: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*signer, true),
+            AccountMeta::new_readonly(*multisig, false),
+            AccountMeta::new(*proposal, false),
+        ],
+        data: EscrowInstruction::Approve.pack(),
+    }
+}
+
+/// Runs the approved `instruction` in place of the multisig signatures and
+/// returns the proposal rent to `proposer`.
+pub fn execute(
+    program_id: &Pubkey,
+    proposer: &Pubkey,
+    multisig: &Pubkey,
+    instruction: &Instruction,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(proposal_address(program_id, multisig, instruction), false),
+        AccountMeta::new(*proposer, false),
+        AccountMeta::new_readonly(*multisig, false),
+    ];
+    // Multisig ne potpisuje, njegovo odobrenje je predlog
+    accounts.extend(instruction.accounts.iter().map(|meta| AccountMeta {
+        is_signer: meta.is_signer && meta.pubkey != *multisig,
+        ..meta.clone()
+    }));
+    let mut data = EscrowInstruction::Execute.pack();
+    data.extend_from_slice(&instruction.data);
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Moves `amount` from `grantor_token_account` into the escrow vault as a
+/// grant to `beneficiary` that unlocks linearly from `start` to `end`, with
+/// nothing claimable before `cliff`.
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    grantor: &Pubkey,
+    grantor_token_account: &Pubkey,
+    beneficiary: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    amount: u64,
+    start: u64,
+    cliff: u64,
+    end: u64,
+    revocable: bool,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*grantor, true),
+            AccountMeta::new(*grantor_token_account, false),
+            AccountMeta::new_readonly(*beneficiary, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Vesting::find_address(token_mint, grantor, beneficiary, program_id).0, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: EscrowInstruction::CreateVesting {
+            amount,
+            start,
+            cliff,
+            end,
+            revocable,
+        }
+        .pack(),
+    }
+}
+
+/// Pays everything unlocked so far of `grantor`'s grant into
+/// `recipient_token_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn claim_vested(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    beneficiary: &Pubkey,
+    recipient_token_account: &Pubkey,
+    grantor: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*beneficiary, true),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Vesting::find_address(token_mint, grantor, beneficiary, program_id).0, false),
+            AccountMeta::new(*grantor, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: EscrowInstruction::ClaimVested.pack(),
+    }
+}
+
+/// Returns the unvested part of a revocable grant to
+/// `grantor_token_account`; what has vested stays claimable.
+#[allow(clippy::too_many_arguments)]
+pub fn revoke(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    grantor: &Pubkey,
+    grantor_token_account: &Pubkey,
+    beneficiary: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*grantor, true),
+            AccountMeta::new(*grantor_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Vesting::find_address(token_mint, grantor, beneficiary, program_id).0, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: EscrowInstruction::Revoke.pack(),
+    }
+}
+
+/// Moves `amount` from `payer_token_account` into the escrow vault for
+/// `payee` under agreement `id`. With an `arbiter` either side may dispute
+/// before `deadline`; `None` leaves no dispute path.
+#[allow(clippy::too_many_arguments)]
+pub fn open_agreement(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    payer: &Pubkey,
+    payer_token_account: &Pubkey,
+    payee: &Pubkey,
+    token_mint: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    id: u64,
+    amount: u64,
+    deadline: u64,
+    arbiter: Option<Pubkey>,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*payer_token_account, false),
+            AccountMeta::new_readonly(*payee, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Agreement::find_address(payer, id, program_id).0, false),
+            AccountMeta::new_readonly(*token_mint, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(sysvar::rent::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: EscrowInstruction::OpenAgreement {
+            id,
+            amount,
+            deadline,
+            arbiter: arbiter.unwrap_or_default(),
+        }
+        .pack(),
+    }
+}
+
+/// Payer's approval: pays agreement `id` out to `payee_token_account`.
+#[allow(clippy::too_many_arguments)]
+pub fn release(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    payer: &Pubkey,
+    payee_token_account: &Pubkey,
+    token_mint: &Pubkey,
+    id: u64,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+) -> Instruction {
+    settle_agreement(
+        program_id,
+        logger_program_id,
+        payer,
+        payee_token_account,
+        payer,
+        token_mint,
+        id,
+        logger_state,
+        message,
+        EscrowInstruction::Release,
+    )
+}
+
+/// Hands agreement `id` of `payer` to its arbiter; `party` is the payer or
+/// the payee.
+pub fn open_dispute(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    party: &Pubkey,
+    payer: &Pubkey,
+    id: u64,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*party, true),
+            AccountMeta::new(Agreement::find_address(payer, id, program_id).0, false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: EscrowInstruction::OpenDispute.pack(),
+    }
+}
+
+/// Arbiter's ruling on a disputed agreement: pays the payee when `to_payee`,
+/// refunds the payer otherwise. `winner_token_account` belongs to that side.
+#[allow(clippy::too_many_arguments)]
+pub fn resolve(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    arbiter: &Pubkey,
+    winner_token_account: &Pubkey,
+    payer: &Pubkey,
+    token_mint: &Pubkey,
+    id: u64,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    to_payee: bool,
+) -> Instruction {
+    settle_agreement(
+        program_id,
+        logger_program_id,
+        arbiter,
+        winner_token_account,
+        payer,
+        token_mint,
+        id,
+        logger_state,
+        message,
+        EscrowInstruction::Resolve { to_payee },
+    )
+}
+
+/// Refunds an agreement to `payer_token_account` once its deadline, or its
+/// dispute deadline when disputed, has passed; `caller` can be anyone.
+#[allow(clippy::too_many_arguments)]
+pub fn refund(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    caller: &Pubkey,
+    payer_token_account: &Pubkey,
+    payer: &Pubkey,
+    token_mint: &Pubkey,
+    id: u64,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+) -> Instruction {
+    settle_agreement(
+        program_id,
+        logger_program_id,
+        caller,
+        payer_token_account,
+        payer,
+        token_mint,
+        id,
+        logger_state,
+        message,
+        EscrowInstruction::Refund,
+    )
+}
+
+// Release, Resolve i Refund dele isti raspored naloga
+#[allow(clippy::too_many_arguments)]
+fn settle_agreement(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    authority: &Pubkey,
+    recipient_token_account: &Pubkey,
+    payer: &Pubkey,
+    token_mint: &Pubkey,
+    id: u64,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    instruction: EscrowInstruction,
+) -> Instruction {
+    let (escrow, _) = EscrowState::find_address(token_mint, program_id);
+    let (vault, _) = EscrowState::find_vault_address(token_mint, program_id);
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*authority, true),
+            AccountMeta::new(*recipient_token_account, false),
+            AccountMeta::new(escrow, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new(Agreement::find_address(payer, id, program_id).0, false),
+            AccountMeta::new(*payer, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(*logger_program_id, false),
+            AccountMeta::new(*logger_state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+        ],
+        data: instruction.pack(),
+    }
+}
+
+/// One mint of a batch: the user's token account (the source of a deposit,
+/// the destination of a withdrawal) and the amount.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BatchLeg {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+/// Deposits every leg from `user` in one instruction. Each leg is credited to
+/// the user's position for its mint; the batch logs one message to `message`
+/// and records its legs in the batch PDA of that message.
+pub fn batch_deposit(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    user: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    legs: &[BatchLeg],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*user, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(sysvar::rent::id(), false),
+        AccountMeta::new_readonly(*logger_program_id, false),
+        AccountMeta::new(*logger_state, false),
+        AccountMeta::new(*message, false),
+        AccountMeta::new(Batch::find_address(message, program_id).0, false),
+        AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+    ];
+    for leg in legs {
+        accounts.extend([
+            AccountMeta::new(leg.token_account, false),
+            AccountMeta::new_readonly(leg.mint, false),
+            AccountMeta::new(EscrowState::find_address(&leg.mint, program_id).0, false),
+            AccountMeta::new(EscrowState::find_vault_address(&leg.mint, program_id).0, false),
+            AccountMeta::new(Position::find_address(&leg.mint, user, program_id).0, false),
+        ]);
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::BatchDeposit {
+            legs: legs.iter().map(|leg| (leg.mint, leg.amount)).collect(),
+        }
+        .pack(),
+    }
+}
+
+/// Withdraws every leg from the positions of `owner`; `authority` is the
+/// owner or its delegate on each of them.
+pub fn batch_withdraw(
+    program_id: &Pubkey,
+    logger_program_id: &Pubkey,
+    authority: &Pubkey,
+    owner: &Pubkey,
+    logger_state: &Pubkey,
+    message: &Pubkey,
+    legs: &[BatchLeg],
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*authority, true),
+        AccountMeta::new_readonly(spl_token::id(), false),
+        AccountMeta::new_readonly(*logger_program_id, false),
+        AccountMeta::new(*logger_state, false),
+        AccountMeta::new(*message, false),
+        AccountMeta::new(Batch::find_address(message, program_id).0, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(EscrowConfig::find_address(program_id).0, false),
+    ];
+    for leg in legs {
+        accounts.extend([
+            AccountMeta::new(leg.token_account, false),
+            AccountMeta::new(EscrowState::find_address(&leg.mint, program_id).0, false),
+            AccountMeta::new(EscrowState::find_vault_address(&leg.mint, program_id).0, false),
+            AccountMeta::new(Position::find_address(&leg.mint, owner, program_id).0, false),
+        ]);
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: EscrowInstruction::BatchWithdraw {
+            legs: legs.iter().map(|leg| (leg.mint, leg.amount)).collect(),
+        }
+        .pack(),
+    }
+}
+
+fn signer_metas<'a>(signers: &'a [&Pubkey]) -> impl Iterator<Item = AccountMeta> + 'a {
+    signers.iter().map(|signer| AccountMeta::new_readonly(**signer, true))
 }
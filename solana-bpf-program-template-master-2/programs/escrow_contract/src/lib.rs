@@ -3,5 +3,7 @@ pub mod instruction;
 pub mod processor;
 pub mod state;
 
+solana_program::declare_id!("6rCwx3QNv8sBL2iiHwrDq7GvEj4wWZTEJY8VN1n6682R");
+
 #[cfg(not(feature = "no-entrypoint"))]
 pub mod entrypoint;
\ No newline at end of file
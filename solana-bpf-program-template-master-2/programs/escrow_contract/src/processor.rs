@@ -1,96 +1,1769 @@
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
     entrypoint::ProgramResult,
-    instruction::Instruction,
     msg,
-    instruction::AccountMeta,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     sysvar::clock::Clock,
     program_pack::Pack,
+    program_utils::limited_deserialize,
     pubkey::Pubkey,
     system_instruction,
-    sysvar::{rent::Rent, Sysvar},
+    system_program,
+    sysvar::{self, rent::Rent, Sysvar},
 };
 use spl_token::state::Account as TokenAccount;
-use crate::{error::EscrowError, instruction::EscrowInstruction, state::EscrowState};
+use bridge_events::{DelegateEvent, DepositEvent, Event, LoggerSetEvent, WithdrawEvent};
+use logger_contract::{
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    state::{ConsistencyLevel, LoggerState, MessageKind},
+    validation::{check_accounts, AccountSpec},
+};
+use crate::{
+    error::EscrowError,
+    instruction::EscrowInstruction,
+    state::{Agreement, Batch, BatchTransfer, EscrowConfig, DISPUTE_WINDOW, EscrowState, Multisig, Position, Proposal, Vesting},
+};
+
+
+pub struct EscrowProcessor;
+impl EscrowProcessor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = EscrowInstruction::unpack(instruction_data)?;
+        if instruction == EscrowInstruction::Execute {
+            msg!("Instruction: Execute");
+            return Self::process_execute(accounts, program_id, &instruction_data[1..]);
+        }
+        Self::dispatch(program_id, accounts, instruction, None)
+    }
+
+    // approved_by je multisig cije je odobrenje vec provereno u Execute
+    fn dispatch(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction: EscrowInstruction,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        match instruction {
+            EscrowInstruction::Deposit { amount } => {
+                msg!("Instruction: Deposit {}", amount);
+                Self::process_deposit(accounts, amount, program_id)
+            }
+            EscrowInstruction::Withdraw { amount } => {
+                msg!("Instruction: Withdraw {}", amount);
+                Self::process_withdraw(accounts, amount, program_id, approved_by)
+            }
+            EscrowInstruction::InitializeConfig => {
+                msg!("Instruction: InitializeConfig");
+                Self::process_initialize_config(accounts, program_id)
+            }
+            EscrowInstruction::SetLogger => {
+                msg!("Instruction: SetLogger");
+                Self::process_set_logger(accounts, program_id, approved_by)
+            }
+            EscrowInstruction::SetDelegate {
+                delegate,
+                cap,
+                expires_at,
+            } => {
+                msg!("Instruction: SetDelegate");
+                Self::process_set_delegate(accounts, program_id, delegate, cap, expires_at)
+            }
+            EscrowInstruction::InitializeMultisig { threshold } => {
+                msg!("Instruction: InitializeMultisig");
+                Self::process_initialize_multisig(accounts, program_id, threshold)
+            }
+            EscrowInstruction::SetAdmin { new_admin } => {
+                msg!("Instruction: SetAdmin");
+                Self::process_set_admin(accounts, program_id, new_admin, approved_by)
+            }
+            EscrowInstruction::SetWithdrawalPolicy { threshold } => {
+                msg!("Instruction: SetWithdrawalPolicy");
+                Self::process_set_withdrawal_policy(accounts, program_id, threshold, approved_by)
+            }
+            EscrowInstruction::Propose { instruction_hash } => {
+                msg!("Instruction: Propose");
+                Self::process_propose(accounts, program_id, instruction_hash)
+            }
+            EscrowInstruction::Approve => {
+                msg!("Instruction: Approve");
+                Self::process_approve(accounts, program_id)
+            }
+            EscrowInstruction::CreateVesting {
+                amount,
+                start,
+                cliff,
+                end,
+                revocable,
+            } => {
+                msg!("Instruction: CreateVesting {}", amount);
+                Self::process_create_vesting(accounts, program_id, amount, start, cliff, end, revocable)
+            }
+            EscrowInstruction::ClaimVested => {
+                msg!("Instruction: ClaimVested");
+                Self::process_claim_vested(accounts, program_id, approved_by)
+            }
+            EscrowInstruction::Revoke => {
+                msg!("Instruction: Revoke");
+                Self::process_revoke(accounts, program_id, approved_by)
+            }
+            EscrowInstruction::OpenAgreement {
+                id,
+                amount,
+                deadline,
+                arbiter,
+            } => {
+                msg!("Instruction: OpenAgreement {}", amount);
+                Self::process_open_agreement(accounts, program_id, id, amount, deadline, arbiter)
+            }
+            EscrowInstruction::OpenDispute => {
+                msg!("Instruction: OpenDispute");
+                Self::process_open_dispute(accounts, program_id)
+            }
+            EscrowInstruction::Release | EscrowInstruction::Resolve { .. } | EscrowInstruction::Refund => {
+                msg!("Instruction: {:?}", instruction);
+                Self::process_settle_agreement(accounts, program_id, instruction, approved_by)
+            }
+            EscrowInstruction::BatchDeposit { legs } => {
+                msg!("Instruction: BatchDeposit {}", legs.len());
+                Self::process_batch_deposit(accounts, program_id, &legs)
+            }
+            EscrowInstruction::BatchWithdraw { legs } => {
+                msg!("Instruction: BatchWithdraw {}", legs.len());
+                Self::process_batch_withdraw(accounts, program_id, &legs, approved_by)
+            }
+            // Execute ne moze da izvrsi drugi Execute
+            EscrowInstruction::Execute => Err(EscrowError::InvalidInstruction.into()),
+        }
+    }
+    fn process_deposit(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let user_signer = next_account_info(acc_iter)?;
+        let user_token_acc_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let rent_sysvar_info = next_account_info(acc_iter)?;
+
+        // Logger
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let payer_account_info = next_account_info(acc_iter)?;
+        let logger_system_program_info = next_account_info(acc_iter)?;
+        let mint_acc_info = next_account_info(acc_iter)?; 
+        let config_acc_info = next_account_info(acc_iter)?;
+        let position_acc_info = next_account_info(acc_iter)?; // PDA ["position", mint, user]
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (user_signer, AccountSpec::new("user").signer().writable()),
+            (user_token_acc_info, AccountSpec::new("user token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").writable()),
+            (vault_acc_info, AccountSpec::new("vault").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (rent_sysvar_info, AccountSpec::new("rent sysvar").address(sysvar::rent::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (payer_account_info, AccountSpec::new("payer").signer().writable()),
+            (logger_system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (mint_acc_info, AccountSpec::new("mint").owner(spl_token::id())),
+            (position_acc_info, AccountSpec::new("position").writable()),
+        ])?;
+
+        let total_deposited = Self::deposit_leg(
+            user_signer,
+            user_token_acc_info,
+            escrow_data_acc_info,
+            vault_acc_info,
+            mint_acc_info,
+            position_acc_info,
+            system_program_info,
+            token_program_info,
+            rent_sysvar_info,
+            amount,
+            program_id,
+        )?;
+        let token_mint = *mint_acc_info.key;
+
+        let clock = Clock::get()?;
+        DepositEvent {
+            user: *user_signer.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            payer_account_info,
+            logger_system_program_info,
+            config_acc_info,
+            program_id,
+            user_signer.key,
+            vault_acc_info.key,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Transfer,
+        )
+    }
+
+    fn process_withdraw(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        msg!("Starting process_withdraw with amount: {}", amount);
+        
+        let acc_iter = &mut accounts.iter();
+    
+        let authority_info = next_account_info(acc_iter)?; // vlasnik pozicije ili delegat
+        let recipient_token_acc_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let vault_authority_info = next_account_info(acc_iter)?; // PDA ["vault", mint]
+        let message_pda_info = next_account_info(acc_iter)?;
+        let payer_account_info = next_account_info(acc_iter)?;
+        let logger_system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+        let position_acc_info = next_account_info(acc_iter)?; // PDA ["position", mint, owner]
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (authority_info, AccountSpec::new("authority").signer()),
+            (recipient_token_acc_info, AccountSpec::new("recipient token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").owner(*program_id).writable()),
+            (vault_acc_info, AccountSpec::new("vault").owner(spl_token::id()).writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (payer_account_info, AccountSpec::new("payer").signer().writable()),
+            (logger_system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (position_acc_info, AccountSpec::new("position").owner(*program_id).writable()),
+        ])?;
+
+        // Posle pozicije: withdrawal multisig i njegovi signeri
+        Self::check_withdrawal_multisig(config.requires_multisig(amount), acc_iter.as_slice(), program_id, approved_by)?;
+    
+        let clock = Clock::get()?;
+        let (token_mint, total_deposited, owner, recipient_owner) = Self::withdraw_leg(
+            authority_info,
+            recipient_token_acc_info,
+            escrow_data_acc_info,
+            vault_acc_info,
+            vault_authority_info,
+            position_acc_info,
+            token_program_info,
+            amount,
+            &clock,
+            program_id,
+        )?;
+
+        WithdrawEvent {
+            user: owner,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            destination: *recipient_token_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            payer_account_info,
+            logger_system_program_info,
+            config_acc_info,
+            program_id,
+            vault_acc_info.key,
+            &recipient_owner,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Transfer,
+        )?;
+
+        msg!("Withdraw completed.");
+        Ok(())
+    }
+
+    fn process_initialize_config(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let admin_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let program_data_info = next_account_info(acc_iter)?;
+
+        check_accounts::<EscrowError>(&[
+            (admin_info, AccountSpec::new("admin").signer().writable()),
+            (config_acc_info, AccountSpec::new("config").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+        Self::check_upgrade_authority(program_id, program_data_info, admin_info.key)?;
+        Self::check_logger(logger_program_info, logger_state_acc_info)?;
+
+        let (expected_config_pda, config_bump) = EscrowConfig::find_address(program_id);
+        if expected_config_pda != *config_acc_info.key {
+            return Err(EscrowError::IncorrectConfigAddress.into());
+        }
+        if config_acc_info.lamports() != 0 {
+            return Err(EscrowError::AlreadyInitialized.into());
+        }
+
+        let space = EscrowConfig::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                admin_info.key,
+                config_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                admin_info.clone(),
+                config_acc_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[b"config", &[config_bump]]],
+        )?;
+
+        let mut config_data = config_acc_info.data.borrow_mut();
+        *EscrowConfig::load_mut_unchecked(&mut config_data)? = EscrowConfig::new(
+            *admin_info.key,
+            *logger_program_info.key,
+            *logger_state_acc_info.key,
+        );
+
+        msg!("Config initialized, logger {} state {}", logger_program_info.key, logger_state_acc_info.key);
+        Ok(())
+    }
+
+    fn process_set_logger(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let admin_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+
+        Self::check_admin(admin_info, config_acc_info, acc_iter.as_slice(), program_id, approved_by)?;
+        Self::check_logger(logger_program_info, logger_state_acc_info)?;
+
+        let mut config_data = config_acc_info.data.borrow_mut();
+        let config = EscrowConfig::load_mut(&mut config_data)?;
+        // Indekseri po ovom eventu znaju koje ranije state-ove escrow priznaje
+        LoggerSetEvent {
+            previous_program: config.logger_program,
+            previous_state: config.logger_state,
+            logger_program: *logger_program_info.key,
+            logger_state: *logger_state_acc_info.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+        config.logger_program = *logger_program_info.key;
+        config.logger_state = *logger_state_acc_info.key;
+
+        msg!("Logger set to {} state {}", logger_program_info.key, logger_state_acc_info.key);
+        Ok(())
+    }
+
+    fn process_set_delegate(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        delegate: Pubkey,
+        cap: Option<u64>,
+        expires_at: Option<u64>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let owner_info = next_account_info(acc_iter)?;
+        let position_acc_info = next_account_info(acc_iter)?; // PDA ["position", mint, owner]
+
+        check_accounts::<EscrowError>(&[
+            (owner_info, AccountSpec::new("owner").signer()),
+            (position_acc_info, AccountSpec::new("position").owner(*program_id).writable()),
+        ])?;
+
+        let mut position_data = position_acc_info.data.borrow_mut();
+        let position = Position::load_mut(&mut position_data)?;
+        if position.owner != *owner_info.key {
+            return Err(EscrowError::NotPositionAuthority.into());
+        }
+        position.set_delegate(delegate, cap, expires_at);
+
+        DelegateEvent {
+            owner: position.owner,
+            mint: position.token_mint,
+            position: *position_acc_info.key,
+            delegate,
+            cap,
+            expires_at,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+
+        msg!("Delegate set to {}", delegate);
+        Ok(())
+    }
+
+    fn process_set_admin(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        new_admin: Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let admin_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        Self::check_admin(admin_info, config_acc_info, acc_iter.as_slice(), program_id, approved_by)?;
+
+        let mut config_data = config_acc_info.data.borrow_mut();
+        EscrowConfig::load_mut(&mut config_data)?.admin = new_admin;
+
+        msg!("Admin set to {}", new_admin);
+        Ok(())
+    }
+
+    fn process_set_withdrawal_policy(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        threshold: u64,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let admin_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+        let multisig_info = next_account_info(acc_iter)?;
+
+        Self::check_admin(admin_info, config_acc_info, acc_iter.as_slice(), program_id, approved_by)?;
+        AccountSpec::new("withdrawal multisig").owner(*program_id).check(multisig_info).map_err(EscrowError::from)?;
+        Multisig::load(&multisig_info.data.borrow())?;
+
+        let mut config_data = config_acc_info.data.borrow_mut();
+        let config = EscrowConfig::load_mut(&mut config_data)?;
+        config.withdrawal_multisig = *multisig_info.key;
+        config.large_withdrawal_threshold = threshold.into();
+
+        msg!("Withdrawals above {} need multisig {}", threshold, multisig_info.key);
+        Ok(())
+    }
+
+    fn process_initialize_multisig(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        threshold: u8,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let multisig_info = next_account_info(acc_iter)?;
+        AccountSpec::new("multisig").owner(*program_id).writable().check(multisig_info).map_err(EscrowError::from)?;
+        if !Rent::get()?.is_exempt(multisig_info.lamports(), multisig_info.data_len()) {
+            return Err(EscrowError::NotRentExempt.into());
+        }
+
+        let signers: Vec<Pubkey> = acc_iter.map(|signer_info| *signer_info.key).collect();
+        let mut multisig_data = multisig_info.data.borrow_mut();
+        let multisig = Multisig::load_mut_unchecked(&mut multisig_data)?;
+        if multisig.discriminator != [0u8; 8] {
+            return Err(EscrowError::AlreadyInitialized.into());
+        }
+        *multisig = Multisig::new(threshold, &signers)?;
+
+        msg!("Multisig {} of {} initialized", threshold, signers.len());
+        Ok(())
+    }
+
+    fn process_propose(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        instruction_hash: [u8; 32],
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let proposer_info = next_account_info(acc_iter)?;
+        let multisig_info = next_account_info(acc_iter)?;
+        let proposal_acc_info = next_account_info(acc_iter)?; // PDA ["proposal", multisig, hash]
+        let system_program_info = next_account_info(acc_iter)?;
+
+        check_accounts::<EscrowError>(&[
+            (proposer_info, AccountSpec::new("proposer").signer().writable()),
+            (multisig_info, AccountSpec::new("multisig").owner(*program_id)),
+            (proposal_acc_info, AccountSpec::new("proposal").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+        let signer_index = Multisig::load(&multisig_info.data.borrow())?
+            .signer_index(proposer_info.key)
+            .ok_or(EscrowError::NotMultisigSigner)?;
+
+        let (expected_proposal_pda, proposal_bump) =
+            Proposal::find_address(multisig_info.key, &instruction_hash, program_id);
+        if expected_proposal_pda != *proposal_acc_info.key {
+            return Err(EscrowError::IncorrectAccountAddress.into());
+        }
+        if proposal_acc_info.lamports() != 0 {
+            return Err(EscrowError::AlreadyInitialized.into());
+        }
+
+        let space = Proposal::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                proposer_info.key,
+                proposal_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                proposer_info.clone(),
+                proposal_acc_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[b"proposal", multisig_info.key.as_ref(), &instruction_hash, &[proposal_bump]]],
+        )?;
+
+        let mut proposal_data = proposal_acc_info.data.borrow_mut();
+        let proposal = Proposal::load_mut_unchecked(&mut proposal_data)?;
+        *proposal = Proposal::new(*multisig_info.key, instruction_hash, *proposer_info.key);
+        proposal.approve(signer_index);
+
+        msg!("Proposal {} created", proposal_acc_info.key);
+        Ok(())
+    }
+
+    fn process_approve(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let signer_info = next_account_info(acc_iter)?;
+        let multisig_info = next_account_info(acc_iter)?;
+        let proposal_acc_info = next_account_info(acc_iter)?;
+
+        check_accounts::<EscrowError>(&[
+            (signer_info, AccountSpec::new("signer").signer()),
+            (multisig_info, AccountSpec::new("multisig").owner(*program_id)),
+            (proposal_acc_info, AccountSpec::new("proposal").owner(*program_id).writable()),
+        ])?;
+        let signer_index = Multisig::load(&multisig_info.data.borrow())?
+            .signer_index(signer_info.key)
+            .ok_or(EscrowError::NotMultisigSigner)?;
+
+        let mut proposal_data = proposal_acc_info.data.borrow_mut();
+        let proposal = Proposal::load_mut(&mut proposal_data)?;
+        if proposal.multisig != *multisig_info.key {
+            return Err(EscrowError::ProposalMismatch.into());
+        }
+        proposal.approve(signer_index);
+
+        msg!("Proposal approved, {} approvals", proposal.approval_count());
+        Ok(())
+    }
+
+    // Predlog se zatvara pre izvrsavanja; ako instrukcija padne, vraca se i zatvaranje
+    fn process_execute(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let proposal_acc_info = next_account_info(acc_iter)?;
+        let proposer_info = next_account_info(acc_iter)?;
+        let multisig_info = next_account_info(acc_iter)?;
+        let instruction_accounts = acc_iter.as_slice();
+
+        check_accounts::<EscrowError>(&[
+            (proposal_acc_info, AccountSpec::new("proposal").owner(*program_id).writable()),
+            (proposer_info, AccountSpec::new("proposer").writable()),
+            (multisig_info, AccountSpec::new("multisig").owner(*program_id)),
+        ])?;
 
+        let proposal = *Proposal::load(&proposal_acc_info.data.borrow())?;
+        let instruction_hash = Proposal::instruction_hash(
+            instruction_data,
+            instruction_accounts.iter().map(|account| account.key),
+        );
+        if proposal.multisig != *multisig_info.key
+            || proposal.proposer != *proposer_info.key
+            || proposal.instruction_hash != instruction_hash
+        {
+            return Err(EscrowError::ProposalMismatch.into());
+        }
+        let threshold = Multisig::load(&multisig_info.data.borrow())?.threshold;
+        if proposal.approval_count() < threshold as u32 {
+            return Err(EscrowError::NotEnoughSigners.into());
+        }
+
+        Self::close_account(proposal_acc_info, proposer_info)?;
+
+        let instruction = EscrowInstruction::unpack(instruction_data)?;
+        Self::dispatch(program_id, instruction_accounts, instruction, Some(*multisig_info.key))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_create_vesting(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        amount: u64,
+        start: u64,
+        cliff: u64,
+        end: u64,
+        revocable: bool,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let grantor_info = next_account_info(acc_iter)?;
+        let grantor_token_acc_info = next_account_info(acc_iter)?;
+        let beneficiary_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let vesting_acc_info = next_account_info(acc_iter)?; // PDA ["vesting", mint, grantor, beneficiary]
+        let mint_acc_info = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let rent_sysvar_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (grantor_info, AccountSpec::new("grantor").signer().writable()),
+            (grantor_token_acc_info, AccountSpec::new("grantor token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").writable()),
+            (vault_acc_info, AccountSpec::new("vault").writable()),
+            (vesting_acc_info, AccountSpec::new("vesting").writable()),
+            (mint_acc_info, AccountSpec::new("mint").owner(spl_token::id())),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (rent_sysvar_info, AccountSpec::new("rent sysvar").address(sysvar::rent::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+        ])?;
+
+        let token_mint = *mint_acc_info.key;
+        let vesting = Vesting::new(
+            *grantor_info.key,
+            *beneficiary_info.key,
+            token_mint,
+            amount,
+            start,
+            cliff,
+            end,
+            revocable,
+        )?;
+
+        let grantor_token_data = TokenAccount::unpack(&grantor_token_acc_info.data.borrow())?;
+        if grantor_token_data.mint != token_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+
+        let (expected_vesting_pda, vesting_bump) =
+            Vesting::find_address(&token_mint, grantor_info.key, beneficiary_info.key, program_id);
+        if expected_vesting_pda != *vesting_acc_info.key {
+            return Err(EscrowError::IncorrectAccountAddress.into());
+        }
+        if vesting_acc_info.lamports() != 0 {
+            return Err(EscrowError::AlreadyInitialized.into());
+        }
+
+        Self::open_escrow(
+            grantor_info,
+            escrow_data_acc_info,
+            vault_acc_info,
+            mint_acc_info,
+            system_program_info,
+            token_program_info,
+            rent_sysvar_info,
+            program_id,
+        )?;
+
+        let space = Vesting::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                grantor_info.key,
+                vesting_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                grantor_info.clone(),
+                vesting_acc_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[
+                b"vesting",
+                token_mint.as_ref(),
+                grantor_info.key.as_ref(),
+                beneficiary_info.key.as_ref(),
+                &[vesting_bump],
+            ]],
+        )?;
+        *Vesting::load_mut_unchecked(&mut vesting_acc_info.data.borrow_mut())? = vesting;
+
+        Self::transfer_to_vault(token_program_info, grantor_token_acc_info, vault_acc_info, grantor_info, amount)?;
+        let total_deposited = Self::credit_escrow(escrow_data_acc_info, amount)?;
+
+        DepositEvent {
+            user: *grantor_info.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            grantor_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            grantor_info.key,
+            vault_acc_info.key,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Escrow,
+        )?;
+
+        msg!("Vesting {} for {} created", amount, beneficiary_info.key);
+        Ok(())
+    }
+
+    fn process_claim_vested(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let beneficiary_info = next_account_info(acc_iter)?;
+        let recipient_token_acc_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let vesting_acc_info = next_account_info(acc_iter)?;
+        let grantor_info = next_account_info(acc_iter)?; // dobija rent kada se vesting zatvori
+        let token_program_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (beneficiary_info, AccountSpec::new("beneficiary").signer().writable()),
+            (recipient_token_acc_info, AccountSpec::new("recipient token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").owner(*program_id).writable()),
+            (vault_acc_info, AccountSpec::new("vault").owner(spl_token::id()).writable()),
+            (vesting_acc_info, AccountSpec::new("vesting").owner(*program_id).writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let clock = Clock::get()?;
+        let mut vesting_data = vesting_acc_info.data.borrow_mut();
+        let vesting = Vesting::load_mut(&mut vesting_data)?;
+        if vesting.beneficiary != *beneficiary_info.key {
+            return Err(EscrowError::NotVestingBeneficiary.into());
+        }
+        AccountSpec::new("grantor").address(vesting.grantor).writable().check(grantor_info).map_err(EscrowError::from)?;
+        let amount = vesting.claimable_at(clock.unix_timestamp as u64);
+        if amount == 0 {
+            return Err(EscrowError::NothingVested.into());
+        }
+        // Posle config-a: withdrawal multisig i njegovi signeri
+        Self::check_withdrawal_multisig(config.requires_multisig(amount), acc_iter.as_slice(), program_id, approved_by)?;
+        vesting.claimed = (vesting.claimed.get() + amount).into();
+        let vesting_mint = vesting.token_mint;
+        let settled = vesting.is_settled();
+        drop(vesting_data);
+
+        let (token_mint, total_deposited) =
+            Self::debit_escrow(escrow_data_acc_info, vault_acc_info, amount, program_id)?;
+        if token_mint != vesting_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+        let recipient_owner = Self::transfer_from_vault(
+            token_program_info,
+            vault_acc_info,
+            vault_acc_info,
+            recipient_token_acc_info,
+            &token_mint,
+            amount,
+            program_id,
+        )?;
+        if settled {
+            Self::close_account(vesting_acc_info, grantor_info)?;
+        }
+
+        WithdrawEvent {
+            user: *beneficiary_info.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            destination: *recipient_token_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            beneficiary_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            vault_acc_info.key,
+            &recipient_owner,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Escrow,
+        )?;
+
+        msg!("Claimed {} vested tokens", amount);
+        Ok(())
+    }
+
+    fn process_revoke(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let grantor_info = next_account_info(acc_iter)?;
+        let grantor_token_acc_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let vesting_acc_info = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (grantor_info, AccountSpec::new("grantor").signer().writable()),
+            (grantor_token_acc_info, AccountSpec::new("grantor token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").owner(*program_id).writable()),
+            (vault_acc_info, AccountSpec::new("vault").owner(spl_token::id()).writable()),
+            (vesting_acc_info, AccountSpec::new("vesting").owner(*program_id).writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let clock = Clock::get()?;
+        let mut vesting_data = vesting_acc_info.data.borrow_mut();
+        let vesting = Vesting::load_mut(&mut vesting_data)?;
+        if vesting.grantor != *grantor_info.key {
+            return Err(EscrowError::NotVestingGrantor.into());
+        }
+        if vesting.revocable == 0 {
+            return Err(EscrowError::NotRevocable.into());
+        }
+        let amount = vesting.revoke(clock.unix_timestamp as u64);
+        // Potpuno otkljucan grant nema sta da vrati; bez ovoga bi se logovala
+        // poruka i transfer od 0 tokena
+        if amount == 0 {
+            return Err(EscrowError::InsufficientAmount.into());
+        }
+        Self::check_withdrawal_multisig(config.requires_multisig(amount), acc_iter.as_slice(), program_id, approved_by)?;
+        let vesting_mint = vesting.token_mint;
+        let settled = vesting.is_settled();
+        drop(vesting_data);
+
+        let (token_mint, total_deposited) =
+            Self::debit_escrow(escrow_data_acc_info, vault_acc_info, amount, program_id)?;
+        if token_mint != vesting_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+        let recipient_owner = Self::transfer_from_vault(
+            token_program_info,
+            vault_acc_info,
+            vault_acc_info,
+            grantor_token_acc_info,
+            &token_mint,
+            amount,
+            program_id,
+        )?;
+        WithdrawEvent {
+            user: *grantor_info.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            destination: *grantor_token_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            grantor_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            vault_acc_info.key,
+            &recipient_owner,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Escrow,
+        )?;
+        // Nista otkljucano nije ostalo za beneficiary-ja; zatvara se posle CPI
+        // jer grantor placa i poruku
+        if settled {
+            Self::close_account(vesting_acc_info, grantor_info)?;
+        }
+
+        msg!("Revoked {} unvested tokens", amount);
+        Ok(())
+    }
+
+    fn process_open_agreement(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        id: u64,
+        amount: u64,
+        deadline: u64,
+        arbiter: Pubkey,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let payer_info = next_account_info(acc_iter)?;
+        let payer_token_acc_info = next_account_info(acc_iter)?;
+        let payee_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let agreement_acc_info = next_account_info(acc_iter)?; // PDA ["agreement", payer, id]
+        let mint_acc_info = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let rent_sysvar_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (payer_info, AccountSpec::new("payer").signer().writable()),
+            (payer_token_acc_info, AccountSpec::new("payer token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").writable()),
+            (vault_acc_info, AccountSpec::new("vault").writable()),
+            (agreement_acc_info, AccountSpec::new("agreement").writable()),
+            (mint_acc_info, AccountSpec::new("mint").owner(spl_token::id())),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (rent_sysvar_info, AccountSpec::new("rent sysvar").address(sysvar::rent::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+        ])?;
+
+        let clock = Clock::get()?;
+        let token_mint = *mint_acc_info.key;
+        let agreement = Agreement::new(
+            *payer_info.key,
+            *payee_info.key,
+            arbiter,
+            token_mint,
+            id,
+            amount,
+            deadline,
+            clock.unix_timestamp as u64,
+        )?;
+
+        let payer_token_data = TokenAccount::unpack(&payer_token_acc_info.data.borrow())?;
+        if payer_token_data.mint != token_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+
+        let (expected_agreement_pda, agreement_bump) = Agreement::find_address(payer_info.key, id, program_id);
+        if expected_agreement_pda != *agreement_acc_info.key {
+            return Err(EscrowError::IncorrectAccountAddress.into());
+        }
+        if agreement_acc_info.lamports() != 0 {
+            return Err(EscrowError::AlreadyInitialized.into());
+        }
+
+        Self::open_escrow(
+            payer_info,
+            escrow_data_acc_info,
+            vault_acc_info,
+            mint_acc_info,
+            system_program_info,
+            token_program_info,
+            rent_sysvar_info,
+            program_id,
+        )?;
+
+        let space = Agreement::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                agreement_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                agreement_acc_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[b"agreement", payer_info.key.as_ref(), &id.to_le_bytes(), &[agreement_bump]]],
+        )?;
+        *Agreement::load_mut_unchecked(&mut agreement_acc_info.data.borrow_mut())? = agreement;
+
+        Self::transfer_to_vault(token_program_info, payer_token_acc_info, vault_acc_info, payer_info, amount)?;
+        let total_deposited = Self::credit_escrow(escrow_data_acc_info, amount)?;
+
+        DepositEvent {
+            user: *payer_info.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            payer_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            payer_info.key,
+            vault_acc_info.key,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Escrow,
+        )?;
+
+        msg!("Agreement {} of {} for {} opened", id, amount, payee_info.key);
+        Ok(())
+    }
+
+    fn process_open_dispute(accounts: &[AccountInfo], program_id: &Pubkey) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let party_info = next_account_info(acc_iter)?;
+        let agreement_acc_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (party_info, AccountSpec::new("party").signer().writable()),
+            (agreement_acc_info, AccountSpec::new("agreement").owner(*program_id).writable()),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let mut agreement_data = agreement_acc_info.data.borrow_mut();
+        let agreement = Agreement::load_mut(&mut agreement_data)?;
+        if *party_info.key != agreement.payer && *party_info.key != agreement.payee {
+            return Err(EscrowError::NotAgreementParty.into());
+        }
+        if !agreement.has_arbiter() {
+            return Err(EscrowError::NotArbiter.into());
+        }
+        if agreement.is_disputed() {
+            return Err(EscrowError::AgreementDisputed.into());
+        }
+        // Posle roka payer vec moze da trazi povracaj
+        let now = Clock::get()?.unix_timestamp as u64;
+        if now >= agreement.deadline.get() {
+            return Err(EscrowError::DeadlinePassed.into());
+        }
+        agreement.disputed = 1;
+        // Arbitar koji se ne javi ne sme trajno da zakljuca sredstva, a rani
+        // spor ne sme da skrati rok koji je payer dao
+        agreement.dispute_deadline = now
+            .saturating_add(DISPUTE_WINDOW)
+            .max(agreement.deadline.get())
+            .into();
+        let (arbiter, amount) = (agreement.arbiter, agreement.amount.get());
+        drop(agreement_data);
+
+        // Sporni sporazum se belezi kao poruka od sporazuma ka arbitru
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            party_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            agreement_acc_info.key,
+            &arbiter,
+            amount,
+            ConsistencyLevel::Confirmed,
+            MessageKind::Escrow,
+        )?;
+
+        msg!("Agreement disputed by {}", party_info.key);
+        Ok(())
+    }
+
+    // Release (payer), Resolve (arbiter) i Refund (posle roka) isplacuju ceo
+    // iznos jednoj strani i zatvaraju sporazum
+    fn process_settle_agreement(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        instruction: EscrowInstruction,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let authority_info = next_account_info(acc_iter)?;
+        let recipient_token_acc_info = next_account_info(acc_iter)?;
+        let escrow_data_acc_info = next_account_info(acc_iter)?;
+        let vault_acc_info = next_account_info(acc_iter)?;
+        let agreement_acc_info = next_account_info(acc_iter)?;
+        let payer_info = next_account_info(acc_iter)?; // dobija rent sporazuma
+        let token_program_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (authority_info, AccountSpec::new("authority").signer().writable()),
+            (recipient_token_acc_info, AccountSpec::new("recipient token").owner(spl_token::id()).writable()),
+            (escrow_data_acc_info, AccountSpec::new("escrow").owner(*program_id).writable()),
+            (vault_acc_info, AccountSpec::new("vault").owner(spl_token::id()).writable()),
+            (agreement_acc_info, AccountSpec::new("agreement").owner(*program_id).writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let clock = Clock::get()?;
+        let agreement = *Agreement::load(&agreement_acc_info.data.borrow())?;
+        let to_payee = match instruction {
+            EscrowInstruction::Release => {
+                if *authority_info.key != agreement.payer {
+                    return Err(EscrowError::NotAgreementParty.into());
+                }
+                true
+            }
+            EscrowInstruction::Resolve { to_payee } => {
+                if !agreement.has_arbiter() || *authority_info.key != agreement.arbiter {
+                    return Err(EscrowError::NotArbiter.into());
+                }
+                if !agreement.is_disputed() {
+                    return Err(EscrowError::AgreementNotDisputed.into());
+                }
+                to_payee
+            }
+            EscrowInstruction::Refund => {
+                if (clock.unix_timestamp as u64) < agreement.refundable_at() {
+                    return Err(EscrowError::DeadlineNotReached.into());
+                }
+                false
+            }
+            _ => return Err(EscrowError::InvalidInstruction.into()),
+        };
+        AccountSpec::new("payer").address(agreement.payer).writable().check(payer_info).map_err(EscrowError::from)?;
+        let winner = if to_payee { agreement.payee } else { agreement.payer };
+        if TokenAccount::unpack(&recipient_token_acc_info.data.borrow())?.owner != winner {
+            return Err(EscrowError::RecipientMismatch.into());
+        }
+
+        let amount = agreement.amount.get();
+        Self::check_withdrawal_multisig(config.requires_multisig(amount), acc_iter.as_slice(), program_id, approved_by)?;
+        let (token_mint, total_deposited) =
+            Self::debit_escrow(escrow_data_acc_info, vault_acc_info, amount, program_id)?;
+        if token_mint != agreement.token_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+        Self::transfer_from_vault(
+            token_program_info,
+            vault_acc_info,
+            vault_acc_info,
+            recipient_token_acc_info,
+            &token_mint,
+            amount,
+            program_id,
+        )?;
+
+        WithdrawEvent {
+            user: *authority_info.key,
+            mint: token_mint,
+            vault: *vault_acc_info.key,
+            destination: *recipient_token_acc_info.key,
+            amount,
+            total_deposited,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit();
+
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            authority_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            vault_acc_info.key,
+            &winner,
+            amount,
+            ConsistencyLevel::Finalized,
+            MessageKind::Escrow,
+        )?;
+        // Zatvara se posle CPI jer payer moze biti i potpisnik (Release)
+        Self::close_account(agreement_acc_info, payer_info)?;
+
+        msg!("Agreement {} settled to {}", agreement.id.get(), winner);
+        Ok(())
+    }
+
+    fn process_batch_deposit(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+        legs: &[(Pubkey, u64)],
+    ) -> ProgramResult {
+        let acc_iter = &mut accounts.iter();
+
+        let user_signer = next_account_info(acc_iter)?;
+        let token_program_info = next_account_info(acc_iter)?;
+        let system_program_info = next_account_info(acc_iter)?;
+        let rent_sysvar_info = next_account_info(acc_iter)?;
+        let logger_program_info = next_account_info(acc_iter)?;
+        let logger_state_acc_info = next_account_info(acc_iter)?;
+        let message_pda_info = next_account_info(acc_iter)?;
+        let batch_acc_info = next_account_info(acc_iter)?; // PDA ["batch", message]
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (user_signer, AccountSpec::new("user").signer().writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+            (rent_sysvar_info, AccountSpec::new("rent sysvar").address(sysvar::rent::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (batch_acc_info, AccountSpec::new("batch").writable()),
+        ])?;
+
+        let clock = Clock::get()?;
+        let mut transfers = Vec::with_capacity(legs.len());
+        for (token_mint, amount) in legs {
+            let user_token_acc_info = next_account_info(acc_iter)?;
+            let mint_acc_info = next_account_info(acc_iter)?;
+            let escrow_data_acc_info = next_account_info(acc_iter)?;
+            let vault_acc_info = next_account_info(acc_iter)?;
+            let position_acc_info = next_account_info(acc_iter)?;
+            check_accounts::<EscrowError>(&[
+                (user_token_acc_info, AccountSpec::new("user token").owner(spl_token::id()).writable()),
+                (mint_acc_info, AccountSpec::new("mint").address(*token_mint).owner(spl_token::id())),
+                (escrow_data_acc_info, AccountSpec::new("escrow").writable()),
+                (vault_acc_info, AccountSpec::new("vault").writable()),
+                (position_acc_info, AccountSpec::new("position").writable()),
+            ])?;
+
+            let total_deposited = Self::deposit_leg(
+                user_signer,
+                user_token_acc_info,
+                escrow_data_acc_info,
+                vault_acc_info,
+                mint_acc_info,
+                position_acc_info,
+                system_program_info,
+                token_program_info,
+                rent_sysvar_info,
+                *amount,
+                program_id,
+            )?;
 
-pub struct EscrowProcessor;
-impl EscrowProcessor {
-    pub fn process(
-        program_id: &Pubkey,
-        accounts: &[AccountInfo],
-        instruction_data: &[u8],
-    ) -> ProgramResult {
-        let instruction = EscrowInstruction::unpack(instruction_data)?;
-        match instruction {
-            EscrowInstruction::Deposit { amount } => {
-                msg!("Instruction: Deposit {}", amount);
-                Self::process_deposit(accounts, amount, program_id)
-            }
-            EscrowInstruction::Withdraw { amount } => {
-                msg!("Instruction: Withdraw {}", amount);
-                Self::process_withdraw(accounts, amount, program_id)
+            DepositEvent {
+                user: *user_signer.key,
+                mint: *token_mint,
+                vault: *vault_acc_info.key,
+                amount: *amount,
+                total_deposited,
+                timestamp: clock.unix_timestamp,
             }
+            .emit();
+
+            transfers.push(BatchTransfer {
+                from_pubkey: *user_signer.key,
+                to_pubkey: *vault_acc_info.key,
+                amount: (*amount).into(),
+            });
         }
+
+        // Jedna poruka korisnik -> batch za ceo batch, legovi ostaju u batch nalogu.
+        // Iznosi razlicitih mint-ova se ne sabiraju, poruka nosi broj legova
+        Self::open_batch(user_signer, batch_acc_info, message_pda_info, system_program_info, &transfers, program_id)?;
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            user_signer,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            user_signer.key,
+            batch_acc_info.key,
+            transfers.len() as u64,
+            ConsistencyLevel::Finalized,
+            MessageKind::Batch,
+        )?;
+
+        msg!("Batch of {} deposits completed.", legs.len());
+        Ok(())
     }
-    fn process_deposit(
+
+    fn process_batch_withdraw(
         accounts: &[AccountInfo],
-        amount: u64,
         program_id: &Pubkey,
+        legs: &[(Pubkey, u64)],
+        approved_by: Option<Pubkey>,
     ) -> ProgramResult {
         let acc_iter = &mut accounts.iter();
 
-        let user_signer = next_account_info(acc_iter)?;
-        if !user_signer.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-
-        let user_token_acc_info = next_account_info(acc_iter)?;
-        let escrow_data_acc_info = next_account_info(acc_iter)?;
-        let vault_acc_info = next_account_info(acc_iter)?;
-        let system_program_info = next_account_info(acc_iter)?;
+        let authority_info = next_account_info(acc_iter)?; // vlasnik pozicija ili delegat
         let token_program_info = next_account_info(acc_iter)?;
-        let rent_sysvar_info = next_account_info(acc_iter)?;
-
-        // Logger
         let logger_program_info = next_account_info(acc_iter)?;
         let logger_state_acc_info = next_account_info(acc_iter)?;
         let message_pda_info = next_account_info(acc_iter)?;
-        let payer_account_info = next_account_info(acc_iter)?;
-        let logger_system_program_info = next_account_info(acc_iter)?;
-        let mint_acc_info = next_account_info(acc_iter)?; 
+        let batch_acc_info = next_account_info(acc_iter)?; // PDA ["batch", message]
+        let system_program_info = next_account_info(acc_iter)?;
+        let config_acc_info = next_account_info(acc_iter)?;
+
+        let config = Self::load_config(config_acc_info, program_id)?;
+        check_accounts::<EscrowError>(&[
+            (authority_info, AccountSpec::new("authority").signer().writable()),
+            (token_program_info, AccountSpec::new("token program").program(spl_token::id())),
+            (logger_program_info, AccountSpec::new("logger program").program(config.logger_program)),
+            (logger_state_acc_info, AccountSpec::new("logger state").address(config.logger_state).writable()),
+            (message_pda_info, AccountSpec::new("message").writable()),
+            (batch_acc_info, AccountSpec::new("batch").writable()),
+            (system_program_info, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let clock = Clock::get()?;
+        let mut leg_infos = Vec::with_capacity(legs.len());
+        for _ in legs {
+            let recipient_token_acc_info = next_account_info(acc_iter)?;
+            let escrow_data_acc_info = next_account_info(acc_iter)?;
+            let vault_acc_info = next_account_info(acc_iter)?;
+            let position_acc_info = next_account_info(acc_iter)?;
+            check_accounts::<EscrowError>(&[
+                (recipient_token_acc_info, AccountSpec::new("recipient token").owner(spl_token::id()).writable()),
+                (escrow_data_acc_info, AccountSpec::new("escrow").owner(*program_id).writable()),
+                (vault_acc_info, AccountSpec::new("vault").owner(spl_token::id()).writable()),
+                (position_acc_info, AccountSpec::new("position").owner(*program_id).writable()),
+            ])?;
+            leg_infos.push((recipient_token_acc_info, escrow_data_acc_info, vault_acc_info, position_acc_info));
+        }
+
+        // Threshold vazi za zbir po mintu, da se veliko povlacenje ne bi delilo na manje legove
+        let mut mint_totals: Vec<(Pubkey, u64)> = Vec::with_capacity(legs.len());
+        for (mint, amount) in legs {
+            match mint_totals.iter_mut().find(|(total_mint, _)| total_mint == mint) {
+                Some((_, total)) => *total = total.checked_add(*amount).ok_or(EscrowError::AmountOverflow)?,
+                None => mint_totals.push((*mint, *amount)),
+            }
+        }
+        // Posle legova: withdrawal multisig i njegovi signeri
+        Self::check_withdrawal_multisig(
+            mint_totals.iter().find_map(|(_, total)| config.requires_multisig(*total)),
+            acc_iter.as_slice(),
+            program_id,
+            approved_by,
+        )?;
+
+        let mut transfers = Vec::with_capacity(legs.len());
+        for ((mint, amount), leg_info) in legs.iter().zip(leg_infos) {
+            let (recipient_token_acc_info, escrow_data_acc_info, vault_acc_info, position_acc_info) = leg_info;
+            let (token_mint, total_deposited, owner, recipient_owner) = Self::withdraw_leg(
+                authority_info,
+                recipient_token_acc_info,
+                escrow_data_acc_info,
+                vault_acc_info,
+                vault_acc_info,
+                position_acc_info,
+                token_program_info,
+                *amount,
+                &clock,
+                program_id,
+            )?;
+            if token_mint != *mint {
+                return Err(EscrowError::MintMismatch.into());
+            }
+
+            WithdrawEvent {
+                user: owner,
+                mint: token_mint,
+                vault: *vault_acc_info.key,
+                destination: *recipient_token_acc_info.key,
+                amount: *amount,
+                total_deposited,
+                timestamp: clock.unix_timestamp,
+            }
+            .emit();
+
+            transfers.push(BatchTransfer {
+                from_pubkey: *vault_acc_info.key,
+                to_pubkey: recipient_owner,
+                amount: (*amount).into(),
+            });
+        }
 
-        if *user_token_acc_info.owner != spl_token::id() {
-            return Err(ProgramError::IncorrectProgramId);
+        Self::open_batch(authority_info, batch_acc_info, message_pda_info, system_program_info, &transfers, program_id)?;
+        Self::post_message(
+            logger_program_info,
+            logger_state_acc_info,
+            message_pda_info,
+            authority_info,
+            system_program_info,
+            config_acc_info,
+            program_id,
+            batch_acc_info.key,
+            authority_info.key,
+            transfers.len() as u64,
+            ConsistencyLevel::Finalized,
+            MessageKind::Batch,
+        )?;
+
+        msg!("Batch of {} withdrawals completed.", legs.len());
+        Ok(())
+    }
+
+    // Batch nalog vezan za adresu poruke, pa svaki batch dobija svoj
+    fn open_batch<'a>(
+        payer_info: &AccountInfo<'a>,
+        batch_acc_info: &AccountInfo<'a>,
+        message_pda_info: &AccountInfo,
+        system_program_info: &AccountInfo<'a>,
+        transfers: &[BatchTransfer],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let (expected_batch_pda, batch_bump) = Batch::find_address(message_pda_info.key, program_id);
+        if expected_batch_pda != *batch_acc_info.key {
+            return Err(EscrowError::IncorrectAccountAddress.into());
+        }
+        if batch_acc_info.lamports() != 0 {
+            return Err(EscrowError::AlreadyInitialized.into());
         }
 
+        let space = Batch::LEN;
+        invoke_signed(
+            &system_instruction::create_account(
+                payer_info.key,
+                batch_acc_info.key,
+                Rent::get()?.minimum_balance(space),
+                space as u64,
+                program_id,
+            ),
+            &[
+                payer_info.clone(),
+                batch_acc_info.clone(),
+                system_program_info.clone(),
+            ],
+            &[&[b"batch", message_pda_info.key.as_ref(), &[batch_bump]]],
+        )?;
+        *Batch::load_mut_unchecked(&mut batch_acc_info.data.borrow_mut())? =
+            Batch::new(*message_pda_info.key, transfers);
+        Ok(())
+    }
+
+    // Jedan depozit: escrow, vault i pozicija se prave po potrebi, vraca novi total_deposited
+    #[allow(clippy::too_many_arguments)]
+    fn deposit_leg<'a>(
+        user_signer: &AccountInfo<'a>,
+        user_token_acc_info: &AccountInfo<'a>,
+        escrow_data_acc_info: &AccountInfo<'a>,
+        vault_acc_info: &AccountInfo<'a>,
+        mint_acc_info: &AccountInfo<'a>,
+        position_acc_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        rent_sysvar_info: &AccountInfo<'a>,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> Result<u64, ProgramError> {
         let token_mint = *mint_acc_info.key;
 
         let user_token_data = TokenAccount::unpack(&user_token_acc_info.data.borrow())?;
         if user_token_data.mint != token_mint {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(EscrowError::MintMismatch.into());
+        }
+
+        let (expected_position_pda, position_bump) =
+            Position::find_address(&token_mint, user_signer.key, program_id);
+        if expected_position_pda != *position_acc_info.key {
+            return Err(EscrowError::IncorrectPositionAddress.into());
+        }
+
+        Self::open_escrow(
+            user_signer,
+            escrow_data_acc_info,
+            vault_acc_info,
+            mint_acc_info,
+            system_program_info,
+            token_program_info,
+            rent_sysvar_info,
+            program_id,
+        )?;
+
+        if position_acc_info.lamports() == 0 {
+            let space = Position::LEN;
+            invoke_signed(
+                &system_instruction::create_account(
+                    user_signer.key,
+                    position_acc_info.key,
+                    Rent::get()?.minimum_balance(space),
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    user_signer.clone(),
+                    position_acc_info.clone(),
+                    system_program_info.clone(),
+                ],
+                &[&[b"position", token_mint.as_ref(), user_signer.key.as_ref(), &[position_bump]]],
+            )?;
+
+            let mut position_data = position_acc_info.data.borrow_mut();
+            *Position::load_mut_unchecked(&mut position_data)? = Position::new(*user_signer.key, token_mint);
+            msg!("Position account created.");
+        } else {
+            AccountSpec::new("position").owner(*program_id).check(position_acc_info).map_err(EscrowError::from)?;
+        }
+
+        Self::transfer_to_vault(token_program_info, user_token_acc_info, vault_acc_info, user_signer, amount)?;
+        let total_deposited = Self::credit_escrow(escrow_data_acc_info, amount)?;
+
+        let mut position_data = position_acc_info.data.borrow_mut();
+        let position = Position::load_mut(&mut position_data)?;
+        position.amount = position
+            .amount
+            .get()
+            .checked_add(amount)
+            .ok_or(EscrowError::AmountOverflow)?
+            .into();
+        Ok(total_deposited)
+    }
+
+    // Jedno povlacenje sa pozicije od strane vlasnika ili delegata; vraca mint,
+    // novi total_deposited, vlasnika pozicije i vlasnika naloga primaoca
+    #[allow(clippy::too_many_arguments)]
+    fn withdraw_leg<'a>(
+        authority_info: &AccountInfo<'a>,
+        recipient_token_acc_info: &AccountInfo<'a>,
+        escrow_data_acc_info: &AccountInfo<'a>,
+        vault_acc_info: &AccountInfo<'a>,
+        vault_authority_info: &AccountInfo<'a>,
+        position_acc_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        amount: u64,
+        clock: &Clock,
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u64, Pubkey, Pubkey), ProgramError> {
+        let (token_mint, total_deposited) =
+            Self::debit_escrow(escrow_data_acc_info, vault_acc_info, amount, program_id)?;
+
+        let mut position_data = position_acc_info.data.borrow_mut();
+        let position = Position::load_mut(&mut position_data)?;
+        if position.token_mint != token_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+        if position.owner != *authority_info.key {
+            if position.delegate() != Some(*authority_info.key) {
+                return Err(EscrowError::NotPositionAuthority.into());
+            }
+            if let Some(expires_at) = position.delegate_expires_at() {
+                if clock.unix_timestamp as u64 >= expires_at {
+                    return Err(EscrowError::DelegateExpired.into());
+                }
+            }
+            if let Some(cap) = position.delegate_cap() {
+                let remaining_cap = cap.checked_sub(amount).ok_or(EscrowError::DelegateCapExceeded)?;
+                position.delegate_cap = remaining_cap.into();
+            }
+        }
+        position.amount = position
+            .amount
+            .get()
+            .checked_sub(amount)
+            .ok_or(EscrowError::InsufficientAmount)?
+            .into();
+        let owner = position.owner;
+        drop(position_data);
+
+        let recipient_owner = Self::transfer_from_vault(
+            token_program_info,
+            vault_acc_info,
+            vault_authority_info,
+            recipient_token_acc_info,
+            &token_mint,
+            amount,
+            program_id,
+        )?;
+
+        Ok((token_mint, total_deposited, owner, recipient_owner))
+    }
+
+    // Config mora biti upisiv, a admin mora da potpise ili da bude multisig koji je odobrio
+    fn check_admin<'a>(
+        admin_info: &AccountInfo<'a>,
+        config_acc_info: &AccountInfo<'a>,
+        signer_infos: &[AccountInfo<'a>],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        AccountSpec::new("config").writable().check(config_acc_info).map_err(EscrowError::from)?;
+        if Self::load_config(config_acc_info, program_id)?.admin != *admin_info.key {
+            return Err(EscrowError::NotAdmin.into());
+        }
+        Self::check_authority(admin_info, signer_infos, program_id, approved_by)
+    }
+
+    // Isplata iz vault-a iznad threshold-a trazi withdrawal multisig kao prvi
+    // nalog iza naloga instrukcije, pa njegove signere
+    fn check_withdrawal_multisig(
+        multisig: Option<Pubkey>,
+        extra_infos: &[AccountInfo],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        if let Some(multisig) = multisig {
+            let (multisig_info, signer_infos) = extra_infos
+                .split_first()
+                .ok_or(EscrowError::LargeWithdrawalNotApproved)?;
+            AccountSpec::new("withdrawal multisig").address(multisig).check(multisig_info).map_err(EscrowError::from)?;
+            Self::check_authority(multisig_info, signer_infos, program_id, approved_by)?;
+        }
+        Ok(())
+    }
+
+    // Autoritet je obican potpisnik ili multisig programa: tada treba threshold
+    // potpisa clanova medju signer_infos, ili odobren predlog kroz Execute
+    fn check_authority(
+        authority_info: &AccountInfo,
+        signer_infos: &[AccountInfo],
+        program_id: &Pubkey,
+        approved_by: Option<Pubkey>,
+    ) -> ProgramResult {
+        if approved_by == Some(*authority_info.key) {
+            return Ok(());
         }
+        if authority_info.owner == program_id {
+            let multisig_data = authority_info.data.borrow();
+            let multisig = Multisig::load(&multisig_data)?;
+            if multisig.count_signatures(signer_infos) < multisig.threshold as usize {
+                return Err(EscrowError::NotEnoughSigners.into());
+            }
+            return Ok(());
+        }
+        AccountSpec::new("authority").signer().check(authority_info).map_err(EscrowError::from)?;
+        Ok(())
+    }
 
+    // Escrow i vault PDA za mint; prvi depozit ih pravi o trosku funder-a
+    #[allow(clippy::too_many_arguments)]
+    fn open_escrow<'a>(
+        funder_info: &AccountInfo<'a>,
+        escrow_data_acc_info: &AccountInfo<'a>,
+        vault_acc_info: &AccountInfo<'a>,
+        mint_acc_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        token_program_info: &AccountInfo<'a>,
+        rent_sysvar_info: &AccountInfo<'a>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let token_mint = *mint_acc_info.key;
         let (expected_escrow_pda, escrow_bump) =
-            Pubkey::find_program_address(&[b"escrow", token_mint.as_ref()], program_id);
+            EscrowState::find_address(&token_mint, program_id);
         if expected_escrow_pda != *escrow_data_acc_info.key {
-            return Err(ProgramError::InvalidAccountData);
+            return Err(EscrowError::IncorrectEscrowAddress.into());
         }
 
         let (expected_vault_pda, vault_bump) =
-            Pubkey::find_program_address(&[b"vault", token_mint.as_ref()], program_id);
+            EscrowState::find_vault_address(&token_mint, program_id);
         if expected_vault_pda != *vault_acc_info.key {
-            msg!("Vault PDA mismatch.");
-            return Err(ProgramError::InvalidAccountData);
+            return Err(EscrowError::IncorrectVaultAddress.into());
         }
 
         if escrow_data_acc_info.lamports() == 0 {
             let space = EscrowState::LEN;
             let rent_lamports = Rent::get()?.minimum_balance(space);
             let create_ix = system_instruction::create_account(
-                user_signer.key,
+                funder_info.key,
                 escrow_data_acc_info.key,
                 rent_lamports,
                 space as u64,
@@ -99,24 +1772,21 @@ impl EscrowProcessor {
             invoke_signed(
                 &create_ix,
                 &[
-                    user_signer.clone(),
+                    funder_info.clone(),
                     escrow_data_acc_info.clone(),
                     system_program_info.clone(),
                 ],
                 &[&[b"escrow", token_mint.as_ref(), &[escrow_bump]]],
             )?;
 
-            let escrow_state = EscrowState {
-                is_initialized: true,
-                token_mint,
-                escrow_vault_account: expected_vault_pda,
-                total_deposited: 0,
-            };
-            EscrowState::pack(escrow_state, &mut escrow_data_acc_info.data.borrow_mut())?;
+            let mut escrow_data = escrow_data_acc_info.data.borrow_mut();
+            *EscrowState::load_mut_unchecked(&mut escrow_data)? =
+                EscrowState::new(token_mint, expected_vault_pda, 0);
             msg!("Escrow account created and initialized.");
         } else {
-            let existing = EscrowState::unpack(&escrow_data_acc_info.data.borrow())?;
-            if existing.token_mint != token_mint {
+            AccountSpec::new("escrow").owner(*program_id).check(escrow_data_acc_info).map_err(EscrowError::from)?;
+            let escrow_data = escrow_data_acc_info.data.borrow();
+            if EscrowState::load(&escrow_data)?.token_mint != token_mint {
                 return Err(EscrowError::MintMismatch.into());
             }
         }
@@ -124,7 +1794,7 @@ impl EscrowProcessor {
         if vault_acc_info.lamports() == 0 {
             let rent = Rent::get()?.minimum_balance(spl_token::state::Account::LEN);
             let create_ix = system_instruction::create_account(
-                user_signer.key,
+                funder_info.key,
                 vault_acc_info.key,
                 rent,
                 spl_token::state::Account::LEN as u64,
@@ -133,7 +1803,7 @@ impl EscrowProcessor {
             invoke_signed(
                 &create_ix,
                 &[
-                    user_signer.clone(),
+                    funder_info.clone(),
                     vault_acc_info.clone(),
                     system_program_info.clone(),
                 ],
@@ -144,7 +1814,7 @@ impl EscrowProcessor {
                 token_program_info.key,
                 vault_acc_info.key,
                 &token_mint,
-                &expected_vault_pda, 
+                &expected_vault_pda,
             )?;
             invoke_signed(
                 &init_ix,
@@ -160,183 +1830,242 @@ impl EscrowProcessor {
 
             msg!("Vault account created and initialized.");
         } else {
+            AccountSpec::new("vault").owner(spl_token::id()).check(vault_acc_info).map_err(EscrowError::from)?;
             let vault_data = TokenAccount::unpack(&vault_acc_info.data.borrow())?;
             if vault_data.mint != token_mint {
                 return Err(EscrowError::MintMismatch.into());
             }
         }
 
+        Ok(())
+    }
+
+    fn transfer_to_vault<'a>(
+        token_program_info: &AccountInfo<'a>,
+        source_token_acc_info: &AccountInfo<'a>,
+        vault_acc_info: &AccountInfo<'a>,
+        source_owner_info: &AccountInfo<'a>,
+        amount: u64,
+    ) -> ProgramResult {
         msg!("Transferring {} tokens to vault...", amount);
         let transfer_ix = spl_token::instruction::transfer(
             token_program_info.key,
-            user_token_acc_info.key,
+            source_token_acc_info.key,
             vault_acc_info.key,
-            user_signer.key,
-            &[&user_signer.key],
+            source_owner_info.key,
+            &[source_owner_info.key],
             amount,
         )?;
         invoke(
             &transfer_ix,
             &[
-                user_token_acc_info.clone(),
+                source_token_acc_info.clone(),
                 vault_acc_info.clone(),
-                user_signer.clone(),
+                source_owner_info.clone(),
                 token_program_info.clone(),
             ],
         )?;
         msg!("Token transfer complete.");
-
-        let mut escrow_state = EscrowState::unpack(&escrow_data_acc_info.data.borrow())?;
-        escrow_state.total_deposited = escrow_state
-            .total_deposited
-            .checked_add(amount)
-            .ok_or(EscrowError::AmountOverflow)?;
-        EscrowState::pack(escrow_state, &mut escrow_data_acc_info.data.borrow_mut())?;
-
-        // Logger
-        let clock = Clock::get()?;
-        let timestamp = clock.unix_timestamp as u64;
-        let mut logger_data = vec![0u8; 80];
-        logger_data[..32].copy_from_slice(user_signer.key.as_ref());
-        logger_data[32..64].copy_from_slice(vault_acc_info.key.as_ref());
-        logger_data[64..72].copy_from_slice(&amount.to_le_bytes());
-        logger_data[72..80].copy_from_slice(&timestamp.to_le_bytes());
-
-        let logger_ix = Instruction {
-            program_id: *logger_program_info.key,
-            accounts: vec![
-                AccountMeta::new(*logger_state_acc_info.key, false),
-                AccountMeta::new(*message_pda_info.key, false),
-                AccountMeta::new(*payer_account_info.key, true),
-                AccountMeta::new_readonly(*logger_system_program_info.key, false),
-            ],
-            data: logger_data,
-        };
-
-        invoke(
-            &logger_ix,
-            &[
-                logger_program_info.clone(),
-                logger_state_acc_info.clone(),
-                message_pda_info.clone(),
-                payer_account_info.clone(),
-                logger_system_program_info.clone(),
-            ],
-        )?;
-        msg!("Logger invoked successfully.");
-
         Ok(())
     }
 
-    fn process_withdraw(
-        accounts: &[AccountInfo],
+    // Isplata potpisana vault PDA-om ["vault", mint]; vraca vlasnika naloga primaoca
+    fn transfer_from_vault<'a>(
+        token_program_info: &AccountInfo<'a>,
+        vault_acc_info: &AccountInfo<'a>,
+        vault_authority_info: &AccountInfo<'a>,
+        recipient_token_acc_info: &AccountInfo<'a>,
+        token_mint: &Pubkey,
         amount: u64,
         program_id: &Pubkey,
-    ) -> ProgramResult {
-        msg!("Starting process_withdraw with amount: {}", amount);
-        
-        let acc_iter = &mut accounts.iter();
-    
-        let user_signer = next_account_info(acc_iter)?;
-        if !user_signer.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-    
-        let user_token_acc_info = next_account_info(acc_iter)?;
-        let escrow_data_acc_info = next_account_info(acc_iter)?;
-        let vault_acc_info = next_account_info(acc_iter)?;
-        let token_program_info = next_account_info(acc_iter)?;
-        let logger_program_info = next_account_info(acc_iter)?;
-        let logger_state_acc_info = next_account_info(acc_iter)?;
-        let vault_authority_info = next_account_info(acc_iter)?; // PDA ["vault", mint]
-        let message_pda_info = next_account_info(acc_iter)?;
-        let payer_account_info = next_account_info(acc_iter)?;
-        let logger_system_program_info = next_account_info(acc_iter)?;
-    
-        let mut escrow_state = EscrowState::unpack(&escrow_data_acc_info.data.borrow())?;
-        if !escrow_state.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
-    
-        let token_mint = escrow_state.token_mint;
-        let (expected_escrow_pda, _) = Pubkey::find_program_address(&[b"escrow", token_mint.as_ref()], program_id);
-        if expected_escrow_pda != *escrow_data_acc_info.key {
-            return Err(ProgramError::InvalidAccountData);
-        }
-    
-        if escrow_state.escrow_vault_account != *vault_acc_info.key {
-            return Err(ProgramError::InvalidAccountData);
-        }
-    
-        if escrow_state.total_deposited < amount {
-            return Err(EscrowError::InsufficientAmount.into());
-        }
-        escrow_state.total_deposited -= amount;
-    
+    ) -> Result<Pubkey, ProgramError> {
         let vault_data = TokenAccount::unpack(&vault_acc_info.data.borrow())?;
-        if vault_data.mint != token_mint {
+        if vault_data.mint != *token_mint {
             return Err(EscrowError::MintMismatch.into());
         }
-    
-        let (vault_pda, vault_bump) = Pubkey::find_program_address(&[b"vault", token_mint.as_ref()], program_id);
+
+        let recipient_data = TokenAccount::unpack(&recipient_token_acc_info.data.borrow())?;
+        if recipient_data.mint != *token_mint {
+            return Err(EscrowError::MintMismatch.into());
+        }
+
+        let (vault_pda, vault_bump) = EscrowState::find_vault_address(token_mint, program_id);
         if vault_pda != *vault_authority_info.key {
             return Err(ProgramError::InvalidSeeds);
         }
-    
+
         let transfer_out_ix = spl_token::instruction::transfer(
             token_program_info.key,
             vault_acc_info.key,
-            user_token_acc_info.key,
+            recipient_token_acc_info.key,
             &vault_pda,
             &[],
             amount,
         )?;
-    
         invoke_signed(
             &transfer_out_ix,
             &[
                 vault_acc_info.clone(),
-                user_token_acc_info.clone(),
+                recipient_token_acc_info.clone(),
                 vault_authority_info.clone(),
                 token_program_info.clone(),
             ],
             &[&[b"vault", token_mint.as_ref(), &[vault_bump]]],
         )?;
-    
-        EscrowState::pack(escrow_state, &mut escrow_data_acc_info.data.borrow_mut())?;
-    
-        let clock = Clock::get()?;
-        let timestamp = clock.unix_timestamp as u64;
-    
-        let mut logger_data = vec![0u8; 80];
-        logger_data[..32].copy_from_slice(vault_acc_info.key.as_ref());
-        logger_data[32..64].copy_from_slice(user_signer.key.as_ref());
-        logger_data[64..72].copy_from_slice(&amount.to_le_bytes());
-        logger_data[72..80].copy_from_slice(&timestamp.to_le_bytes());
-    
-        let logger_ix = Instruction {
-            program_id: *logger_program_info.key,
-            accounts: vec![
-                AccountMeta::new(*logger_state_acc_info.key, false),
-                AccountMeta::new(*message_pda_info.key, false),
-                AccountMeta::new(*payer_account_info.key, true),
-                AccountMeta::new_readonly(*logger_system_program_info.key, false),
-            ],
-            data: logger_data,
-        };
-    
-        invoke(
+        Ok(recipient_data.owner)
+    }
+
+    fn credit_escrow(escrow_data_acc_info: &AccountInfo, amount: u64) -> Result<u64, ProgramError> {
+        let mut escrow_data = escrow_data_acc_info.data.borrow_mut();
+        let escrow_state = EscrowState::load_mut(&mut escrow_data)?;
+        let total_deposited = escrow_state
+            .total_deposited
+            .get()
+            .checked_add(amount)
+            .ok_or(EscrowError::AmountOverflow)?;
+        escrow_state.total_deposited = total_deposited.into();
+        Ok(total_deposited)
+    }
+
+    // Proverava escrow PDA i njegov vault, vraca mint i novi total_deposited
+    fn debit_escrow(
+        escrow_data_acc_info: &AccountInfo,
+        vault_acc_info: &AccountInfo,
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u64), ProgramError> {
+        // Transfer posle ne dira escrow nalog, pozajmica se pusta pre CPI
+        let mut escrow_data = escrow_data_acc_info.data.borrow_mut();
+        let escrow_state = EscrowState::load_mut(&mut escrow_data)?;
+
+        let token_mint = escrow_state.token_mint;
+        let (expected_escrow_pda, _) = EscrowState::find_address(&token_mint, program_id);
+        if expected_escrow_pda != *escrow_data_acc_info.key {
+            return Err(EscrowError::IncorrectEscrowAddress.into());
+        }
+
+        if escrow_state.escrow_vault_account != *vault_acc_info.key {
+            return Err(EscrowError::IncorrectVaultAddress.into());
+        }
+
+        let total_deposited = escrow_state
+            .total_deposited
+            .get()
+            .checked_sub(amount)
+            .ok_or(EscrowError::InsufficientAmount)?;
+        escrow_state.total_deposited = total_deposited.into();
+        Ok((token_mint, total_deposited))
+    }
+
+    // Emitter je config PDA, pa logger poruku prihvata samo iz ovog programa
+    #[allow(clippy::too_many_arguments)]
+    fn post_message<'a>(
+        logger_program_info: &AccountInfo<'a>,
+        logger_state_acc_info: &AccountInfo<'a>,
+        message_pda_info: &AccountInfo<'a>,
+        payer_account_info: &AccountInfo<'a>,
+        system_program_info: &AccountInfo<'a>,
+        config_acc_info: &AccountInfo<'a>,
+        program_id: &Pubkey,
+        from: &Pubkey,
+        to: &Pubkey,
+        amount: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    ) -> ProgramResult {
+        let (_, config_bump) = EscrowConfig::find_address(program_id);
+        let logger_ix = logger_instruction::post_message(
+            logger_program_info.key,
+            logger_state_acc_info.key,
+            message_pda_info.key,
+            payer_account_info.key,
+            config_acc_info.key,
+            from,
+            to,
+            amount,
+            Clock::get()?.unix_timestamp as u64,
+            consistency_level,
+            kind,
+        );
+        invoke_signed(
             &logger_ix,
             &[
                 logger_program_info.clone(),
                 logger_state_acc_info.clone(),
                 message_pda_info.clone(),
                 payer_account_info.clone(),
-                logger_system_program_info.clone(),
+                system_program_info.clone(),
+                config_acc_info.clone(),
             ],
+            &[&[b"config", &[config_bump]]],
         )?;
-    
-        msg!("Withdraw completed.");
+        msg!("Logger invoked successfully.");
+        Ok(())
+    }
+
+    // Lamporti idu na destination, a nalog se vraca system programu
+    fn close_account(account_info: &AccountInfo, destination_info: &AccountInfo) -> ProgramResult {
+        let destination_lamports = destination_info.lamports();
+        **destination_info.lamports.borrow_mut() = destination_lamports
+            .checked_add(account_info.lamports())
+            .ok_or(EscrowError::AmountOverflow)?;
+        **account_info.lamports.borrow_mut() = 0;
+        account_info.assign(&system_program::id());
+        account_info.realloc(0, false)?;
+        Ok(())
+    }
+
+    fn load_config(config_acc_info: &AccountInfo, program_id: &Pubkey) -> Result<EscrowConfig, ProgramError> {
+        if EscrowConfig::find_address(program_id).0 != *config_acc_info.key {
+            return Err(EscrowError::IncorrectConfigAddress.into());
+        }
+        AccountSpec::new("config").owner(*program_id).check(config_acc_info).map_err(EscrowError::from)?;
+        let config_data = config_acc_info.data.borrow();
+        Ok(*EscrowConfig::load(&config_data)?)
+    }
+
+    // Config inicijalizuje samo upgrade authority, da niko ne preuzme admin
+    // ulogu izmedju deploy-a i init-a. Namerno: bez upgrade authority-ja
+    // (immutable deploy) config se ne moze inicijalizovati, a program van
+    // upgradeable loader-a (builtin u testovima) prihvata prvog pozivaoca
+    fn check_upgrade_authority(
+        program_id: &Pubkey,
+        program_data_info: &AccountInfo,
+        admin: &Pubkey,
+    ) -> ProgramResult {
+        if *program_data_info.key != EscrowConfig::program_data_address(program_id) {
+            return Err(EscrowError::IncorrectAccountAddress.into());
+        }
+        // Adresa je izvedena iz program id-a, pa ProgramData ne postoji samo kada
+        // program nije deployovan kroz upgradeable loader (builtin, stari loaderi)
+        if *program_data_info.owner != bpf_loader_upgradeable::id() {
+            return Ok(());
+        }
+        let state = limited_deserialize(
+            &program_data_info.data.borrow(),
+            UpgradeableLoaderState::size_of_programdata_metadata() as u64,
+        )
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+        match state {
+            UpgradeableLoaderState::ProgramData {
+                upgrade_authority_address: Some(authority),
+                ..
+            } if authority == *admin => Ok(()),
+            UpgradeableLoaderState::ProgramData { .. } => Err(EscrowError::NotUpgradeAuthority.into()),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+
+    // Logger koji se upisuje u config mora biti program sa inicijalizovanim state-om
+    fn check_logger<'a>(
+        logger_program_info: &AccountInfo<'a>,
+        logger_state_acc_info: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        check_accounts::<EscrowError>(&[
+            (logger_program_info, AccountSpec::new("logger program").executable()),
+            (logger_state_acc_info, AccountSpec::new("logger state").owner(*logger_program_info.key)),
+        ])?;
+        LoggerState::load(&logger_state_acc_info.data.borrow())?;
         Ok(())
     }
 }
@@ -1,55 +1,562 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use logger_contract::{
+    pod::{PodU64, ZeroCopy},
+    state::{MessageData, MessageKind},
+};
 use solana_program::{
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
+    account_info::AccountInfo, bpf_loader_upgradeable, hash::hashv, program_error::ProgramError, pubkey::Pubkey,
 };
-use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
 
+use crate::error::EscrowError;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
 pub struct EscrowState {
-    pub is_initialized: bool,
+    pub discriminator: [u8; 8],
     pub token_mint: Pubkey,
     pub escrow_vault_account: Pubkey, // SPL Token account (PDA) koji drži tokene
-    pub total_deposited: u64,
+    pub total_deposited: PodU64,
+}
+
+impl ZeroCopy for EscrowState {
+    const DISCRIMINATOR: [u8; 8] = *b"escrow\0\0";
+}
+
+impl EscrowState {
+    // 8 + 32 + 32 + 8 = 80 bajtova
+    pub const LEN: usize = size_of::<EscrowState>();
+
+    pub fn new(token_mint: Pubkey, escrow_vault_account: Pubkey, total_deposited: u64) -> Self {
+        EscrowState {
+            discriminator: Self::DISCRIMINATOR,
+            token_mint,
+            escrow_vault_account,
+            total_deposited: total_deposited.into(),
+        }
+    }
+
+    pub fn find_address(token_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"escrow", token_mint.as_ref()], program_id)
+    }
+
+    pub fn find_vault_address(token_mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"vault", token_mint.as_ref()], program_id)
+    }
+}
+
+/// One depositor's share of an escrow, PDA ["position", mint, owner]. The
+/// owner may name a delegate that withdraws on their behalf.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Position {
+    pub discriminator: [u8; 8],
+    pub owner: Pubkey,
+    pub token_mint: Pubkey,
+    pub amount: PodU64,
+    // Pubkey::default() kada delegat nije postavljen
+    pub delegate: Pubkey,
+    // Preostali iznos za delegata, u64::MAX bez limita
+    pub delegate_cap: PodU64,
+    // Unix timestamp posle kog delegat ne vazi, u64::MAX bez isteka
+    pub delegate_expires_at: PodU64,
+}
+
+impl ZeroCopy for Position {
+    const DISCRIMINATOR: [u8; 8] = *b"position";
+}
+
+impl Position {
+    // 8 + 32 + 32 + 8 + 32 + 8 + 8 = 128 bajtova
+    pub const LEN: usize = size_of::<Position>();
+
+    pub fn new(owner: Pubkey, token_mint: Pubkey) -> Self {
+        Position {
+            discriminator: Self::DISCRIMINATOR,
+            owner,
+            token_mint,
+            ..Position::default()
+        }
+    }
+
+    pub fn set_delegate(&mut self, delegate: Pubkey, cap: Option<u64>, expires_at: Option<u64>) {
+        self.delegate = delegate;
+        self.delegate_cap = cap.unwrap_or(u64::MAX).into();
+        self.delegate_expires_at = expires_at.unwrap_or(u64::MAX).into();
+    }
+
+    pub fn delegate(&self) -> Option<Pubkey> {
+        Some(self.delegate).filter(|delegate| *delegate != Pubkey::default())
+    }
+
+    pub fn delegate_cap(&self) -> Option<u64> {
+        Some(self.delegate_cap.get()).filter(|cap| *cap != u64::MAX)
+    }
+
+    pub fn delegate_expires_at(&self) -> Option<u64> {
+        Some(self.delegate_expires_at.get()).filter(|expires_at| *expires_at != u64::MAX)
+    }
+
+    pub fn find_address(token_mint: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"position", token_mint.as_ref(), owner.as_ref()], program_id)
+    }
+}
+
+/// Token grant held in the escrow vault, PDA ["vesting", mint, grantor,
+/// beneficiary]. Nothing unlocks before `cliff`; from then on the grant
+/// unlocks linearly between `start` and `end` (unix seconds).
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Vesting {
+    pub discriminator: [u8; 8],
+    pub grantor: Pubkey,
+    pub beneficiary: Pubkey,
+    pub token_mint: Pubkey,
+    // Posle opoziva samo deo koji je do tada otkljucan
+    pub total_amount: PodU64,
+    pub claimed: PodU64,
+    pub start: PodU64,
+    pub cliff: PodU64,
+    pub end: PodU64,
+    pub revocable: u8,
+    pub padding: [u8; 7],
 }
 
-impl Sealed for EscrowState {}
+impl ZeroCopy for Vesting {
+    const DISCRIMINATOR: [u8; 8] = *b"vesting\0";
+
+    fn validate(&self) -> Result<(), ProgramError> {
+        if self.start.get() > self.cliff.get()
+            || self.cliff.get() > self.end.get()
+            || self.claimed.get() > self.total_amount.get()
+            || self.revocable > 1
+        {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+}
+
+impl Vesting {
+    // 8 + 32 + 32 + 32 + 8 * 5 + 1 + 7 = 152 bajta
+    pub const LEN: usize = size_of::<Vesting>();
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        grantor: Pubkey,
+        beneficiary: Pubkey,
+        token_mint: Pubkey,
+        amount: u64,
+        start: u64,
+        cliff: u64,
+        end: u64,
+        revocable: bool,
+    ) -> Result<Self, ProgramError> {
+        if amount == 0 || start > cliff || cliff > end {
+            return Err(EscrowError::InvalidVestingSchedule.into());
+        }
+        Ok(Vesting {
+            discriminator: Self::DISCRIMINATOR,
+            grantor,
+            beneficiary,
+            token_mint,
+            total_amount: amount.into(),
+            start: start.into(),
+            cliff: cliff.into(),
+            end: end.into(),
+            revocable: revocable as u8,
+            ..Vesting::default()
+        })
+    }
+
+    /// Amount unlocked at `now`, claimed or not.
+    pub fn vested_at(&self, now: u64) -> u64 {
+        let total = self.total_amount.get();
+        let (start, end) = (self.start.get(), self.end.get());
+        if now < self.cliff.get() {
+            0
+        } else if now >= end {
+            total
+        } else {
+            // cliff >= start, pa je now - start < end - start
+            (total as u128 * (now - start) as u128 / (end - start) as u128) as u64
+        }
+    }
+
+    pub fn claimable_at(&self, now: u64) -> u64 {
+        self.vested_at(now).saturating_sub(self.claimed.get())
+    }
+
+    /// Cuts the grant down to what has vested by `now` and returns the
+    /// unvested remainder owed back to the grantor.
+    pub fn revoke(&mut self, now: u64) -> u64 {
+        let vested = self.vested_at(now);
+        let unvested = self.total_amount.get() - vested;
+        // Otkljucani deo ostaje dostupan odmah, bez daljeg rasporeda
+        let end = now.clamp(self.start.get(), self.end.get());
+        self.total_amount = vested.into();
+        self.cliff = end.into();
+        self.end = end.into();
+        self.revocable = 0;
+        unvested
+    }
+
+    /// Whether every token of the grant has left the vault.
+    pub fn is_settled(&self) -> bool {
+        self.claimed.get() == self.total_amount.get()
+    }
 
-impl IsInitialized for EscrowState {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    pub fn find_address(
+        token_mint: &Pubkey,
+        grantor: &Pubkey,
+        beneficiary: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"vesting", token_mint.as_ref(), grantor.as_ref(), beneficiary.as_ref()],
+            program_id,
+        )
     }
 }
 
-impl Pack for EscrowState {
-    // 1 bajt + 32 + 32 + 8 = 73 bajta
-    const LEN: usize = 73;
+/// Seconds the arbiter has to rule on a dispute before the payer can take a
+/// refund.
+pub const DISPUTE_WINDOW: u64 = 7 * 24 * 60 * 60;
 
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let src = array_ref![src, 0, EscrowState::LEN];
-        let (is_init_arr, mint_arr, vault_arr, deposited_arr) = array_refs![src, 1, 32, 32, 8];
+/// Payment held in the vault for `payee`, PDA ["agreement", payer, id]. The
+/// payer releases it, the arbiter rules on a dispute, and after `deadline`
+/// (or `dispute_deadline` once disputed) it refunds to the payer.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Agreement {
+    pub discriminator: [u8; 8],
+    pub payer: Pubkey,
+    pub payee: Pubkey,
+    // Pubkey::default() kada sporazum nema arbitra
+    pub arbiter: Pubkey,
+    pub token_mint: Pubkey,
+    pub id: PodU64,
+    pub amount: PodU64,
+    pub deadline: PodU64,
+    // Postavlja ga OpenDispute na max(deadline, now + DISPUTE_WINDOW), 0 dok nema spora
+    pub dispute_deadline: PodU64,
+    pub disputed: u8,
+    pub padding: [u8; 7],
+}
+
+impl ZeroCopy for Agreement {
+    const DISCRIMINATOR: [u8; 8] = *b"agreemnt";
+
+    fn validate(&self) -> Result<(), ProgramError> {
+        if self.disputed > 1 {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+}
+
+impl Agreement {
+    // 8 + 32 * 4 + 8 * 4 + 1 + 7 = 176 bajtova
+    pub const LEN: usize = size_of::<Agreement>();
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        payer: Pubkey,
+        payee: Pubkey,
+        arbiter: Pubkey,
+        token_mint: Pubkey,
+        id: u64,
+        amount: u64,
+        deadline: u64,
+        now: u64,
+    ) -> Result<Self, ProgramError> {
+        if amount == 0 || deadline <= now || payer == payee || arbiter == payer || arbiter == payee {
+            return Err(EscrowError::InvalidAgreement.into());
+        }
+        Ok(Agreement {
+            discriminator: Self::DISCRIMINATOR,
+            payer,
+            payee,
+            arbiter,
+            token_mint,
+            id: id.into(),
+            amount: amount.into(),
+            deadline: deadline.into(),
+            ..Agreement::default()
+        })
+    }
+
+    pub fn has_arbiter(&self) -> bool {
+        self.arbiter != Pubkey::default()
+    }
+
+    pub fn is_disputed(&self) -> bool {
+        self.disputed == 1
+    }
+
+    /// Unix time from which `Refund` returns the payment to the payer.
+    pub fn refundable_at(&self) -> u64 {
+        if self.is_disputed() {
+            self.dispute_deadline.get()
+        } else {
+            self.deadline.get()
+        }
+    }
 
-        let is_initialized = match is_init_arr {
-            [0] => false,
-            [1] => true,
-            _ => return Err(ProgramError::InvalidAccountData),
+    pub fn find_address(payer: &Pubkey, id: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"agreement", payer.as_ref(), &id.to_le_bytes()], program_id)
+    }
+}
+
+/// Most (mint, amount) legs a batch instruction carries.
+pub const MAX_BATCH_LEGS: usize = 8;
+
+/// One token movement of a batch, read like a logger message: deposits go
+/// from the user to a vault, withdrawals from a vault to the recipient.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct BatchTransfer {
+    pub from_pubkey: Pubkey,
+    pub to_pubkey: Pubkey,
+    pub amount: PodU64,
+}
+
+/// Legs of one batch, PDA ["batch", message]. A batch posts a single
+/// `MessageKind::Batch` logger message between its signer and this account,
+/// carrying the leg count as its amount; the legs record which vault and how
+/// much of each mint moved.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Batch {
+    pub discriminator: [u8; 8],
+    // Logger poruka ciji je ovo batch
+    pub message: Pubkey,
+    pub leg_count: u8,
+    pub padding: [u8; 7],
+    pub legs: [BatchTransfer; MAX_BATCH_LEGS],
+}
+
+impl ZeroCopy for Batch {
+    const DISCRIMINATOR: [u8; 8] = *b"batch\0\0\0";
+
+    fn validate(&self) -> Result<(), ProgramError> {
+        if self.leg_count == 0 || self.leg_count as usize > MAX_BATCH_LEGS {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+}
+
+impl Batch {
+    // 8 + 32 + 1 + 7 + 72 * 8 = 624 bajtova
+    pub const LEN: usize = size_of::<Batch>();
+
+    pub fn new(message: Pubkey, legs: &[BatchTransfer]) -> Self {
+        let mut batch = Batch {
+            discriminator: Self::DISCRIMINATOR,
+            message,
+            leg_count: legs.len() as u8,
+            ..Batch::default()
         };
+        batch.legs[..legs.len()].copy_from_slice(legs);
+        batch
+    }
+
+    pub fn legs(&self) -> &[BatchTransfer] {
+        &self.legs[..(self.leg_count as usize).min(MAX_BATCH_LEGS)]
+    }
 
-        Ok(EscrowState {
-            is_initialized,
-            token_mint: Pubkey::new_from_array(*mint_arr),
-            escrow_vault_account: Pubkey::new_from_array(*vault_arr),
-            total_deposited: u64::from_le_bytes(*deposited_arr),
+    /// The combined `message` split into one `Transfer` message per leg,
+    /// each keeping the sequence of the batch, for readers that account per vault.
+    pub fn leg_messages<'a>(&'a self, message: &'a MessageData) -> impl Iterator<Item = MessageData> + 'a {
+        self.legs().iter().map(move |leg| MessageData {
+            from_pubkey: leg.from_pubkey,
+            to_pubkey: leg.to_pubkey,
+            amount: leg.amount,
+            kind: MessageKind::Transfer as u8,
+            ..*message
         })
     }
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let dst = array_mut_ref![dst, 0, EscrowState::LEN];
-        let (is_init_dst, mint_dst, vault_dst, deposited_dst) =
-            mut_array_refs![dst, 1, 32, 32, 8];
+    pub fn find_address(message: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"batch", message.as_ref()], program_id)
+    }
+}
+
+/// Program-wide settings, PDA ["config"]. Deposits and withdrawals only
+/// CPI into the logger program and state recorded here.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EscrowConfig {
+    pub discriminator: [u8; 8],
+    pub admin: Pubkey,
+    pub logger_program: Pubkey,
+    pub logger_state: Pubkey,
+    // Multisig koji odobrava velika povlacenja, Pubkey::default() kada nije postavljen
+    pub withdrawal_multisig: Pubkey,
+    pub large_withdrawal_threshold: PodU64,
+}
+
+impl ZeroCopy for EscrowConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"config\0\0";
+}
+
+impl EscrowConfig {
+    // 8 + 32 + 32 + 32 + 32 + 8 = 144 bajta
+    pub const LEN: usize = size_of::<EscrowConfig>();
+
+    pub fn new(admin: Pubkey, logger_program: Pubkey, logger_state: Pubkey) -> Self {
+        EscrowConfig {
+            discriminator: Self::DISCRIMINATOR,
+            admin,
+            logger_program,
+            logger_state,
+            ..EscrowConfig::default()
+        }
+    }
+
+    /// The multisig that must approve withdrawals above
+    /// `large_withdrawal_threshold`, if any.
+    pub fn withdrawal_multisig(&self) -> Option<Pubkey> {
+        Some(self.withdrawal_multisig).filter(|multisig| *multisig != Pubkey::default())
+    }
+
+    pub fn requires_multisig(&self, amount: u64) -> Option<Pubkey> {
+        self.withdrawal_multisig()
+            .filter(|_| amount > self.large_withdrawal_threshold.get())
+    }
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"config"], program_id)
+    }
+
+    /// ProgramData account of an upgradeable escrow deployment; only its
+    /// upgrade authority may initialize the config.
+    pub fn program_data_address(program_id: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id()).0
+    }
+}
+
+/// Most signers a multisig can hold.
+pub const MAX_SIGNERS: usize = 11;
+
+/// M-of-N authority owned by the escrow program. Its address stands in for
+/// the config admin or the withdrawal approver; `threshold` of `signers` must
+/// sign in the same transaction or approve a `Proposal`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Multisig {
+    pub discriminator: [u8; 8],
+    pub threshold: u8,
+    pub signer_count: u8,
+    pub padding: [u8; 6],
+    pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl ZeroCopy for Multisig {
+    const DISCRIMINATOR: [u8; 8] = *b"multisig";
+
+    fn validate(&self) -> Result<(), ProgramError> {
+        let count = self.signer_count as usize;
+        if count > MAX_SIGNERS || self.threshold == 0 || self.threshold > self.signer_count {
+            return Err(EscrowError::InvalidMultisig.into());
+        }
+        Ok(())
+    }
+}
+
+impl Multisig {
+    // 8 + 1 + 1 + 6 + 32 * 11 = 368 bajtova
+    pub const LEN: usize = size_of::<Multisig>();
+
+    pub fn new(threshold: u8, signers: &[Pubkey]) -> Result<Self, ProgramError> {
+        if signers.len() > MAX_SIGNERS
+            || signers.iter().enumerate().any(|(i, signer)| signers[..i].contains(signer))
+        {
+            return Err(EscrowError::InvalidMultisig.into());
+        }
+        let mut multisig = Multisig {
+            discriminator: Self::DISCRIMINATOR,
+            threshold,
+            signer_count: signers.len() as u8,
+            ..Multisig::default()
+        };
+        multisig.signers[..signers.len()].copy_from_slice(signers);
+        multisig.validate()?;
+        Ok(multisig)
+    }
+
+    pub fn signers(&self) -> &[Pubkey] {
+        &self.signers[..(self.signer_count as usize).min(MAX_SIGNERS)]
+    }
+
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers().iter().position(|signer| signer == key)
+    }
+
+    /// Number of distinct multisig signers among `accounts` that signed.
+    pub fn count_signatures(&self, accounts: &[AccountInfo]) -> usize {
+        let mut signed = [false; MAX_SIGNERS];
+        for account in accounts.iter().filter(|account| account.is_signer) {
+            if let Some(index) = self.signer_index(account.key) {
+                signed[index] = true;
+            }
+        }
+        signed.iter().filter(|signed| **signed).count()
+    }
+}
+
+/// Pending multisig approval of one escrow instruction, PDA
+/// ["proposal", multisig, instruction_hash]. Executing it runs the instruction
+/// as if the multisig had signed and closes the proposal.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Proposal {
+    pub discriminator: [u8; 8],
+    pub multisig: Pubkey,
+    pub instruction_hash: [u8; 32],
+    // Dobija lamporte kada se predlog zatvori
+    pub proposer: Pubkey,
+    // Bit i je postavljen kada je signers[i] odobrio
+    pub approvals: PodU64,
+}
+
+impl ZeroCopy for Proposal {
+    const DISCRIMINATOR: [u8; 8] = *b"proposal";
+}
+
+impl Proposal {
+    // 8 + 32 + 32 + 32 + 8 = 112 bajtova
+    pub const LEN: usize = size_of::<Proposal>();
+
+    pub fn new(multisig: Pubkey, instruction_hash: [u8; 32], proposer: Pubkey) -> Self {
+        Proposal {
+            discriminator: Self::DISCRIMINATOR,
+            multisig,
+            instruction_hash,
+            proposer,
+            ..Proposal::default()
+        }
+    }
+
+    pub fn approve(&mut self, signer_index: usize) {
+        self.approvals = (self.approvals.get() | 1 << signer_index).into();
+    }
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.get().count_ones()
+    }
+
+    /// Hash of an escrow instruction's data and account keys; the proposal
+    /// only executes with exactly these.
+    pub fn instruction_hash<'k>(data: &[u8], keys: impl IntoIterator<Item = &'k Pubkey>) -> [u8; 32] {
+        let mut parts = vec![data];
+        parts.extend(keys.into_iter().map(|key| key.as_ref()));
+        hashv(&parts).to_bytes()
+    }
 
-        is_init_dst[0] = self.is_initialized as u8;
-        mint_dst.copy_from_slice(self.token_mint.as_ref());
-        vault_dst.copy_from_slice(self.escrow_vault_account.as_ref());
-        *deposited_dst = self.total_deposited.to_le_bytes();
+    pub fn find_address(multisig: &Pubkey, instruction_hash: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[b"proposal", multisig.as_ref(), instruction_hash], program_id)
     }
 }
@@ -0,0 +1,364 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction, state::DISPUTE_WINDOW};
+use solana_sdk::{
+    instruction::AccountMeta,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+struct Parties {
+    mint: Pubkey,
+    payer: Keypair,
+    payer_tokens: Pubkey,
+    payee: Keypair,
+    payee_tokens: Pubkey,
+    arbiter: Keypair,
+}
+
+/// Mint with a payer holding 10_000 tokens, a payee with an empty token
+/// account and an arbiter.
+async fn parties(harness: &mut Harness) -> Parties {
+    let mint = harness.create_mint().await;
+    let payer = harness.create_user().await;
+    let payer_tokens = harness
+        .create_token_account(&mint, &payer.pubkey(), 10_000)
+        .await;
+    let payee = harness.create_user().await;
+    let payee_tokens = harness
+        .create_token_account(&mint, &payee.pubkey(), 0)
+        .await;
+    let arbiter = harness.create_user().await;
+    Parties {
+        mint,
+        payer,
+        payer_tokens,
+        payee,
+        payee_tokens,
+        arbiter,
+    }
+}
+
+#[tokio::test]
+async fn payer_release_pays_the_payee() {
+    let mut harness = Harness::start().await;
+    let p = parties(&mut harness).await;
+    let now = harness.now().await;
+
+    for (amount, deadline, arbiter) in [
+        (0, now + 100, None),
+        (1_000, now, None),
+        (1_000, now + 100, Some(p.payee.pubkey())),
+    ] {
+        let err = harness
+            .open_agreement(&p.payer, &p.payer_tokens, &p.payee.pubkey(), &p.mint, 1, amount, deadline, arbiter)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            program_error(EscrowError::InvalidAgreement.into()),
+            "{amount} until {deadline}"
+        );
+    }
+
+    harness
+        .open_agreement(&p.payer, &p.payer_tokens, &p.payee.pubkey(), &p.mint, 1, 1_000, now + 100, None)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payer_tokens).await, 9_000);
+    assert_eq!(harness.total_deposited(&p.mint).await, 1_000);
+    let agreement = harness.agreement(&p.payer.pubkey(), 1).await.unwrap();
+    assert_eq!(agreement.payee, p.payee.pubkey());
+    assert_eq!(agreement.arbiter, Pubkey::default());
+    assert_eq!(agreement.amount.get(), 1_000);
+
+    let message = harness.message(1).await.unwrap();
+    assert_eq!(message.from_pubkey, p.payer.pubkey());
+    assert_eq!(message.to_pubkey, harness.vault_address(&p.mint));
+    assert_eq!(message.amount.get(), 1_000);
+
+    let err = harness
+        .open_agreement(&p.payer, &p.payer_tokens, &p.payee.pubkey(), &p.mint, 1, 1_000, now + 100, None)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AlreadyInitialized.into()));
+
+    // Payee ne moze sam sebi da oslobodi sredstva
+    let message = harness.next_message_address().await;
+    let mut instruction = escrow_instruction::release(
+        &harness.escrow_program_id,
+        &harness.logger_program_id,
+        &p.payer.pubkey(),
+        &p.payee_tokens,
+        &p.mint,
+        1,
+        &harness.logger_state,
+        &message,
+    );
+    instruction.accounts[0] = AccountMeta::new(p.payee.pubkey(), true);
+    let err = harness
+        .process(&[instruction], &[&p.payee])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotAgreementParty.into()));
+
+    let err = harness
+        .release(&p.payer, &p.payer_tokens, &p.mint, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::RecipientMismatch.into()));
+
+    let sequence = harness.logger_sequence().await;
+    harness
+        .release(&p.payer, &p.payee_tokens, &p.mint, 1)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payee_tokens).await, 1_000);
+    assert_eq!(harness.total_deposited(&p.mint).await, 0);
+    assert!(harness.agreement(&p.payer.pubkey(), 1).await.is_none());
+
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.vault_address(&p.mint));
+    assert_eq!(message.to_pubkey, p.payee.pubkey());
+    assert_eq!(message.amount.get(), 1_000);
+}
+
+#[tokio::test]
+async fn arbiter_rules_on_a_dispute() {
+    let mut harness = Harness::start().await;
+    let p = parties(&mut harness).await;
+    let outsider = harness.create_user().await;
+    let now = harness.now().await;
+    harness
+        .open_agreement(
+            &p.payer,
+            &p.payer_tokens,
+            &p.payee.pubkey(),
+            &p.mint,
+            1,
+            1_000,
+            now + 100,
+            Some(p.arbiter.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    let err = harness
+        .resolve(&p.arbiter, &p.payee_tokens, &p.payer.pubkey(), &p.mint, 1, true)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AgreementNotDisputed.into()));
+    let err = harness
+        .open_dispute(&outsider, &p.payer.pubkey(), 1)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotAgreementParty.into()));
+
+    let sequence = harness.logger_sequence().await;
+    harness
+        .open_dispute(&p.payee, &p.payer.pubkey(), 1)
+        .await
+        .unwrap();
+    assert_eq!(harness.agreement(&p.payer.pubkey(), 1).await.unwrap().disputed, 1);
+    let agreement_address =
+        escrow_contract::state::Agreement::find_address(&p.payer.pubkey(), 1, &harness.escrow_program_id).0;
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, agreement_address);
+    assert_eq!(message.to_pubkey, p.arbiter.pubkey());
+    assert_eq!(message.amount.get(), 1_000);
+
+    let err = harness
+        .open_dispute(&p.payer, &p.payer.pubkey(), 1)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AgreementDisputed.into()));
+
+    // Spor odlaze povracaj i posle roka sporazuma
+    harness.set_clock(now + 100).await;
+    let err = harness
+        .refund(&outsider, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 1)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DeadlineNotReached.into()));
+
+    let err = harness
+        .resolve(&outsider, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 1, false)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotArbiter.into()));
+    let err = harness
+        .resolve(&p.arbiter, &p.payee_tokens, &p.payer.pubkey(), &p.mint, 1, false)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::RecipientMismatch.into()));
+
+    let sequence = harness.logger_sequence().await;
+    harness
+        .resolve(&p.arbiter, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 1, false)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payer_tokens).await, 10_000);
+    assert_eq!(harness.token_balance(harness.vault_address(&p.mint)).await, 0);
+    assert!(harness.agreement(&p.payer.pubkey(), 1).await.is_none());
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.vault_address(&p.mint));
+    assert_eq!(message.to_pubkey, p.payer.pubkey());
+
+    // Bez arbitra nema spora
+    harness
+        .open_agreement(&p.payer, &p.payer_tokens, &p.payee.pubkey(), &p.mint, 2, 500, now + 200, None)
+        .await
+        .unwrap();
+    let err = harness
+        .open_dispute(&p.payee, &p.payer.pubkey(), 2)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotArbiter.into()));
+}
+
+#[tokio::test]
+async fn undisputed_agreement_refunds_after_the_deadline() {
+    let mut harness = Harness::start().await;
+    let p = parties(&mut harness).await;
+    let keeper = harness.create_user().await;
+    let now = harness.now().await;
+    harness
+        .open_agreement(
+            &p.payer,
+            &p.payer_tokens,
+            &p.payee.pubkey(),
+            &p.mint,
+            7,
+            2_500,
+            now + 100,
+            Some(p.arbiter.pubkey()),
+        )
+        .await
+        .unwrap();
+
+    let err = harness
+        .refund(&keeper, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 7)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DeadlineNotReached.into()));
+
+    harness.set_clock(now + 100).await;
+    let err = harness
+        .open_dispute(&p.payee, &p.payer.pubkey(), 7)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DeadlinePassed.into()));
+    let err = harness
+        .refund(&keeper, &p.payee_tokens, &p.payer.pubkey(), &p.mint, 7)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::RecipientMismatch.into()));
+
+    let sequence = harness.logger_sequence().await;
+    let payer_lamports = harness.banks.get_balance(p.payer.pubkey()).await.unwrap();
+    harness
+        .refund(&keeper, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 7)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payer_tokens).await, 10_000);
+    assert_eq!(harness.total_deposited(&p.mint).await, 0);
+    assert!(harness.agreement(&p.payer.pubkey(), 7).await.is_none());
+    // Rent sporazuma se vraca payer-u, ne pozivaocu
+    assert!(harness.banks.get_balance(p.payer.pubkey()).await.unwrap() > payer_lamports);
+
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.vault_address(&p.mint));
+    assert_eq!(message.to_pubkey, p.payer.pubkey());
+    assert_eq!(message.amount.get(), 2_500);
+}
+
+#[tokio::test]
+async fn unanswered_dispute_refunds_after_the_dispute_window() {
+    let mut harness = Harness::start().await;
+    let p = parties(&mut harness).await;
+    let now = harness.now().await;
+    harness
+        .open_agreement(
+            &p.payer,
+            &p.payer_tokens,
+            &p.payee.pubkey(),
+            &p.mint,
+            3,
+            1_200,
+            now + 100,
+            Some(p.arbiter.pubkey()),
+        )
+        .await
+        .unwrap();
+    harness.set_clock(now + 50).await;
+    harness
+        .open_dispute(&p.payee, &p.payer.pubkey(), 3)
+        .await
+        .unwrap();
+    let agreement = harness.agreement(&p.payer.pubkey(), 3).await.unwrap();
+    let dispute_deadline = now + 50 + DISPUTE_WINDOW;
+    assert_eq!(agreement.dispute_deadline.get(), dispute_deadline);
+
+    harness.set_clock(dispute_deadline - 1).await;
+    let err = harness
+        .refund(&p.payer, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 3)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DeadlineNotReached.into()));
+
+    // Arbitar se nije javio, pa payer dobija povracaj
+    harness.set_clock(dispute_deadline).await;
+    harness
+        .refund(&p.payer, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 3)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payer_tokens).await, 10_000);
+    assert_eq!(harness.total_deposited(&p.mint).await, 0);
+    assert!(harness.agreement(&p.payer.pubkey(), 3).await.is_none());
+}
+
+#[tokio::test]
+async fn early_dispute_keeps_the_agreement_deadline() {
+    let mut harness = Harness::start().await;
+    let p = parties(&mut harness).await;
+    let now = harness.now().await;
+    let deadline = now + 3 * DISPUTE_WINDOW;
+    harness
+        .open_agreement(
+            &p.payer,
+            &p.payer_tokens,
+            &p.payee.pubkey(),
+            &p.mint,
+            4,
+            1_200,
+            deadline,
+            Some(p.arbiter.pubkey()),
+        )
+        .await
+        .unwrap();
+    // Spor otvoren odmah ne sme da omoguci povracaj pre roka sporazuma
+    harness.set_clock(now + 50).await;
+    harness
+        .open_dispute(&p.payer, &p.payer.pubkey(), 4)
+        .await
+        .unwrap();
+    let agreement = harness.agreement(&p.payer.pubkey(), 4).await.unwrap();
+    assert_eq!(agreement.dispute_deadline.get(), deadline);
+    assert_eq!(agreement.refundable_at(), deadline);
+
+    harness.set_clock(now + 50 + DISPUTE_WINDOW).await;
+    let err = harness
+        .refund(&p.payer, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 4)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DeadlineNotReached.into()));
+
+    harness.set_clock(deadline).await;
+    harness
+        .refund(&p.payer, &p.payer_tokens, &p.payer.pubkey(), &p.mint, 4)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(p.payer_tokens).await, 10_000);
+    assert!(harness.agreement(&p.payer.pubkey(), 4).await.is_none());
+}
@@ -0,0 +1,217 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction};
+use logger_contract::state::MessageKind;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+/// Two mints with a user holding 1_000 of each.
+async fn two_mints(harness: &mut Harness) -> (Keypair, [(Pubkey, Pubkey); 2]) {
+    let user = harness.create_user().await;
+    let mut mints = [(Pubkey::default(), Pubkey::default()); 2];
+    for (mint, tokens) in &mut mints {
+        *mint = harness.create_mint().await;
+        *tokens = harness
+            .create_token_account(mint, &user.pubkey(), 1_000)
+            .await;
+    }
+    (user, mints)
+}
+
+#[tokio::test]
+async fn batch_deposit_credits_every_mint_or_none() {
+    let mut harness = Harness::start().await;
+    let (user, [(mint_a, tokens_a), (mint_b, tokens_b)]) = two_mints(&mut harness).await;
+
+    // Drugi leg prelazi stanje, pa se ni prvi ne izvrsava
+    let err = harness
+        .batch_deposit(&user, &[(tokens_a, mint_a, 300), (tokens_b, mint_b, 1_500)])
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, TransactionError::InstructionError(0, _)),
+        "{err:?}"
+    );
+    assert_eq!(harness.token_balance(tokens_a).await, 1_000);
+    assert!(harness.escrow_state(&mint_a).await.is_none());
+    assert_eq!(harness.logger_sequence().await, 0);
+
+    // Mint iz podataka mora da odgovara mint nalogu svog lega
+    let mut instruction = harness
+        .batch_deposit_instruction(&user.pubkey(), &[(tokens_a, mint_a, 300)])
+        .await;
+    instruction.data = escrow_instruction::EscrowInstruction::BatchDeposit {
+        legs: vec![(mint_b, 300)],
+    }
+    .pack();
+    let err = harness
+        .process(&[instruction], &[&user])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::IncorrectAccountAddress.into()));
+
+    harness
+        .batch_deposit(&user, &[(tokens_a, mint_a, 300), (tokens_b, mint_b, 500)])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(tokens_a).await, 700);
+    assert_eq!(harness.token_balance(tokens_b).await, 500);
+    assert_eq!(harness.total_deposited(&mint_a).await, 300);
+    assert_eq!(harness.total_deposited(&mint_b).await, 500);
+    assert_eq!(
+        harness
+            .position(&mint_b, &user.pubkey())
+            .await
+            .unwrap()
+            .amount
+            .get(),
+        500
+    );
+
+    // Jedna poruka za ceo batch, a legovi po vault-u ostaju u batch nalogu
+    assert_eq!(harness.logger_sequence().await, 1);
+    let message = harness.message(1).await.unwrap();
+    assert_eq!(message.from_pubkey, user.pubkey());
+    assert_eq!(message.to_pubkey, harness.batch_address(1));
+    // Iznosi razlicitih mint-ova se ne sabiraju, poruka nosi broj legova
+    assert_eq!(message.kind(), MessageKind::Batch);
+    assert_eq!(message.amount.get(), 2);
+    let batch = harness.batch(1).await.unwrap();
+    assert_eq!(batch.message, harness.message_address(1));
+    let legs: Vec<_> = batch
+        .leg_messages(&message)
+        .map(|leg| (leg.from_pubkey, leg.to_pubkey, leg.amount.get(), leg.sequence.get()))
+        .collect();
+    assert!(batch
+        .leg_messages(&message)
+        .all(|leg| leg.kind() == MessageKind::Transfer));
+    assert_eq!(
+        legs,
+        [
+            (user.pubkey(), harness.vault_address(&mint_a), 300, 1),
+            (user.pubkey(), harness.vault_address(&mint_b), 500, 1),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn batch_withdraw_pays_every_leg_or_none() {
+    let mut harness = Harness::start().await;
+    let (user, [(mint_a, tokens_a), (mint_b, tokens_b)]) = two_mints(&mut harness).await;
+    harness
+        .batch_deposit(&user, &[(tokens_a, mint_a, 600), (tokens_b, mint_b, 400)])
+        .await
+        .unwrap();
+
+    let err = harness
+        .batch_withdraw(&user, &[(tokens_a, mint_a, 100), (tokens_b, mint_b, 401)])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::InsufficientAmount.into()));
+    assert_eq!(harness.total_deposited(&mint_a).await, 600);
+
+    // Tudje pozicije se ne mogu prazniti kroz batch
+    let outsider = harness.create_user().await;
+    let outsider_tokens = harness
+        .create_token_account(&mint_a, &outsider.pubkey(), 0)
+        .await;
+    let message = harness.next_message_address().await;
+    let instruction = escrow_instruction::batch_withdraw(
+        &harness.escrow_program_id,
+        &harness.logger_program_id,
+        &outsider.pubkey(),
+        &user.pubkey(),
+        &harness.logger_state,
+        &message,
+        &Harness::batch_legs(&[(outsider_tokens, mint_a, 100)]),
+    );
+    let err = harness
+        .process(&[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotPositionAuthority.into()));
+
+    let sequence = harness.logger_sequence().await;
+    harness
+        .batch_withdraw(&user, &[(tokens_a, mint_a, 250), (tokens_b, mint_b, 400)])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(tokens_a).await, 650);
+    assert_eq!(harness.token_balance(tokens_b).await, 1_000);
+    assert_eq!(harness.total_deposited(&mint_a).await, 350);
+    assert_eq!(harness.total_deposited(&mint_b).await, 0);
+
+    assert_eq!(harness.logger_sequence().await, sequence + 1);
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.batch_address(sequence + 1));
+    assert_eq!(message.to_pubkey, user.pubkey());
+    assert_eq!(message.kind(), MessageKind::Batch);
+    assert_eq!(message.amount.get(), 2);
+    let batch = harness.batch(sequence + 1).await.unwrap();
+    let legs: Vec<_> = batch
+        .leg_messages(&message)
+        .map(|leg| (leg.from_pubkey, leg.to_pubkey, leg.amount.get()))
+        .collect();
+    assert_eq!(
+        legs,
+        [
+            (harness.vault_address(&mint_a), user.pubkey(), 250),
+            (harness.vault_address(&mint_b), user.pubkey(), 400),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn large_batch_leg_needs_the_withdrawal_multisig() {
+    let mut harness = Harness::start().await;
+    let (user, [(mint_a, tokens_a), (mint_b, tokens_b)]) = two_mints(&mut harness).await;
+    let signer = harness.create_user().await;
+    let multisig = harness.create_multisig(1, &[signer.pubkey()]).await.unwrap();
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::set_withdrawal_policy(
+        &harness.escrow_program_id,
+        &payer,
+        &multisig,
+        100,
+        &[],
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+    harness
+        .batch_deposit(&user, &[(tokens_a, mint_a, 500), (tokens_b, mint_b, 500)])
+        .await
+        .unwrap();
+
+    let legs = [(tokens_a, mint_a, 50), (tokens_b, mint_b, 150)];
+    let err = harness.batch_withdraw(&user, &legs).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::LargeWithdrawalNotApproved.into())
+    );
+    // Legovi istog minta ispod threshold-a se sabiraju
+    let split = [(tokens_a, mint_a, 60), (tokens_a, mint_a, 60)];
+    let err = harness.batch_withdraw(&user, &split).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::LargeWithdrawalNotApproved.into())
+    );
+    harness
+        .batch_withdraw(&user, &[(tokens_a, mint_a, 50), (tokens_b, mint_b, 50)])
+        .await
+        .unwrap();
+
+    let instruction = harness
+        .batch_withdraw_instruction(&user.pubkey(), &[split[0], split[1], legs[1]])
+        .await;
+    let instruction =
+        escrow_instruction::with_multisig(instruction, &multisig, &[&signer.pubkey()]);
+    harness
+        .process(&[instruction], &[&user, &signer])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(tokens_a).await, 670);
+    assert_eq!(harness.token_balance(tokens_b).await, 700);
+}
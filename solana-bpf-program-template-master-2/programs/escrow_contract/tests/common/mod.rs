@@ -0,0 +1,802 @@
+#![allow(dead_code)]
+
+use escrow_contract::{
+    instruction::{self as escrow_instruction, BatchLeg},
+    processor::EscrowProcessor,
+    state::{Agreement, Batch, EscrowConfig, EscrowState, Multisig, Position, Vesting},
+};
+use logger_contract::{
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{LoggerState, MessageData},
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Both programs loaded into one `ProgramTest` bank with an initialized
+/// logger state and escrow config; the payer is the logger authority and
+/// the config admin.
+pub struct Harness {
+    pub banks: BanksClient,
+    pub payer: Keypair,
+    pub blockhash: Hash,
+    pub escrow_program_id: Pubkey,
+    pub logger_program_id: Pubkey,
+    pub logger_state: Pubkey,
+    context: ProgramTestContext,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let mut harness = Self::launch(Self::program_test()).await;
+        let logger_state = harness.create_logger_state().await;
+        let payer = harness.payer.pubkey();
+        harness
+            .process(
+                &[escrow_instruction::initialize_config(
+                    &harness.escrow_program_id,
+                    &payer,
+                    &harness.logger_program_id,
+                    &logger_state,
+                )],
+                &[],
+            )
+            .await
+            .unwrap();
+        harness.logger_state = logger_state;
+        harness
+    }
+
+    /// Starts with the escrow deployed through the upgradeable loader under
+    /// `upgrade_authority`, with a logger state but no escrow config yet.
+    pub async fn start_deployed(upgrade_authority: Option<Pubkey>) -> Self {
+        let mut program_test = Self::program_test();
+        let program_data = Account::new_data(
+            1_000_000_000,
+            &UpgradeableLoaderState::ProgramData {
+                slot: 0,
+                upgrade_authority_address: upgrade_authority,
+            },
+            &bpf_loader_upgradeable::id(),
+        )
+        .unwrap();
+        program_test.add_account(
+            EscrowConfig::program_data_address(&escrow_contract::id()),
+            program_data,
+        );
+        let mut harness = Self::launch(program_test).await;
+        harness.logger_state = harness.create_logger_state().await;
+        harness
+    }
+
+    /// Starts with the escrow loaded as a builtin, so no ProgramData
+    /// exists, with a logger state but no escrow config yet.
+    pub async fn start_unconfigured() -> Self {
+        let mut harness = Self::launch(Self::program_test()).await;
+        harness.logger_state = harness.create_logger_state().await;
+        harness
+    }
+
+    fn program_test() -> ProgramTest {
+        let mut program_test = ProgramTest::new(
+            "escrow_contract",
+            escrow_contract::id(),
+            processor!(EscrowProcessor::process),
+        );
+        program_test.add_program(
+            "logger_contract",
+            logger_contract::id(),
+            processor!(LoggerProcessor::process),
+        );
+        program_test.prefer_bpf(false);
+        program_test
+    }
+
+    async fn launch(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        Harness {
+            banks: context.banks_client.clone(),
+            payer: context.payer.insecure_clone(),
+            blockhash: context.last_blockhash,
+            escrow_program_id: escrow_contract::id(),
+            logger_program_id: logger_contract::id(),
+            logger_state: Pubkey::default(),
+            context,
+        }
+    }
+
+    /// Creates and initializes a logger state with the payer as authority.
+    pub async fn create_logger_state(&mut self) -> Pubkey {
+        let logger_state = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &logger_state.pubkey(),
+                    Rent::default().minimum_balance(LoggerState::LEN),
+                    LoggerState::LEN as u64,
+                    &self.logger_program_id,
+                ),
+                logger_instruction::initialize(
+                    &self.logger_program_id,
+                    &logger_state.pubkey(),
+                    &payer,
+                ),
+            ],
+            &[&logger_state],
+        )
+        .await
+        .unwrap();
+        logger_state.pubkey()
+    }
+
+    /// Signs with the payer plus `signers` and returns the transaction error, if any.
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        );
+        let result = self
+            .banks
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap());
+        self.blockhash = self
+            .banks
+            .get_new_latest_blockhash(&self.blockhash)
+            .await
+            .unwrap();
+        result
+    }
+
+    /// Creates and initializes a `threshold`-of-`signers` multisig.
+    pub async fn create_multisig(
+        &mut self,
+        threshold: u8,
+        signers: &[Pubkey],
+    ) -> Result<Pubkey, TransactionError> {
+        let multisig = Keypair::new();
+        let payer = self.payer.pubkey();
+        let signers: Vec<&Pubkey> = signers.iter().collect();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &multisig.pubkey(),
+                    Rent::default().minimum_balance(Multisig::LEN),
+                    Multisig::LEN as u64,
+                    &self.escrow_program_id,
+                ),
+                escrow_instruction::initialize_multisig(
+                    &self.escrow_program_id,
+                    &multisig.pubkey(),
+                    &signers,
+                    threshold,
+                ),
+            ],
+            &[&multisig],
+        )
+        .await?;
+        Ok(multisig.pubkey())
+    }
+
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    Rent::default().minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_mint(
+                    &spl_token::id(),
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    0,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates a token account of `mint` owned by `owner` holding `amount`.
+    pub async fn create_token_account(
+        &mut self,
+        mint: &Pubkey,
+        owner: &Pubkey,
+        amount: u64,
+    ) -> Pubkey {
+        let account = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &account.pubkey(),
+                    Rent::default().minimum_balance(spl_token::state::Account::LEN),
+                    spl_token::state::Account::LEN as u64,
+                    &spl_token::id(),
+                ),
+                spl_token::instruction::initialize_account(
+                    &spl_token::id(),
+                    &account.pubkey(),
+                    mint,
+                    owner,
+                )
+                .unwrap(),
+                spl_token::instruction::mint_to(
+                    &spl_token::id(),
+                    mint,
+                    &account.pubkey(),
+                    &payer,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            ],
+            &[&account],
+        )
+        .await
+        .unwrap();
+        account.pubkey()
+    }
+
+    /// A funded keypair that is not the fee payer.
+    pub async fn create_user(&mut self) -> Keypair {
+        let user = Keypair::new();
+        let payer = self.payer.pubkey();
+        self.process(
+            &[system_instruction::transfer(
+                &payer,
+                &user.pubkey(),
+                1_000_000_000,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        user
+    }
+
+    pub async fn logger_sequence(&mut self) -> u64 {
+        let account = self
+            .banks
+            .get_account(self.logger_state)
+            .await
+            .unwrap()
+            .unwrap();
+        LoggerState::load(&account.data).unwrap().sequence.get()
+    }
+
+    pub fn message_address(&self, sequence: u64) -> Pubkey {
+        MessageData::find_address(&self.logger_state, sequence, &self.logger_program_id).0
+    }
+
+    pub async fn next_message_address(&mut self) -> Pubkey {
+        let sequence = self.logger_sequence().await + 1;
+        self.message_address(sequence)
+    }
+
+    pub async fn message(&mut self, sequence: u64) -> Option<MessageData> {
+        let address = self.message_address(sequence);
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *MessageData::load_unchecked(&account.data).unwrap())
+    }
+
+    pub async fn config(&mut self) -> EscrowConfig {
+        let address = EscrowConfig::find_address(&self.escrow_program_id).0;
+        let account = self.banks.get_account(address).await.unwrap().unwrap();
+        *EscrowConfig::load(&account.data).unwrap()
+    }
+
+    pub async fn escrow_state(&mut self, mint: &Pubkey) -> Option<EscrowState> {
+        let address = EscrowState::find_address(mint, &self.escrow_program_id).0;
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *EscrowState::load(&account.data).unwrap())
+    }
+
+    pub async fn position(&mut self, mint: &Pubkey, owner: &Pubkey) -> Option<Position> {
+        let address = Position::find_address(mint, owner, &self.escrow_program_id).0;
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *Position::load(&account.data).unwrap())
+    }
+
+    pub async fn vesting(
+        &mut self,
+        mint: &Pubkey,
+        grantor: &Pubkey,
+        beneficiary: &Pubkey,
+    ) -> Option<Vesting> {
+        let address = Vesting::find_address(mint, grantor, beneficiary, &self.escrow_program_id).0;
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *Vesting::load(&account.data).unwrap())
+    }
+
+    pub async fn agreement(&mut self, payer: &Pubkey, id: u64) -> Option<Agreement> {
+        let address = Agreement::find_address(payer, id, &self.escrow_program_id).0;
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *Agreement::load(&account.data).unwrap())
+    }
+
+    /// Legs recorded for the batch logged at `sequence`.
+    pub async fn batch(&mut self, sequence: u64) -> Option<Batch> {
+        let address = self.batch_address(sequence);
+        self.banks
+            .get_account(address)
+            .await
+            .unwrap()
+            .map(|account| *Batch::load(&account.data).unwrap())
+    }
+
+    pub fn batch_address(&self, sequence: u64) -> Pubkey {
+        Batch::find_address(&self.message_address(sequence), &self.escrow_program_id).0
+    }
+
+    /// Unix timestamp of the bank clock.
+    pub async fn now(&mut self) -> u64 {
+        let clock: Clock = self.banks.get_sysvar().await.unwrap();
+        clock.unix_timestamp as u64
+    }
+
+    /// Moves the bank clock to `unix_timestamp`.
+    pub async fn set_clock(&mut self, unix_timestamp: u64) {
+        let mut clock: Clock = self.banks.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp as i64;
+        self.context.set_sysvar(&clock);
+    }
+
+    pub async fn total_deposited(&mut self, mint: &Pubkey) -> u64 {
+        self.escrow_state(mint).await.unwrap().total_deposited.get()
+    }
+
+    pub fn vault_address(&self, mint: &Pubkey) -> Pubkey {
+        EscrowState::find_vault_address(mint, &self.escrow_program_id).0
+    }
+
+    pub async fn token_balance(&mut self, account: Pubkey) -> u64 {
+        let account = self.banks.get_account(account).await.unwrap().unwrap();
+        spl_token::state::Account::unpack(&account.data)
+            .unwrap()
+            .amount
+    }
+
+    pub async fn deposit_instruction(
+        &mut self,
+        user: &Pubkey,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            user,
+            token_account,
+            mint,
+            &self.logger_state,
+            &message,
+            amount,
+        )
+    }
+
+    pub async fn withdraw_instruction(
+        &mut self,
+        user: &Pubkey,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            user,
+            user,
+            token_account,
+            mint,
+            &self.logger_state,
+            &message,
+            amount,
+        )
+    }
+
+    /// Withdraws from the position of `owner` with `authority` signing, the
+    /// owner itself or its delegate.
+    pub async fn withdraw_from_position(
+        &mut self,
+        authority: &Keypair,
+        owner: &Pubkey,
+        recipient: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &authority.pubkey(),
+            owner,
+            recipient,
+            mint,
+            &self.logger_state,
+            &message,
+            amount,
+        );
+        self.process(&[instruction], &[authority]).await
+    }
+
+    pub async fn set_delegate(
+        &mut self,
+        owner: &Keypair,
+        mint: &Pubkey,
+        delegate: &Pubkey,
+        cap: Option<u64>,
+        expires_at: Option<u64>,
+    ) -> Result<(), TransactionError> {
+        let instruction = escrow_instruction::set_delegate(
+            &self.escrow_program_id,
+            &owner.pubkey(),
+            mint,
+            delegate,
+            cap,
+            expires_at,
+        );
+        self.process(&[instruction], &[owner]).await
+    }
+
+    /// Deposits from `user` and returns the error of the transaction, if any.
+    pub async fn deposit(
+        &mut self,
+        user: &Keypair,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = self
+            .deposit_instruction(&user.pubkey(), token_account, mint, amount)
+            .await;
+        self.process(&[instruction], &[user]).await
+    }
+
+    pub async fn withdraw(
+        &mut self,
+        user: &Keypair,
+        token_account: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = self
+            .withdraw_instruction(&user.pubkey(), token_account, mint, amount)
+            .await;
+        self.process(&[instruction], &[user]).await
+    }
+
+    /// Grants `amount` from `grantor_tokens` to `beneficiary` on the given
+    /// schedule.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_vesting(
+        &mut self,
+        grantor: &Keypair,
+        grantor_tokens: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+        (start, cliff, end): (u64, u64, u64),
+        revocable: bool,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::create_vesting(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &grantor.pubkey(),
+            grantor_tokens,
+            beneficiary,
+            mint,
+            &self.logger_state,
+            &message,
+            amount,
+            start,
+            cliff,
+            end,
+            revocable,
+        );
+        self.process(&[instruction], &[grantor]).await
+    }
+
+    pub async fn claim_vested(
+        &mut self,
+        beneficiary: &Keypair,
+        recipient: &Pubkey,
+        grantor: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<(), TransactionError> {
+        let instruction = self
+            .claim_vested_instruction(&beneficiary.pubkey(), recipient, grantor, mint)
+            .await;
+        self.process(&[instruction], &[beneficiary]).await
+    }
+
+    pub async fn claim_vested_instruction(
+        &mut self,
+        beneficiary: &Pubkey,
+        recipient: &Pubkey,
+        grantor: &Pubkey,
+        mint: &Pubkey,
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::claim_vested(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            beneficiary,
+            recipient,
+            grantor,
+            mint,
+            &self.logger_state,
+            &message,
+        )
+    }
+
+    pub async fn revoke_instruction(
+        &mut self,
+        grantor: &Pubkey,
+        grantor_tokens: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::revoke(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            grantor,
+            grantor_tokens,
+            beneficiary,
+            mint,
+            &self.logger_state,
+            &message,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn open_agreement(
+        &mut self,
+        payer: &Keypair,
+        payer_tokens: &Pubkey,
+        payee: &Pubkey,
+        mint: &Pubkey,
+        id: u64,
+        amount: u64,
+        deadline: u64,
+        arbiter: Option<Pubkey>,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::open_agreement(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &payer.pubkey(),
+            payer_tokens,
+            payee,
+            mint,
+            &self.logger_state,
+            &message,
+            id,
+            amount,
+            deadline,
+            arbiter,
+        );
+        self.process(&[instruction], &[payer]).await
+    }
+
+    pub async fn release(
+        &mut self,
+        payer: &Keypair,
+        payee_tokens: &Pubkey,
+        mint: &Pubkey,
+        id: u64,
+    ) -> Result<(), TransactionError> {
+        let instruction = self
+            .release_instruction(&payer.pubkey(), payee_tokens, mint, id)
+            .await;
+        self.process(&[instruction], &[payer]).await
+    }
+
+    pub async fn release_instruction(
+        &mut self,
+        payer: &Pubkey,
+        payee_tokens: &Pubkey,
+        mint: &Pubkey,
+        id: u64,
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::release(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            payer,
+            payee_tokens,
+            mint,
+            id,
+            &self.logger_state,
+            &message,
+        )
+    }
+
+    pub async fn open_dispute(
+        &mut self,
+        party: &Keypair,
+        payer: &Pubkey,
+        id: u64,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::open_dispute(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &party.pubkey(),
+            payer,
+            id,
+            &self.logger_state,
+            &message,
+        );
+        self.process(&[instruction], &[party]).await
+    }
+
+    pub async fn resolve(
+        &mut self,
+        arbiter: &Keypair,
+        winner_tokens: &Pubkey,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        id: u64,
+        to_payee: bool,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::resolve(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &arbiter.pubkey(),
+            winner_tokens,
+            payer,
+            mint,
+            id,
+            &self.logger_state,
+            &message,
+            to_payee,
+        );
+        self.process(&[instruction], &[arbiter]).await
+    }
+
+    pub async fn refund(
+        &mut self,
+        caller: &Keypair,
+        payer_tokens: &Pubkey,
+        payer: &Pubkey,
+        mint: &Pubkey,
+        id: u64,
+    ) -> Result<(), TransactionError> {
+        let message = self.next_message_address().await;
+        let instruction = escrow_instruction::refund(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            &caller.pubkey(),
+            payer_tokens,
+            payer,
+            mint,
+            id,
+            &self.logger_state,
+            &message,
+        );
+        self.process(&[instruction], &[caller]).await
+    }
+
+    /// `(token account, mint, amount)` legs of a batch.
+    pub fn batch_legs(legs: &[(Pubkey, Pubkey, u64)]) -> Vec<BatchLeg> {
+        legs.iter()
+            .map(|&(token_account, mint, amount)| BatchLeg {
+                token_account,
+                mint,
+                amount,
+            })
+            .collect()
+    }
+
+    pub async fn batch_deposit_instruction(
+        &mut self,
+        user: &Pubkey,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::batch_deposit(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            user,
+            &self.logger_state,
+            &message,
+            &Self::batch_legs(legs),
+        )
+    }
+
+    pub async fn batch_withdraw_instruction(
+        &mut self,
+        user: &Pubkey,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Instruction {
+        let message = self.next_message_address().await;
+        escrow_instruction::batch_withdraw(
+            &self.escrow_program_id,
+            &self.logger_program_id,
+            user,
+            user,
+            &self.logger_state,
+            &message,
+            &Self::batch_legs(legs),
+        )
+    }
+
+    pub async fn batch_deposit(
+        &mut self,
+        user: &Keypair,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Result<(), TransactionError> {
+        let instruction = self.batch_deposit_instruction(&user.pubkey(), legs).await;
+        self.process(&[instruction], &[user]).await
+    }
+
+    pub async fn batch_withdraw(
+        &mut self,
+        user: &Keypair,
+        legs: &[(Pubkey, Pubkey, u64)],
+    ) -> Result<(), TransactionError> {
+        let instruction = self.batch_withdraw_instruction(&user.pubkey(), legs).await;
+        self.process(&[instruction], &[user]).await
+    }
+}
+
+pub fn program_error(err: ProgramError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::from(u64::from(err)))
+}
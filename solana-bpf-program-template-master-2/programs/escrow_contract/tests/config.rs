@@ -0,0 +1,265 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+
+#[tokio::test]
+async fn config_records_admin_and_trusted_logger() {
+    let mut harness = Harness::start().await;
+    let config = harness.config().await;
+    assert_eq!(config.admin, harness.payer.pubkey());
+    assert_eq!(config.logger_program, logger_contract::id());
+    assert_eq!(config.logger_state, harness.logger_state);
+
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &payer,
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    let err = harness.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AlreadyInitialized.into()));
+}
+
+#[tokio::test]
+async fn only_the_upgrade_authority_initializes_the_config() {
+    let authority = Keypair::new();
+    let mut harness = Harness::start_deployed(Some(authority.pubkey())).await;
+    let intruder = harness.create_user().await;
+    let instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &intruder.pubkey(),
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    let err = harness.process(&[instruction], &[&intruder]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotUpgradeAuthority.into()));
+
+    // ProgramData adresa je izvedena, drugi nalog ne prolazi
+    let mut instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &intruder.pubkey(),
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    instruction.accounts[5].pubkey = Pubkey::new_unique();
+    let err = harness.process(&[instruction], &[&intruder]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectAccountAddress.into())
+    );
+
+    let payer = harness.payer.pubkey();
+    harness
+        .process(
+            &[system_instruction::transfer(&payer, &authority.pubkey(), 1_000_000_000)],
+            &[],
+        )
+        .await
+        .unwrap();
+    let instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &authority.pubkey(),
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    harness.process(&[instruction], &[&authority]).await.unwrap();
+    assert_eq!(harness.config().await.admin, authority.pubkey());
+}
+
+#[tokio::test]
+async fn immutable_deployment_cannot_initialize_the_config() {
+    let mut harness = Harness::start_deployed(None).await;
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &payer,
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    let err = harness.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotUpgradeAuthority.into()));
+}
+
+#[tokio::test]
+async fn builtin_deployment_lets_the_first_caller_initialize_the_config() {
+    // Bez upgradeable loader-a nema upgrade authority-ja, pa nema ni provere
+    let mut harness = Harness::start_unconfigured().await;
+    let user = harness.create_user().await;
+    let instruction = escrow_instruction::initialize_config(
+        &harness.escrow_program_id,
+        &user.pubkey(),
+        &harness.logger_program_id,
+        &harness.logger_state,
+    );
+    harness.process(&[instruction], &[&user]).await.unwrap();
+    assert_eq!(harness.config().await.admin, user.pubkey());
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_only_use_the_configured_logger() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    let other_state = harness.create_logger_state().await;
+
+    // Logger program je na indeksu 7 u deposit i 5 u withdraw instrukciji
+    for (program_index, state_index, withdraw) in [(7, 8, false), (5, 6, true)] {
+        for (index, pubkey) in [(program_index, spl_token::id()), (state_index, other_state)] {
+            let mut instruction = if withdraw {
+                harness
+                    .withdraw_instruction(&user.pubkey(), &token_account, &mint, 100)
+                    .await
+            } else {
+                harness
+                    .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+                    .await
+            };
+            instruction.accounts[index].pubkey = pubkey;
+            let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+            assert_eq!(
+                err,
+                program_error(EscrowError::IncorrectAccountAddress.into())
+            );
+        }
+        if !withdraw {
+            harness
+                .deposit(&user, &token_account, &mint, 100)
+                .await
+                .unwrap();
+        }
+    }
+    assert_eq!(harness.total_deposited(&mint).await, 100);
+}
+
+#[tokio::test]
+async fn deposit_with_wrong_config_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+
+    let mut instruction = harness
+        .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    instruction.accounts[13].pubkey = Pubkey::new_unique();
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectConfigAddress.into())
+    );
+}
+
+#[tokio::test]
+async fn set_logger_migrates_to_a_new_state() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+
+    // Stari state vec ima poruke, pa nove sekvence krecu ispocetka
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+    let old_message = harness.message(1).await.unwrap();
+    let old_state = harness.logger_state;
+    let new_state = harness.create_logger_state().await;
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::set_logger(
+        &harness.escrow_program_id,
+        &payer,
+        &harness.logger_program_id,
+        &new_state,
+        &[],
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(harness.config().await.logger_state, new_state);
+
+    // Stari state se vise ne prihvata
+    let instruction = harness
+        .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectAccountAddress.into())
+    );
+
+    harness.logger_state = new_state;
+    harness
+        .deposit(&user, &token_account, &mint, 50)
+        .await
+        .unwrap();
+    assert_eq!(harness.logger_sequence().await, 1);
+    let new_address = harness.message_address(1);
+    assert_eq!(harness.message(1).await.unwrap().amount.get(), 50);
+
+    // Sekvenca 1 novog state-a ima svoju PDA, stara poruka ostaje netaknuta
+    harness.logger_state = old_state;
+    assert_eq!(harness.logger_sequence().await, 1);
+    assert_ne!(harness.message_address(1), new_address);
+    assert_eq!(harness.message(1).await.unwrap(), old_message);
+}
+
+#[tokio::test]
+async fn set_logger_requires_admin_and_a_logger_state() {
+    let mut harness = Harness::start().await;
+    let outsider = harness.create_user().await;
+    let new_state = harness.create_logger_state().await;
+    let mint = harness.create_mint().await;
+    let token_account = harness
+        .create_token_account(&mint, &outsider.pubkey(), 0)
+        .await;
+    let payer = harness.payer.pubkey();
+
+    let not_admin = escrow_instruction::set_logger(
+        &harness.escrow_program_id,
+        &outsider.pubkey(),
+        &harness.logger_program_id,
+        &new_state,
+        &[],
+    );
+    let err = harness
+        .process(&[not_admin], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotAdmin.into()));
+
+    for (logger_program, logger_state, expected) in [
+        (
+            Pubkey::new_unique(),
+            new_state,
+            EscrowError::AccountNotExecutable,
+        ),
+        (
+            harness.logger_program_id,
+            token_account,
+            EscrowError::IncorrectAccountOwner,
+        ),
+    ] {
+        let instruction = escrow_instruction::set_logger(
+            &harness.escrow_program_id,
+            &payer,
+            &logger_program,
+            &logger_state,
+            &[],
+        );
+        let err = harness.process(&[instruction], &[]).await.unwrap_err();
+        assert_eq!(err, program_error(expected.into()));
+    }
+    assert_eq!(harness.config().await.logger_state, harness.logger_state);
+}
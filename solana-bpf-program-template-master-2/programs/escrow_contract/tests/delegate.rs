@@ -0,0 +1,243 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction, state::Position};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn owner_withdraws_to_another_recipient() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    let recipient = Pubkey::new_unique();
+    let recipient_tokens = harness.create_token_account(&mint, &recipient, 0).await;
+
+    harness
+        .deposit(&user, &token_account, &mint, 400)
+        .await
+        .unwrap();
+    let position = harness.position(&mint, &user.pubkey()).await.unwrap();
+    assert_eq!(position.owner, user.pubkey());
+    assert_eq!(position.token_mint, mint);
+    assert_eq!(position.amount.get(), 400);
+    assert_eq!(position.delegate(), None);
+
+    harness
+        .withdraw_from_position(&user, &user.pubkey(), &recipient_tokens, &mint, 150)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(recipient_tokens).await, 150);
+    assert_eq!(harness.token_balance(token_account).await, 600);
+    assert_eq!(
+        harness
+            .position(&mint, &user.pubkey())
+            .await
+            .unwrap()
+            .amount
+            .get(),
+        250
+    );
+
+    // Poruka ide na vlasnika token naloga primaoca
+    let message = harness.message(2).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.vault_address(&mint));
+    assert_eq!(message.to_pubkey, recipient);
+    assert_eq!(message.amount.get(), 150);
+}
+
+#[tokio::test]
+async fn positions_are_kept_per_owner() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let alice = harness.create_user().await;
+    let bob = harness.create_user().await;
+    let alice_tokens = harness
+        .create_token_account(&mint, &alice.pubkey(), 1_000)
+        .await;
+    let bob_tokens = harness
+        .create_token_account(&mint, &bob.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&alice, &alice_tokens, &mint, 100)
+        .await
+        .unwrap();
+    harness
+        .deposit(&bob, &bob_tokens, &mint, 300)
+        .await
+        .unwrap();
+
+    let err = harness
+        .withdraw(&alice, &alice_tokens, &mint, 200)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::InsufficientAmount.into()));
+
+    // Tudja pozicija ne moze da se prazni bez delegiranja
+    let err = harness
+        .withdraw_from_position(&alice, &bob.pubkey(), &alice_tokens, &mint, 100)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotPositionAuthority.into()));
+    assert_eq!(harness.total_deposited(&mint).await, 400);
+}
+
+#[tokio::test]
+async fn delegate_withdraws_up_to_its_cap() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let delegate = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    let delegate_tokens = harness
+        .create_token_account(&mint, &delegate.pubkey(), 0)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 500)
+        .await
+        .unwrap();
+
+    harness
+        .set_delegate(&user, &mint, &delegate.pubkey(), Some(300), None)
+        .await
+        .unwrap();
+    let position = harness.position(&mint, &user.pubkey()).await.unwrap();
+    assert_eq!(position.delegate(), Some(delegate.pubkey()));
+    assert_eq!(position.delegate_cap(), Some(300));
+    assert_eq!(position.delegate_expires_at(), None);
+    // Delegat se objavljuje kao DelegateEvent, ne kao logger poruka
+    assert_eq!(harness.logger_sequence().await, 1);
+
+    harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &delegate_tokens, &mint, 200)
+        .await
+        .unwrap();
+    let err = harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &delegate_tokens, &mint, 150)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DelegateCapExceeded.into()));
+    harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &delegate_tokens, &mint, 100)
+        .await
+        .unwrap();
+
+    let position = harness.position(&mint, &user.pubkey()).await.unwrap();
+    assert_eq!(position.amount.get(), 200);
+    assert_eq!(position.delegate_cap(), Some(0));
+    assert_eq!(harness.token_balance(delegate_tokens).await, 300);
+
+    // Vlasnik i dalje povlaci bez obzira na limit delegata
+    harness
+        .withdraw(&user, &token_account, &mint, 200)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn expired_or_revoked_delegate_cannot_withdraw() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let delegate = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 500)
+        .await
+        .unwrap();
+
+    harness
+        .set_delegate(&user, &mint, &delegate.pubkey(), None, Some(0))
+        .await
+        .unwrap();
+    let err = harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &token_account, &mint, 100)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::DelegateExpired.into()));
+
+    harness
+        .set_delegate(&user, &mint, &delegate.pubkey(), None, Some(u64::MAX - 1))
+        .await
+        .unwrap();
+    harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    let sequence = harness.logger_sequence().await;
+    harness
+        .set_delegate(&user, &mint, &Pubkey::default(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(harness.logger_sequence().await, sequence);
+    assert_eq!(
+        harness
+            .position(&mint, &user.pubkey())
+            .await
+            .unwrap()
+            .delegate(),
+        None
+    );
+
+    let err = harness
+        .withdraw_from_position(&delegate, &user.pubkey(), &token_account, &mint, 100)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotPositionAuthority.into()));
+}
+
+#[tokio::test]
+async fn only_the_owner_sets_a_delegate() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let outsider = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 500)
+        .await
+        .unwrap();
+
+    // Pozicija drugog korisnika ne postoji, pa je nalog i dalje sistemski
+    let err = harness
+        .set_delegate(&outsider, &mint, &outsider.pubkey(), None, None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectAccountOwner.into())
+    );
+
+    let mut instruction = escrow_instruction::set_delegate(
+        &harness.escrow_program_id,
+        &outsider.pubkey(),
+        &mint,
+        &outsider.pubkey(),
+        None,
+        None,
+    );
+    instruction.accounts[1].pubkey =
+        Position::find_address(&mint, &user.pubkey(), &harness.escrow_program_id).0;
+    let err = harness
+        .process(&[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotPositionAuthority.into()));
+    assert_eq!(
+        harness
+            .position(&mint, &user.pubkey())
+            .await
+            .unwrap()
+            .delegate(),
+        None
+    );
+}
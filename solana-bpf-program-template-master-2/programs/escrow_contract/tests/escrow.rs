@@ -0,0 +1,354 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::error::EscrowError;
+use logger_contract::{error::LoggerError, pod::ZeroCopy, state::ConsistencyLevel};
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey, signature::Signer, sysvar};
+
+#[tokio::test]
+async fn first_deposit_creates_escrow_vault_and_message() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    assert!(harness.escrow_state(&mint).await.is_none());
+
+    harness
+        .deposit(&user, &token_account, &mint, 400)
+        .await
+        .unwrap();
+
+    let vault = harness.vault_address(&mint);
+    let escrow = harness.escrow_state(&mint).await.unwrap();
+    assert!(escrow.is_initialized());
+    assert_eq!(escrow.token_mint, mint);
+    assert_eq!(escrow.escrow_vault_account, vault);
+    assert_eq!(escrow.total_deposited.get(), 400);
+    assert_eq!(harness.token_balance(vault).await, 400);
+    assert_eq!(harness.token_balance(token_account).await, 600);
+
+    // Logger CPI: sequence 1, poruka user -> vault
+    assert_eq!(harness.logger_sequence().await, 1);
+    let message = harness.message(1).await.unwrap();
+    assert_eq!(message.sequence.get(), 1);
+    assert_eq!(message.from_pubkey, user.pubkey());
+    assert_eq!(message.to_pubkey, vault);
+    assert_eq!(message.amount.get(), 400);
+    assert_eq!(message.payer, user.pubkey());
+    assert_eq!(message.consistency_level(), ConsistencyLevel::Finalized);
+}
+
+#[tokio::test]
+async fn repeat_deposits_accumulate_and_increment_sequence() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let alice = harness.create_user().await;
+    let bob = harness.create_user().await;
+    let alice_tokens = harness
+        .create_token_account(&mint, &alice.pubkey(), 1_000)
+        .await;
+    let bob_tokens = harness
+        .create_token_account(&mint, &bob.pubkey(), 1_000)
+        .await;
+
+    harness
+        .deposit(&alice, &alice_tokens, &mint, 100)
+        .await
+        .unwrap();
+    harness
+        .deposit(&bob, &bob_tokens, &mint, 250)
+        .await
+        .unwrap();
+    harness
+        .deposit(&alice, &alice_tokens, &mint, 50)
+        .await
+        .unwrap();
+
+    assert_eq!(harness.total_deposited(&mint).await, 400);
+    assert_eq!(
+        harness.token_balance(harness.vault_address(&mint)).await,
+        400
+    );
+    assert_eq!(harness.logger_sequence().await, 3);
+    for (sequence, user, amount) in [(1, &alice, 100), (2, &bob, 250), (3, &alice, 50)] {
+        let message = harness.message(sequence).await.unwrap();
+        assert_eq!(message.sequence.get(), sequence);
+        assert_eq!(message.from_pubkey, user.pubkey());
+        assert_eq!(message.amount.get(), amount);
+    }
+}
+
+#[tokio::test]
+async fn withdraw_returns_tokens_and_logs_message() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 500)
+        .await
+        .unwrap();
+
+    harness
+        .withdraw(&user, &token_account, &mint, 200)
+        .await
+        .unwrap();
+
+    let vault = harness.vault_address(&mint);
+    assert_eq!(harness.total_deposited(&mint).await, 300);
+    assert_eq!(harness.token_balance(vault).await, 300);
+    assert_eq!(harness.token_balance(token_account).await, 700);
+
+    let message = harness.message(2).await.unwrap();
+    assert_eq!(message.from_pubkey, vault);
+    assert_eq!(message.to_pubkey, user.pubkey());
+    assert_eq!(message.amount.get(), 200);
+}
+
+#[tokio::test]
+async fn deposit_from_token_account_of_another_mint_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let other_mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let other_tokens = harness
+        .create_token_account(&other_mint, &user.pubkey(), 1_000)
+        .await;
+
+    let err = harness
+        .deposit(&user, &other_tokens, &mint, 100)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::MintMismatch.into()));
+    assert!(harness.escrow_state(&mint).await.is_none());
+    assert_eq!(harness.logger_sequence().await, 0);
+}
+
+#[tokio::test]
+async fn withdraw_into_token_account_of_another_mint_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let other_mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    let other_tokens = harness
+        .create_token_account(&other_mint, &user.pubkey(), 0)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    // Primalac mora biti token nalog istog mint-a kao escrow
+    let err = harness
+        .withdraw(&user, &other_tokens, &mint, 100)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::MintMismatch.into()));
+    assert_eq!(harness.total_deposited(&mint).await, 100);
+}
+
+#[tokio::test]
+async fn withdraw_more_than_deposited_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    let err = harness
+        .withdraw(&user, &token_account, &mint, 101)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::InsufficientAmount.into()));
+    assert_eq!(harness.total_deposited(&mint).await, 100);
+    assert_eq!(harness.logger_sequence().await, 1);
+}
+
+#[tokio::test]
+async fn deposit_with_wrong_escrow_or_vault_pda_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+
+    for (index, expected) in [
+        (2, EscrowError::IncorrectEscrowAddress),
+        (3, EscrowError::IncorrectVaultAddress),
+    ] {
+        let mut instruction = harness
+            .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+            .await;
+        instruction.accounts[index].pubkey = Pubkey::new_unique();
+        let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+        assert_eq!(err, program_error(expected.into()));
+    }
+    assert!(harness.escrow_state(&mint).await.is_none());
+}
+
+#[tokio::test]
+async fn deposit_with_wrong_message_pda_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+
+    // PDA za sledecu sekvencu je 1, poruka za sekvencu 2 se odbija
+    let mut instruction = harness
+        .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    instruction.accounts[9].pubkey = harness.message_address(2);
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(LoggerError::IncorrectMessageAddress.into())
+    );
+    assert_eq!(harness.logger_sequence().await, 0);
+    assert_eq!(harness.token_balance(token_account).await, 1_000);
+}
+
+#[tokio::test]
+async fn deposit_checks_account_spec() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+
+    // Indeksi prate redosled naloga u instruction::deposit
+    for (index, pubkey, expected) in [
+        (
+            5,
+            Pubkey::new_unique(),
+            EscrowError::IncorrectAccountAddress,
+        ),
+        (6, sysvar::clock::id(), EscrowError::IncorrectAccountAddress),
+        (
+            7,
+            Pubkey::new_unique(),
+            EscrowError::IncorrectAccountAddress,
+        ),
+        (
+            8,
+            Pubkey::new_unique(),
+            EscrowError::IncorrectAccountAddress,
+        ),
+        (12, Pubkey::new_unique(), EscrowError::IncorrectAccountOwner),
+        (
+            14,
+            Pubkey::new_unique(),
+            EscrowError::IncorrectPositionAddress,
+        ),
+    ] {
+        let mut instruction = harness
+            .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+            .await;
+        instruction.accounts[index].pubkey = pubkey;
+        let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+        assert_eq!(err, program_error(expected.into()), "account {index}");
+    }
+
+    let mut instruction = harness
+        .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    instruction.accounts[2].is_writable = false;
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AccountNotWritable.into()));
+
+    assert!(harness.escrow_state(&mint).await.is_none());
+    assert_eq!(harness.token_balance(token_account).await, 1_000);
+}
+
+#[tokio::test]
+async fn withdraw_from_escrow_of_another_program_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    let mut instruction = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    instruction.accounts[2].pubkey = token_account;
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectAccountOwner.into())
+    );
+    assert_eq!(harness.total_deposited(&mint).await, 100);
+}
+
+#[tokio::test]
+async fn withdraw_with_wrong_vault_authority_fails() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    let mut instruction = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    instruction.accounts[7].pubkey = Pubkey::new_unique();
+    let err = harness.process(&[instruction], &[&user]).await.unwrap_err();
+    assert_eq!(err, program_error(ProgramError::InvalidSeeds));
+}
+
+#[tokio::test]
+async fn deposit_and_withdraw_require_user_signature() {
+    let mut harness = Harness::start().await;
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+
+    let deposit = harness
+        .deposit_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    let withdraw = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 100)
+        .await;
+    for mut instruction in [deposit, withdraw] {
+        for account in instruction.accounts.iter_mut() {
+            if account.pubkey == user.pubkey() {
+                account.is_signer = false;
+            }
+        }
+        let err = harness.process(&[instruction], &[]).await.unwrap_err();
+        assert_eq!(err, program_error(EscrowError::MissingSignature.into()));
+    }
+    assert_eq!(harness.total_deposited(&mint).await, 100);
+    assert_eq!(harness.logger_sequence().await, 1);
+}
@@ -0,0 +1,399 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction, state::Multisig};
+use logger_contract::pod::ZeroCopy;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::TransactionError,
+};
+
+async fn members(harness: &mut Harness) -> [Keypair; 3] {
+    [
+        harness.create_user().await,
+        harness.create_user().await,
+        harness.create_user().await,
+    ]
+}
+
+/// Harness whose config admin is a 2-of-3 multisig of `members`.
+async fn multisig_admin(harness: &mut Harness, members: &[Keypair; 3]) -> Pubkey {
+    let keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+    let multisig = harness.create_multisig(2, &keys).await.unwrap();
+    let payer = harness.payer.pubkey();
+    let instruction =
+        escrow_instruction::set_admin(&harness.escrow_program_id, &payer, &multisig, &[]);
+    harness.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(harness.config().await.admin, multisig);
+    multisig
+}
+
+#[tokio::test]
+async fn initialize_multisig_checks_threshold_and_signers() {
+    let mut harness = Harness::start().await;
+    let [a, b, _] = members(&mut harness).await;
+    let (a, b) = (a.pubkey(), b.pubkey());
+
+    // Initialize je druga instrukcija, posle create_account
+    for (threshold, signers) in [(0, vec![a, b]), (3, vec![a, b]), (1, vec![a, a])] {
+        let err = harness
+            .create_multisig(threshold, &signers)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(EscrowError::InvalidMultisig as u32)
+            ),
+            "{threshold} of {signers:?}"
+        );
+    }
+
+    let multisig = harness.create_multisig(2, &[a, b]).await.unwrap();
+    let account = harness.banks.get_account(multisig).await.unwrap().unwrap();
+    let state = Multisig::load(&account.data).unwrap();
+    assert_eq!(state.threshold, 2);
+    assert_eq!(state.signers(), &[a, b]);
+
+    let instruction =
+        escrow_instruction::initialize_multisig(&harness.escrow_program_id, &multisig, &[&a], 1);
+    let err = harness.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AlreadyInitialized.into()));
+}
+
+#[tokio::test]
+async fn multisig_admin_signs_in_one_transaction() {
+    let mut harness = Harness::start().await;
+    let members = members(&mut harness).await;
+    let multisig = multisig_admin(&mut harness, &members).await;
+    let new_state = harness.create_logger_state().await;
+    let [a, b, _] = &members;
+
+    // Stari admin vise nema prava
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::set_logger(
+        &harness.escrow_program_id,
+        &payer,
+        &harness.logger_program_id,
+        &new_state,
+        &[],
+    );
+    let err = harness.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotAdmin.into()));
+
+    // Isti clan dva puta se broji jednom
+    for signers in [vec![a.pubkey()], vec![a.pubkey(), a.pubkey()]] {
+        let signers: Vec<&Pubkey> = signers.iter().collect();
+        let instruction = escrow_instruction::set_logger(
+            &harness.escrow_program_id,
+            &multisig,
+            &harness.logger_program_id,
+            &new_state,
+            &signers,
+        );
+        let err = harness.process(&[instruction], &[a]).await.unwrap_err();
+        assert_eq!(err, program_error(EscrowError::NotEnoughSigners.into()));
+    }
+
+    let instruction = escrow_instruction::set_logger(
+        &harness.escrow_program_id,
+        &multisig,
+        &harness.logger_program_id,
+        &new_state,
+        &[&a.pubkey(), &b.pubkey()],
+    );
+    harness.process(&[instruction], &[a, b]).await.unwrap();
+    assert_eq!(harness.config().await.logger_state, new_state);
+}
+
+#[tokio::test]
+async fn proposal_executes_after_threshold_approvals() {
+    let mut harness = Harness::start().await;
+    let members = members(&mut harness).await;
+    let multisig = multisig_admin(&mut harness, &members).await;
+    let new_state = harness.create_logger_state().await;
+    let outsider = harness.create_user().await;
+    let [a, b, _] = &members;
+
+    let set_logger = escrow_instruction::set_logger(
+        &harness.escrow_program_id,
+        &multisig,
+        &harness.logger_program_id,
+        &new_state,
+        &[],
+    );
+    let proposal =
+        escrow_instruction::proposal_address(&harness.escrow_program_id, &multisig, &set_logger);
+    let execute = escrow_instruction::execute(
+        &harness.escrow_program_id,
+        &a.pubkey(),
+        &multisig,
+        &set_logger,
+    );
+
+    let propose = escrow_instruction::propose(
+        &harness.escrow_program_id,
+        &outsider.pubkey(),
+        &multisig,
+        &set_logger,
+    );
+    let err = harness.process(&[propose], &[&outsider]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotMultisigSigner.into()));
+
+    let propose = escrow_instruction::propose(
+        &harness.escrow_program_id,
+        &a.pubkey(),
+        &multisig,
+        &set_logger,
+    );
+    harness.process(&[propose], &[a]).await.unwrap();
+
+    let err = harness.process(std::slice::from_ref(&execute), &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotEnoughSigners.into()));
+
+    let approve = escrow_instruction::approve(
+        &harness.escrow_program_id,
+        &outsider.pubkey(),
+        &multisig,
+        &proposal,
+    );
+    let err = harness.process(&[approve], &[&outsider]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotMultisigSigner.into()));
+
+    let approve = escrow_instruction::approve(
+        &harness.escrow_program_id,
+        &b.pubkey(),
+        &multisig,
+        &proposal,
+    );
+    harness.process(&[approve], &[b]).await.unwrap();
+
+    // Nalozi instrukcije moraju biti bas oni koji su predlozeni
+    let mut tampered = execute.clone();
+    tampered.accounts[6].pubkey = harness.logger_state;
+    let err = harness.process(&[tampered], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::ProposalMismatch.into()));
+
+    let proposer_lamports = harness.banks.get_balance(a.pubkey()).await.unwrap();
+    harness.process(std::slice::from_ref(&execute), &[]).await.unwrap();
+    assert_eq!(harness.config().await.logger_state, new_state);
+    assert!(harness.banks.get_account(proposal).await.unwrap().is_none());
+    assert!(harness.banks.get_balance(a.pubkey()).await.unwrap() > proposer_lamports);
+
+    // Zatvoren predlog ne moze ponovo da se izvrsi
+    let err = harness.process(&[execute], &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::IncorrectAccountOwner.into())
+    );
+}
+
+#[tokio::test]
+async fn large_withdrawals_need_the_withdrawal_multisig() {
+    let mut harness = Harness::start().await;
+    let members = members(&mut harness).await;
+    let [a, b, c] = &members;
+    let keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+    let multisig = harness.create_multisig(2, &keys).await.unwrap();
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::set_withdrawal_policy(
+        &harness.escrow_program_id,
+        &payer,
+        &multisig,
+        100,
+        &[],
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    let mint = harness.create_mint().await;
+    let user = harness.create_user().await;
+    let token_account = harness
+        .create_token_account(&mint, &user.pubkey(), 1_000)
+        .await;
+    harness
+        .deposit(&user, &token_account, &mint, 1_000)
+        .await
+        .unwrap();
+
+    harness
+        .withdraw(&user, &token_account, &mint, 100)
+        .await
+        .unwrap();
+    let err = harness
+        .withdraw(&user, &token_account, &mint, 150)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        program_error(EscrowError::LargeWithdrawalNotApproved.into())
+    );
+
+    let instruction = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 150)
+        .await;
+    let instruction = escrow_instruction::with_multisig(instruction, &multisig, &[&a.pubkey()]);
+    let err = harness
+        .process(&[instruction], &[&user, a])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotEnoughSigners.into()));
+
+    let instruction = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 150)
+        .await;
+    let instruction =
+        escrow_instruction::with_multisig(instruction, &multisig, &[&a.pubkey(), &c.pubkey()]);
+    harness
+        .process(&[instruction], &[&user, a, c])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(token_account).await, 250);
+
+    // Isto povlacenje kroz predlog: signeri odobravaju u posebnim transakcijama
+    let withdraw = harness
+        .withdraw_instruction(&user.pubkey(), &token_account, &mint, 300)
+        .await;
+    let withdraw = escrow_instruction::with_multisig(withdraw, &multisig, &[]);
+    let propose = escrow_instruction::propose(
+        &harness.escrow_program_id,
+        &b.pubkey(),
+        &multisig,
+        &withdraw,
+    );
+    harness.process(&[propose], &[b]).await.unwrap();
+    let proposal =
+        escrow_instruction::proposal_address(&harness.escrow_program_id, &multisig, &withdraw);
+    let approve = escrow_instruction::approve(
+        &harness.escrow_program_id,
+        &c.pubkey(),
+        &multisig,
+        &proposal,
+    );
+    harness.process(&[approve], &[c]).await.unwrap();
+
+    let execute = escrow_instruction::execute(
+        &harness.escrow_program_id,
+        &b.pubkey(),
+        &multisig,
+        &withdraw,
+    );
+    harness.process(&[execute], &[&user]).await.unwrap();
+    assert_eq!(harness.token_balance(token_account).await, 550);
+    assert_eq!(harness.total_deposited(&mint).await, 450);
+}
+
+#[tokio::test]
+async fn vesting_and_agreement_payouts_need_the_withdrawal_multisig() {
+    let mut harness = Harness::start().await;
+    let members = members(&mut harness).await;
+    let [a, _, c] = &members;
+    let keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+    let multisig = harness.create_multisig(2, &keys).await.unwrap();
+    let payer = harness.payer.pubkey();
+    let instruction = escrow_instruction::set_withdrawal_policy(
+        &harness.escrow_program_id,
+        &payer,
+        &multisig,
+        100,
+        &[],
+    );
+    harness.process(&[instruction], &[]).await.unwrap();
+
+    let mint = harness.create_mint().await;
+    let grantor = harness.create_user().await;
+    let grantor_tokens = harness
+        .create_token_account(&mint, &grantor.pubkey(), 10_000)
+        .await;
+    let beneficiary = harness.create_user().await;
+    let beneficiary_tokens = harness
+        .create_token_account(&mint, &beneficiary.pubkey(), 0)
+        .await;
+    let not_approved = program_error(EscrowError::LargeWithdrawalNotApproved.into());
+
+    // Claim celog granta iznad threshold-a
+    let now = harness.now().await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            500,
+            (now, now, now + 100),
+            false,
+        )
+        .await
+        .unwrap();
+    harness.set_clock(now + 200).await;
+    let claim = harness
+        .claim_vested_instruction(&beneficiary.pubkey(), &beneficiary_tokens, &grantor.pubkey(), &mint)
+        .await;
+    let err = harness
+        .process(std::slice::from_ref(&claim), &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_eq!(err, not_approved);
+    let claim = escrow_instruction::with_multisig(claim, &multisig, &[&a.pubkey(), &c.pubkey()]);
+    harness
+        .process(&[claim], &[&beneficiary, a, c])
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(beneficiary_tokens).await, 500);
+
+    // Opoziv vraca grantor-u neotkljucani deo
+    let other = harness.create_user().await;
+    let now = harness.now().await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &other.pubkey(),
+            &mint,
+            1_000,
+            (now, now + 500, now + 1_000),
+            true,
+        )
+        .await
+        .unwrap();
+    let revoke = harness
+        .revoke_instruction(&grantor.pubkey(), &grantor_tokens, &other.pubkey(), &mint)
+        .await;
+    let err = harness
+        .process(std::slice::from_ref(&revoke), &[&grantor])
+        .await
+        .unwrap_err();
+    assert_eq!(err, not_approved);
+    let revoke = escrow_instruction::with_multisig(revoke, &multisig, &[&a.pubkey(), &c.pubkey()]);
+    harness.process(&[revoke], &[&grantor, a, c]).await.unwrap();
+    assert_eq!(harness.token_balance(grantor_tokens).await, 9_500);
+
+    // Isplata sporazuma
+    harness
+        .open_agreement(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            1,
+            300,
+            now + 1_000,
+            None,
+        )
+        .await
+        .unwrap();
+    let release = harness
+        .release_instruction(&grantor.pubkey(), &beneficiary_tokens, &mint, 1)
+        .await;
+    let err = harness
+        .process(std::slice::from_ref(&release), &[&grantor])
+        .await
+        .unwrap_err();
+    assert_eq!(err, not_approved);
+    let release = escrow_instruction::with_multisig(release, &multisig, &[&a.pubkey(), &c.pubkey()]);
+    harness.process(&[release], &[&grantor, a, c]).await.unwrap();
+    assert_eq!(harness.token_balance(beneficiary_tokens).await, 800);
+    assert_eq!(harness.total_deposited(&mint).await, 0);
+}
@@ -0,0 +1,168 @@
+use bytemuck::bytes_of;
+use escrow_contract::{
+    instruction::{EscrowInstruction, MAX_BATCH_LEGS},
+    state::{EscrowState, Position},
+};
+use logger_contract::pod::ZeroCopy;
+use proptest::{collection::vec, prelude::*};
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey};
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn instruction() -> impl Strategy<Value = EscrowInstruction> {
+    prop_oneof![
+        any::<u64>().prop_map(|amount| EscrowInstruction::Deposit { amount }),
+        any::<u64>().prop_map(|amount| EscrowInstruction::Withdraw { amount }),
+        Just(EscrowInstruction::InitializeConfig),
+        Just(EscrowInstruction::SetLogger),
+        (
+            pubkey(),
+            proptest::option::of(any::<u64>()),
+            proptest::option::of(any::<u64>())
+        )
+            .prop_map(
+                |(delegate, cap, expires_at)| EscrowInstruction::SetDelegate {
+                    delegate,
+                    cap,
+                    expires_at,
+                }
+            ),
+        (
+            any::<u64>(),
+            any::<(u64, u64, u64)>(),
+            any::<bool>()
+        )
+            .prop_map(
+                |(amount, (start, cliff, end), revocable)| EscrowInstruction::CreateVesting {
+                    amount,
+                    start,
+                    cliff,
+                    end,
+                    revocable,
+                }
+            ),
+        Just(EscrowInstruction::ClaimVested),
+        Just(EscrowInstruction::Revoke),
+        (any::<(u64, u64, u64)>(), pubkey()).prop_map(|((id, amount, deadline), arbiter)| {
+            EscrowInstruction::OpenAgreement {
+                id,
+                amount,
+                deadline,
+                arbiter,
+            }
+        }),
+        Just(EscrowInstruction::Release),
+        Just(EscrowInstruction::OpenDispute),
+        any::<bool>().prop_map(|to_payee| EscrowInstruction::Resolve { to_payee }),
+        Just(EscrowInstruction::Refund),
+        vec((pubkey(), any::<u64>()), 1..=MAX_BATCH_LEGS)
+            .prop_map(|legs| EscrowInstruction::BatchDeposit { legs }),
+        vec((pubkey(), any::<u64>()), 1..=MAX_BATCH_LEGS)
+            .prop_map(|legs| EscrowInstruction::BatchWithdraw { legs }),
+    ]
+}
+
+fn discriminator() -> impl Strategy<Value = [u8; 8]> {
+    prop_oneof![
+        Just(EscrowState::DISCRIMINATOR),
+        Just([0u8; 8]),
+        any::<[u8; 8]>(),
+    ]
+}
+
+fn escrow_state() -> impl Strategy<Value = EscrowState> {
+    (pubkey(), pubkey(), any::<u64>()).prop_map(
+        |(token_mint, escrow_vault_account, total_deposited)| {
+            EscrowState::new(token_mint, escrow_vault_account, total_deposited)
+        },
+    )
+}
+
+fn position() -> impl Strategy<Value = Position> {
+    (
+        (pubkey(), pubkey(), any::<u64>()),
+        (pubkey(), any::<u64>(), any::<u64>()),
+    )
+        .prop_map(
+            |((owner, token_mint, amount), (delegate, cap, expires_at))| Position {
+                amount: amount.into(),
+                delegate,
+                delegate_cap: cap.into(),
+                delegate_expires_at: expires_at.into(),
+                ..Position::new(owner, token_mint)
+            },
+        )
+}
+
+proptest! {
+    #[test]
+    fn instruction_round_trips(instruction in instruction()) {
+        prop_assert_eq!(EscrowInstruction::unpack(&instruction.pack()).unwrap(), instruction);
+    }
+
+    #[test]
+    fn instruction_unpack_accepts_only_its_own_encoding(input in vec(any::<u8>(), 0..64)) {
+        match EscrowInstruction::unpack(&input) {
+            Ok(instruction) => prop_assert!(input.starts_with(&instruction.pack())),
+            // SetDelegate odbija i kratak unos i neispravne Option zastavice
+            Err(_) => prop_assert!(
+                input.is_empty() || input[0] >= 4 || input.len() < 9
+            ),
+        }
+    }
+
+    #[test]
+    fn batch_unpack_rejects_empty_and_oversized_batches(tag in 19u8..=20, legs in vec(any::<u8>(), 40)) {
+        for count in [0, MAX_BATCH_LEGS + 1] {
+            let mut input = vec![tag, count as u8];
+            for _ in 0..count {
+                input.extend_from_slice(&legs);
+            }
+            prop_assert!(EscrowInstruction::unpack(&input).is_err());
+        }
+    }
+
+    #[test]
+    fn position_round_trips(position in position()) {
+        prop_assert_eq!(*Position::load(bytes_of(&position)).unwrap(), position);
+    }
+
+    #[test]
+    fn escrow_state_round_trips(state in escrow_state()) {
+        prop_assert_eq!(*EscrowState::load(bytes_of(&state)).unwrap(), state);
+    }
+
+    #[test]
+    fn escrow_state_load_requires_exact_length(input in vec(any::<u8>(), 0..EscrowState::LEN * 2)) {
+        prop_assert_eq!(
+            EscrowState::load_unchecked(&input).is_ok(),
+            input.len() == EscrowState::LEN
+        );
+    }
+
+    #[test]
+    fn escrow_state_load_checks_discriminator(
+        discriminator in discriminator(),
+        rest in vec(any::<u8>(), EscrowState::LEN - 8),
+    ) {
+        let data = [&discriminator[..], &rest].concat();
+        match EscrowState::load(&data) {
+            Ok(state) => prop_assert_eq!(bytes_of(state), &data[..]),
+            Err(ProgramError::UninitializedAccount) => prop_assert_eq!(discriminator, [0u8; 8]),
+            Err(err) => {
+                prop_assert_eq!(err, ProgramError::InvalidAccountData);
+                prop_assert_ne!(discriminator, EscrowState::DISCRIMINATOR);
+            }
+        }
+    }
+
+    #[test]
+    fn escrow_state_load_mut_writes_in_place(state in escrow_state(), total in any::<u64>()) {
+        let mut data = bytes_of(&state).to_vec();
+        EscrowState::load_mut(&mut data).unwrap().total_deposited = total.into();
+        prop_assert_eq!(&data[..72], &bytes_of(&state)[..72]);
+        prop_assert_eq!(&data[72..], &total.to_le_bytes()[..]);
+    }
+}
@@ -0,0 +1,302 @@
+mod common;
+
+use common::{program_error, Harness};
+use escrow_contract::{error::EscrowError, instruction as escrow_instruction, state::Vesting};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+};
+
+/// Mint with a grantor holding 10_000 tokens and a beneficiary with an
+/// empty token account.
+async fn grant_setup(harness: &mut Harness) -> (Pubkey, Keypair, Pubkey, Keypair, Pubkey) {
+    let mint = harness.create_mint().await;
+    let grantor = harness.create_user().await;
+    let grantor_tokens = harness
+        .create_token_account(&mint, &grantor.pubkey(), 10_000)
+        .await;
+    let beneficiary = harness.create_user().await;
+    let beneficiary_tokens = harness
+        .create_token_account(&mint, &beneficiary.pubkey(), 0)
+        .await;
+    (mint, grantor, grantor_tokens, beneficiary, beneficiary_tokens)
+}
+
+#[tokio::test]
+async fn create_vesting_moves_the_grant_into_the_vault() {
+    let mut harness = Harness::start().await;
+    let (mint, grantor, grantor_tokens, beneficiary, _) = grant_setup(&mut harness).await;
+    let now = harness.now().await;
+
+    for (amount, schedule) in [
+        (0, (now, now, now + 100)),
+        (1_000, (now, now - 1, now + 100)),
+        (1_000, (now, now + 200, now + 100)),
+    ] {
+        let err = harness
+            .create_vesting(
+                &grantor,
+                &grantor_tokens,
+                &beneficiary.pubkey(),
+                &mint,
+                amount,
+                schedule,
+                true,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            program_error(EscrowError::InvalidVestingSchedule.into()),
+            "{amount} over {schedule:?}"
+        );
+    }
+
+    let schedule = (now, now + 50, now + 100);
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            1_000,
+            schedule,
+            true,
+        )
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(grantor_tokens).await, 9_000);
+    assert_eq!(harness.token_balance(harness.vault_address(&mint)).await, 1_000);
+    assert_eq!(harness.total_deposited(&mint).await, 1_000);
+
+    let vesting = harness
+        .vesting(&mint, &grantor.pubkey(), &beneficiary.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(vesting.beneficiary, beneficiary.pubkey());
+    assert_eq!(vesting.total_amount.get(), 1_000);
+    assert_eq!(vesting.claimed.get(), 0);
+    assert_eq!(
+        (vesting.start.get(), vesting.cliff.get(), vesting.end.get()),
+        schedule
+    );
+
+    let message = harness.message(1).await.unwrap();
+    assert_eq!(message.from_pubkey, grantor.pubkey());
+    assert_eq!(message.to_pubkey, harness.vault_address(&mint));
+    assert_eq!(message.amount.get(), 1_000);
+
+    // Jedan grant po grantor/beneficiary paru dok se ne isplati
+    let err = harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            1_000,
+            schedule,
+            true,
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::AlreadyInitialized.into()));
+}
+
+#[tokio::test]
+async fn beneficiary_claims_only_after_the_cliff() {
+    let mut harness = Harness::start().await;
+    let (mint, grantor, grantor_tokens, beneficiary, beneficiary_tokens) =
+        grant_setup(&mut harness).await;
+    let now = harness.now().await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            1_000,
+            (now - 100, now + 10_000, now + 20_000),
+            false,
+        )
+        .await
+        .unwrap();
+
+    let err = harness
+        .claim_vested(&beneficiary, &beneficiary_tokens, &grantor.pubkey(), &mint)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NothingVested.into()));
+
+    // Grant koji je vec potpuno otkljucan se isplacuje odjednom
+    let other = harness.create_user().await;
+    let other_tokens = harness
+        .create_token_account(&mint, &other.pubkey(), 0)
+        .await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &other.pubkey(),
+            &mint,
+            500,
+            (now - 200, now - 100, now - 50),
+            false,
+        )
+        .await
+        .unwrap();
+    let message = harness.next_message_address().await;
+    let mut instruction = escrow_instruction::claim_vested(
+        &harness.escrow_program_id,
+        &harness.logger_program_id,
+        &beneficiary.pubkey(),
+        &beneficiary_tokens,
+        &grantor.pubkey(),
+        &mint,
+        &harness.logger_state,
+        &message,
+    );
+    instruction.accounts[4].pubkey = Vesting::find_address(
+        &mint,
+        &grantor.pubkey(),
+        &other.pubkey(),
+        &harness.escrow_program_id,
+    )
+    .0;
+    let err = harness
+        .process(&[instruction], &[&beneficiary])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotVestingBeneficiary.into()));
+
+    let sequence = harness.logger_sequence().await;
+    let grantor_lamports = harness.banks.get_balance(grantor.pubkey()).await.unwrap();
+    harness
+        .claim_vested(&other, &other_tokens, &grantor.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(other_tokens).await, 500);
+    assert_eq!(harness.total_deposited(&mint).await, 1_000);
+    assert!(harness
+        .vesting(&mint, &grantor.pubkey(), &other.pubkey())
+        .await
+        .is_none());
+    assert!(harness.banks.get_balance(grantor.pubkey()).await.unwrap() > grantor_lamports);
+
+    let message = harness.message(sequence + 1).await.unwrap();
+    assert_eq!(message.from_pubkey, harness.vault_address(&mint));
+    assert_eq!(message.to_pubkey, other.pubkey());
+    assert_eq!(message.amount.get(), 500);
+
+    // Isplacen grant je zatvoren
+    let err = harness
+        .claim_vested(&other, &other_tokens, &grantor.pubkey(), &mint)
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::IncorrectAccountOwner.into()));
+}
+
+#[tokio::test]
+async fn revoke_returns_the_unvested_remainder() {
+    let mut harness = Harness::start().await;
+    let (mint, grantor, grantor_tokens, beneficiary, beneficiary_tokens) =
+        grant_setup(&mut harness).await;
+    let outsider = harness.create_user().await;
+    let outsider_tokens = harness
+        .create_token_account(&mint, &outsider.pubkey(), 0)
+        .await;
+    let now = harness.now().await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            2_000,
+            (now - 1_000, now - 500, now + 1_000),
+            true,
+        )
+        .await
+        .unwrap();
+
+    // Tudji potpis na pravom vesting nalogu
+    let mut instruction = harness
+        .revoke_instruction(&outsider.pubkey(), &outsider_tokens, &beneficiary.pubkey(), &mint)
+        .await;
+    let real = harness
+        .revoke_instruction(&grantor.pubkey(), &grantor_tokens, &beneficiary.pubkey(), &mint)
+        .await;
+    instruction.accounts[4] = real.accounts[4].clone();
+    let err = harness
+        .process(&[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotVestingGrantor.into()));
+
+    harness.process(&[real], &[&grantor]).await.unwrap();
+    let returned = harness.token_balance(grantor_tokens).await - 8_000;
+    assert!((900..=1_000).contains(&returned), "returned {returned}");
+    let vesting = harness
+        .vesting(&mint, &grantor.pubkey(), &beneficiary.pubkey())
+        .await
+        .unwrap();
+    assert_eq!(vesting.total_amount.get(), 2_000 - returned);
+    assert_eq!(vesting.revocable, 0);
+
+    let again = harness
+        .revoke_instruction(&grantor.pubkey(), &grantor_tokens, &beneficiary.pubkey(), &mint)
+        .await;
+    let err = harness.process(&[again], &[&grantor]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::NotRevocable.into()));
+
+    // Otkljucani deo ostaje beneficiary-ju i posle opoziva
+    harness
+        .claim_vested(&beneficiary, &beneficiary_tokens, &grantor.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(
+        harness.token_balance(beneficiary_tokens).await,
+        2_000 - returned
+    );
+    assert!(harness
+        .vesting(&mint, &grantor.pubkey(), &beneficiary.pubkey())
+        .await
+        .is_none());
+    assert_eq!(harness.total_deposited(&mint).await, 0);
+    assert_eq!(harness.token_balance(harness.vault_address(&mint)).await, 0);
+}
+
+#[tokio::test]
+async fn revoking_a_fully_vested_grant_fails() {
+    let mut harness = Harness::start().await;
+    let (mint, grantor, grantor_tokens, beneficiary, beneficiary_tokens) =
+        grant_setup(&mut harness).await;
+    let now = harness.now().await;
+    harness
+        .create_vesting(
+            &grantor,
+            &grantor_tokens,
+            &beneficiary.pubkey(),
+            &mint,
+            1_000,
+            (now - 200, now - 100, now - 50),
+            true,
+        )
+        .await
+        .unwrap();
+
+    let sequence = harness.logger_sequence().await;
+    let revoke = harness
+        .revoke_instruction(&grantor.pubkey(), &grantor_tokens, &beneficiary.pubkey(), &mint)
+        .await;
+    let err = harness.process(&[revoke], &[&grantor]).await.unwrap_err();
+    assert_eq!(err, program_error(EscrowError::InsufficientAmount.into()));
+    // Nijedna poruka od 0 tokena nije logovana
+    assert_eq!(harness.logger_sequence().await, sequence);
+    assert_eq!(harness.token_balance(grantor_tokens).await, 9_000);
+
+    harness
+        .claim_vested(&beneficiary, &beneficiary_tokens, &grantor.pubkey(), &mint)
+        .await
+        .unwrap();
+    assert_eq!(harness.token_balance(beneficiary_tokens).await, 1_000);
+}
@@ -0,0 +1,24 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint,
+    entrypoint::ProgramResult,
+    program_error::PrintProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{error::LoggerError, processor::LoggerProcessor};
+
+// Standardni Solana entrypoint
+entrypoint!(process_instruction);
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    if let Err(error) = LoggerProcessor::process(program_id, accounts, instruction_data) {
+        error.print::<LoggerError>();
+        return Err(error);
+    }
+    Ok(())
+}
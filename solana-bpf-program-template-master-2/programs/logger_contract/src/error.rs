@@ -0,0 +1,97 @@
+use std::ops::RangeInclusive;
+
+use num_derive::FromPrimitive;
+use num_traits::FromPrimitive;
+use thiserror::Error;
+use solana_program::{
+    decode_error::DecodeError,
+    msg,
+    program_error::{PrintProgramError, ProgramError},
+};
+
+use crate::validation::AccountCheck;
+
+/// Custom error codes owned by the logger program, disjoint from the
+/// escrow range so a code surfacing through CPI still names its program.
+pub const LOGGER_ERROR_CODES: RangeInclusive<u32> = 2000..=2999;
+
+#[derive(Error, Debug, Copy, Clone, PartialEq, Eq, FromPrimitive)]
+pub enum LoggerError {
+    #[error("Invalid Instruction")]
+    InvalidInstruction = 2000,
+    #[error("Logger State Already Initialized")]
+    AlreadyInitialized = 2001,
+    #[error("Account Not Writable")]
+    AccountNotWritable = 2002,
+    #[error("Incorrect Message Address")]
+    IncorrectMessageAddress = 2003,
+    #[error("Signer Is Not The Logger Authority")]
+    NotAuthority = 2004,
+    #[error("Payer Mismatch")]
+    PayerMismatch = 2005,
+    #[error("Sequence Overflow")]
+    SequenceOverflow = 2006,
+    #[error("Ring Buffer State Mismatch")]
+    RingStateMismatch = 2007,
+    #[error("Ring Buffer Too Small")]
+    RingTooSmall = 2008,
+    #[error("Ring Buffer Can Only Grow")]
+    RingCannotShrink = 2009,
+    #[error("Ring Buffer Growth Too Large")]
+    RingGrowthTooLarge = 2010,
+    #[error("Invalid Consistency Level")]
+    InvalidConsistencyLevel = 2011,
+    #[error("Lamports Overflow")]
+    LamportsOverflow = 2012,
+    #[error("Unexpected Account Address")]
+    IncorrectAccountAddress = 2013,
+    #[error("Incorrect Account Owner")]
+    IncorrectAccountOwner = 2014,
+    #[error("Missing Required Signature")]
+    MissingSignature = 2015,
+    #[error("Account Not Executable")]
+    AccountNotExecutable = 2016,
+    #[error("Message Already Posted")]
+    MessageAlreadyPosted = 2017,
+    #[error("Invalid Message Kind")]
+    InvalidMessageKind = 2018,
+}
+
+impl LoggerError {
+    pub fn from_code(code: u32) -> Option<Self> {
+        Self::from_u32(code)
+    }
+}
+
+impl From<AccountCheck> for LoggerError {
+    fn from(check: AccountCheck) -> Self {
+        match check {
+            AccountCheck::Address => LoggerError::IncorrectAccountAddress,
+            AccountCheck::Owner => LoggerError::IncorrectAccountOwner,
+            AccountCheck::Signer => LoggerError::MissingSignature,
+            AccountCheck::Writable => LoggerError::AccountNotWritable,
+            AccountCheck::Executable => LoggerError::AccountNotExecutable,
+        }
+    }
+}
+
+impl From<LoggerError> for ProgramError {
+    fn from(e: LoggerError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+
+impl<T> DecodeError<T> for LoggerError {
+    fn type_of() -> &'static str {
+        "LoggerError"
+    }
+}
+
+impl PrintProgramError for LoggerError {
+    fn print<E>(&self)
+    where
+        E: 'static + std::error::Error + DecodeError<E> + PrintProgramError + FromPrimitive,
+    {
+        msg!("LoggerError {}: {}", *self as u32, self);
+    }
+}
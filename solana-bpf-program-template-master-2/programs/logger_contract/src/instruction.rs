@@ -0,0 +1,336 @@
+use arrayref::array_ref;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use crate::{
+    error::LoggerError::InvalidInstruction,
+    state::{ConsistencyLevel, MessageKind},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LoggerInstruction {
+    // Accounts: [state (w), authority (s)]
+    Initialize {
+        authority: Pubkey,
+    },
+    // Accounts: [state (w), message PDA ["logger", state, sequence] (w), payer (s, w),
+    // system program, emitter (s)]
+    PostMessage {
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    },
+    // Accounts: [authority (s), state, message PDA (w), original payer (w)]
+    CloseMessage,
+    // Accounts: [authority (s), state (w)]
+    SetAuthority {
+        new_authority: Pubkey,
+    },
+    // Accounts: [state], sequence se vraca kroz return data
+    GetSequence,
+    // Accounts: [authority (s), state, ring (w)]
+    InitializeRing,
+    // Accounts: [authority (s), state, ring (w), payer (s, w), system program]
+    ResizeRing {
+        capacity: u64,
+    },
+    // Accounts: [state (w), ring (w), emitter (s)], poruka ide u slot sequence % N bez rent-a
+    PostRingMessage {
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    },
+}
+
+impl LoggerInstruction {
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        let (tag, rest) = input.split_first().ok_or(InvalidInstruction)?;
+        Ok(match tag {
+            0 => {
+                let authority = Self::unpack_pubkey(rest)?;
+                LoggerInstruction::Initialize { authority }
+            },
+            1 | 7 => {
+                if rest.len() < 82 {
+                    return Err(InvalidInstruction.into());
+                }
+                let from_pubkey = Pubkey::new_from_array(*array_ref![rest, 0, 32]);
+                let to_pubkey = Pubkey::new_from_array(*array_ref![rest, 32, 32]);
+                let amount = u64::from_le_bytes(*array_ref![rest, 64, 8]);
+                let timestamp = u64::from_le_bytes(*array_ref![rest, 72, 8]);
+                let consistency_level = ConsistencyLevel::try_from(rest[80])?;
+                let kind = MessageKind::try_from(rest[81])?;
+                if *tag == 1 {
+                    LoggerInstruction::PostMessage {
+                        from_pubkey,
+                        to_pubkey,
+                        amount,
+                        timestamp,
+                        consistency_level,
+                        kind,
+                    }
+                } else {
+                    LoggerInstruction::PostRingMessage {
+                        from_pubkey,
+                        to_pubkey,
+                        amount,
+                        timestamp,
+                        consistency_level,
+                        kind,
+                    }
+                }
+            },
+            2 => LoggerInstruction::CloseMessage,
+            3 => {
+                let new_authority = Self::unpack_pubkey(rest)?;
+                LoggerInstruction::SetAuthority { new_authority }
+            },
+            4 => LoggerInstruction::GetSequence,
+            5 => LoggerInstruction::InitializeRing,
+            6 => {
+                if rest.len() < 8 {
+                    return Err(InvalidInstruction.into());
+                }
+                let capacity = u64::from_le_bytes(*array_ref![rest, 0, 8]);
+                LoggerInstruction::ResizeRing { capacity }
+            },
+            _ => return Err(InvalidInstruction.into()),
+        })
+    }
+
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(83);
+        match self {
+            LoggerInstruction::Initialize { authority } => {
+                buf.push(0);
+                buf.extend_from_slice(authority.as_ref());
+            }
+            LoggerInstruction::PostMessage {
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            } => {
+                buf.push(1);
+                Self::pack_message(&mut buf, from_pubkey, to_pubkey, *amount, *timestamp, *consistency_level, *kind);
+            }
+            LoggerInstruction::CloseMessage => buf.push(2),
+            LoggerInstruction::SetAuthority { new_authority } => {
+                buf.push(3);
+                buf.extend_from_slice(new_authority.as_ref());
+            }
+            LoggerInstruction::GetSequence => buf.push(4),
+            LoggerInstruction::InitializeRing => buf.push(5),
+            LoggerInstruction::ResizeRing { capacity } => {
+                buf.push(6);
+                buf.extend_from_slice(&capacity.to_le_bytes());
+            }
+            LoggerInstruction::PostRingMessage {
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            } => {
+                buf.push(7);
+                Self::pack_message(&mut buf, from_pubkey, to_pubkey, *amount, *timestamp, *consistency_level, *kind);
+            }
+        }
+        buf
+    }
+
+    fn pack_message(
+        buf: &mut Vec<u8>,
+        from_pubkey: &Pubkey,
+        to_pubkey: &Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    ) {
+        buf.extend_from_slice(from_pubkey.as_ref());
+        buf.extend_from_slice(to_pubkey.as_ref());
+        buf.extend_from_slice(&amount.to_le_bytes());
+        buf.extend_from_slice(&timestamp.to_le_bytes());
+        buf.push(consistency_level as u8);
+        buf.push(kind as u8);
+    }
+
+    fn unpack_pubkey(input: &[u8]) -> Result<Pubkey, ProgramError> {
+        if input.len() < 32 {
+            return Err(InvalidInstruction.into());
+        }
+        Ok(Pubkey::new_from_array(*array_ref![input, 0, 32]))
+    }
+}
+
+pub fn initialize(program_id: &Pubkey, state: &Pubkey, authority: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*state, false),
+            AccountMeta::new_readonly(*authority, true),
+        ],
+        data: LoggerInstruction::Initialize { authority: *authority }.pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn post_message(
+    program_id: &Pubkey,
+    state: &Pubkey,
+    message: &Pubkey,
+    payer: &Pubkey,
+    emitter: &Pubkey,
+    from_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    amount: u64,
+    timestamp: u64,
+    consistency_level: ConsistencyLevel,
+    kind: MessageKind,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(*emitter, true),
+        ],
+        data: LoggerInstruction::PostMessage {
+            from_pubkey: *from_pubkey,
+            to_pubkey: *to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        }
+        .pack(),
+    }
+}
+
+pub fn close_message(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    message: &Pubkey,
+    payer: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*state, false),
+            AccountMeta::new(*message, false),
+            AccountMeta::new(*payer, false),
+        ],
+        data: LoggerInstruction::CloseMessage.pack(),
+    }
+}
+
+pub fn set_authority(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    new_authority: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*state, false),
+        ],
+        data: LoggerInstruction::SetAuthority { new_authority: *new_authority }.pack(),
+    }
+}
+
+pub fn get_sequence(program_id: &Pubkey, state: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*state, false)],
+        data: LoggerInstruction::GetSequence.pack(),
+    }
+}
+
+pub fn initialize_ring(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    ring: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*state, false),
+            AccountMeta::new(*ring, false),
+        ],
+        data: LoggerInstruction::InitializeRing.pack(),
+    }
+}
+
+pub fn resize_ring(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    state: &Pubkey,
+    ring: &Pubkey,
+    payer: &Pubkey,
+    capacity: u64,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new_readonly(*state, false),
+            AccountMeta::new(*ring, false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: LoggerInstruction::ResizeRing { capacity }.pack(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn post_ring_message(
+    program_id: &Pubkey,
+    state: &Pubkey,
+    ring: &Pubkey,
+    emitter: &Pubkey,
+    from_pubkey: &Pubkey,
+    to_pubkey: &Pubkey,
+    amount: u64,
+    timestamp: u64,
+    consistency_level: ConsistencyLevel,
+    kind: MessageKind,
+) -> Instruction {
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*state, false),
+            AccountMeta::new(*ring, false),
+            AccountMeta::new_readonly(*emitter, true),
+        ],
+        data: LoggerInstruction::PostRingMessage {
+            from_pubkey: *from_pubkey,
+            to_pubkey: *to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        }
+        .pack(),
+    }
+}
@@ -0,0 +1,99 @@
+use std::{fmt, mem::size_of};
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::program_error::ProgramError;
+
+/// Little-endian `u64` with alignment 1, so a struct made of these casts
+/// from account data at any address.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Pod, Zeroable)]
+pub struct PodU64([u8; 8]);
+
+impl PodU64 {
+    pub const fn new(value: u64) -> Self {
+        PodU64(value.to_le_bytes())
+    }
+
+    pub const fn get(self) -> u64 {
+        u64::from_le_bytes(self.0)
+    }
+}
+
+impl From<u64> for PodU64 {
+    fn from(value: u64) -> Self {
+        PodU64::new(value)
+    }
+}
+
+impl From<PodU64> for u64 {
+    fn from(value: PodU64) -> Self {
+        value.get()
+    }
+}
+
+impl fmt::Debug for PodU64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+impl fmt::Display for PodU64 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.get().fmt(f)
+    }
+}
+
+/// Account layout cast in place from account data. The first 8 bytes are
+/// the discriminator; all zeroes means the account is not initialized.
+pub trait ZeroCopy: Pod {
+    const DISCRIMINATOR: [u8; 8];
+
+    // Dodatne provere polja, npr. da enum bajt ima poznatu vrednost
+    fn validate(&self) -> Result<(), ProgramError> {
+        Ok(())
+    }
+
+    fn is_initialized(&self) -> bool {
+        bytemuck::bytes_of(self)[..8] == Self::DISCRIMINATOR
+    }
+
+    /// Casts `data` without looking at the discriminator; `data` must be
+    /// exactly as long as the layout.
+    fn load_unchecked(data: &[u8]) -> Result<&Self, ProgramError> {
+        if data.len() != size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(bytemuck::from_bytes(data))
+    }
+
+    fn load_mut_unchecked(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        if data.len() != size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(bytemuck::from_bytes_mut(data))
+    }
+
+    /// Casts initialized account data of this type.
+    fn load(data: &[u8]) -> Result<&Self, ProgramError> {
+        let account = Self::load_unchecked(data)?;
+        check(account)?;
+        Ok(account)
+    }
+
+    fn load_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let account = Self::load_mut_unchecked(data)?;
+        check(&*account)?;
+        Ok(account)
+    }
+}
+
+fn check<T: ZeroCopy>(account: &T) -> Result<(), ProgramError> {
+    let discriminator = &bytemuck::bytes_of(account)[..8];
+    if discriminator == [0u8; 8] {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if discriminator != T::DISCRIMINATOR {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    account.validate()
+}
@@ -0,0 +1,519 @@
+use bridge_events::{Event, MessagePosted};
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::{ProgramResult, MAX_PERMITTED_DATA_INCREASE},
+    msg,
+    program::{invoke, invoke_signed, set_return_data},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_instruction,
+    system_program,
+    sysvar::{clock::Clock, rent::Rent, Sysvar},
+};
+use crate::{
+    error::LoggerError,
+    instruction::LoggerInstruction,
+    pod::ZeroCopy,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+    validation::{check_accounts, AccountSpec},
+};
+
+pub struct LoggerProcessor;
+impl LoggerProcessor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = LoggerInstruction::unpack(instruction_data)?;
+        match instruction {
+            LoggerInstruction::Initialize { authority } => {
+                msg!("Logger: Initialize");
+                Self::process_initialize(program_id, accounts, authority)
+            }
+            LoggerInstruction::PostMessage {
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            } => Self::process_post_message(
+                program_id,
+                accounts,
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            ),
+            LoggerInstruction::CloseMessage => {
+                msg!("Logger: CloseMessage");
+                Self::process_close_message(program_id, accounts)
+            }
+            LoggerInstruction::SetAuthority { new_authority } => {
+                msg!("Logger: SetAuthority");
+                Self::process_set_authority(program_id, accounts, new_authority)
+            }
+            LoggerInstruction::GetSequence => Self::process_get_sequence(program_id, accounts),
+            LoggerInstruction::InitializeRing => {
+                msg!("Logger: InitializeRing");
+                Self::process_initialize_ring(program_id, accounts)
+            }
+            LoggerInstruction::ResizeRing { capacity } => {
+                msg!("Logger: ResizeRing {}", capacity);
+                Self::process_resize_ring(program_id, accounts, capacity)
+            }
+            LoggerInstruction::PostRingMessage {
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            } => Self::process_post_ring_message(
+                program_id,
+                accounts,
+                from_pubkey,
+                to_pubkey,
+                amount,
+                timestamp,
+                consistency_level,
+                kind,
+            ),
+        }
+    }
+
+    fn process_initialize(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        authority: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let state_account = next_account_info(accounts_iter)?;
+        let authority_account = next_account_info(accounts_iter)?;
+
+        // Authority potpisuje, inace bi bilo ko mogao da preduhitri
+        // inicijalizaciju tudjeg state naloga svojim authority-jem
+        check_accounts::<LoggerError>(&[
+            (state_account, AccountSpec::new("state").owner(*program_id).writable()),
+            (authority_account, AccountSpec::new("authority").address(authority).signer()),
+        ])?;
+
+        let mut state_data = state_account.data.borrow_mut();
+        let logger_state = LoggerState::load_mut_unchecked(&mut state_data)?;
+        if logger_state.is_initialized() {
+            return Err(LoggerError::AlreadyInitialized.into());
+        }
+        *logger_state = LoggerState::new(authority);
+
+        msg!("Logger: State initialized, authority {}", authority);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_post_message(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let state_account = next_account_info(accounts_iter)?;
+        let message_pda_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+        let system_program_account = next_account_info(accounts_iter)?;
+        let emitter_account = next_account_info(accounts_iter)?;
+
+        check_accounts::<LoggerError>(&[
+            (state_account, AccountSpec::new("state").owner(*program_id).writable()),
+            (message_pda_account, AccountSpec::new("message").writable()),
+            (payer_account, AccountSpec::new("payer").signer().writable()),
+            (system_program_account, AccountSpec::new("system program").program(system_program::id())),
+            (emitter_account, AccountSpec::new("emitter").signer()),
+        ])?;
+
+        let message_data = Self::next_message(
+            state_account,
+            *payer_account.key,
+            *emitter_account.key,
+            from_pubkey,
+            to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        )?;
+        let sequence = message_data.sequence.get();
+
+        let (expected_pda, bump) =
+            MessageData::find_address(state_account.key, sequence, program_id);
+        if &expected_pda != message_pda_account.key {
+            return Err(LoggerError::IncorrectMessageAddress.into());
+        }
+        // Postojecu poruku nikad ne prepisujemo
+        if message_pda_account.owner == program_id {
+            return Err(LoggerError::MessageAlreadyPosted.into());
+        }
+
+        let space = MessageData::LEN;
+        let rent_lamports = Rent::get()?.minimum_balance(space);
+        let sequence_bytes = sequence.to_le_bytes();
+        let seeds: &[&[u8]] = &[b"logger", state_account.key.as_ref(), &sequence_bytes, &[bump]];
+        if message_pda_account.lamports() == 0 {
+            invoke_signed(
+                &system_instruction::create_account(
+                    payer_account.key,
+                    message_pda_account.key,
+                    rent_lamports,
+                    space as u64,
+                    program_id,
+                ),
+                &[
+                    payer_account.clone(),
+                    message_pda_account.clone(),
+                    system_program_account.clone(),
+                ],
+                &[seeds],
+            )?;
+        } else {
+            // PDA je predvidljiva, pa neko moze unapred poslati lamporte na nju i
+            // create_account bi pao; nalog se dopuni do rent-a, alocira i dodeli
+            let top_up = rent_lamports.saturating_sub(message_pda_account.lamports());
+            if top_up > 0 {
+                invoke(
+                    &system_instruction::transfer(payer_account.key, message_pda_account.key, top_up),
+                    &[
+                        payer_account.clone(),
+                        message_pda_account.clone(),
+                        system_program_account.clone(),
+                    ],
+                )?;
+            }
+            invoke_signed(
+                &system_instruction::allocate(message_pda_account.key, space as u64),
+                &[message_pda_account.clone(), system_program_account.clone()],
+                &[seeds],
+            )?;
+            invoke_signed(
+                &system_instruction::assign(message_pda_account.key, program_id),
+                &[message_pda_account.clone(), system_program_account.clone()],
+                &[seeds],
+            )?;
+        }
+
+        let mut pda_data = message_pda_account.data.borrow_mut();
+        *MessageData::load_mut_unchecked(&mut pda_data)? = message_data;
+
+        Self::announce(state_account.key, &message_data);
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_post_ring_message(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let state_account = next_account_info(accounts_iter)?;
+        let ring_account = next_account_info(accounts_iter)?;
+        let emitter_account = next_account_info(accounts_iter)?;
+
+        check_accounts::<LoggerError>(&[
+            (state_account, AccountSpec::new("state").owner(*program_id).writable()),
+            (ring_account, AccountSpec::new("ring").owner(*program_id).writable()),
+            (emitter_account, AccountSpec::new("emitter").signer()),
+        ])?;
+        if ring_account.key == state_account.key {
+            return Err(LoggerError::IncorrectMessageAddress.into());
+        }
+
+        // Ring ne placa rent, pa poruka nema payer-a kome bi se vratio
+        let message_data = Self::next_message(
+            state_account,
+            Pubkey::default(),
+            *emitter_account.key,
+            from_pubkey,
+            to_pubkey,
+            amount,
+            timestamp,
+            consistency_level,
+            kind,
+        )?;
+
+        let mut ring_data = ring_account.data.borrow_mut();
+        let capacity = RingHeader::capacity(ring_data.len());
+        if capacity == 0 {
+            return Err(LoggerError::RingTooSmall.into());
+        }
+        let header = RingHeader::load(&ring_data[..RingHeader::LEN])?;
+        if header.logger_state != *state_account.key {
+            return Err(LoggerError::RingStateMismatch.into());
+        }
+
+        let offset = RingHeader::slot_offset(message_data.sequence.get(), capacity);
+        *MessageData::load_mut_unchecked(&mut ring_data[offset..offset + MessageData::LEN])? =
+            message_data;
+
+        Self::announce(state_account.key, &message_data);
+        Ok(())
+    }
+
+    // Dodeljuje sledecu sekvencu i dodaje poruku u accumulator state-a
+    #[allow(clippy::too_many_arguments)]
+    fn next_message(
+        state_account: &AccountInfo,
+        payer: Pubkey,
+        emitter: Pubkey,
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        consistency_level: ConsistencyLevel,
+        kind: MessageKind,
+    ) -> Result<MessageData, ProgramError> {
+        let mut state_data = state_account.data.borrow_mut();
+        let logger_state = LoggerState::load_mut(&mut state_data)?;
+        let sequence = logger_state
+            .sequence
+            .get()
+            .checked_add(1)
+            .ok_or(LoggerError::SequenceOverflow)?;
+        logger_state.sequence = sequence.into();
+
+        let clock = Clock::get()?;
+        let message_data = MessageData::new(
+            from_pubkey,
+            to_pubkey,
+            amount,
+            timestamp,
+            sequence,
+            payer,
+            clock.slot,
+            clock.epoch,
+            consistency_level,
+            emitter,
+            kind,
+        );
+        logger_state.accumulate(&message_data.hash());
+        Ok(message_data)
+    }
+
+    fn announce(logger_state: &Pubkey, message_data: &MessageData) {
+        msg!(
+            "--------------------------------\n\
+             FROM:      {}\n\
+             TO:        {}\n\
+             AMOUNT:    {}\n\
+             TIMESTAMP: {}\n\
+             SEQUENCE:  {}\n\
+             SLOT:      {}\n\
+             EMITTER:   {}\n\
+             --------------------------------",
+            message_data.from_pubkey,
+            message_data.to_pubkey,
+            message_data.amount,
+            message_data.timestamp,
+            message_data.sequence,
+            message_data.slot,
+            message_data.emitter
+        );
+
+        MessagePosted {
+            sequence: message_data.sequence.get(),
+            logger_state: *logger_state,
+            from: message_data.from_pubkey,
+            to: message_data.to_pubkey,
+            amount: message_data.amount.get(),
+            timestamp: message_data.timestamp.get(),
+            payer: message_data.payer,
+            slot: message_data.slot.get(),
+            epoch: message_data.epoch.get(),
+            consistency_level: message_data.consistency_level,
+            emitter: message_data.emitter,
+            kind: message_data.kind,
+        }
+        .emit();
+    }
+
+    fn process_close_message(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let authority_account = next_account_info(accounts_iter)?;
+        let state_account = next_account_info(accounts_iter)?;
+        let message_pda_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+
+        Self::check_authority(program_id, authority_account, state_account)?;
+
+        check_accounts::<LoggerError>(&[
+            (message_pda_account, AccountSpec::new("message").owner(*program_id).writable()),
+            (payer_account, AccountSpec::new("payer").writable()),
+        ])?;
+
+        let message_data = *MessageData::load(&message_pda_account.data.borrow())?;
+
+        let (expected_pda, _) =
+            MessageData::find_address(state_account.key, message_data.sequence.get(), program_id);
+        if &expected_pda != message_pda_account.key {
+            return Err(LoggerError::IncorrectMessageAddress.into());
+        }
+
+        if message_data.payer != *payer_account.key {
+            return Err(LoggerError::PayerMismatch.into());
+        }
+
+        let payer_lamports = payer_account.lamports();
+        **payer_account.lamports.borrow_mut() = payer_lamports
+            .checked_add(message_pda_account.lamports())
+            .ok_or(LoggerError::LamportsOverflow)?;
+        **message_pda_account.lamports.borrow_mut() = 0;
+
+        message_pda_account.assign(&system_program::id());
+        message_pda_account.realloc(0, false)?;
+
+        msg!("Logger: Message {} closed.", message_data.sequence);
+        Ok(())
+    }
+
+    fn process_set_authority(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        new_authority: Pubkey,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let authority_account = next_account_info(accounts_iter)?;
+        let state_account = next_account_info(accounts_iter)?;
+
+        Self::check_authority(program_id, authority_account, state_account)?;
+
+        check_accounts::<LoggerError>(&[(state_account, AccountSpec::new("state").writable())])?;
+
+        let mut state_data = state_account.data.borrow_mut();
+        LoggerState::load_mut(&mut state_data)?.authority = new_authority;
+
+        msg!("Logger: Authority set to {}", new_authority);
+        Ok(())
+    }
+
+    fn process_get_sequence(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let state_account = next_account_info(accounts_iter)?;
+
+        check_accounts::<LoggerError>(&[(state_account, AccountSpec::new("state").owner(*program_id))])?;
+
+        let state_data = state_account.data.borrow();
+        set_return_data(&LoggerState::load(&state_data)?.sequence.get().to_le_bytes());
+        Ok(())
+    }
+
+    fn check_authority<'a>(
+        program_id: &Pubkey,
+        authority_account: &AccountInfo<'a>,
+        state_account: &AccountInfo<'a>,
+    ) -> ProgramResult {
+        check_accounts::<LoggerError>(&[
+            (authority_account, AccountSpec::new("authority").signer()),
+            (state_account, AccountSpec::new("state").owner(*program_id)),
+        ])?;
+        let state_data = state_account.data.borrow();
+        if LoggerState::load(&state_data)?.authority != *authority_account.key {
+            return Err(LoggerError::NotAuthority.into());
+        }
+        Ok(())
+    }
+
+    fn process_initialize_ring(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let authority_account = next_account_info(accounts_iter)?;
+        let state_account = next_account_info(accounts_iter)?;
+        let ring_account = next_account_info(accounts_iter)?;
+
+        Self::check_authority(program_id, authority_account, state_account)?;
+
+        check_accounts::<LoggerError>(&[(ring_account, AccountSpec::new("ring").owner(*program_id).writable())])?;
+
+        let mut ring_data = ring_account.data.borrow_mut();
+        let capacity = RingHeader::capacity(ring_data.len());
+        if capacity == 0 {
+            return Err(LoggerError::RingTooSmall.into());
+        }
+
+        let header = RingHeader::load_mut_unchecked(&mut ring_data[..RingHeader::LEN])?;
+        if header.is_initialized() {
+            return Err(LoggerError::AlreadyInitialized.into());
+        }
+        *header = RingHeader::new(*state_account.key);
+
+        msg!("Logger: Ring buffer initialized with {} slots.", capacity);
+        Ok(())
+    }
+
+    fn process_resize_ring(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        capacity: u64,
+    ) -> ProgramResult {
+        let accounts_iter = &mut accounts.iter();
+        let authority_account = next_account_info(accounts_iter)?;
+        let state_account = next_account_info(accounts_iter)?;
+        let ring_account = next_account_info(accounts_iter)?;
+        let payer_account = next_account_info(accounts_iter)?;
+        let system_program_account = next_account_info(accounts_iter)?;
+
+        Self::check_authority(program_id, authority_account, state_account)?;
+
+        check_accounts::<LoggerError>(&[
+            (ring_account, AccountSpec::new("ring").owner(*program_id).writable()),
+            (payer_account, AccountSpec::new("payer").signer().writable()),
+            (system_program_account, AccountSpec::new("system program").program(system_program::id())),
+        ])?;
+
+        let header = match ring_account.data.borrow().get(..RingHeader::LEN) {
+            Some(header) => *RingHeader::load(header)?,
+            None => return Err(LoggerError::RingTooSmall.into()),
+        };
+        if header.logger_state != *state_account.key {
+            return Err(LoggerError::RingStateMismatch.into());
+        }
+
+        // Kapacitet proveravamo pre space() da mnozenje ne bi prekoracilo usize
+        let old_len = ring_account.data_len();
+        if capacity > RingHeader::capacity(old_len + MAX_PERMITTED_DATA_INCREASE) {
+            return Err(LoggerError::RingGrowthTooLarge.into());
+        }
+        let new_len = RingHeader::space(capacity);
+        if new_len <= old_len {
+            return Err(LoggerError::RingCannotShrink.into());
+        }
+
+        let required_lamports = Rent::get()?
+            .minimum_balance(new_len)
+            .saturating_sub(ring_account.lamports());
+        if required_lamports > 0 {
+            invoke(
+                &system_instruction::transfer(payer_account.key, ring_account.key, required_lamports),
+                &[
+                    payer_account.clone(),
+                    ring_account.clone(),
+                    system_program_account.clone(),
+                ],
+            )?;
+        }
+
+        ring_account.realloc(new_len, true)?;
+
+        msg!("Logger: Ring buffer resized to {} slots.", capacity);
+        Ok(())
+    }
+}
@@ -0,0 +1,229 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+    hash::{hash, hashv, Hash},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::{
+    error::LoggerError,
+    pod::{PodU64, ZeroCopy},
+};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct LoggerState {
+    pub discriminator: [u8; 8],
+    pub sequence: PodU64,
+    // Relayer koji potvrdjuje obradjene poruke i zatvara njihove PDA
+    pub authority: Pubkey,
+    // Hash lanac svih poruka, ostaje i kada se poruka zatvori
+    pub accumulator: [u8; 32],
+}
+
+impl ZeroCopy for LoggerState {
+    const DISCRIMINATOR: [u8; 8] = *b"logstate";
+}
+
+impl LoggerState {
+    // 8 + 8 + 32 + 32 = 80 bajtova
+    pub const LEN: usize = size_of::<LoggerState>();
+
+    pub fn new(authority: Pubkey) -> Self {
+        LoggerState {
+            discriminator: Self::DISCRIMINATOR,
+            authority,
+            ..LoggerState::default()
+        }
+    }
+
+    pub fn accumulate(&mut self, message_hash: &Hash) {
+        self.accumulator = hashv(&[&self.accumulator, message_hash.as_ref()]).to_bytes();
+    }
+}
+
+// Koliko relayer treba da ceka pre nego sto postupi po poruci
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConsistencyLevel {
+    #[default]
+    Confirmed = 0,
+    Finalized = 1,
+}
+
+impl TryFrom<u8> for ConsistencyLevel {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ConsistencyLevel::Confirmed),
+            1 => Ok(ConsistencyLevel::Finalized),
+            _ => Err(LoggerError::InvalidConsistencyLevel.into()),
+        }
+    }
+}
+
+// Sta poruka znaci za citaoce; logger vrstu samo cuva, a tumaci je emitter
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MessageKind {
+    // Lock u vault ili unlock iz vault-a preko mosta
+    #[default]
+    Transfer = 0,
+    // Vesting, sporazum ili spor: tokeni u vault-u koji ne prelaze most
+    Escrow = 1,
+    // Vise transfera razlicitih mint-ova: amount je broj legova, a legovi su u batch nalogu emittera
+    Batch = 2,
+}
+
+impl TryFrom<u8> for MessageKind {
+    type Error = ProgramError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MessageKind::Transfer),
+            1 => Ok(MessageKind::Escrow),
+            2 => Ok(MessageKind::Batch),
+            _ => Err(LoggerError::InvalidMessageKind.into()),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct MessageData {
+    pub discriminator: [u8; 8],
+    pub from_pubkey: Pubkey,
+    pub to_pubkey: Pubkey,
+    pub amount: PodU64,
+    pub timestamp: PodU64,
+    pub sequence: PodU64,
+    // Ko je platio rent za PDA, dobija ga nazad na CloseMessage
+    pub payer: Pubkey,
+    pub slot: PodU64,
+    pub epoch: PodU64,
+    // ConsistencyLevel kao bajt, enum ne moze biti Pod
+    pub consistency_level: u8,
+    // Potpisnik koji je objavio poruku; za escrow je to njegov config PDA
+    pub emitter: Pubkey,
+    // MessageKind kao bajt
+    pub kind: u8,
+}
+
+impl ZeroCopy for MessageData {
+    const DISCRIMINATOR: [u8; 8] = *b"logmsg\0\0";
+
+    fn validate(&self) -> Result<(), ProgramError> {
+        ConsistencyLevel::try_from(self.consistency_level)?;
+        MessageKind::try_from(self.kind).map(|_| ())
+    }
+}
+
+impl MessageData {
+    // 8 + 32 + 32 + 8 + 8 + 8 + 32 + 8 + 8 + 1 + 32 + 1 = 178 bajtova
+    pub const LEN: usize = size_of::<MessageData>();
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        from_pubkey: Pubkey,
+        to_pubkey: Pubkey,
+        amount: u64,
+        timestamp: u64,
+        sequence: u64,
+        payer: Pubkey,
+        slot: u64,
+        epoch: u64,
+        consistency_level: ConsistencyLevel,
+        emitter: Pubkey,
+        kind: MessageKind,
+    ) -> Self {
+        MessageData {
+            discriminator: Self::DISCRIMINATOR,
+            from_pubkey,
+            to_pubkey,
+            amount: amount.into(),
+            timestamp: timestamp.into(),
+            sequence: sequence.into(),
+            payer,
+            slot: slot.into(),
+            epoch: epoch.into(),
+            consistency_level: consistency_level as u8,
+            emitter,
+            kind: kind as u8,
+        }
+    }
+
+    // Neispravan bajt odbija vec load(), pa ovde ostaje samo Confirmed
+    pub fn consistency_level(&self) -> ConsistencyLevel {
+        ConsistencyLevel::try_from(self.consistency_level).unwrap_or_default()
+    }
+
+    pub fn kind(&self) -> MessageKind {
+        MessageKind::try_from(self.kind).unwrap_or_default()
+    }
+
+    // State je deo seed-a, pa sekvence novog state-a ne sudaraju sa starim PDA
+    pub fn find_address(logger_state: &Pubkey, sequence: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[b"logger", logger_state.as_ref(), &sequence.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    pub fn hash(&self) -> Hash {
+        hash(bytemuck::bytes_of(self))
+    }
+}
+
+// Ring buffer: header + N slotova po MessageData::LEN, poruka ide u slot sequence % N
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct RingHeader {
+    pub discriminator: [u8; 8],
+    pub logger_state: Pubkey,
+}
+
+impl ZeroCopy for RingHeader {
+    const DISCRIMINATOR: [u8; 8] = *b"logring\0";
+}
+
+impl RingHeader {
+    // 8 + 32 = 40 bajtova
+    pub const LEN: usize = size_of::<RingHeader>();
+
+    pub fn new(logger_state: Pubkey) -> Self {
+        RingHeader {
+            discriminator: Self::DISCRIMINATOR,
+            logger_state,
+        }
+    }
+
+    pub fn space(capacity: u64) -> usize {
+        RingHeader::LEN + capacity as usize * MessageData::LEN
+    }
+
+    pub fn capacity(data_len: usize) -> u64 {
+        (data_len.saturating_sub(RingHeader::LEN) / MessageData::LEN) as u64
+    }
+
+    pub fn slot_offset(sequence: u64, capacity: u64) -> usize {
+        RingHeader::LEN + (sequence % capacity) as usize * MessageData::LEN
+    }
+
+    // Vraca None ako je slot u medjuvremenu prepisan novijom porukom
+    pub fn read_message(data: &[u8], sequence: u64) -> Result<Option<MessageData>, ProgramError> {
+        RingHeader::load(&data[..RingHeader::LEN.min(data.len())])?;
+        let capacity = RingHeader::capacity(data.len());
+        if capacity == 0 {
+            return Err(LoggerError::RingTooSmall.into());
+        }
+        let offset = RingHeader::slot_offset(sequence, capacity);
+        let message = MessageData::load_unchecked(&data[offset..offset + MessageData::LEN])?;
+        if !message.is_initialized() || message.sequence.get() != sequence {
+            return Ok(None);
+        }
+        message.validate()?;
+        Ok(Some(*message))
+    }
+}
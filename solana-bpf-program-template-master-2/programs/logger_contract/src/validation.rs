@@ -0,0 +1,131 @@
+use std::fmt;
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+/// The first expectation an account failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccountCheck {
+    Address,
+    Owner,
+    Signer,
+    Writable,
+    Executable,
+}
+
+impl fmt::Display for AccountCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AccountCheck::Address => write!(f, "unexpected address"),
+            AccountCheck::Owner => write!(f, "wrong owner"),
+            AccountCheck::Signer => write!(f, "missing signature"),
+            AccountCheck::Writable => write!(f, "not writable"),
+            AccountCheck::Executable => write!(f, "not executable"),
+        }
+    }
+}
+
+/// What an instruction expects of one of its accounts; anything left
+/// unset is not checked.
+#[derive(Clone, Copy, Debug)]
+pub struct AccountSpec {
+    pub name: &'static str,
+    pub address: Option<Pubkey>,
+    pub owner: Option<Pubkey>,
+    pub signer: bool,
+    pub writable: bool,
+    pub executable: bool,
+}
+
+impl AccountSpec {
+    pub fn new(name: &'static str) -> Self {
+        AccountSpec {
+            name,
+            address: None,
+            owner: None,
+            signer: false,
+            writable: false,
+            executable: false,
+        }
+    }
+
+    pub fn address(self, address: Pubkey) -> Self {
+        AccountSpec {
+            address: Some(address),
+            ..self
+        }
+    }
+
+    pub fn owner(self, owner: Pubkey) -> Self {
+        AccountSpec {
+            owner: Some(owner),
+            ..self
+        }
+    }
+
+    pub fn signer(self) -> Self {
+        AccountSpec {
+            signer: true,
+            ..self
+        }
+    }
+
+    pub fn writable(self) -> Self {
+        AccountSpec {
+            writable: true,
+            ..self
+        }
+    }
+
+    pub fn executable(self) -> Self {
+        AccountSpec {
+            executable: true,
+            ..self
+        }
+    }
+
+    /// The executable account of `program_id`, e.g. a CPI target.
+    pub fn program(self, program_id: Pubkey) -> Self {
+        self.address(program_id).executable()
+    }
+
+    pub fn check(&self, account: &AccountInfo) -> Result<(), AccountCheck> {
+        let failed = if self.address.is_some_and(|address| address != *account.key) {
+            Some(AccountCheck::Address)
+        } else if self.owner.is_some_and(|owner| owner != *account.owner) {
+            Some(AccountCheck::Owner)
+        } else if self.signer && !account.is_signer {
+            Some(AccountCheck::Signer)
+        } else if self.writable && !account.is_writable {
+            Some(AccountCheck::Writable)
+        } else if self.executable && !account.executable {
+            Some(AccountCheck::Executable)
+        } else {
+            None
+        };
+        match failed {
+            Some(check) => {
+                msg!("Account {} ({}): {}", self.name, account.key, check);
+                Err(check)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Checks every account against its spec and reports the first failure as
+/// the calling program's error `E`.
+pub fn check_accounts<E>(accounts: &[(&AccountInfo, AccountSpec)]) -> ProgramResult
+where
+    E: From<AccountCheck> + Into<ProgramError>,
+{
+    for (account, spec) in accounts {
+        spec.check(account).map_err(|check| E::from(check).into())?;
+    }
+    Ok(())
+}
@@ -0,0 +1,218 @@
+#![allow(dead_code)]
+
+use logger_contract::{
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    processor::LoggerProcessor,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+};
+use solana_program_test::{processor, BanksClient, ProgramTest, ProgramTestBanksClientExt};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    instruction::{Instruction, InstructionError},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+    transaction::{Transaction, TransactionError},
+};
+
+/// Logger program in a `ProgramTest` bank with an initialized state whose
+/// authority is the payer.
+pub struct Logger {
+    pub banks: BanksClient,
+    pub payer: Keypair,
+    pub blockhash: Hash,
+    pub program_id: Pubkey,
+    pub state: Keypair,
+}
+
+impl Logger {
+    pub async fn start() -> Self {
+        Self::start_with(Pubkey::new_unique(), Keypair::new(), &[]).await
+    }
+
+    /// Like `start`, with `accounts` already in the bank.
+    pub async fn start_with(
+        program_id: Pubkey,
+        state: Keypair,
+        accounts: &[(Pubkey, Account)],
+    ) -> Self {
+        let mut program_test = ProgramTest::new(
+            "logger_contract",
+            program_id,
+            processor!(LoggerProcessor::process),
+        );
+        program_test.prefer_bpf(false);
+        for (address, account) in accounts {
+            program_test.add_account(*address, account.clone());
+        }
+        let (banks, payer, blockhash) = program_test.start().await;
+        let mut logger = Logger {
+            banks,
+            payer,
+            blockhash,
+            program_id,
+            state,
+        };
+
+        let payer = logger.payer.pubkey();
+        let state = logger.state.pubkey();
+        let create = system_instruction::create_account(
+            &payer,
+            &state,
+            Rent::default().minimum_balance(LoggerState::LEN),
+            LoggerState::LEN as u64,
+            &program_id,
+        );
+        let state_keypair = logger.state.insecure_clone();
+        logger
+            .process(
+                &[
+                    create,
+                    logger_instruction::initialize(&program_id, &state, &payer),
+                ],
+                &[&state_keypair],
+            )
+            .await
+            .unwrap();
+        logger
+    }
+
+    pub async fn process(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), TransactionError> {
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            self.blockhash,
+        );
+        let result = self
+            .banks
+            .process_transaction(transaction)
+            .await
+            .map_err(|err| err.unwrap());
+        self.blockhash = self
+            .banks
+            .get_new_latest_blockhash(&self.blockhash)
+            .await
+            .unwrap();
+        result
+    }
+
+    pub async fn state(&mut self) -> LoggerState {
+        let account = self
+            .banks
+            .get_account(self.state.pubkey())
+            .await
+            .unwrap()
+            .unwrap();
+        *LoggerState::load(&account.data).unwrap()
+    }
+
+    pub fn message_address(&self, sequence: u64) -> Pubkey {
+        MessageData::find_address(&self.state.pubkey(), sequence, &self.program_id).0
+    }
+
+    pub fn post_instruction(&self, message: &Pubkey, amount: u64) -> Instruction {
+        logger_instruction::post_message(
+            &self.program_id,
+            &self.state.pubkey(),
+            message,
+            &self.payer.pubkey(),
+            &self.payer.pubkey(),
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            amount,
+            1_700_000_000,
+            ConsistencyLevel::Confirmed,
+            MessageKind::Transfer,
+        )
+    }
+
+    /// Creates a program-owned account of `space` bytes for a ring buffer.
+    pub async fn create_ring_account(&mut self, space: usize) -> Pubkey {
+        let ring = Keypair::new();
+        let create = system_instruction::create_account(
+            &self.payer.pubkey(),
+            &ring.pubkey(),
+            Rent::default().minimum_balance(space),
+            space as u64,
+            &self.program_id,
+        );
+        self.process(&[create], &[&ring]).await.unwrap();
+        ring.pubkey()
+    }
+
+    /// Creates and initializes a ring buffer of `capacity` slots for the state.
+    pub async fn create_ring(&mut self, capacity: u64) -> Pubkey {
+        let ring = self.create_ring_account(RingHeader::space(capacity)).await;
+        let instruction = logger_instruction::initialize_ring(
+            &self.program_id,
+            &self.payer.pubkey(),
+            &self.state.pubkey(),
+            &ring,
+        );
+        self.process(&[instruction], &[]).await.unwrap();
+        ring
+    }
+
+    pub fn post_ring_instruction(&self, ring: &Pubkey, amount: u64) -> Instruction {
+        logger_instruction::post_ring_message(
+            &self.program_id,
+            &self.state.pubkey(),
+            ring,
+            &self.payer.pubkey(),
+            &Pubkey::new_from_array([1; 32]),
+            &Pubkey::new_from_array([2; 32]),
+            amount,
+            1_700_000_000,
+            ConsistencyLevel::Confirmed,
+            MessageKind::Transfer,
+        )
+    }
+
+    pub async fn post_ring(&mut self, ring: &Pubkey, amount: u64) -> Result<(), TransactionError> {
+        let instruction = self.post_ring_instruction(ring, amount);
+        self.process(&[instruction], &[]).await
+    }
+
+    pub async fn resize_ring(&mut self, ring: &Pubkey, capacity: u64) -> Result<(), TransactionError> {
+        let payer = self.payer.pubkey();
+        let instruction = logger_instruction::resize_ring(
+            &self.program_id,
+            &payer,
+            &self.state.pubkey(),
+            ring,
+            &payer,
+            capacity,
+        );
+        self.process(&[instruction], &[]).await
+    }
+
+    pub async fn ring_data(&mut self, ring: &Pubkey) -> Vec<u8> {
+        self.banks.get_account(*ring).await.unwrap().unwrap().data
+    }
+
+    /// Message `sequence` from the ring, None once a newer one took its slot.
+    pub async fn ring_message(&mut self, ring: &Pubkey, sequence: u64) -> Option<MessageData> {
+        RingHeader::read_message(&self.ring_data(ring).await, sequence).unwrap()
+    }
+
+    pub async fn post(&mut self, amount: u64) -> Result<(), TransactionError> {
+        let sequence = self.state().await.sequence.get() + 1;
+        let instruction = self.post_instruction(&self.message_address(sequence), amount);
+        self.process(&[instruction], &[]).await
+    }
+}
+
+pub fn program_error(err: ProgramError) -> TransactionError {
+    TransactionError::InstructionError(0, InstructionError::from(u64::from(err)))
+}
@@ -0,0 +1,338 @@
+mod common;
+
+use common::{program_error, Logger};
+use logger_contract::{
+    error::LoggerError,
+    instruction as logger_instruction,
+    pod::ZeroCopy,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind},
+};
+use solana_sdk::{
+    account::Account,
+    clock::Clock,
+    instruction::AccountMeta,
+    message::Message,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction,
+};
+
+#[tokio::test]
+async fn initialize_sets_authority_and_rejects_second_call() {
+    let mut logger = Logger::start().await;
+    let state = logger.state().await;
+    assert!(state.is_initialized());
+    assert_eq!(state.sequence.get(), 0);
+    assert_eq!(state.authority, logger.payer.pubkey());
+
+    let other = Keypair::new();
+    let instruction =
+        logger_instruction::initialize(&logger.program_id, &logger.state.pubkey(), &other.pubkey());
+    let err = logger.process(&[instruction], &[&other]).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::AlreadyInitialized.into()));
+}
+
+#[tokio::test]
+async fn initialize_requires_the_authority_signature() {
+    let mut logger = Logger::start().await;
+    let payer = logger.payer.pubkey();
+    let state = Keypair::new();
+    let authority = Keypair::new();
+    let create = system_instruction::create_account(
+        &payer,
+        &state.pubkey(),
+        Rent::default().minimum_balance(LoggerState::LEN),
+        LoggerState::LEN as u64,
+        &logger.program_id,
+    );
+    logger.process(&[create], &[&state]).await.unwrap();
+
+    // Authority iz podataka instrukcije bez potpisa ne prolazi
+    let mut instruction =
+        logger_instruction::initialize(&logger.program_id, &state.pubkey(), &authority.pubkey());
+    instruction.accounts[1].is_signer = false;
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::MissingSignature.into()));
+
+    // Potpisnik koji nije authority iz podataka takodje ne prolazi
+    let mut instruction =
+        logger_instruction::initialize(&logger.program_id, &state.pubkey(), &authority.pubkey());
+    instruction.accounts[1].pubkey = payer;
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(LoggerError::IncorrectAccountAddress.into())
+    );
+
+    let instruction =
+        logger_instruction::initialize(&logger.program_id, &state.pubkey(), &authority.pubkey());
+    logger.process(&[instruction], &[&authority]).await.unwrap();
+    let account = logger
+        .banks
+        .get_account(state.pubkey())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        LoggerState::load(&account.data).unwrap().authority,
+        authority.pubkey()
+    );
+}
+
+#[tokio::test]
+async fn post_message_increments_sequence_and_fills_pda() {
+    let mut logger = Logger::start().await;
+    logger.post(10).await.unwrap();
+    logger.post(20).await.unwrap();
+
+    let state = logger.state().await;
+    assert_eq!(state.sequence.get(), 2);
+    assert_ne!(state.accumulator, [0u8; 32]);
+
+    for (sequence, amount) in [(1, 10), (2, 20)] {
+        let account = logger
+            .banks
+            .get_account(logger.message_address(sequence))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, logger.program_id);
+        let message = MessageData::load(&account.data).unwrap();
+        assert_eq!(message.sequence.get(), sequence);
+        assert_eq!(message.amount.get(), amount);
+        assert_eq!(message.from_pubkey, Pubkey::new_from_array([1; 32]));
+        assert_eq!(message.to_pubkey, Pubkey::new_from_array([2; 32]));
+        assert_eq!(message.timestamp.get(), 1_700_000_000);
+        assert_eq!(message.payer, logger.payer.pubkey());
+        assert_eq!(message.consistency_level(), ConsistencyLevel::Confirmed);
+        assert_eq!(message.emitter, logger.payer.pubkey());
+        assert_eq!(message.kind(), MessageKind::Transfer);
+    }
+}
+
+#[tokio::test]
+async fn post_message_records_the_slot_and_epoch_it_was_posted_in() {
+    let mut logger = Logger::start().await;
+    let before: Clock = logger.banks.get_sysvar().await.unwrap();
+    logger.post(10).await.unwrap();
+    let after: Clock = logger.banks.get_sysvar().await.unwrap();
+
+    let account = logger
+        .banks
+        .get_account(logger.message_address(1))
+        .await
+        .unwrap()
+        .unwrap();
+    let message = MessageData::load(&account.data).unwrap();
+    // Slot i epoha dolaze iz Clock-a u trenutku objave, ne od pozivaoca
+    assert!((before.slot..=after.slot).contains(&message.slot.get()));
+    assert!((before.epoch..=after.epoch).contains(&message.epoch.get()));
+    assert_eq!(message.consistency_level(), ConsistencyLevel::Confirmed);
+}
+
+#[tokio::test]
+async fn post_message_to_wrong_pda_fails() {
+    let mut logger = Logger::start().await;
+    let instruction = logger.post_instruction(&logger.message_address(5), 10);
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(LoggerError::IncorrectMessageAddress.into())
+    );
+    assert_eq!(logger.state().await.sequence.get(), 0);
+}
+
+#[tokio::test]
+async fn post_message_never_overwrites_a_posted_message() {
+    let program_id = Pubkey::new_unique();
+    let state = Keypair::new();
+    let existing = MessageData::new(
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        77,
+        1,
+        1,
+        Pubkey::new_unique(),
+        1,
+        0,
+        ConsistencyLevel::Finalized,
+        Pubkey::new_unique(),
+        MessageKind::Transfer,
+    );
+    let address = MessageData::find_address(&state.pubkey(), 1, &program_id).0;
+    let account = Account {
+        lamports: Rent::default().minimum_balance(MessageData::LEN),
+        data: bytemuck::bytes_of(&existing).to_vec(),
+        owner: program_id,
+        ..Account::default()
+    };
+    let mut logger = Logger::start_with(program_id, state, &[(address, account)]).await;
+
+    let err = logger.post(10).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::MessageAlreadyPosted.into()));
+    assert_eq!(logger.state().await.sequence.get(), 0);
+    let account = logger.banks.get_account(address).await.unwrap().unwrap();
+    assert_eq!(*MessageData::load(&account.data).unwrap(), existing);
+}
+
+#[tokio::test]
+async fn post_message_takes_over_a_prefunded_pda() {
+    let mut logger = Logger::start().await;
+    let payer = logger.payer.pubkey();
+    let rent = Rent::default().minimum_balance(MessageData::LEN);
+
+    // Adrese narednih poruka su poznate, pa neko moze unapred poslati lamporte
+    // na njih; jedna dobija najmanji rent praznog naloga, druga vise od rent-a poruke
+    let transfers = [
+        system_instruction::transfer(
+            &payer,
+            &logger.message_address(1),
+            Rent::default().minimum_balance(0),
+        ),
+        system_instruction::transfer(&payer, &logger.message_address(2), rent + 5),
+    ];
+    logger.process(&transfers, &[]).await.unwrap();
+    logger.post(10).await.unwrap();
+    logger.post(20).await.unwrap();
+
+    for (sequence, amount, lamports) in [(1, 10, rent), (2, 20, rent + 5)] {
+        let account = logger
+            .banks
+            .get_account(logger.message_address(sequence))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, logger.program_id);
+        assert_eq!(account.lamports, lamports);
+        let message = MessageData::load(&account.data).unwrap();
+        assert_eq!(message.sequence.get(), sequence);
+        assert_eq!(message.amount.get(), amount);
+    }
+    assert_eq!(logger.state().await.sequence.get(), 2);
+}
+
+#[tokio::test]
+async fn post_message_checks_account_spec() {
+    let mut logger = Logger::start().await;
+    let message = logger.message_address(1);
+
+    let mut read_only_state = logger.post_instruction(&message, 10);
+    read_only_state.accounts[0].is_writable = false;
+    let mut foreign_state = logger.post_instruction(&message, 10);
+    foreign_state.accounts[0].pubkey = Pubkey::new_unique();
+    let mut fake_system_program = logger.post_instruction(&message, 10);
+    fake_system_program.accounts[3].pubkey = Pubkey::new_unique();
+    // Bez potpisa emitter-a poruka bi se mogla podmetnuti u ime escrow-a
+    let mut unsigned_emitter = logger.post_instruction(&message, 10);
+    unsigned_emitter.accounts[4] = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+
+    for (instruction, expected) in [
+        (read_only_state, LoggerError::AccountNotWritable),
+        (foreign_state, LoggerError::IncorrectAccountOwner),
+        (fake_system_program, LoggerError::IncorrectAccountAddress),
+        (unsigned_emitter, LoggerError::MissingSignature),
+    ] {
+        let err = logger.process(&[instruction], &[]).await.unwrap_err();
+        assert_eq!(err, program_error(expected.into()));
+    }
+    assert_eq!(logger.state().await.sequence.get(), 0);
+}
+
+#[tokio::test]
+async fn close_message_refunds_payer() {
+    let mut logger = Logger::start().await;
+    logger.post(10).await.unwrap();
+    let message = logger.message_address(1);
+    let payer = logger.payer.pubkey();
+
+    let instruction = logger_instruction::close_message(
+        &logger.program_id,
+        &payer,
+        &logger.state.pubkey(),
+        &message,
+        &payer,
+    );
+    logger.process(&[instruction], &[]).await.unwrap();
+    assert!(logger.banks.get_account(message).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn close_message_refunds_only_the_payer_of_the_message() {
+    let mut logger = Logger::start().await;
+    logger.post(10).await.unwrap();
+    let message = logger.message_address(1);
+    let payer = logger.payer.pubkey();
+    let rent = logger.banks.get_balance(message).await.unwrap();
+
+    // Rent se vraca samo nalogu koji je platio poruku
+    let instruction = logger_instruction::close_message(
+        &logger.program_id,
+        &payer,
+        &logger.state.pubkey(),
+        &message,
+        &Pubkey::new_unique(),
+    );
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::PayerMismatch.into()));
+
+    let instruction = logger_instruction::close_message(
+        &logger.program_id,
+        &payer,
+        &logger.state.pubkey(),
+        &message,
+        &payer,
+    );
+    let fee = logger
+        .banks
+        .get_fee_for_message(Message::new_with_blockhash(
+            std::slice::from_ref(&instruction),
+            Some(&payer),
+            &logger.blockhash,
+        ))
+        .await
+        .unwrap()
+        .unwrap();
+    let before = logger.banks.get_balance(payer).await.unwrap();
+    logger.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(
+        logger.banks.get_balance(payer).await.unwrap(),
+        before + rent - fee
+    );
+}
+
+#[tokio::test]
+async fn close_message_requires_authority_signature() {
+    let mut logger = Logger::start().await;
+    logger.post(10).await.unwrap();
+    let message = logger.message_address(1);
+    let payer = logger.payer.pubkey();
+    let outsider = Keypair::new();
+
+    let instruction = logger_instruction::close_message(
+        &logger.program_id,
+        &outsider.pubkey(),
+        &logger.state.pubkey(),
+        &message,
+        &payer,
+    );
+    let err = logger
+        .process(&[instruction], &[&outsider])
+        .await
+        .unwrap_err();
+    assert_eq!(err, program_error(LoggerError::NotAuthority.into()));
+
+    let mut instruction = logger_instruction::close_message(
+        &logger.program_id,
+        &payer,
+        &logger.state.pubkey(),
+        &message,
+        &payer,
+    );
+    instruction.accounts[0].is_signer = false;
+    // Payer je i fee payer, pa za nepotpisan authority koristimo drugi kljuc
+    instruction.accounts[0].pubkey = outsider.pubkey();
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::MissingSignature.into()));
+    assert!(logger.banks.get_account(message).await.unwrap().is_some());
+}
@@ -0,0 +1,235 @@
+use std::mem::size_of;
+
+use bytemuck::bytes_of;
+use logger_contract::{
+    instruction::LoggerInstruction,
+    pod::ZeroCopy,
+    state::{ConsistencyLevel, LoggerState, MessageData, MessageKind, RingHeader},
+};
+use proptest::{collection::vec, prelude::*};
+use solana_sdk::{program_error::ProgramError, pubkey::Pubkey};
+
+fn pubkey() -> impl Strategy<Value = Pubkey> {
+    any::<[u8; 32]>().prop_map(Pubkey::new_from_array)
+}
+
+fn consistency_level() -> impl Strategy<Value = ConsistencyLevel> {
+    prop_oneof![
+        Just(ConsistencyLevel::Confirmed),
+        Just(ConsistencyLevel::Finalized)
+    ]
+}
+
+fn message_kind() -> impl Strategy<Value = MessageKind> {
+    prop_oneof![
+        Just(MessageKind::Transfer),
+        Just(MessageKind::Escrow),
+        Just(MessageKind::Batch)
+    ]
+}
+
+fn instruction() -> impl Strategy<Value = LoggerInstruction> {
+    prop_oneof![
+        pubkey().prop_map(|authority| LoggerInstruction::Initialize { authority }),
+        (
+            pubkey(),
+            pubkey(),
+            any::<u64>(),
+            any::<u64>(),
+            consistency_level(),
+            message_kind()
+        )
+            .prop_map(
+                |(from_pubkey, to_pubkey, amount, timestamp, consistency_level, kind)| {
+                    LoggerInstruction::PostMessage {
+                        from_pubkey,
+                        to_pubkey,
+                        amount,
+                        timestamp,
+                        consistency_level,
+                        kind,
+                    }
+                }
+            ),
+        Just(LoggerInstruction::CloseMessage),
+        pubkey().prop_map(|new_authority| LoggerInstruction::SetAuthority { new_authority }),
+        Just(LoggerInstruction::GetSequence),
+        Just(LoggerInstruction::InitializeRing),
+        any::<u64>().prop_map(|capacity| LoggerInstruction::ResizeRing { capacity }),
+        (
+            pubkey(),
+            pubkey(),
+            any::<u64>(),
+            any::<u64>(),
+            consistency_level(),
+            message_kind()
+        )
+            .prop_map(
+                |(from_pubkey, to_pubkey, amount, timestamp, consistency_level, kind)| {
+                    LoggerInstruction::PostRingMessage {
+                        from_pubkey,
+                        to_pubkey,
+                        amount,
+                        timestamp,
+                        consistency_level,
+                        kind,
+                    }
+                }
+            ),
+    ]
+}
+
+fn logger_state() -> impl Strategy<Value = LoggerState> {
+    (any::<u64>(), pubkey(), any::<[u8; 32]>()).prop_map(|(sequence, authority, accumulator)| {
+        LoggerState {
+            sequence: sequence.into(),
+            accumulator,
+            ..LoggerState::new(authority)
+        }
+    })
+}
+
+fn message_data() -> impl Strategy<Value = MessageData> {
+    (
+        (pubkey(), pubkey(), any::<u64>(), any::<u64>()),
+        (any::<u64>(), pubkey(), any::<u64>(), any::<u64>()),
+        (consistency_level(), pubkey(), message_kind()),
+    )
+        .prop_map(
+            |(
+                (from_pubkey, to_pubkey, amount, timestamp),
+                (sequence, payer, slot, epoch),
+                (consistency_level, emitter, kind),
+            )| {
+                MessageData::new(
+                    from_pubkey,
+                    to_pubkey,
+                    amount,
+                    timestamp,
+                    sequence,
+                    payer,
+                    slot,
+                    epoch,
+                    consistency_level,
+                    emitter,
+                    kind,
+                )
+            },
+        )
+}
+
+/// Load results agree with the discriminator: all zeroes is uninitialized,
+/// anything other than `T::DISCRIMINATOR` is invalid, and a matching one
+/// fails only through `validate`.
+fn check_load<T: ZeroCopy>(data: &[u8]) -> Result<(), TestCaseError> {
+    let discriminator = &data[..8];
+    match T::load(data) {
+        Ok(value) => {
+            prop_assert_eq!(discriminator, T::DISCRIMINATOR);
+            prop_assert_eq!(bytes_of(value), data);
+        }
+        Err(ProgramError::UninitializedAccount) => prop_assert_eq!(discriminator, [0u8; 8]),
+        // Ispravan discriminator, a greska dolazi iz validate()
+        Err(err) if discriminator == T::DISCRIMINATOR => {
+            prop_assert_eq!(T::load_unchecked(data).unwrap().validate(), Err(err));
+        }
+        Err(err) => prop_assert_eq!(err, ProgramError::InvalidAccountData),
+    }
+    Ok(())
+}
+
+fn account_data<T: ZeroCopy>() -> impl Strategy<Value = Vec<u8>> {
+    (
+        prop_oneof![Just(T::DISCRIMINATOR), Just([0u8; 8]), any::<[u8; 8]>()],
+        vec(any::<u8>(), size_of::<T>() - 8),
+    )
+        .prop_map(|(discriminator, rest)| [&discriminator[..], &rest].concat())
+}
+
+proptest! {
+    #[test]
+    fn instruction_round_trips(instruction in instruction()) {
+        prop_assert_eq!(LoggerInstruction::unpack(&instruction.pack()).unwrap(), instruction);
+    }
+
+    #[test]
+    fn instruction_unpack_accepts_only_its_own_encoding(input in vec(any::<u8>(), 0..128)) {
+        // Visak bajtova posle instrukcije se ignorise
+        if let Ok(instruction) = LoggerInstruction::unpack(&input) {
+            prop_assert!(input.starts_with(&instruction.pack()));
+        }
+    }
+
+    #[test]
+    fn logger_state_round_trips(state in logger_state()) {
+        prop_assert_eq!(*LoggerState::load(bytes_of(&state)).unwrap(), state);
+    }
+
+    #[test]
+    fn logger_state_load_checks_discriminator(data in account_data::<LoggerState>()) {
+        check_load::<LoggerState>(&data)?;
+    }
+
+    #[test]
+    fn logger_state_load_requires_exact_length(input in vec(any::<u8>(), 0..LoggerState::LEN * 2)) {
+        prop_assert_eq!(
+            LoggerState::load_unchecked(&input).is_ok(),
+            input.len() == LoggerState::LEN
+        );
+    }
+
+    #[test]
+    fn message_data_round_trips(message in message_data()) {
+        prop_assert_eq!(*MessageData::load(bytes_of(&message)).unwrap(), message);
+    }
+
+    #[test]
+    fn message_data_load_checks_discriminator_and_level(data in account_data::<MessageData>()) {
+        check_load::<MessageData>(&data)?;
+        if let Ok(message) = MessageData::load(&data) {
+            prop_assert!(message.consistency_level <= 1);
+            prop_assert!(message.kind <= 1);
+        }
+    }
+
+    #[test]
+    fn message_data_load_requires_exact_length(input in vec(any::<u8>(), 0..MessageData::LEN * 2)) {
+        prop_assert_eq!(
+            MessageData::load_unchecked(&input).is_ok(),
+            input.len() == MessageData::LEN
+        );
+    }
+
+    #[test]
+    fn ring_header_load_checks_discriminator(data in account_data::<RingHeader>()) {
+        check_load::<RingHeader>(&data)?;
+    }
+
+    #[test]
+    fn ring_read_message_never_panics(
+        data in vec(any::<u8>(), 0..RingHeader::space(3) + 16),
+        sequence in any::<u64>(),
+    ) {
+        if let Ok(Some(message)) = RingHeader::read_message(&data, sequence) {
+            prop_assert_eq!(message.sequence.get(), sequence);
+        }
+    }
+
+    #[test]
+    fn ring_read_message_finds_written_slot(
+        logger_state in pubkey(),
+        message in message_data(),
+        capacity in 1u64..8,
+    ) {
+        let mut data = vec![0u8; RingHeader::space(capacity)];
+        *RingHeader::load_mut_unchecked(&mut data[..RingHeader::LEN]).unwrap() =
+            RingHeader::new(logger_state);
+        let offset = RingHeader::slot_offset(message.sequence.get(), capacity);
+        data[offset..offset + MessageData::LEN].copy_from_slice(bytes_of(&message));
+
+        prop_assert_eq!(
+            RingHeader::read_message(&data, message.sequence.get()).unwrap(),
+            Some(message)
+        );
+    }
+}
@@ -0,0 +1,139 @@
+mod common;
+
+use common::{program_error, Logger};
+use logger_contract::{
+    error::LoggerError,
+    instruction as logger_instruction,
+    state::{ConsistencyLevel, RingHeader},
+};
+use solana_sdk::{
+    account::Account,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+};
+
+#[tokio::test]
+async fn ring_messages_take_no_pda_and_no_payer() {
+    let mut logger = Logger::start().await;
+    let ring = logger.create_ring(4).await;
+    logger.post_ring(&ring, 10).await.unwrap();
+    logger.post_ring(&ring, 20).await.unwrap();
+
+    let state = logger.state().await;
+    assert_eq!(state.sequence.get(), 2);
+    assert_ne!(state.accumulator, [0u8; 32]);
+    for (sequence, amount) in [(1, 10), (2, 20)] {
+        let message = logger.ring_message(&ring, sequence).await.unwrap();
+        assert_eq!(message.amount.get(), amount);
+        assert_eq!(message.from_pubkey, Pubkey::new_from_array([1; 32]));
+        assert_eq!(message.to_pubkey, Pubkey::new_from_array([2; 32]));
+        assert_eq!(message.payer, Pubkey::default());
+        assert_eq!(message.emitter, logger.payer.pubkey());
+        assert_eq!(message.consistency_level(), ConsistencyLevel::Confirmed);
+    }
+    let pda = logger.message_address(1);
+    assert!(logger.banks.get_account(pda).await.unwrap().is_none());
+    // Ring se bira instrukcijom, PostMessage ga ne prihvata umesto PDA
+    let instruction = logger.post_instruction(&ring, 30);
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        program_error(LoggerError::IncorrectMessageAddress.into())
+    );
+    assert_eq!(logger.state().await.sequence.get(), 2);
+}
+
+#[tokio::test]
+async fn ring_wraps_around_and_evicts_old_sequences() {
+    let mut logger = Logger::start().await;
+    let ring = logger.create_ring(2).await;
+    for amount in [10, 20, 30] {
+        logger.post_ring(&ring, amount).await.unwrap();
+    }
+
+    // Sekvenca 3 je zauzela slot sekvence 1
+    assert_eq!(logger.ring_message(&ring, 1).await, None);
+    assert_eq!(logger.ring_message(&ring, 2).await.unwrap().amount.get(), 20);
+    assert_eq!(logger.ring_message(&ring, 3).await.unwrap().amount.get(), 30);
+    assert_eq!(logger.ring_message(&ring, 5).await, None);
+}
+
+#[tokio::test]
+async fn ring_of_another_state_or_uninitialized_is_rejected() {
+    let program_id = Pubkey::new_unique();
+    let foreign = Pubkey::new_unique();
+    let mut data = vec![0u8; RingHeader::space(2)];
+    data[..RingHeader::LEN].copy_from_slice(bytemuck::bytes_of(&RingHeader::new(
+        Pubkey::new_unique(),
+    )));
+    let account = Account {
+        lamports: Rent::default().minimum_balance(data.len()),
+        data,
+        owner: program_id,
+        ..Account::default()
+    };
+    let mut logger = Logger::start_with(program_id, Keypair::new(), &[(foreign, account)]).await;
+
+    let err = logger.post_ring(&foreign, 10).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingStateMismatch.into()));
+    let err = logger.resize_ring(&foreign, 4).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingStateMismatch.into()));
+
+    let uninitialized = logger.create_ring_account(RingHeader::space(2)).await;
+    let err = logger.post_ring(&uninitialized, 10).await.unwrap_err();
+    assert_eq!(err, program_error(ProgramError::UninitializedAccount));
+    assert_eq!(logger.state().await.sequence.get(), 0);
+}
+
+#[tokio::test]
+async fn resize_grows_the_ring_and_keeps_matching_slots() {
+    let mut logger = Logger::start().await;
+    let ring = logger.create_ring(2).await;
+    logger.post_ring(&ring, 10).await.unwrap();
+    logger.post_ring(&ring, 20).await.unwrap();
+
+    logger.resize_ring(&ring, 4).await.unwrap();
+    assert_eq!(logger.ring_data(&ring).await.len(), RingHeader::space(4));
+    // Sekvenca 1 je u slotu 1 i pre i posle, sekvenca 2 je bila u slotu 0
+    assert_eq!(logger.ring_message(&ring, 1).await.unwrap().amount.get(), 10);
+    assert_eq!(logger.ring_message(&ring, 2).await, None);
+
+    for amount in [30, 40, 50] {
+        logger.post_ring(&ring, amount).await.unwrap();
+    }
+    assert_eq!(logger.ring_message(&ring, 1).await, None);
+    for (sequence, amount) in [(3, 30), (4, 40), (5, 50)] {
+        assert_eq!(
+            logger.ring_message(&ring, sequence).await.unwrap().amount.get(),
+            amount
+        );
+    }
+
+    let err = logger.resize_ring(&ring, 4).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingCannotShrink.into()));
+    let err = logger.resize_ring(&ring, 1_000).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingGrowthTooLarge.into()));
+}
+
+#[tokio::test]
+async fn ring_shorter_than_its_header_is_rejected() {
+    let mut logger = Logger::start().await;
+    // Nalog programa kraci od RingHeader::LEN
+    let short = logger.create_ring_account(RingHeader::LEN - 1).await;
+
+    let err = logger.resize_ring(&short, 4).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingTooSmall.into()));
+    let err = logger.post_ring(&short, 10).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingTooSmall.into()));
+    let payer = logger.payer.pubkey();
+    let instruction = logger_instruction::initialize_ring(
+        &logger.program_id,
+        &payer,
+        &logger.state.pubkey(),
+        &short,
+    );
+    let err = logger.process(&[instruction], &[]).await.unwrap_err();
+    assert_eq!(err, program_error(LoggerError::RingTooSmall.into()));
+}